@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
+
+use casper_execution_engine::{
+    core::{engine_state::EngineConfig, wasm_engine},
+    shared::wasm_module_cache::WasmModuleCache,
+};
+use casper_types::ContractWasmHash;
+use casper_wasm::{
+    builder,
+    elements::{Instruction, Instructions},
+};
+
+/// Builds a module of roughly the size of a typical stored contract.
+fn module_bytes() -> Vec<u8> {
+    let mut module_builder = builder::module();
+    for index in 0..500 {
+        let mut instructions = vec![Instruction::I32Const(index); 50];
+        instructions.extend(vec![Instruction::Drop; 50]);
+        instructions.push(Instruction::End);
+        module_builder = module_builder
+            .function()
+            .signature()
+            .build()
+            .body()
+            .with_instructions(Instructions::new(instructions))
+            .build()
+            .build();
+    }
+    casper_wasm::serialize(module_builder.build()).unwrap()
+}
+
+fn prepare_module(b: &mut Bencher) {
+    let bytes = module_bytes();
+    let engine_config = EngineConfig::default();
+    b.iter(|| wasm_engine::deserialize_and_prepare(black_box(&bytes), &engine_config).unwrap());
+}
+
+fn get_cached_module(b: &mut Bencher) {
+    let bytes = module_bytes();
+    let cache = WasmModuleCache::new(1);
+    let contract_wasm_hash = ContractWasmHash::new([1; 32]);
+    let engine_config = EngineConfig::default();
+    let prepare = |bytes: &[u8]| wasm_engine::deserialize_and_prepare(bytes, &engine_config);
+    cache
+        .get_or_insert_with(contract_wasm_hash, &bytes, prepare)
+        .unwrap();
+    b.iter(|| {
+        cache
+            .get_or_insert_with(contract_wasm_hash, black_box(&bytes), prepare)
+            .unwrap()
+    });
+}
+
+fn wasm_module_cache_bench(c: &mut Criterion) {
+    c.bench_function("prepare_module", prepare_module);
+    c.bench_function("get_cached_module", get_cached_module);
+}
+
+criterion_group!(benches, wasm_module_cache_bench);
+criterion_main!(benches);
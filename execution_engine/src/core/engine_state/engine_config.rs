@@ -10,7 +10,10 @@ use num_traits::One;
 
 use casper_types::{account::AccountHash, PublicKey};
 
-use crate::shared::{system_config::SystemConfig, wasm_config::WasmConfig};
+use crate::{
+    core::wasm_engine::WasmEngineKind,
+    shared::{system_config::SystemConfig, wasm_config::WasmConfig},
+};
 
 pub use self::{fee_handling::FeeHandling, refund_handling::RefundHandling};
 
@@ -81,6 +84,8 @@ pub struct EngineConfig {
     pub(crate) refund_handling: RefundHandling,
     /// Fee handling.
     pub(crate) fee_handling: FeeHandling,
+    /// The engine used to execute Wasm.
+    pub(crate) wasm_engine: WasmEngineKind,
 }
 
 impl Default for EngineConfig {
@@ -100,6 +105,7 @@ impl Default for EngineConfig {
             allow_unrestricted_transfers: DEFAULT_ALLOW_UNRESTRICTED_TRANSFERS,
             refund_handling: DEFAULT_REFUND_HANDLING,
             fee_handling: DEFAULT_FEE_HANDLING,
+            wasm_engine: WasmEngineKind::default(),
         }
     }
 }
@@ -140,6 +146,7 @@ impl EngineConfig {
             allow_unrestricted_transfers: DEFAULT_ALLOW_UNRESTRICTED_TRANSFERS,
             refund_handling: DEFAULT_REFUND_HANDLING,
             fee_handling: DEFAULT_FEE_HANDLING,
+            wasm_engine: WasmEngineKind::default(),
         }
     }
 
@@ -213,6 +220,17 @@ impl EngineConfig {
         self.fee_handling
     }
 
+    /// Returns the engine used to execute Wasm.
+    pub fn wasm_engine(&self) -> WasmEngineKind {
+        self.wasm_engine
+    }
+
+    /// Sets the engine used to execute Wasm.
+    #[cfg(feature = "test-support")]
+    pub fn set_wasm_engine(&mut self, wasm_engine: WasmEngineKind) {
+        self.wasm_engine = wasm_engine;
+    }
+
     /// Sets the `wasm_config.max_memory` to `new_value`.
     #[cfg(feature = "test-support")]
     pub fn set_max_memory(&mut self, new_value: u32) {
@@ -240,6 +258,7 @@ pub struct EngineConfigBuilder {
     allow_unrestricted_transfers: Option<bool>,
     refund_handling: Option<RefundHandling>,
     fee_handling: Option<FeeHandling>,
+    wasm_engine: Option<WasmEngineKind>,
 }
 
 impl EngineConfigBuilder {
@@ -354,6 +373,12 @@ impl EngineConfigBuilder {
         self
     }
 
+    /// Sets the engine used to execute Wasm.
+    pub fn with_wasm_engine(mut self, wasm_engine: WasmEngineKind) -> Self {
+        self.wasm_engine = Some(wasm_engine);
+        self
+    }
+
     /// Builds a new [`EngineConfig`] object.
     pub fn build(self) -> EngineConfig {
         let max_query_depth = self.max_query_depth.unwrap_or(DEFAULT_MAX_QUERY_DEPTH);
@@ -383,6 +408,7 @@ impl EngineConfigBuilder {
             .unwrap_or(DEFAULT_ALLOW_UNRESTRICTED_TRANSFERS);
         let refund_handling = self.refund_handling.unwrap_or(DEFAULT_REFUND_HANDLING);
         let fee_handling = self.fee_handling.unwrap_or(DEFAULT_FEE_HANDLING);
+        let wasm_engine = self.wasm_engine.unwrap_or_default();
 
         let strict_argument_checking = self
             .strict_argument_checking
@@ -407,6 +433,7 @@ impl EngineConfigBuilder {
             strict_argument_checking,
             vesting_schedule_period_millis,
             max_delegators_per_validator,
+            wasm_engine,
         }
     }
 }
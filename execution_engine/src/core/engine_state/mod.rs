@@ -88,17 +88,27 @@ use crate::{
             upgrade::{ProtocolUpgradeError, SystemUpgrader},
         },
         execution::{self, DirectSystemContractCall, Executor},
-        runtime::RuntimeStack,
+        runtime::{
+            trace::{ExecutionTrace, ExecutionTracer},
+            RuntimeStack,
+        },
+        runtime_context::dictionary::DictionaryValue,
         tracking_copy::{TrackingCopy, TrackingCopyExt},
+        wasm_engine::{self, WasmEngineKind},
+    },
+    shared::{
+        additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform,
+        wasm_module_cache::WasmModuleCache,
     },
-    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
     storage::{
         global_state::{
-            lmdb::LmdbGlobalState, scratch::ScratchGlobalState, CommitProvider, StateProvider,
-            StateReader,
+            lmdb::LmdbGlobalState,
+            recording::{ReadSet, RecordingGlobalState},
+            scratch::ScratchGlobalState,
+            CommitProvider, StateProvider, StateReader,
         },
         trie::{merkle_proof::TrieMerkleProof, TrieRaw},
-        trie_store::operations::DeleteResult,
+        trie_store::operations::{DeleteResult, DiffResult, ListKeysResult},
     },
     system::auction,
 };
@@ -125,6 +135,7 @@ pub const WASMLESS_TRANSFER_FIXED_GAS_PRICE: u64 = 1;
 pub struct EngineState<S> {
     config: EngineConfig,
     state: S,
+    wasm_module_cache: Option<WasmModuleCache>,
 }
 
 impl EngineState<ScratchGlobalState> {
@@ -132,6 +143,26 @@ impl EngineState<ScratchGlobalState> {
     pub fn into_inner(self) -> ScratchGlobalState {
         self.state
     }
+
+    /// Executes the deploys as per `run_execute`, additionally returning the reads from global
+    /// state, including the keys which were not found.
+    ///
+    /// Executions only read from this state, so they can be run concurrently.  Their results remain
+    /// valid after further effects are committed to this state, unless those effects change one of
+    /// the [dependencies](ReadSet::dependencies) of the execution.
+    pub fn run_execute_recording_reads(
+        &self,
+        correlation_id: CorrelationId,
+        exec_request: ExecuteRequest,
+    ) -> Result<(ExecutionResults, ReadSet), Error> {
+        let recording_engine_state = EngineState {
+            config: self.config.clone(),
+            state: RecordingGlobalState::new(self.state.clone()),
+            wasm_module_cache: self.wasm_module_cache.clone(),
+        };
+        let execution_results = recording_engine_state.run_execute(correlation_id, exec_request)?;
+        Ok((execution_results, recording_engine_state.state.read_set()))
+    }
 }
 
 impl EngineState<LmdbGlobalState> {
@@ -153,9 +184,80 @@ impl EngineState<LmdbGlobalState> {
         EngineState {
             config: self.config.clone(),
             state: self.state.create_scratch(),
+            wasm_module_cache: self.wasm_module_cache.clone(),
         }
     }
 
+    /// Returns up to `limit` differences between the global states at `old_state_root_hash` and
+    /// `new_state_root_hash`, in the order of their serialized keys, starting after `start_after`
+    /// if given.
+    ///
+    /// Subtries which are identical in both states are skipped without being read.
+    pub fn get_state_diff(
+        &self,
+        correlation_id: CorrelationId,
+        old_state_root_hash: Digest,
+        new_state_root_hash: Digest,
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<DiffResult<Key, StoredValue>, Error> {
+        self.state
+            .diff(
+                correlation_id,
+                old_state_root_hash,
+                new_state_root_hash,
+                start_after,
+                limit,
+            )
+            .map_err(Into::into)
+    }
+
+    /// Returns up to `limit` keys in the global state under `state_root_hash` whose serialized
+    /// forms start with `prefix`, in the order of their serialized forms, starting after
+    /// `start_after` if given.
+    ///
+    /// If `dictionary_seed_uref` is given, only `Key::Dictionary` entries of the dictionary with
+    /// that seed are returned.  As dictionary keys are hashes, this requires reading the dictionary
+    /// entries of all dictionaries following `start_after` until `limit` matching ones are found.
+    /// At most `max_scanned` entries are read though, in which case fewer keys may be returned
+    /// along with the key to resume scanning after.
+    #[allow(clippy::too_many_arguments)]
+    pub fn list_keys(
+        &self,
+        correlation_id: CorrelationId,
+        state_root_hash: Digest,
+        prefix: &[u8],
+        dictionary_seed_uref: Option<URef>,
+        start_after: Option<&Key>,
+        limit: usize,
+        max_scanned: usize,
+    ) -> Result<ListKeysResult<Key>, Error> {
+        let filter = |key: &Key, value: &StoredValue| match dictionary_seed_uref {
+            None => true,
+            Some(seed_uref) => match (key, value) {
+                (Key::Dictionary(_), StoredValue::CLValue(cl_value)) => cl_value
+                    .clone()
+                    .into_t::<DictionaryValue>()
+                    .map(|dictionary_value| {
+                        dictionary_value.seed_uref_addr() == seed_uref.addr().as_slice()
+                    })
+                    .unwrap_or(false),
+                _ => false,
+            },
+        };
+        self.state
+            .list_keys(
+                correlation_id,
+                state_root_hash,
+                prefix,
+                start_after,
+                limit,
+                max_scanned,
+                filter,
+            )
+            .map_err(Into::into)
+    }
+
     /// Writes state cached in an `EngineState<ScratchEngineState>` to LMDB.
     pub fn write_scratch_to_db(
         &self,
@@ -176,7 +278,22 @@ where
 {
     /// Creates new engine state.
     pub fn new(state: S, config: EngineConfig) -> EngineState<S> {
-        EngineState { config, state }
+        EngineState {
+            config,
+            state,
+            wasm_module_cache: None,
+        }
+    }
+
+    /// Sets the cache of deserialized stored contract modules shared by all executions.
+    pub fn with_wasm_module_cache(mut self, wasm_module_cache: WasmModuleCache) -> Self {
+        self.wasm_module_cache = Some(wasm_module_cache);
+        self
+    }
+
+    /// Returns the cache of deserialized stored contract modules, if any.
+    pub fn wasm_module_cache(&self) -> Option<&WasmModuleCache> {
+        self.wasm_module_cache.as_ref()
     }
 
     /// Returns engine config.
@@ -258,6 +375,11 @@ where
         correlation_id: CorrelationId,
         upgrade_config: UpgradeConfig,
     ) -> Result<UpgradeSuccess, Error> {
+        // Modules deserialized before the upgrade are not reused after it.
+        if let Some(wasm_module_cache) = &self.wasm_module_cache {
+            wasm_module_cache.clear();
+        }
+
         // per specification:
         // https://casperlabs.atlassian.net/wiki/spaces/EN/pages/139854367/Upgrading+System+Contracts+Specification
 
@@ -552,43 +674,117 @@ where
         correlation_id: CorrelationId,
         mut exec_request: ExecuteRequest,
     ) -> Result<ExecutionResults, Error> {
-        let executor = Executor::new(self.config().clone());
+        let executor = Executor::new(self.config().clone())
+            .with_wasm_module_cache(self.wasm_module_cache.clone());
 
         let deploys = exec_request.take_deploys();
         let mut results = ExecutionResults::with_capacity(deploys.len());
 
         for deploy_item in deploys {
-            let result = match deploy_item.session {
-                ExecutableDeployItem::Transfer { .. } => self.transfer(
-                    correlation_id,
-                    &executor,
-                    exec_request.protocol_version,
-                    exec_request.parent_state_hash,
-                    BlockTime::new(exec_request.block_time),
-                    deploy_item,
-                    exec_request.proposer.clone(),
-                ),
-                _ => self.deploy(
-                    correlation_id,
-                    &executor,
-                    exec_request.protocol_version,
-                    exec_request.parent_state_hash,
-                    BlockTime::new(exec_request.block_time),
-                    deploy_item,
-                    exec_request.proposer.clone(),
-                ),
-            };
-            match result {
-                Ok(result) => results.push_back(result),
-                Err(error) => {
-                    return Err(error);
-                }
-            };
+            let result =
+                self.execute_deploy_item(correlation_id, &executor, &exec_request, deploy_item)?;
+            results.push_back(result);
         }
 
         Ok(results)
     }
 
+    /// Executes a deploy as per `run_execute`, while recording each deploy's host function calls.
+    ///
+    /// Returns execution results which contains results from each deploy ran, along with an
+    /// execution trace for each deploy ran.
+    pub fn run_execute_with_trace(
+        &self,
+        correlation_id: CorrelationId,
+        mut exec_request: ExecuteRequest,
+    ) -> Result<(ExecutionResults, Vec<ExecutionTrace>), Error> {
+        let deploys = exec_request.take_deploys();
+        let mut results = ExecutionResults::with_capacity(deploys.len());
+        let mut traces = Vec::with_capacity(deploys.len());
+
+        for deploy_item in deploys {
+            let execution_tracer = ExecutionTracer::default();
+            let executor =
+                Executor::new_with_tracer(self.config().clone(), execution_tracer.clone())
+                    .with_wasm_module_cache(self.wasm_module_cache.clone());
+            let result =
+                self.execute_deploy_item(correlation_id, &executor, &exec_request, deploy_item)?;
+            results.push_back(result);
+            traces.push(execution_tracer.trace());
+        }
+
+        Ok((results, traces))
+    }
+
+    /// Executes a single deploy from the given request, taking the native transfer shortcut where
+    /// applicable.
+    ///
+    /// If the compiled Wasm engine fails in a way the interpreter might not have, the deploy is
+    /// executed again with the interpreter, so that the result doesn't depend on the engine used.
+    fn execute_deploy_item(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        exec_request: &ExecuteRequest,
+        deploy_item: DeployItem,
+    ) -> Result<ExecutionResult, Error> {
+        if self.config().wasm_engine() == WasmEngineKind::Interpreter {
+            return self.execute_deploy_item_with(
+                correlation_id,
+                executor,
+                exec_request,
+                deploy_item,
+            );
+        }
+
+        let _ = wasm_engine::take_interpreter_fallback();
+        let result = self.execute_deploy_item_with(
+            correlation_id,
+            executor,
+            exec_request,
+            deploy_item.clone(),
+        );
+        if !wasm_engine::take_interpreter_fallback() {
+            return result;
+        }
+
+        warn!(
+            deploy_hash = ?deploy_item.deploy_hash,
+            "re-executing deploy with the wasm interpreter"
+        );
+        let executor = executor.with_interpreter();
+        self.execute_deploy_item_with(correlation_id, &executor, exec_request, deploy_item)
+    }
+
+    fn execute_deploy_item_with(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        exec_request: &ExecuteRequest,
+        deploy_item: DeployItem,
+    ) -> Result<ExecutionResult, Error> {
+        match deploy_item.session {
+            ExecutableDeployItem::Transfer { .. } => self.transfer(
+                correlation_id,
+                executor,
+                exec_request.protocol_version,
+                exec_request.parent_state_hash,
+                BlockTime::new(exec_request.block_time),
+                deploy_item,
+                exec_request.proposer.clone(),
+            ),
+            _ => self.deploy(
+                correlation_id,
+                executor,
+                exec_request.protocol_version,
+                exec_request.parent_state_hash,
+                BlockTime::new(exec_request.block_time),
+                deploy_item,
+                exec_request.proposer.clone(),
+            ),
+        }
+    }
+
     fn get_authorized_account(
         &self,
         correlation_id: CorrelationId,
@@ -1161,6 +1357,9 @@ where
             session_result = session_result.with_journal(tracking_copy.borrow().execution_journal())
         }
 
+        executor.record_phase_cost(Phase::Payment, payment_result.cost());
+        executor.record_phase_cost(Phase::Session, session_result.cost());
+
         let mut execution_result_builder = ExecutionResultBuilder::new();
         execution_result_builder.set_payment_execution_result(payment_result);
         execution_result_builder.set_session_execution_result(session_result);
@@ -1516,6 +1715,7 @@ where
         };
 
         // Transfer the contents of the rewards purse to block proposer
+        executor.record_phase_cost(Phase::Payment, payment_result.cost());
         execution_result_builder.set_payment_execution_result(payment_result);
 
         // Begin session logic handling
@@ -1622,6 +1822,7 @@ where
 
         // NOTE: session_code_spec_3: (do not include session execution effects in
         // results) is enforced in execution_result_builder.build()
+        executor.record_phase_cost(Phase::Session, session_result.cost());
         execution_result_builder.set_session_execution_result(session_result);
 
         // payment_code_spec_5: run finalize process
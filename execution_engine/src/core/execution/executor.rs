@@ -16,11 +16,12 @@ use crate::{
             ExecError,
         },
         execution::{address_generator::AddressGenerator, Error},
-        runtime::{Runtime, RuntimeStack},
+        runtime::{trace::ExecutionTracer, Runtime, RuntimeStack},
         runtime_context::RuntimeContext,
         tracking_copy::{TrackingCopy, TrackingCopyExt},
+        wasm_engine::WasmEngineKind,
     },
-    shared::newtypes::CorrelationId,
+    shared::{newtypes::CorrelationId, wasm_module_cache::WasmModuleCache},
     storage::global_state::StateReader,
 };
 
@@ -35,12 +36,61 @@ fn try_get_amount(runtime_args: &RuntimeArgs) -> Result<U512, ExecError> {
 /// Executor object deals with execution of WASM modules.
 pub struct Executor {
     config: EngineConfig,
+    execution_tracer: Option<ExecutionTracer>,
+    wasm_module_cache: Option<WasmModuleCache>,
 }
 
 impl Executor {
     /// Creates new executor object.
     pub fn new(config: EngineConfig) -> Self {
-        Executor { config }
+        Executor {
+            config,
+            execution_tracer: None,
+            wasm_module_cache: None,
+        }
+    }
+
+    /// Creates new executor object which records host function calls to `execution_tracer`.
+    pub(crate) fn new_with_tracer(config: EngineConfig, execution_tracer: ExecutionTracer) -> Self {
+        Executor {
+            config,
+            execution_tracer: Some(execution_tracer),
+            wasm_module_cache: None,
+        }
+    }
+
+    /// Sets the cache of deserialized stored contract modules used by this executor.
+    pub(crate) fn with_wasm_module_cache(
+        mut self,
+        wasm_module_cache: Option<WasmModuleCache>,
+    ) -> Self {
+        self.wasm_module_cache = wasm_module_cache;
+        self
+    }
+
+    /// Returns a copy of this executor which executes Wasm with the interpreter, discarding
+    /// anything traced so far.
+    ///
+    /// The copy doesn't use the module cache: modules it prepared would lack their compiled form,
+    /// and would then be interpreted by later executions too.
+    pub(crate) fn with_interpreter(&self) -> Self {
+        let mut config = self.config.clone();
+        config.wasm_engine = WasmEngineKind::Interpreter;
+        if let Some(execution_tracer) = &self.execution_tracer {
+            execution_tracer.clear();
+        }
+        Executor {
+            config,
+            execution_tracer: self.execution_tracer.clone(),
+            wasm_module_cache: None,
+        }
+    }
+
+    /// Records the gas consumed by the given phase of a deploy, if tracing.
+    pub(crate) fn record_phase_cost(&self, phase: Phase, cost: Gas) {
+        if let Some(execution_tracer) = &self.execution_tracer {
+            execution_tracer.record_phase_cost(phase, cost);
+        }
     }
 
     /// Executes a WASM module.
@@ -100,7 +150,12 @@ impl Executor {
             spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config.clone(), context);
+        let mut runtime = Runtime::new(
+            self.config.clone(),
+            context,
+            self.execution_tracer.clone(),
+            self.wasm_module_cache.clone(),
+        );
 
         let result = match execution_kind {
             ExecutionKind::Module(module_bytes) => {
@@ -190,7 +245,12 @@ impl Executor {
 
         // Standard payment is executed in the calling account's context; the stack already
         // captures that.
-        let mut runtime = Runtime::new(self.config.clone(), runtime_context);
+        let mut runtime = Runtime::new(
+            self.config.clone(),
+            runtime_context,
+            self.execution_tracer.clone(),
+            self.wasm_module_cache.clone(),
+        );
 
         match runtime.call_host_standard_payment(stack) {
             Ok(()) => ExecutionResult::Success {
@@ -306,7 +366,12 @@ impl Executor {
             remaining_spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config.clone(), runtime_context);
+        let mut runtime = Runtime::new(
+            self.config.clone(),
+            runtime_context,
+            self.execution_tracer.clone(),
+            self.wasm_module_cache.clone(),
+        );
 
         // DO NOT alter this logic to call a system contract directly (such as via mint_internal,
         // etc). Doing so would bypass necessary context based security checks in some use cases. It
@@ -4,6 +4,9 @@ pub mod memory_resolver;
 pub(crate) mod v1_function_index;
 mod v1_resolver;
 
+#[cfg(feature = "compiled-wasm-engine")]
+pub(crate) use self::v1_resolver::resolve_host_function;
+
 use casper_wasmi::ModuleImportResolver;
 
 use casper_types::ProtocolVersion;
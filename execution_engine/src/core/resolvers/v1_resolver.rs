@@ -39,220 +39,13 @@ impl ModuleImportResolver for RuntimeModuleImportResolver {
         field_name: &str,
         _signature: &Signature,
     ) -> Result<FuncRef, InterpreterError> {
-        let func_ref = match field_name {
-            "casper_read_value" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::ReadFuncIndex.into(),
-            ),
-            "casper_load_named_keys" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::LoadNamedKeysFuncIndex.into(),
-            ),
-            "casper_write" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], None),
-                FunctionIndex::WriteFuncIndex.into(),
-            ),
-            "casper_add" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], None),
-                FunctionIndex::AddFuncIndex.into(),
-            ),
-            "casper_new_uref" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], None),
-                FunctionIndex::NewFuncIndex.into(),
-            ),
-            "casper_ret" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], None),
-                FunctionIndex::RetFuncIndex.into(),
-            ),
-            "casper_get_key" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 5][..], Some(ValueType::I32)),
-                FunctionIndex::GetKeyFuncIndex.into(),
-            ),
-            "casper_has_key" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::HasKeyFuncIndex.into(),
-            ),
-            "casper_put_key" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], None),
-                FunctionIndex::PutKeyFuncIndex.into(),
-            ),
-            "gas" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 1][..], None),
-                FunctionIndex::GasFuncIndex.into(),
-            ),
-            "casper_is_valid_uref" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::IsValidURefFnIndex.into(),
-            ),
-            "casper_revert" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 1][..], None),
-                FunctionIndex::RevertFuncIndex.into(),
-            ),
-            "casper_add_associated_key" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::AddAssociatedKeyFuncIndex.into(),
-            ),
-            "casper_remove_associated_key" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::RemoveAssociatedKeyFuncIndex.into(),
-            ),
-            "casper_update_associated_key" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::UpdateAssociatedKeyFuncIndex.into(),
-            ),
-            "casper_set_action_threshold" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::SetActionThresholdFuncIndex.into(),
-            ),
-            "casper_remove_key" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], None),
-                FunctionIndex::RemoveKeyFuncIndex.into(),
-            ),
-            "casper_get_caller" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 1][..], Some(ValueType::I32)),
-                FunctionIndex::GetCallerIndex.into(),
-            ),
-            "casper_get_blocktime" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 1][..], None),
-                FunctionIndex::GetBlocktimeIndex.into(),
-            ),
-            "casper_create_purse" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::CreatePurseIndex.into(),
-            ),
-            "casper_transfer_to_account" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 7][..], Some(ValueType::I32)),
-                FunctionIndex::TransferToAccountIndex.into(),
-            ),
-            "casper_transfer_from_purse_to_account" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 9][..], Some(ValueType::I32)),
-                FunctionIndex::TransferFromPurseToAccountIndex.into(),
-            ),
-            "casper_transfer_from_purse_to_purse" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 8][..], Some(ValueType::I32)),
-                FunctionIndex::TransferFromPurseToPurseIndex.into(),
-            ),
-            "casper_get_balance" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::GetBalanceIndex.into(),
-            ),
-            "casper_get_phase" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 1][..], None),
-                FunctionIndex::GetPhaseIndex.into(),
-            ),
-            "casper_get_system_contract" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::GetSystemContractIndex.into(),
-            ),
-            "casper_get_main_purse" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 1][..], None),
-                FunctionIndex::GetMainPurseIndex.into(),
-            ),
-            "casper_read_host_buffer" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::ReadHostBufferIndex.into(),
-            ),
-            "casper_create_contract_package_at_hash" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], None),
-                FunctionIndex::CreateContractPackageAtHash.into(),
-            ),
-            "casper_create_contract_user_group" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 8][..], Some(ValueType::I32)),
-                FunctionIndex::CreateContractUserGroup.into(),
-            ),
-            "casper_add_contract_version" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 10][..], Some(ValueType::I32)),
-                FunctionIndex::AddContractVersion.into(),
-            ),
-            "casper_disable_contract_version" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
-                FunctionIndex::DisableContractVersion.into(),
-            ),
-            "casper_call_contract" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 7][..], Some(ValueType::I32)),
-                FunctionIndex::CallContractFuncIndex.into(),
-            ),
-            "casper_call_versioned_contract" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 9][..], Some(ValueType::I32)),
-                FunctionIndex::CallVersionedContract.into(),
-            ),
-            "casper_get_named_arg_size" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::GetRuntimeArgsizeIndex.into(),
-            ),
-            "casper_get_named_arg" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
-                FunctionIndex::GetRuntimeArgIndex.into(),
-            ),
-            "casper_remove_contract_user_group" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
-                FunctionIndex::RemoveContractUserGroupIndex.into(),
-            ),
-            "casper_provision_contract_user_group_uref" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 5][..], Some(ValueType::I32)),
-                FunctionIndex::ExtendContractUserGroupURefsIndex.into(),
-            ),
-            "casper_remove_contract_user_group_urefs" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 6][..], Some(ValueType::I32)),
-                FunctionIndex::RemoveContractUserGroupURefsIndex.into(),
-            ),
-            "casper_blake2b" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
-                FunctionIndex::Blake2b.into(),
-            ),
-            "casper_record_transfer" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 10][..], Some(ValueType::I32)),
-                FunctionIndex::RecordTransfer.into(),
-            ),
-            "casper_record_era_info" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
-                FunctionIndex::RecordEraInfo.into(),
-            ),
-            "casper_load_call_stack" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::LoadCallStack.into(),
-            ),
-            #[cfg(feature = "test-support")]
-            "casper_print" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], None),
-                FunctionIndex::PrintIndex.into(),
-            ),
-            "casper_dictionary_get" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 5][..], Some(ValueType::I32)),
-                FunctionIndex::DictionaryGetFuncIndex.into(),
-            ),
-            "casper_dictionary_read" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
-                FunctionIndex::DictionaryReadFuncIndex.into(),
-            ),
-            "casper_dictionary_put" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 6][..], Some(ValueType::I32)),
-                FunctionIndex::DictionaryPutFuncIndex.into(),
-            ),
-            "casper_new_dictionary" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 1][..], Some(ValueType::I32)),
-                FunctionIndex::NewDictionaryFuncIndex.into(),
-            ),
-            "casper_load_authorization_keys" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::LoadAuthorizationKeys.into(),
-            ),
-            "casper_random_bytes" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
-                FunctionIndex::RandomBytes.into(),
-            ),
-            "casper_enable_contract_version" => FuncInstance::alloc_host(
-                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
-                FunctionIndex::EnableContractVersion.into(),
-            ),
-            _ => {
-                return Err(InterpreterError::Function(format!(
-                    "host module doesn't export function with name {}",
-                    field_name
-                )));
-            }
-        };
-        Ok(func_ref)
+        let (signature, function_index) = resolve_host_function(field_name).ok_or_else(|| {
+            InterpreterError::Function(format!(
+                "host module doesn't export function with name {}",
+                field_name
+            ))
+        })?;
+        Ok(FuncInstance::alloc_host(signature, function_index.into()))
     }
 
     fn resolve_memory(
@@ -297,3 +90,216 @@ impl ModuleImportResolver for RuntimeModuleImportResolver {
         }
     }
 }
+
+/// Returns the signature and index of the host function imported under `field_name`, if any.
+pub(crate) fn resolve_host_function(field_name: &str) -> Option<(Signature, FunctionIndex)> {
+    let host_function = match field_name {
+        "casper_read_value" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::ReadFuncIndex,
+        ),
+        "casper_load_named_keys" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::LoadNamedKeysFuncIndex,
+        ),
+        "casper_write" => (
+            Signature::new(&[ValueType::I32; 4][..], None),
+            FunctionIndex::WriteFuncIndex,
+        ),
+        "casper_add" => (
+            Signature::new(&[ValueType::I32; 4][..], None),
+            FunctionIndex::AddFuncIndex,
+        ),
+        "casper_new_uref" => (
+            Signature::new(&[ValueType::I32; 3][..], None),
+            FunctionIndex::NewFuncIndex,
+        ),
+        "casper_ret" => (
+            Signature::new(&[ValueType::I32; 2][..], None),
+            FunctionIndex::RetFuncIndex,
+        ),
+        "casper_get_key" => (
+            Signature::new(&[ValueType::I32; 5][..], Some(ValueType::I32)),
+            FunctionIndex::GetKeyFuncIndex,
+        ),
+        "casper_has_key" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::HasKeyFuncIndex,
+        ),
+        "casper_put_key" => (
+            Signature::new(&[ValueType::I32; 4][..], None),
+            FunctionIndex::PutKeyFuncIndex,
+        ),
+        "gas" => (
+            Signature::new(&[ValueType::I32; 1][..], None),
+            FunctionIndex::GasFuncIndex,
+        ),
+        "casper_is_valid_uref" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::IsValidURefFnIndex,
+        ),
+        "casper_revert" => (
+            Signature::new(&[ValueType::I32; 1][..], None),
+            FunctionIndex::RevertFuncIndex,
+        ),
+        "casper_add_associated_key" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::AddAssociatedKeyFuncIndex,
+        ),
+        "casper_remove_associated_key" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::RemoveAssociatedKeyFuncIndex,
+        ),
+        "casper_update_associated_key" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::UpdateAssociatedKeyFuncIndex,
+        ),
+        "casper_set_action_threshold" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::SetActionThresholdFuncIndex,
+        ),
+        "casper_remove_key" => (
+            Signature::new(&[ValueType::I32; 2][..], None),
+            FunctionIndex::RemoveKeyFuncIndex,
+        ),
+        "casper_get_caller" => (
+            Signature::new(&[ValueType::I32; 1][..], Some(ValueType::I32)),
+            FunctionIndex::GetCallerIndex,
+        ),
+        "casper_get_blocktime" => (
+            Signature::new(&[ValueType::I32; 1][..], None),
+            FunctionIndex::GetBlocktimeIndex,
+        ),
+        "casper_create_purse" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::CreatePurseIndex,
+        ),
+        "casper_transfer_to_account" => (
+            Signature::new(&[ValueType::I32; 7][..], Some(ValueType::I32)),
+            FunctionIndex::TransferToAccountIndex,
+        ),
+        "casper_transfer_from_purse_to_account" => (
+            Signature::new(&[ValueType::I32; 9][..], Some(ValueType::I32)),
+            FunctionIndex::TransferFromPurseToAccountIndex,
+        ),
+        "casper_transfer_from_purse_to_purse" => (
+            Signature::new(&[ValueType::I32; 8][..], Some(ValueType::I32)),
+            FunctionIndex::TransferFromPurseToPurseIndex,
+        ),
+        "casper_get_balance" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::GetBalanceIndex,
+        ),
+        "casper_get_phase" => (
+            Signature::new(&[ValueType::I32; 1][..], None),
+            FunctionIndex::GetPhaseIndex,
+        ),
+        "casper_get_system_contract" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::GetSystemContractIndex,
+        ),
+        "casper_get_main_purse" => (
+            Signature::new(&[ValueType::I32; 1][..], None),
+            FunctionIndex::GetMainPurseIndex,
+        ),
+        "casper_read_host_buffer" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::ReadHostBufferIndex,
+        ),
+        "casper_create_contract_package_at_hash" => (
+            Signature::new(&[ValueType::I32; 3][..], None),
+            FunctionIndex::CreateContractPackageAtHash,
+        ),
+        "casper_create_contract_user_group" => (
+            Signature::new(&[ValueType::I32; 8][..], Some(ValueType::I32)),
+            FunctionIndex::CreateContractUserGroup,
+        ),
+        "casper_add_contract_version" => (
+            Signature::new(&[ValueType::I32; 10][..], Some(ValueType::I32)),
+            FunctionIndex::AddContractVersion,
+        ),
+        "casper_disable_contract_version" => (
+            Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+            FunctionIndex::DisableContractVersion,
+        ),
+        "casper_call_contract" => (
+            Signature::new(&[ValueType::I32; 7][..], Some(ValueType::I32)),
+            FunctionIndex::CallContractFuncIndex,
+        ),
+        "casper_call_versioned_contract" => (
+            Signature::new(&[ValueType::I32; 9][..], Some(ValueType::I32)),
+            FunctionIndex::CallVersionedContract,
+        ),
+        "casper_get_named_arg_size" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::GetRuntimeArgsizeIndex,
+        ),
+        "casper_get_named_arg" => (
+            Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+            FunctionIndex::GetRuntimeArgIndex,
+        ),
+        "casper_remove_contract_user_group" => (
+            Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+            FunctionIndex::RemoveContractUserGroupIndex,
+        ),
+        "casper_provision_contract_user_group_uref" => (
+            Signature::new(&[ValueType::I32; 5][..], Some(ValueType::I32)),
+            FunctionIndex::ExtendContractUserGroupURefsIndex,
+        ),
+        "casper_remove_contract_user_group_urefs" => (
+            Signature::new(&[ValueType::I32; 6][..], Some(ValueType::I32)),
+            FunctionIndex::RemoveContractUserGroupURefsIndex,
+        ),
+        "casper_blake2b" => (
+            Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+            FunctionIndex::Blake2b,
+        ),
+        "casper_record_transfer" => (
+            Signature::new(&[ValueType::I32; 10][..], Some(ValueType::I32)),
+            FunctionIndex::RecordTransfer,
+        ),
+        "casper_record_era_info" => (
+            Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+            FunctionIndex::RecordEraInfo,
+        ),
+        "casper_load_call_stack" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::LoadCallStack,
+        ),
+        #[cfg(feature = "test-support")]
+        "casper_print" => (
+            Signature::new(&[ValueType::I32; 2][..], None),
+            FunctionIndex::PrintIndex,
+        ),
+        "casper_dictionary_get" => (
+            Signature::new(&[ValueType::I32; 5][..], Some(ValueType::I32)),
+            FunctionIndex::DictionaryGetFuncIndex,
+        ),
+        "casper_dictionary_read" => (
+            Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+            FunctionIndex::DictionaryReadFuncIndex,
+        ),
+        "casper_dictionary_put" => (
+            Signature::new(&[ValueType::I32; 6][..], Some(ValueType::I32)),
+            FunctionIndex::DictionaryPutFuncIndex,
+        ),
+        "casper_new_dictionary" => (
+            Signature::new(&[ValueType::I32; 1][..], Some(ValueType::I32)),
+            FunctionIndex::NewDictionaryFuncIndex,
+        ),
+        "casper_load_authorization_keys" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::LoadAuthorizationKeys,
+        ),
+        "casper_random_bytes" => (
+            Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
+            FunctionIndex::RandomBytes,
+        ),
+        "casper_enable_contract_version" => (
+            Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+            FunctionIndex::EnableContractVersion,
+        ),
+        _ => return None,
+    };
+    Some(host_function)
+}
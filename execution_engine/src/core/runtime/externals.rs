@@ -13,7 +13,7 @@ use casper_types::{
     StoredValue, URef, U512, UREF_SERIALIZED_LENGTH,
 };
 
-use super::{args::Args, Error, Runtime};
+use super::{args::Args, trace::HostFunctionArg, Error, Runtime, RuntimeStack};
 use crate::{
     core::resolvers::v1_function_index::FunctionIndex,
     shared::host_function_costs::{Cost, HostFunction},
@@ -32,6 +32,118 @@ where
     ) -> Result<Option<RuntimeValue>, Trap> {
         let func = FunctionIndex::try_from(index).expect("unknown function index");
 
+        // Gas metering calls are injected into every Wasm module and aren't worth tracing.
+        let execution_tracer = match &self.execution_tracer {
+            Some(execution_tracer) if func != FunctionIndex::GasFuncIndex => {
+                execution_tracer.clone()
+            }
+            _ => return self.invoke_host_function(func, args),
+        };
+
+        execution_tracer.enter(
+            func.host_function_name(),
+            self.trace_args(func, &args),
+            self.context.phase(),
+            self.stack.as_ref().map_or(0, RuntimeStack::len),
+            self.stack
+                .as_ref()
+                .and_then(RuntimeStack::current_frame)
+                .cloned(),
+        );
+        let gas_before = self.gas_counter();
+        let result = self.invoke_host_function(func, args);
+        let gas_consumed = self
+            .gas_counter()
+            .checked_sub(gas_before)
+            .unwrap_or_default();
+        execution_tracer.exit(gas_consumed, result.as_ref().err().map(ToString::to_string));
+        result
+    }
+}
+
+impl<'a, R> Runtime<'a, R>
+where
+    R: StateReader<Key, StoredValue>,
+    R::Error: Into<Error>,
+{
+    /// Decodes the arguments of a host function call identifying what it operates on, for tracing.
+    ///
+    /// This only reads from the module's memory, so it doesn't affect the outcome of the call.
+    fn trace_args(&self, func: FunctionIndex, args: &RuntimeArgs) -> Vec<HostFunctionArg> {
+        let arg = |index: usize| args.nth_checked::<u32>(index).ok();
+        let key = |ptr_index: usize| {
+            let (ptr, size) = (arg(ptr_index)?, arg(ptr_index + 1)?);
+            self.t_from_mem::<Key>(ptr, size)
+                .ok()
+                .map(HostFunctionArg::Key)
+        };
+        let contract_hash = |ptr_index: usize| {
+            let (ptr, size) = (arg(ptr_index)?, arg(ptr_index + 1)?);
+            self.t_from_mem::<ContractHash>(ptr, size)
+                .ok()
+                .map(|contract_hash| HostFunctionArg::Key(contract_hash.into()))
+        };
+        let contract_package_hash = |ptr_index: usize| {
+            let (ptr, size) = (arg(ptr_index)?, arg(ptr_index + 1)?);
+            self.t_from_mem::<ContractPackageHash>(ptr, size)
+                .ok()
+                .map(|contract_package_hash| HostFunctionArg::Key(contract_package_hash.into()))
+        };
+        let uref = |ptr_index: usize| {
+            let (ptr, size) = (arg(ptr_index)?, arg(ptr_index + 1)?);
+            self.t_from_mem::<URef>(ptr, size)
+                .ok()
+                .map(|uref| HostFunctionArg::Key(Key::URef(uref)))
+        };
+        let name = |ptr_index: usize| {
+            let (ptr, size) = (arg(ptr_index)?, arg(ptr_index + 1)?);
+            self.t_from_mem::<String>(ptr, size)
+                .ok()
+                .map(HostFunctionArg::Name)
+        };
+        let dictionary_item_key = |ptr_index: usize| {
+            let (ptr, size) = (arg(ptr_index)?, arg(ptr_index + 1)?);
+            self.checked_memory_slice(ptr as usize, size as usize, |utf8_bytes| {
+                String::from_utf8(utf8_bytes.to_vec()).ok()
+            })
+            .ok()
+            .flatten()
+            .map(HostFunctionArg::Name)
+        };
+        let byte_length = |index: usize| arg(index).map(HostFunctionArg::ByteLength);
+
+        let args = match func {
+            FunctionIndex::ReadFuncIndex => vec![key(0)],
+            FunctionIndex::WriteFuncIndex | FunctionIndex::AddFuncIndex => {
+                vec![key(0), byte_length(3)]
+            }
+            FunctionIndex::NewFuncIndex => vec![byte_length(2)],
+            FunctionIndex::RetFuncIndex => vec![byte_length(1)],
+            FunctionIndex::GetKeyFuncIndex
+            | FunctionIndex::HasKeyFuncIndex
+            | FunctionIndex::RemoveKeyFuncIndex => vec![name(0)],
+            FunctionIndex::PutKeyFuncIndex => vec![name(0), key(2)],
+            FunctionIndex::RevertFuncIndex => vec![arg(0).map(HostFunctionArg::ErrorCode)],
+            FunctionIndex::CallContractFuncIndex => {
+                vec![contract_hash(0), name(2), byte_length(5)]
+            }
+            FunctionIndex::CallVersionedContract => {
+                vec![contract_package_hash(0), name(4), byte_length(7)]
+            }
+            FunctionIndex::DictionaryGetFuncIndex => vec![uref(0), dictionary_item_key(2)],
+            FunctionIndex::DictionaryPutFuncIndex => {
+                vec![uref(0), dictionary_item_key(2), byte_length(5)]
+            }
+            _ => vec![],
+        };
+        args.into_iter().flatten().collect()
+    }
+
+    fn invoke_host_function(
+        &mut self,
+        func: FunctionIndex,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
         let host_function_costs = self.config.wasm_config().take_host_function_costs();
 
         match func {
@@ -149,6 +149,21 @@ where
         }
     }
 
+    fn balance_exists(&mut self, uref: URef) -> Result<bool, Error> {
+        let maybe_value = self
+            .context
+            .read_gs_direct_without_noting(&Key::Balance(uref.addr()))
+            .map_err(|exec_error| <Option<Error>>::from(exec_error).unwrap_or(Error::Storage))?;
+        match maybe_value {
+            Some(StoredValue::CLValue(value)) => {
+                CLValue::into_t::<U512>(value).map_err(|_| Error::CLValue)?;
+                Ok(true)
+            }
+            Some(_cl_value) => Err(Error::CLValue),
+            None => Ok(false),
+        }
+    }
+
     fn write_balance(&mut self, uref: URef, balance: U512) -> Result<(), Error> {
         let cl_value = CLValue::from_t(balance).map_err(|_| Error::CLValue)?;
         self.context
@@ -7,6 +7,7 @@ mod host_function_flag;
 mod mint_internal;
 pub mod stack;
 mod standard_payment_internal;
+pub mod trace;
 mod utils;
 
 use std::{
@@ -14,15 +15,19 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     convert::{TryFrom, TryInto},
     iter::FromIterator,
+    sync::Arc,
 };
 
 use casper_wasm::elements::Module;
-use casper_wasmi::{MemoryRef, Trap, TrapCode};
+use casper_wasmi::{Trap, TrapCode};
 use tracing::error;
 
 #[cfg(feature = "test-support")]
 use casper_wasmi::RuntimeValue;
 
+#[cfg(feature = "test-support")]
+use crate::core::wasm_engine::WasmInstance;
+
 use casper_types::{
     account::{
         Account, AccountHash, ActionType, AddKeyFailure, RemoveKeyFailure, SetThresholdFailure,
@@ -50,12 +55,14 @@ use crate::{
     core::{
         engine_state::EngineConfig,
         execution::{self, Error},
-        runtime::host_function_flag::HostFunctionFlag,
+        runtime::{host_function_flag::HostFunctionFlag, trace::ExecutionTracer},
         runtime_context::{self, RuntimeContext},
         tracking_copy::TrackingCopyExt,
+        wasm_engine::{self, PreparedModule, WasmMemory},
     },
     shared::{
         host_function_costs::{Cost, HostFunction},
+        wasm_module_cache::WasmModuleCache,
         wasm_prep::{self, PreprocessingError},
     },
     storage::global_state::StateReader,
@@ -65,6 +72,7 @@ use crate::{
     },
 };
 pub use stack::{RuntimeStack, RuntimeStackFrame, RuntimeStackOverflow};
+pub use trace::{ExecutionTrace, HostFunctionArg, HostFunctionCall};
 
 enum CallContractIdentifier {
     Contract {
@@ -79,12 +87,14 @@ enum CallContractIdentifier {
 /// Represents the runtime properties of a WASM execution.
 pub struct Runtime<'a, R> {
     config: EngineConfig,
-    memory: Option<MemoryRef>,
-    module: Option<Module>,
+    memory: Option<WasmMemory>,
+    module: Option<Arc<PreparedModule>>,
     host_buffer: Option<CLValue>,
     context: RuntimeContext<'a, R>,
     stack: Option<RuntimeStack>,
     host_function_flag: HostFunctionFlag,
+    execution_tracer: Option<ExecutionTracer>,
+    wasm_module_cache: Option<WasmModuleCache>,
 }
 
 impl<'a, R> Runtime<'a, R>
@@ -92,8 +102,14 @@ where
     R: StateReader<Key, StoredValue>,
     R::Error: Into<Error>,
 {
-    /// Creates a new runtime instance.
-    pub(crate) fn new(config: EngineConfig, context: RuntimeContext<'a, R>) -> Self {
+    /// Creates a new runtime instance, optionally recording host function calls to
+    /// `execution_tracer`.
+    pub(crate) fn new(
+        config: EngineConfig,
+        context: RuntimeContext<'a, R>,
+        execution_tracer: Option<ExecutionTracer>,
+        wasm_module_cache: Option<WasmModuleCache>,
+    ) -> Self {
         Runtime {
             config,
             memory: None,
@@ -102,15 +118,18 @@ where
             context,
             stack: None,
             host_function_flag: HostFunctionFlag::default(),
+            execution_tracer,
+            wasm_module_cache,
         }
     }
 
-    /// Creates a new runtime instance by cloning the config, and host function flag from `self`.
+    /// Creates a new runtime instance by cloning the config, host function flag and execution
+    /// tracer from `self`.
     fn new_invocation_runtime(
         &self,
         context: RuntimeContext<'a, R>,
-        module: Module,
-        memory: MemoryRef,
+        module: Arc<PreparedModule>,
+        memory: WasmMemory,
         stack: RuntimeStack,
     ) -> Self {
         Self::check_preconditions(&stack);
@@ -122,6 +141,8 @@ where
             context,
             stack: Some(stack),
             host_function_flag: self.host_function_flag.clone(),
+            execution_tracer: self.execution_tracer.clone(),
+            wasm_module_cache: self.wasm_module_cache.clone(),
         }
     }
 
@@ -140,6 +161,8 @@ where
             context,
             stack: Some(stack),
             host_function_flag: self.host_function_flag.clone(),
+            execution_tracer: self.execution_tracer.clone(),
+            wasm_module_cache: self.wasm_module_cache.clone(),
         }
     }
 
@@ -206,8 +229,7 @@ where
         // that calls a user defined function with a validated slice of memory. This allows
         // usage patterns that does not involve copying data onto heap first i.e. deserialize
         // values without copying data first, etc.
-        // NOTE: Depending on the VM backend used in future, this may change, as not all VMs may
-        // support direct memory access.
+        // Both Wasm engines support direct memory access; see `WasmMemory`.
         self.try_get_memory()?
             .with_direct_access(|buffer| {
                 let end = offset.checked_add(size).ok_or_else(|| {
@@ -1070,8 +1092,9 @@ where
         let protocol_version = self.context.protocol_version();
         let engine_config = self.config.clone();
         let module = wasm_prep::preprocess(*engine_config.wasm_config(), module_bytes)?;
+        let module = Arc::new(wasm_engine::prepare(module, &engine_config)?);
         let (instance, memory) =
-            utils::instance_and_memory(module.clone(), protocol_version, &engine_config)?;
+            wasm_engine::instantiate(&module, protocol_version, &engine_config)?;
         self.memory = Some(memory);
         self.module = Some(module);
         self.stack = Some(stack);
@@ -1081,7 +1104,7 @@ where
             AccessRights::WRITE,
         )?);
 
-        let result = instance.invoke_export(DEFAULT_ENTRY_POINT_NAME, &[], self);
+        let result = instance.invoke_export(DEFAULT_ENTRY_POINT_NAME, self);
 
         let error = match result {
             Err(error) => error,
@@ -1095,7 +1118,7 @@ where
         };
 
         #[cfg(feature = "test-support")]
-        dump_runtime_stack_info(instance, engine_config.wasm_config().max_stack_height);
+        dump_runtime_stack_info(&instance, engine_config.wasm_config().max_stack_height);
 
         if let Some(host_error) = error.as_host_error() {
             // If the "error" was in fact a trap caused by calling `ret` then
@@ -1164,7 +1187,7 @@ where
         }
     }
 
-    fn try_get_memory(&self) -> Result<&MemoryRef, Error> {
+    fn try_get_memory(&self) -> Result<&WasmMemory, Error> {
         self.memory.as_ref().ok_or(Error::WasmPreprocessing(
             PreprocessingError::MissingMemorySection,
         ))
@@ -1172,7 +1195,8 @@ where
 
     fn try_get_module(&self) -> Result<&Module, Error> {
         self.module
-            .as_ref()
+            .as_deref()
+            .map(PreparedModule::module)
             .ok_or(Error::WasmPreprocessing(PreprocessingError::MissingModule))
     }
 
@@ -1383,7 +1407,7 @@ where
             return self.call_host_auction(entry_point.name(), &context_args, access_rights, stack);
         }
 
-        let module: Module = {
+        let module: Arc<PreparedModule> = {
             let wasm_key = contract.contract_wasm_key();
 
             let contract_wasm: ContractWasm = match self.context.read_gs(&wasm_key)? {
@@ -1392,7 +1416,17 @@ where
                 None => return Err(Error::KeyNotFound(context_key)),
             };
 
-            casper_wasm::deserialize_buffer(contract_wasm.bytes())?
+            match &self.wasm_module_cache {
+                Some(wasm_module_cache) => wasm_module_cache.get_or_insert_with(
+                    contract.contract_wasm_hash(),
+                    contract_wasm.bytes(),
+                    |bytes| wasm_engine::deserialize_and_prepare(bytes, &self.config),
+                )?,
+                None => Arc::new(wasm_engine::deserialize_and_prepare(
+                    contract_wasm.bytes(),
+                    &self.config,
+                )?),
+            }
         };
 
         let context = self.context.new_from_self(
@@ -1403,11 +1437,10 @@ where
             context_args,
         );
         let protocol_version = self.context.protocol_version();
-        let (instance, memory) =
-            utils::instance_and_memory(module.clone(), protocol_version, &self.config)?;
+        let (instance, memory) = wasm_engine::instantiate(&module, protocol_version, &self.config)?;
         let runtime = &mut Runtime::new_invocation_runtime(self, context, module, memory, stack);
 
-        let result = instance.invoke_export(entry_point.name(), &[], runtime);
+        let result = instance.invoke_export(entry_point.name(), runtime);
 
         // The `runtime`'s context was initialized with our counter from before the call and any gas
         // charged by the sub-call was added to its counter - so let's copy the correct value of the
@@ -1439,7 +1472,7 @@ where
         };
 
         #[cfg(feature = "test-support")]
-        dump_runtime_stack_info(instance, self.config.wasm_config().max_stack_height);
+        dump_runtime_stack_info(&instance, self.config.wasm_config().max_stack_height);
 
         if let Some(host_error) = error.as_host_error() {
             // If the "error" was in fact a trap caused by calling `ret` then this is normal
@@ -2819,6 +2852,9 @@ where
         T: AsRef<[Cost]> + Copy,
     {
         let cost = host_function.calculate_gas_cost(weights);
+        if let Some(execution_tracer) = &self.execution_tracer {
+            execution_tracer.record_cost(cost);
+        }
         self.gas(cost)?;
         Ok(())
     }
@@ -3043,15 +3079,8 @@ where
 }
 
 #[cfg(feature = "test-support")]
-fn dump_runtime_stack_info(instance: casper_wasmi::ModuleRef, max_stack_height: u32) {
-    let globals = instance.globals();
-    let Some(current_runtime_call_stack_height) = globals.last() else {
-        return;
-    };
-
-    if let RuntimeValue::I32(current_runtime_call_stack_height) =
-        current_runtime_call_stack_height.get()
-    {
+fn dump_runtime_stack_info(instance: &WasmInstance, max_stack_height: u32) {
+    if let Some(RuntimeValue::I32(current_runtime_call_stack_height)) = instance.last_global() {
         if current_runtime_call_stack_height > max_stack_height as i32 {
             eprintln!("runtime stack overflow, current={current_runtime_call_stack_height}, max={max_stack_height}");
         }
@@ -0,0 +1,203 @@
+//! Tracing of host function calls made during execution.
+
+use std::{cell::RefCell, rc::Rc};
+
+use casper_types::{system::CallStackElement, Gas, Key, Phase};
+
+/// An argument of a host function call, decoded from the calling module's memory where the raw
+/// argument is a pointer.
+///
+/// Only arguments identifying what the call operates on are recorded; pointers to output buffers
+/// are omitted, as are arguments which couldn't be decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HostFunctionArg {
+    /// A global state key, or the address of a called contract or contract package.
+    Key(Key),
+    /// The name of a named key, an entry point or a dictionary item.
+    Name(String),
+    /// The size in bytes of a serialized value or of serialized runtime arguments.
+    ByteLength(u32),
+    /// The error code passed to `casper_revert`.
+    ErrorCode(u32),
+}
+
+/// A record of a single host function call made by a Wasm module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostFunctionCall {
+    /// The name of the host function, as imported by the Wasm module.
+    pub name: &'static str,
+    /// The decoded arguments passed by the Wasm module.
+    pub args: Vec<HostFunctionArg>,
+    /// The execution phase in which the call was made.
+    pub phase: Phase,
+    /// The height of the call stack when the call was made.
+    pub depth: usize,
+    /// The call stack element of the contract or session code making the call.
+    pub call_stack_element: Option<CallStackElement>,
+    /// The gas charged for the call itself, as per the configured host function costs.
+    pub cost: Gas,
+    /// The total gas consumed while the call was in progress, including the call's own cost and
+    /// that of any contract calls made by it.
+    pub gas_consumed: Gas,
+    /// The trap raised by the call, if any.  This is the case when e.g. reverting, returning from
+    /// a contract, or running out of gas.
+    pub trap: Option<String>,
+}
+
+/// The ordered sequence of host function calls made during the execution of a single deploy, along
+/// with the gas consumed by each of its phases.
+///
+/// Calls made by nested contract calls follow the call which invoked them, with a greater `depth`.
+/// Calls to the gas metering function injected into each Wasm module are not recorded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionTrace {
+    /// The host function calls, in the order in which they were made.
+    pub host_function_calls: Vec<HostFunctionCall>,
+    /// The gas consumed by the payment phase.
+    pub payment_cost: Gas,
+    /// The gas consumed by the session phase.
+    pub session_cost: Gas,
+}
+
+/// A shared handle used by the runtime to record an `ExecutionTrace`.
+///
+/// Clones all share the same underlying trace, so nested runtimes record to the same trace.
+#[derive(Clone, Default)]
+pub(crate) struct ExecutionTracer {
+    inner: Rc<RefCell<TracerState>>,
+}
+
+#[derive(Default)]
+struct TracerState {
+    trace: ExecutionTrace,
+    /// The indices of the calls which have been entered but not yet exited, innermost last.
+    open_calls: Vec<usize>,
+}
+
+impl ExecutionTracer {
+    /// Records the start of a host function call.
+    pub(crate) fn enter(
+        &self,
+        name: &'static str,
+        args: Vec<HostFunctionArg>,
+        phase: Phase,
+        depth: usize,
+        call_stack_element: Option<CallStackElement>,
+    ) {
+        let mut state = self.inner.borrow_mut();
+        let index = state.trace.host_function_calls.len();
+        state.trace.host_function_calls.push(HostFunctionCall {
+            name,
+            args,
+            phase,
+            depth,
+            call_stack_element,
+            cost: Gas::default(),
+            gas_consumed: Gas::default(),
+            trap: None,
+        });
+        state.open_calls.push(index);
+    }
+
+    /// Records the cost charged for the innermost open host function call.
+    pub(crate) fn record_cost(&self, cost: Gas) {
+        let mut state = self.inner.borrow_mut();
+        if let Some(index) = state.open_calls.last().copied() {
+            state.trace.host_function_calls[index].cost = cost;
+        }
+    }
+
+    /// Records the end of the innermost open host function call.
+    pub(crate) fn exit(&self, gas_consumed: Gas, trap: Option<String>) {
+        let mut state = self.inner.borrow_mut();
+        if let Some(index) = state.open_calls.pop() {
+            let call = &mut state.trace.host_function_calls[index];
+            call.gas_consumed = gas_consumed;
+            call.trap = trap;
+        }
+    }
+
+    /// Records the gas consumed by the given phase of the deploy.  Costs of other phases than
+    /// payment and session are ignored.
+    pub(crate) fn record_phase_cost(&self, phase: Phase, cost: Gas) {
+        let mut state = self.inner.borrow_mut();
+        match phase {
+            Phase::Payment => state.trace.payment_cost = cost,
+            Phase::Session => state.trace.session_cost = cost,
+            Phase::System | Phase::FinalizePayment => {}
+        }
+    }
+
+    /// Discards everything recorded so far.
+    pub(crate) fn clear(&self) {
+        *self.inner.borrow_mut() = TracerState::default();
+    }
+
+    /// Returns a copy of the trace recorded so far.
+    pub(crate) fn trace(&self) -> ExecutionTrace {
+        self.inner.borrow().trace.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::account::AccountHash;
+
+    use super::*;
+
+    #[test]
+    fn should_record_nested_calls() {
+        let tracer = ExecutionTracer::default();
+        let session = CallStackElement::session(AccountHash::new([1; 32]));
+
+        tracer.enter(
+            "casper_call_contract",
+            vec![
+                HostFunctionArg::Key(Key::Hash([2; 32])),
+                HostFunctionArg::Name("call".to_string()),
+            ],
+            Phase::Session,
+            1,
+            Some(session.clone()),
+        );
+        tracer.record_cost(Gas::new(10.into()));
+        {
+            // A clone used by a nested runtime records to the same trace.
+            let nested_tracer = tracer.clone();
+            nested_tracer.enter(
+                "casper_revert",
+                vec![HostFunctionArg::ErrorCode(1)],
+                Phase::Session,
+                2,
+                None,
+            );
+            nested_tracer.record_cost(Gas::new(5.into()));
+            nested_tracer.exit(Gas::new(5.into()), Some("revert".to_string()));
+        }
+        tracer.exit(Gas::new(100.into()), None);
+        tracer.record_phase_cost(Phase::Session, Gas::new(150.into()));
+
+        let trace = tracer.trace();
+        assert_eq!(trace.payment_cost, Gas::default());
+        assert_eq!(trace.session_cost, Gas::new(150.into()));
+        let calls = trace.host_function_calls;
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].name, "casper_call_contract");
+        assert_eq!(
+            calls[0].args,
+            vec![
+                HostFunctionArg::Key(Key::Hash([2; 32])),
+                HostFunctionArg::Name("call".to_string())
+            ]
+        );
+        assert_eq!(calls[0].depth, 1);
+        assert_eq!(calls[0].call_stack_element, Some(session));
+        assert_eq!(calls[0].cost, Gas::new(10.into()));
+        assert_eq!(calls[0].gas_consumed, Gas::new(100.into()));
+        assert_eq!(calls[0].trap, None);
+        assert_eq!(calls[1].name, "casper_revert");
+        assert_eq!(calls[1].depth, 2);
+        assert_eq!(calls[1].cost, Gas::new(5.into()));
+        assert_eq!(calls[1].trap, Some("revert".to_string()));
+    }
+}
@@ -35,6 +35,11 @@ impl DictionaryValue {
     pub fn into_cl_value(self) -> CLValue {
         self.cl_value
     }
+
+    /// Get the address of the seed [`casper_types::URef`] of the dictionary.
+    pub fn seed_uref_addr(&self) -> &[u8] {
+        self.seed_uref_addr.as_ref()
+    }
 }
 
 impl CLTyped for DictionaryValue {
@@ -528,6 +528,23 @@ where
             .map_err(Into::into)
     }
 
+    /// Reads a value from a global state directly like `read_gs_direct`, for callers which don't
+    /// depend on the value itself, only on whether it exists and has the expected type.
+    ///
+    /// # Usage
+    ///
+    /// DO NOT EXPOSE THIS VIA THE FFI - This function bypasses security checks and should be used
+    /// with caution.
+    pub fn read_gs_direct_without_noting(
+        &mut self,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Error> {
+        self.tracking_copy
+            .borrow_mut()
+            .read_without_noting(self.correlation_id, key)
+            .map_err(Into::into)
+    }
+
     /// This method is a wrapper over `read_gs` in the sense that it extracts the type held by a
     /// `StoredValue` stored in the global state in a type safe manner.
     ///
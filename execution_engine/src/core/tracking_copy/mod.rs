@@ -332,6 +332,17 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         &mut self,
         correlation_id: CorrelationId,
         key: &Key,
+    ) -> Result<Option<StoredValue>, R::Error> {
+        self.reader.note_read(&key.normalize());
+        self.read_without_noting(correlation_id, key)
+    }
+
+    /// Reads the value stored under `key` like [`read`](Self::read), for callers which don't depend
+    /// on the value itself, only on whether it exists and has the expected type.
+    pub fn read_without_noting(
+        &mut self,
+        correlation_id: CorrelationId,
+        key: &Key,
     ) -> Result<Option<StoredValue>, R::Error> {
         let normalized_key = key.normalize();
         if let Some(value) = self.get(correlation_id, &normalized_key)? {
@@ -599,6 +610,10 @@ impl<R: StateReader<Key, StoredValue>> StateReader<Key, StoredValue> for &Tracki
     ) -> Result<Vec<Key>, Self::Error> {
         self.reader.keys_with_prefix(correlation_id, prefix)
     }
+
+    fn note_read(&self, key: &Key) {
+        self.reader.note_read(key)
+    }
 }
 
 /// Error conditions of a proof validation.
@@ -8,6 +8,7 @@ use casper_types::{
     account::{
         Account, AccountHash, ActionThresholds, AssociatedKeys, Weight, ACCOUNT_HASH_LENGTH,
     },
+    bytesrepr::{self, ToBytes},
     contracts::NamedKeys,
     gens::*,
     AccessRights, CLValue, Contract, EntryPoints, HashAddr, Key, KeyTag, ProtocolVersion,
@@ -673,6 +674,18 @@ fn validate_query_proof_should_work() {
     crate::core::validate_query_proof(&root_hash, &proofs, &main_account_key, path, &account_value)
         .expect("should validate");
 
+    // The same proofs should be verifiable without the execution engine, e.g. by light clients
+    let portable_proofs: Vec<casper_types::global_state::TrieMerkleProof<Key, StoredValue>> =
+        bytesrepr::deserialize(proofs.to_bytes().unwrap()).expect("should deserialize");
+    casper_types::global_state::validate_query_proof(
+        &root_hash.value(),
+        &portable_proofs,
+        &main_account_key,
+        path,
+        &account_value,
+    )
+    .expect("should validate using casper-types");
+
     // Path should be the same length as the proofs less one (so it should be of length 2)
     assert_eq!(
         crate::core::validate_query_proof(
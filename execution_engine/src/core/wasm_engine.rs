@@ -0,0 +1,216 @@
+//! The Wasm engines able to execute contracts on behalf of the
+//! [`Runtime`](super::runtime::Runtime).
+//!
+//! Host function calls made by an instance are dispatched to the runtime's [`Externals`]
+//! implementation, whichever engine executes the instance.
+#[cfg(feature = "compiled-wasm-engine")]
+mod compiled;
+
+use casper_wasm::elements::Module;
+use casper_wasmi::{Externals, ImportsBuilder, MemoryRef, ModuleInstance, ModuleRef, RuntimeValue};
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::ProtocolVersion;
+
+use crate::core::{
+    engine_state::EngineConfig,
+    execution::Error,
+    resolvers::{self, memory_resolver::MemoryResolver},
+};
+
+/// The kind of engine used to execute Wasm.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug, DataSize)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmEngineKind {
+    /// Executes Wasm with the `casper-wasmi` interpreter.
+    #[default]
+    Interpreter,
+    /// Compiles Wasm to native code with Cranelift, via `wasmtime`.
+    #[cfg(feature = "compiled-wasm-engine")]
+    Compiled,
+}
+
+/// The memory of a Wasm instance, as accessed by host functions.
+#[derive(Clone)]
+pub(crate) enum WasmMemory {
+    Interpreted(MemoryRef),
+    #[cfg(feature = "compiled-wasm-engine")]
+    Compiled(compiled::MemoryView),
+}
+
+impl WasmMemory {
+    /// Copies `value` into the memory at `offset`.
+    pub(crate) fn set(&self, offset: u32, value: &[u8]) -> Result<(), casper_wasmi::Error> {
+        match self {
+            WasmMemory::Interpreted(memory) => memory.set(offset, value),
+            #[cfg(feature = "compiled-wasm-engine")]
+            WasmMemory::Compiled(memory) => memory.set(offset, value),
+        }
+    }
+
+    /// Calls `func` with the whole contents of the memory.
+    pub(crate) fn with_direct_access<R>(&self, func: impl FnOnce(&[u8]) -> R) -> R {
+        match self {
+            WasmMemory::Interpreted(memory) => memory.with_direct_access(func),
+            #[cfg(feature = "compiled-wasm-engine")]
+            WasmMemory::Compiled(memory) => memory.with_direct_access(func),
+        }
+    }
+}
+
+/// An instantiated Wasm module.
+pub(crate) enum WasmInstance {
+    Interpreted(ModuleRef),
+    #[cfg(feature = "compiled-wasm-engine")]
+    Compiled(compiled::Instance),
+}
+
+impl WasmInstance {
+    /// Invokes the exported function `name`, which takes no arguments, dispatching the host
+    /// function calls it makes to `externals`.
+    pub(crate) fn invoke_export<E: Externals>(
+        &self,
+        name: &str,
+        externals: &mut E,
+    ) -> Result<Option<RuntimeValue>, casper_wasmi::Error> {
+        match self {
+            WasmInstance::Interpreted(instance) => instance.invoke_export(name, &[], externals),
+            #[cfg(feature = "compiled-wasm-engine")]
+            WasmInstance::Compiled(instance) => instance.invoke_export(name, externals),
+        }
+    }
+
+    /// Returns the value of the last global of the instance, if it can be inspected.
+    #[cfg(feature = "test-support")]
+    pub(crate) fn last_global(&self) -> Option<RuntimeValue> {
+        match self {
+            WasmInstance::Interpreted(instance) => {
+                instance.globals().last().map(|global| global.get())
+            }
+            // Globals which aren't exported can't be read from a compiled instance.
+            #[cfg(feature = "compiled-wasm-engine")]
+            WasmInstance::Compiled(_) => None,
+        }
+    }
+}
+
+/// A module validated and prepared for instantiation, which can be instantiated any number of
+/// times.
+///
+/// Preparing a module is much more expensive than instantiating it, so stored contracts are
+/// cached in this form, see [`WasmModuleCache`](crate::shared::wasm_module_cache::WasmModuleCache).
+pub struct PreparedModule {
+    module: Module,
+    interpreted: casper_wasmi::Module,
+    /// The natively compiled module, if prepared for the compiled engine and compilation
+    /// succeeded.
+    #[cfg(feature = "compiled-wasm-engine")]
+    compiled: Option<wasmtime::Module>,
+}
+
+impl PreparedModule {
+    /// Returns the module the prepared module was created from.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+}
+
+/// Deserializes the module in `bytes` and prepares it for the engine selected in
+/// `engine_config`.
+pub fn deserialize_and_prepare(
+    bytes: &[u8],
+    engine_config: &EngineConfig,
+) -> Result<PreparedModule, Error> {
+    let module = casper_wasm::deserialize_buffer(bytes)?;
+    prepare_with_bytes(module, Some(bytes), engine_config)
+}
+
+/// Validates `module` and prepares it for the engine selected in `engine_config`.
+pub(crate) fn prepare(
+    module: Module,
+    engine_config: &EngineConfig,
+) -> Result<PreparedModule, Error> {
+    prepare_with_bytes(module, None, engine_config)
+}
+
+/// Prepares `module`, which was deserialized from `bytes` if given.
+fn prepare_with_bytes(
+    module: Module,
+    bytes: Option<&[u8]>,
+    engine_config: &EngineConfig,
+) -> Result<PreparedModule, Error> {
+    // Modules are validated by the interpreter whichever engine is used, so validation errors don't
+    // depend on the engine, and so the interpreter can take over if the compiled engine can't run
+    // the module.
+    let interpreted = casper_wasmi::Module::from_casper_wasm_module(module.clone())?;
+    #[cfg(feature = "compiled-wasm-engine")]
+    let compiled = match engine_config.wasm_engine() {
+        WasmEngineKind::Interpreter => None,
+        WasmEngineKind::Compiled => compiled::compile(&module, bytes),
+    };
+    #[cfg(not(feature = "compiled-wasm-engine"))]
+    let _ = (bytes, engine_config);
+    Ok(PreparedModule {
+        module,
+        interpreted,
+        #[cfg(feature = "compiled-wasm-engine")]
+        compiled,
+    })
+}
+
+/// Instantiates `module` with the engine selected in `engine_config`, returning the instance and
+/// its memory.
+///
+/// The module's imports are resolved to the host functions and a memory of the given protocol
+/// version.  Modules with a "start" section are rejected, as running it isn't supported.
+pub(crate) fn instantiate(
+    module: &PreparedModule,
+    protocol_version: ProtocolVersion,
+    engine_config: &EngineConfig,
+) -> Result<(WasmInstance, WasmMemory), Error> {
+    match engine_config.wasm_engine() {
+        WasmEngineKind::Interpreter => {
+            let (instance, memory) =
+                instantiate_interpreted(module, protocol_version, engine_config)?;
+            Ok((
+                WasmInstance::Interpreted(instance),
+                WasmMemory::Interpreted(memory),
+            ))
+        }
+        #[cfg(feature = "compiled-wasm-engine")]
+        WasmEngineKind::Compiled => compiled::instantiate(module, protocol_version, engine_config),
+    }
+}
+
+/// Returns `true` if, since the previous call on this thread, the compiled engine failed in a way
+/// the interpreter might not have, e.g. by running out of native stack.
+///
+/// Deploys affected by such a failure have to be re-executed with the interpreter.
+pub(crate) fn take_interpreter_fallback() -> bool {
+    #[cfg(feature = "compiled-wasm-engine")]
+    return compiled::take_interpreter_fallback();
+    #[cfg(not(feature = "compiled-wasm-engine"))]
+    false
+}
+
+/// Creates an interpreted module instance and its memory.
+///
+/// This ensures that the memory instance is properly resolved into a pre-allocated memory area, and
+/// a host function resolver is attached to the module.
+fn instantiate_interpreted(
+    module: &PreparedModule,
+    protocol_version: ProtocolVersion,
+    engine_config: &EngineConfig,
+) -> Result<(ModuleRef, MemoryRef), Error> {
+    let resolver = resolvers::create_module_resolver(protocol_version, engine_config)?;
+    let mut imports = ImportsBuilder::new();
+    imports.push_resolver("env", &resolver);
+    let not_started_module = ModuleInstance::new(&module.interpreted, &imports)?;
+    if not_started_module.has_start() {
+        return Err(Error::UnsupportedWasmStart);
+    }
+    let instance = not_started_module.not_started_instance().clone();
+    let memory = resolver.memory_ref()?;
+    Ok((instance, memory))
+}
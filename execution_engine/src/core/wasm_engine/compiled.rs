@@ -0,0 +1,758 @@
+//! A Wasm engine compiling modules to native code with Cranelift, via `wasmtime`.
+//!
+//! Modules are validated by the interpreter when prepared, and compiled once, so that prepared
+//! stored contracts are cached along with their compiled form.  Modules which can't be compiled, or
+//! which the interpreter might fail to instantiate, are instantiated by the interpreter instead,
+//! so that instantiation errors are identical to the ones of the interpreter.
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{self, Display, Formatter},
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use casper_wasm::elements::{External, Module};
+use casper_wasmi::{
+    nan_preserving_float::{F32, F64},
+    Error as InterpreterError, Externals, RuntimeArgs, RuntimeValue, Signature, Trap, TrapCode,
+    ValueType,
+};
+use once_cell::sync::Lazy;
+use tracing::{debug, warn};
+use wasmtime::{
+    Caller, Config, Engine, Extern, ExternType, Func, FuncType, Memory, MemoryType, Store, Val,
+    ValType,
+};
+
+use casper_types::ProtocolVersion;
+
+use super::{PreparedModule, WasmInstance, WasmMemory};
+use crate::core::{engine_state::EngineConfig, execution::Error, resolvers};
+
+/// Maximum native stack space usable by a single compiled instance.
+///
+/// This is small enough to always be exhausted before the interpreter's call stack limit could be
+/// reached, so running out of it never makes a deploy fail which the interpreter would execute.
+const MAX_WASM_STACK: usize = 512 * 1024;
+
+static ENGINE: Lazy<Result<Engine, String>> = Lazy::new(|| {
+    let mut config = Config::new();
+    config
+        .wasm_threads(false)
+        .wasm_reference_types(false)
+        .wasm_simd(false)
+        .wasm_bulk_memory(false)
+        .wasm_multi_value(false)
+        .wasm_backtrace(false)
+        .cranelift_nan_canonicalization(true)
+        .max_wasm_stack(MAX_WASM_STACK);
+    Engine::new(&config).map_err(|error| error.to_string())
+});
+
+thread_local! {
+    static INTERPRETER_FALLBACK: Cell<bool> = Cell::new(false);
+}
+
+pub(super) fn take_interpreter_fallback() -> bool {
+    INTERPRETER_FALLBACK.with(|fallback| fallback.replace(false))
+}
+
+fn request_interpreter_fallback() {
+    INTERPRETER_FALLBACK.with(|fallback| fallback.set(true));
+}
+
+/// The start and length of a compiled instance's memory.
+type MemoryRegion = (NonNull<u8>, usize);
+
+/// The memory of a compiled instance, accessible only while the instance calls a host function.
+#[derive(Clone, Default)]
+pub(crate) struct MemoryView {
+    region: Rc<Cell<Option<MemoryRegion>>>,
+}
+
+impl MemoryView {
+    pub(super) fn set(&self, offset: u32, value: &[u8]) -> Result<(), InterpreterError> {
+        let (ptr, len) = self.region.get().unwrap_or((NonNull::dangling(), 0));
+        // The same checks, with the same error messages, as done by the interpreter's memory.
+        let offset = offset as usize;
+        let end = offset.checked_add(value.len()).ok_or_else(|| {
+            InterpreterError::Memory(format!(
+                "trying to access memory block of size {} from offset {}",
+                value.len(),
+                offset
+            ))
+        })?;
+        if end > len {
+            return Err(InterpreterError::Memory(format!(
+                "trying to access region [{}..{}] in memory [0..{}]",
+                offset, end, len
+            )));
+        }
+        // SAFETY: the region is only set by `host_function` for the duration of a host function
+        // call, from the instance's memory as it is at the start of that call, and is cleared by
+        // `MemoryRegionGuard` before the call returns or unwinds. While the instance is suspended
+        // in the host function:
+        // - no Wasm code runs, so nothing else reads or writes the memory;
+        // - `memory.grow` can't be executed, so the memory isn't reallocated and `len` is exact;
+        // - the store, which owns the memory, is borrowed by the ongoing `invoke_export` call, so
+        //   it can't be dropped.
+        // The memory view isn't `Send`, so it can't be used from another thread meanwhile, and no
+        // other slice into the region is alive since both accessors only lend one out for the
+        // duration of a call.
+        let buffer = unsafe { std::slice::from_raw_parts_mut(ptr.as_ptr(), len) };
+        buffer[offset..end].copy_from_slice(value);
+        Ok(())
+    }
+
+    pub(super) fn with_direct_access<R>(&self, func: impl FnOnce(&[u8]) -> R) -> R {
+        match self.region.get() {
+            // SAFETY: the region is valid for reads of `len` bytes and not written to while
+            // `func` runs, see `MemoryView::set`.
+            Some((ptr, len)) => func(unsafe { std::slice::from_raw_parts(ptr.as_ptr(), len) }),
+            None => func(&[]),
+        }
+    }
+}
+
+/// Makes a compiled instance's memory accessible through a `MemoryView` until dropped.
+struct MemoryRegionGuard<'a>(&'a MemoryView);
+
+impl<'a> MemoryRegionGuard<'a> {
+    fn new(memory_view: &'a MemoryView, region: Option<MemoryRegion>) -> Self {
+        memory_view.region.set(region);
+        MemoryRegionGuard(memory_view)
+    }
+}
+
+impl Drop for MemoryRegionGuard<'_> {
+    fn drop(&mut self) {
+        self.0.region.set(None);
+    }
+}
+
+/// The data of the store of a compiled instance.
+struct HostState {
+    /// The externals of the ongoing `Instance::invoke_export` call.
+    externals: Option<NonNull<dyn Externals>>,
+    memory: Option<Memory>,
+    memory_view: MemoryView,
+}
+
+/// A trap raised by a host function, passed through the compiled code.
+#[derive(Debug)]
+struct HostTrap(Trap);
+
+impl Display for HostTrap {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:?}", self.0)
+    }
+}
+
+impl std::error::Error for HostTrap {}
+
+/// A compiled instance.
+pub(crate) struct Instance {
+    store: RefCell<Store<HostState>>,
+    instance: wasmtime::Instance,
+}
+
+impl Instance {
+    pub(super) fn invoke_export<E: Externals>(
+        &self,
+        name: &str,
+        externals: &mut E,
+    ) -> Result<Option<RuntimeValue>, InterpreterError> {
+        let mut store = self.store.borrow_mut();
+        // The same lookup, with the same errors, as done by the interpreter.
+        let func = match self.instance.get_export(&mut *store, name) {
+            Some(Extern::Func(func)) => func,
+            Some(_) => {
+                // The interpreter's error describes the export's instance, which is only known to
+                // the interpreter: let it report the error.
+                request_interpreter_fallback();
+                return Err(InterpreterError::Function(format!(
+                    "Export {} is not a function",
+                    name
+                )));
+            }
+            None => {
+                return Err(InterpreterError::Function(format!(
+                    "Module doesn't have export {}",
+                    name
+                )))
+            }
+        };
+        let func_type = func.ty(&*store);
+        if func_type.params().len() > 0 {
+            return Err(InterpreterError::Trap(TrapCode::UnexpectedSignature.into()));
+        }
+        let mut results = vec![Val::I32(0); func_type.results().len()];
+
+        let externals: NonNull<dyn Externals + '_> = NonNull::from(externals);
+        // SAFETY: this only erases the lifetime of the trait object; the pointer and vtable are
+        // unchanged. The resulting pointer outlives the borrow of `externals`, so it must never be
+        // dereferenced after this function returns:
+        // - it is only dereferenced by `host_function`, which the compiled code can only call from
+        //   within `func.call` below;
+        // - `ExternalsGuard` removes it from the store when dropped, i.e. before this function
+        //   returns or unwinds, and the store is exclusively borrowed until then.
+        let externals: NonNull<dyn Externals> = unsafe { std::mem::transmute(externals) };
+        let guard = ExternalsGuard::new(&mut store, externals);
+        let result = func.call(&mut *guard.0, &[], &mut results);
+        drop(guard);
+
+        match result {
+            Ok(()) => Ok(results.first().map(to_runtime_value)),
+            Err(error) => Err(to_interpreter_error(error)),
+        }
+    }
+}
+
+/// Sets the externals of an ongoing `Instance::invoke_export` call, and removes them when dropped.
+struct ExternalsGuard<'a>(&'a mut Store<HostState>);
+
+impl<'a> ExternalsGuard<'a> {
+    fn new(store: &'a mut Store<HostState>, externals: NonNull<dyn Externals>) -> Self {
+        store.data_mut().externals = Some(externals);
+        ExternalsGuard(store)
+    }
+}
+
+impl Drop for ExternalsGuard<'_> {
+    fn drop(&mut self) {
+        self.0.data_mut().externals = None;
+    }
+}
+
+/// Compiles `module`, which was deserialized from `bytes` if given, returning `None` if it can't
+/// be compiled.
+pub(super) fn compile(module: &Module, bytes: Option<&[u8]>) -> Option<wasmtime::Module> {
+    let engine = match ENGINE.as_ref() {
+        Ok(engine) => engine,
+        Err(error) => {
+            warn!(%error, "failed to create wasm engine, falling back to the interpreter");
+            return None;
+        }
+    };
+    let result = match bytes {
+        Some(bytes) => wasmtime::Module::from_binary(engine, bytes),
+        // Session code is instrumented after deserialization, so has to be serialized again.
+        None => casper_wasm::serialize(module.clone())
+            .map_err(anyhow::Error::new)
+            .and_then(|bytes| wasmtime::Module::from_binary(engine, &bytes)),
+    };
+    match result {
+        Ok(compiled_module) => Some(compiled_module),
+        Err(error) => {
+            warn!(%error, "failed to compile wasm, falling back to the interpreter");
+            None
+        }
+    }
+}
+
+/// Instantiates `module`, preferring a compiled instance and using an interpreted one if the
+/// module wasn't compiled, or if the interpreter might fail to instantiate it.
+pub(super) fn instantiate(
+    module: &PreparedModule,
+    protocol_version: ProtocolVersion,
+    engine_config: &EngineConfig,
+) -> Result<(WasmInstance, WasmMemory), Error> {
+    if let Some(compiled_module) = &module.compiled {
+        match instantiate_compiled(
+            compiled_module,
+            &module.module,
+            protocol_version,
+            engine_config,
+        ) {
+            Ok((instance, memory_view)) => {
+                return Ok((
+                    WasmInstance::Compiled(instance),
+                    WasmMemory::Compiled(memory_view),
+                ))
+            }
+            Err(error) => debug!(%error, "instantiating wasm with the interpreter"),
+        }
+    }
+    let (instance, memory) =
+        super::instantiate_interpreted(module, protocol_version, engine_config)?;
+    Ok((
+        WasmInstance::Interpreted(instance),
+        WasmMemory::Interpreted(memory),
+    ))
+}
+
+/// Returns the type of the memory imported by `module`, if it imports a single one under the name
+/// and within the limits the interpreter accepts.
+fn imported_memory_type(module: &Module, max_memory: u32) -> Option<MemoryType> {
+    let mut memory_imports = module
+        .import_section()?
+        .entries()
+        .iter()
+        .filter_map(|entry| match entry.external() {
+            External::Memory(memory_type) => Some((entry.field(), memory_type.limits())),
+            _ => None,
+        });
+    let (field, limits) = memory_imports.next()?;
+    if field != "memory" || memory_imports.next().is_some() {
+        return None;
+    }
+    // The same checks as done by the interpreter's import resolver.
+    let maximum = limits.maximum().unwrap_or(max_memory);
+    if limits.initial() > maximum || maximum > max_memory {
+        return None;
+    }
+    Some(MemoryType::new(limits.initial(), limits.maximum()))
+}
+
+/// Instantiates the compiled module, returning an error if it can't be instantiated, or if the
+/// interpreter might fail to instantiate `module`.
+fn instantiate_compiled(
+    compiled_module: &wasmtime::Module,
+    module: &Module,
+    protocol_version: ProtocolVersion,
+    engine_config: &EngineConfig,
+) -> Result<(Instance, MemoryView), anyhow::Error> {
+    // The interpreter reports unsupported protocol versions and start functions.
+    resolvers::create_module_resolver(protocol_version, engine_config)?;
+    if module.start_section().is_some() {
+        anyhow::bail!("start functions are unsupported");
+    }
+    let memory_type = imported_memory_type(module, engine_config.wasm_config().max_memory)
+        .ok_or_else(|| anyhow::anyhow!("unsupported memory import"))?;
+
+    let engine = compiled_module.engine();
+    let memory_view = MemoryView::default();
+    let mut store = Store::new(
+        engine,
+        HostState {
+            externals: None,
+            memory: None,
+            memory_view: memory_view.clone(),
+        },
+    );
+
+    let mut imports = Vec::new();
+    for import in compiled_module.imports() {
+        // The interpreter only resolves imports from the "env" module.
+        if import.module() != "env" {
+            anyhow::bail!("unknown import module {}", import.module());
+        }
+        let import = match import.ty() {
+            ExternType::Func(_) => {
+                let (signature, function_index) = resolvers::resolve_host_function(import.name())
+                    .ok_or_else(|| {
+                    anyhow::anyhow!("unknown host function {}", import.name())
+                })?;
+                Extern::Func(host_function(
+                    &mut store,
+                    &signature,
+                    usize::from(function_index),
+                ))
+            }
+            ExternType::Memory(_) => {
+                let memory = Memory::new(&mut store, memory_type.clone())?;
+                store.data_mut().memory = Some(memory);
+                Extern::Memory(memory)
+            }
+            unexpected => anyhow::bail!("unsupported import {:?}", unexpected),
+        };
+        imports.push(import);
+    }
+
+    let instance = wasmtime::Instance::new(&mut store, compiled_module, &imports)?;
+    let instance = Instance {
+        store: RefCell::new(store),
+        instance,
+    };
+    Ok((instance, memory_view))
+}
+
+fn host_function(store: &mut Store<HostState>, signature: &Signature, index: usize) -> Func {
+    let func_type = FuncType::new(
+        signature.params().iter().copied().map(to_val_type),
+        signature.return_type().map(to_val_type),
+    );
+    Func::new(
+        store,
+        func_type,
+        move |mut caller: Caller<'_, HostState>, params: &[Val], results: &mut [Val]| {
+            let region = caller.data().memory.map(|memory| {
+                let data = memory.data_mut(&mut caller);
+                (NonNull::new(data.as_mut_ptr()), data.len())
+            });
+            let host_state = caller.data();
+            let mut externals = host_state
+                .externals
+                .ok_or_else(|| anyhow::anyhow!("host function called outside of an invocation"))?;
+            let memory_view = host_state.memory_view.clone();
+            let region_guard = MemoryRegionGuard::new(
+                &memory_view,
+                region.and_then(|(ptr, len)| Some((ptr?, len))),
+            );
+
+            let args: Vec<RuntimeValue> = params.iter().map(to_runtime_value).collect();
+            // SAFETY: the externals are only set by `Instance::invoke_export`, which exclusively
+            // borrows them for the whole duration of the call into the compiled code that led
+            // here, and removes them from the store before that borrow ends. No other reference
+            // to them is alive meanwhile, since host functions don't nest.
+            let result =
+                unsafe { externals.as_mut() }.invoke_index(index, RuntimeArgs::from(&args[..]));
+            drop(region_guard);
+
+            match result.map_err(|trap| anyhow::Error::new(HostTrap(trap)))? {
+                Some(value) => {
+                    if let Some(result) = results.first_mut() {
+                        *result = to_val(value);
+                    }
+                    Ok(())
+                }
+                None => Ok(()),
+            }
+        },
+    )
+}
+
+fn to_interpreter_error(error: anyhow::Error) -> InterpreterError {
+    let error = match error.downcast::<HostTrap>() {
+        Ok(HostTrap(trap)) => return InterpreterError::Trap(trap),
+        Err(error) => error,
+    };
+    let trap_code = match error.downcast_ref::<wasmtime::Trap>() {
+        Some(wasmtime::Trap::StackOverflow) => {
+            // The interpreter might still succeed, with its deeper call stack.
+            request_interpreter_fallback();
+            TrapCode::StackOverflow
+        }
+        Some(wasmtime::Trap::MemoryOutOfBounds) | Some(wasmtime::Trap::HeapMisaligned) => {
+            TrapCode::MemoryAccessOutOfBounds
+        }
+        Some(wasmtime::Trap::TableOutOfBounds) => TrapCode::TableAccessOutOfBounds,
+        Some(wasmtime::Trap::IndirectCallToNull) => TrapCode::ElemUninitialized,
+        Some(wasmtime::Trap::BadSignature) => TrapCode::UnexpectedSignature,
+        Some(wasmtime::Trap::IntegerOverflow) => TrapCode::IntegerOverflow,
+        Some(wasmtime::Trap::IntegerDivisionByZero) => TrapCode::DivisionByZero,
+        Some(wasmtime::Trap::BadConversionToInteger) => TrapCode::InvalidConversionToInt,
+        Some(wasmtime::Trap::UnreachableCodeReached) => TrapCode::Unreachable,
+        _ => {
+            // An error the interpreter can't raise: let the interpreter decide the outcome.
+            request_interpreter_fallback();
+            return InterpreterError::Function(error.to_string());
+        }
+    };
+    InterpreterError::Trap(trap_code.into())
+}
+
+fn to_val_type(value_type: ValueType) -> ValType {
+    match value_type {
+        ValueType::I32 => ValType::I32,
+        ValueType::I64 => ValType::I64,
+        ValueType::F32 => ValType::F32,
+        ValueType::F64 => ValType::F64,
+    }
+}
+
+fn to_val(value: RuntimeValue) -> Val {
+    match value {
+        RuntimeValue::I32(value) => Val::I32(value),
+        RuntimeValue::I64(value) => Val::I64(value),
+        RuntimeValue::F32(value) => Val::F32(value.to_bits()),
+        RuntimeValue::F64(value) => Val::F64(value.to_bits()),
+    }
+}
+
+fn to_runtime_value(value: &Val) -> RuntimeValue {
+    match value {
+        Val::I32(value) => RuntimeValue::I32(*value),
+        Val::I64(value) => RuntimeValue::I64(*value),
+        Val::F32(bits) => F32::from_bits(*bits).into(),
+        Val::F64(bits) => F64::from_bits(*bits).into(),
+        // Reference types are disabled in the engine's config.
+        Val::V128(_) | Val::FuncRef(_) | Val::ExternRef(_) => {
+            unreachable!("reference and vector types are disabled")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::ApiError;
+    use casper_wasm::elements::{
+        CodeSection, ExportEntry, ExportSection, External, Func, FuncBody, FunctionSection,
+        FunctionType, ImportEntry, ImportSection, Instruction, Instructions, Internal,
+        MemoryType as MemoryImportType, Module, Section, Type, TypeSection,
+        ValueType as ElementsValueType,
+    };
+
+    use super::*;
+    use crate::{
+        core::{
+            resolvers::v1_function_index::FunctionIndex,
+            wasm_engine::{self, WasmEngineKind},
+        },
+        shared::wasm_config::DEFAULT_WASM_MAX_MEMORY,
+    };
+
+    const EXPORT_NAME: &str = "call";
+
+    /// Builds a module importing a memory and `casper_get_blocktime`, and exporting a function
+    /// with the given body, returning an `i32`.
+    fn module(body: Vec<Instruction>) -> Module {
+        Module::new(vec![
+            Section::Type(TypeSection::with_types(vec![
+                Type::Function(FunctionType::new(vec![ElementsValueType::I32], vec![])),
+                Type::Function(FunctionType::new(vec![], vec![ElementsValueType::I32])),
+            ])),
+            Section::Import(ImportSection::with_entries(vec![
+                ImportEntry::new(
+                    "env".to_string(),
+                    "memory".to_string(),
+                    External::Memory(MemoryImportType::new(1, Some(1))),
+                ),
+                ImportEntry::new(
+                    "env".to_string(),
+                    "casper_get_blocktime".to_string(),
+                    External::Function(0),
+                ),
+                ImportEntry::new(
+                    "env".to_string(),
+                    "casper_revert".to_string(),
+                    External::Function(0),
+                ),
+            ])),
+            Section::Function(FunctionSection::with_entries(vec![Func::new(1)])),
+            Section::Export(ExportSection::with_entries(vec![ExportEntry::new(
+                EXPORT_NAME.to_string(),
+                Internal::Function(2),
+            )])),
+            Section::Code(CodeSection::with_bodies(vec![FuncBody::new(
+                vec![],
+                Instructions::new(body),
+            )])),
+        ])
+    }
+
+    /// Records host function calls, writing a byte to the given address on
+    /// `casper_get_blocktime` and reverting on `casper_revert`.
+    struct TestExternals {
+        memory: WasmMemory,
+        calls: Vec<(usize, Vec<RuntimeValue>)>,
+    }
+
+    impl Externals for TestExternals {
+        fn invoke_index(
+            &mut self,
+            index: usize,
+            args: RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap> {
+            self.calls.push((index, args.as_ref().to_vec()));
+            if index == usize::from(FunctionIndex::GetBlocktimeIndex) {
+                let ptr: u32 = args.nth_checked(0)?;
+                self.memory
+                    .set(ptr, &[42])
+                    .map_err(|error| Trap::host(Error::Interpreter(error.into())))?;
+                Ok(None)
+            } else {
+                let status: u32 = args.nth_checked(0)?;
+                Err(Trap::host(Error::Revert(ApiError::from(status))))
+            }
+        }
+    }
+
+    /// Invokes the module's export with the given engine, returning the result formatted for
+    /// comparison along with the host function calls made.
+    fn invoke(
+        module: Module,
+        wasm_engine: WasmEngineKind,
+        export_name: &str,
+    ) -> (String, Vec<(usize, Vec<RuntimeValue>)>) {
+        let mut engine_config = EngineConfig::default();
+        engine_config.wasm_engine = wasm_engine;
+        let module = wasm_engine::prepare(module, &engine_config).unwrap();
+        let (instance, memory) =
+            wasm_engine::instantiate(&module, ProtocolVersion::V1_0_0, &engine_config).unwrap();
+        match (&instance, wasm_engine) {
+            (WasmInstance::Interpreted(_), WasmEngineKind::Interpreter)
+            | (WasmInstance::Compiled(_), WasmEngineKind::Compiled) => {}
+            _ => panic!("unexpected instance kind for {:?}", wasm_engine),
+        }
+        let mut externals = TestExternals {
+            memory,
+            calls: vec![],
+        };
+        let result = instance.invoke_export(export_name, &mut externals);
+        (format!("{:?}", result), externals.calls)
+    }
+
+    fn assert_same_outcome(body: Vec<Instruction>, export_name: &str) -> String {
+        let interpreted = invoke(
+            module(body.clone()),
+            WasmEngineKind::Interpreter,
+            export_name,
+        );
+        let compiled = invoke(module(body), WasmEngineKind::Compiled, export_name);
+        assert_eq!(interpreted, compiled);
+        assert!(!take_interpreter_fallback());
+        interpreted.0
+    }
+
+    #[test]
+    fn should_call_host_functions_with_memory_access() {
+        let outcome = assert_same_outcome(
+            vec![
+                Instruction::I32Const(100),
+                Instruction::Call(0),
+                Instruction::I32Const(100),
+                Instruction::I32Load8U(0, 0),
+                Instruction::End,
+            ],
+            EXPORT_NAME,
+        );
+        assert_eq!(
+            outcome,
+            format!("{:?}", Ok::<_, ()>(Some(RuntimeValue::I32(42))))
+        );
+    }
+
+    #[test]
+    fn should_pass_through_host_errors_and_memory_errors() {
+        let outcome = assert_same_outcome(
+            vec![
+                Instruction::I32Const(7),
+                Instruction::Call(1),
+                Instruction::I32Const(0),
+                Instruction::End,
+            ],
+            EXPORT_NAME,
+        );
+        assert!(outcome.contains("Revert"), "{}", outcome);
+
+        let outcome = assert_same_outcome(
+            vec![
+                Instruction::I32Const(65536),
+                Instruction::Call(0),
+                Instruction::I32Const(0),
+                Instruction::End,
+            ],
+            EXPORT_NAME,
+        );
+        assert!(
+            outcome.contains("trying to access region [65536..65537] in memory [0..65536]"),
+            "{}",
+            outcome
+        );
+    }
+
+    #[test]
+    fn should_raise_the_same_traps() {
+        let bodies = vec![
+            vec![Instruction::Unreachable, Instruction::End],
+            vec![
+                Instruction::I32Const(1),
+                Instruction::I32Const(0),
+                Instruction::I32DivS,
+                Instruction::End,
+            ],
+            vec![
+                Instruction::I32Const(i32::MIN),
+                Instruction::I32Const(-1),
+                Instruction::I32DivS,
+                Instruction::End,
+            ],
+            vec![
+                Instruction::I32Const(65535),
+                Instruction::I32Load(2, 0),
+                Instruction::End,
+            ],
+        ];
+        for body in bodies {
+            let outcome = assert_same_outcome(body, EXPORT_NAME);
+            assert!(outcome.starts_with("Err(Trap(Code("), "{}", outcome);
+        }
+    }
+
+    #[test]
+    fn should_report_missing_exports_as_the_interpreter_does() {
+        let outcome = assert_same_outcome(vec![Instruction::Unreachable, Instruction::End], "nope");
+        assert!(
+            outcome.contains("Module doesn't have export nope"),
+            "{}",
+            outcome
+        );
+    }
+
+    #[test]
+    fn should_request_interpreter_fallback_on_native_stack_overflow() {
+        let recurse = vec![Instruction::Call(2), Instruction::End];
+        let (outcome, _) = invoke(module(recurse), WasmEngineKind::Compiled, EXPORT_NAME);
+        assert!(outcome.contains("StackOverflow"), "{}", outcome);
+        assert!(take_interpreter_fallback());
+        assert!(!take_interpreter_fallback());
+    }
+
+    /// Instantiates `module` with the given engine, returning the error formatted for comparison,
+    /// or the kind of instance created.
+    fn instantiate_outcome(module: Module, wasm_engine: WasmEngineKind) -> String {
+        let mut engine_config = EngineConfig::default();
+        engine_config.wasm_engine = wasm_engine;
+        let module = wasm_engine::prepare(module, &engine_config).unwrap();
+        match wasm_engine::instantiate(&module, ProtocolVersion::V1_0_0, &engine_config) {
+            Ok((WasmInstance::Interpreted(_), _)) => "interpreted".to_string(),
+            Ok((WasmInstance::Compiled(_), _)) => "compiled".to_string(),
+            Err(error) => format!("{:?}", error),
+        }
+    }
+
+    #[test]
+    fn should_fail_instantiation_as_the_interpreter_does() {
+        let body = vec![Instruction::I32Const(0), Instruction::End];
+
+        let mut too_much_memory = module(body.clone());
+        too_much_memory.import_section_mut().unwrap().entries_mut()[0] = ImportEntry::new(
+            "env".to_string(),
+            "memory".to_string(),
+            External::Memory(MemoryImportType::new(1, Some(DEFAULT_WASM_MAX_MEMORY + 1))),
+        );
+
+        let mut unknown_import_module = module(body.clone());
+        unknown_import_module
+            .import_section_mut()
+            .unwrap()
+            .entries_mut()[1] = ImportEntry::new(
+            "other".to_string(),
+            "casper_get_blocktime".to_string(),
+            External::Function(0),
+        );
+
+        let mut with_start = module(body.clone());
+        with_start
+            .type_section_mut()
+            .unwrap()
+            .types_mut()
+            .push(Type::Function(FunctionType::new(vec![], vec![])));
+        with_start
+            .function_section_mut()
+            .unwrap()
+            .entries_mut()
+            .push(Func::new(2));
+        with_start
+            .code_section_mut()
+            .unwrap()
+            .bodies_mut()
+            .push(FuncBody::new(
+                vec![],
+                Instructions::new(vec![Instruction::End]),
+            ));
+        with_start.set_start_section(3);
+
+        for module in [too_much_memory, unknown_import_module, with_start] {
+            let interpreted = instantiate_outcome(module.clone(), WasmEngineKind::Interpreter);
+            let compiled = instantiate_outcome(module, WasmEngineKind::Compiled);
+            assert_ne!(interpreted, "interpreted");
+            assert_eq!(interpreted, compiled);
+        }
+        assert_eq!(
+            instantiate_outcome(module(body), WasmEngineKind::Compiled),
+            "compiled"
+        );
+    }
+}
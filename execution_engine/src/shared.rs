@@ -11,4 +11,5 @@ pub mod test_utils;
 pub mod transform;
 pub mod utils;
 pub mod wasm_config;
+pub mod wasm_module_cache;
 pub mod wasm_prep;
@@ -0,0 +1,232 @@
+//! An in-memory LRU cache of stored contract Wasm modules prepared for instantiation.
+use std::{
+    fmt::{self, Debug, Formatter},
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use casper_types::ContractWasmHash;
+use linked_hash_map::LinkedHashMap;
+
+use crate::core::wasm_engine::PreparedModule;
+
+struct CachedModule {
+    module: Arc<PreparedModule>,
+    /// The bytes the module was prepared from.
+    bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Cached modules, least recently used first.
+    modules: LinkedHashMap<ContractWasmHash, CachedModule>,
+    stats: WasmModuleCacheStats,
+}
+
+/// Hit and miss counts of a [`WasmModuleCache`].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct WasmModuleCacheStats {
+    /// Number of lookups served from the cache.
+    pub hits: u64,
+    /// Number of lookups which required preparing the module.
+    pub misses: u64,
+}
+
+/// A least-recently-used cache of stored contract modules prepared for instantiation, keyed by
+/// their [`ContractWasmHash`].
+///
+/// A contract Wasm hash doesn't commit to the stored bytes, so a cached module is only returned if
+/// it was prepared from the same bytes as the ones being looked up. This way executions against
+/// uncommitted state can't poison the cache. Clones share the same cache.
+///
+/// The key doesn't include the chainspec's `WasmConfig`: stored contracts are already instrumented
+/// for gas metering, and preparing them doesn't depend on the config, whose memory limit is only
+/// checked when instantiating. The cache is held in memory only; modules evicted from it, or lost
+/// on restart, are prepared again from global state.
+#[derive(Clone)]
+pub struct WasmModuleCache {
+    capacity: usize,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl WasmModuleCache {
+    /// Creates a cache holding at most `capacity` modules. A capacity of zero disables caching.
+    pub fn new(capacity: usize) -> Self {
+        WasmModuleCache {
+            capacity,
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    /// Returns the module stored under `contract_wasm_hash` as `bytes`, preparing it with
+    /// `prepare` and caching the result if it isn't already cached.
+    pub fn get_or_insert_with<F, E>(
+        &self,
+        contract_wasm_hash: ContractWasmHash,
+        bytes: &[u8],
+        prepare: F,
+    ) -> Result<Arc<PreparedModule>, E>
+    where
+        F: FnOnce(&[u8]) -> Result<PreparedModule, E>,
+    {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let cached_module = inner
+                .modules
+                .get_refresh(&contract_wasm_hash)
+                .filter(|cached| cached.bytes == bytes)
+                .map(|cached| Arc::clone(&cached.module));
+            match cached_module {
+                Some(module) => {
+                    inner.stats.hits += 1;
+                    return Ok(module);
+                }
+                None => inner.stats.misses += 1,
+            }
+        }
+
+        // The lock isn't held while preparing so concurrent executions aren't serialized.
+        let module = Arc::new(prepare(bytes)?);
+        if self.capacity > 0 {
+            let cached = CachedModule {
+                module: Arc::clone(&module),
+                bytes: bytes.to_vec(),
+            };
+            let mut inner = self.inner.lock().unwrap();
+            inner.modules.insert(contract_wasm_hash, cached);
+            while inner.modules.len() > self.capacity {
+                inner.modules.pop_front();
+            }
+        }
+        Ok(module)
+    }
+
+    /// Removes all cached modules, e.g. after a protocol upgrade.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().modules.clear();
+    }
+
+    /// Returns the number of cached modules.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().modules.len()
+    }
+
+    /// Returns `true` if no modules are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the hit and miss counts accumulated since the previous call, resetting them.
+    pub fn take_stats(&self) -> WasmModuleCacheStats {
+        mem::take(&mut self.inner.lock().unwrap().stats)
+    }
+}
+
+impl Debug for WasmModuleCache {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("WasmModuleCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_wasm::{
+        builder,
+        elements::{Instruction, Instructions},
+    };
+
+    use super::*;
+    use crate::core::{engine_state::EngineConfig, wasm_engine};
+
+    fn module_bytes(function_count: usize) -> Vec<u8> {
+        let mut module_builder = builder::module();
+        for _ in 0..function_count {
+            module_builder = module_builder
+                .function()
+                .signature()
+                .build()
+                .body()
+                .with_instructions(Instructions::new(vec![Instruction::End]))
+                .build()
+                .build();
+        }
+        casper_wasm::serialize(module_builder.build()).unwrap()
+    }
+
+    fn get(cache: &WasmModuleCache, hash_byte: u8, bytes: &[u8]) -> Arc<PreparedModule> {
+        cache
+            .get_or_insert_with(ContractWasmHash::new([hash_byte; 32]), bytes, |bytes| {
+                wasm_engine::deserialize_and_prepare(bytes, &EngineConfig::default())
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_module() {
+        let cache = WasmModuleCache::new(2);
+        let bytes = module_bytes(1);
+
+        get(&cache, 1, &bytes);
+        get(&cache, 2, &bytes);
+        // Touch the first module so that the second is evicted next.
+        get(&cache, 1, &bytes);
+        get(&cache, 3, &bytes);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.take_stats(),
+            WasmModuleCacheStats { hits: 1, misses: 3 }
+        );
+
+        get(&cache, 1, &bytes);
+        get(&cache, 2, &bytes);
+        assert_eq!(
+            cache.take_stats(),
+            WasmModuleCacheStats { hits: 1, misses: 1 }
+        );
+    }
+
+    #[test]
+    fn should_miss_on_different_bytes() {
+        let cache = WasmModuleCache::new(10);
+
+        let first = get(&cache, 1, &module_bytes(1));
+        let module = get(&cache, 1, &module_bytes(2));
+        assert_eq!(module.module().functions_space(), 2);
+        assert_eq!(
+            cache.take_stats(),
+            WasmModuleCacheStats { hits: 0, misses: 2 }
+        );
+
+        let cached = get(&cache, 1, &module_bytes(2));
+        assert!(Arc::ptr_eq(&cached, &module));
+        assert!(!Arc::ptr_eq(&cached, &first));
+        assert_eq!(
+            cache.take_stats(),
+            WasmModuleCacheStats { hits: 1, misses: 0 }
+        );
+    }
+
+    #[test]
+    fn should_not_cache_failed_preparation_or_with_zero_capacity() {
+        let cache = WasmModuleCache::new(10);
+        let result =
+            cache.get_or_insert_with(ContractWasmHash::new([1; 32]), &[0, 1, 2], |bytes| {
+                wasm_engine::deserialize_and_prepare(bytes, &EngineConfig::default())
+            });
+        assert!(result.is_err());
+        assert!(cache.is_empty());
+
+        let cache = WasmModuleCache::new(0);
+        get(&cache, 1, &module_bytes(1));
+        assert!(cache.is_empty());
+
+        let cache = WasmModuleCache::new(10);
+        get(&cache, 1, &module_bytes(1));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,4 +1,11 @@
-use std::{collections::HashMap, ops::Deref, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    ops::Deref,
+    sync::Arc,
+};
+
+use lmdb::Cursor;
 
 use casper_hashing::Digest;
 use casper_types::{Key, StoredValue};
@@ -19,13 +26,18 @@ use crate::{
         trie_store::{
             lmdb::{LmdbTrieStore, ScratchTrieStore},
             operations::{
-                delete, keys_with_prefix, missing_children, put_trie, read, read_with_proof,
-                DeleteResult, ReadResult,
+                check_trie_integrity, delete, diff, keys_with_prefix, list_keys, mark_reachable,
+                missing_children, put_trie, read, read_with_proof, DeleteResult, DiffResult,
+                IntegrityReport, ListKeysResult, ReadResult,
             },
         },
     },
 };
 
+/// The maximum number of unreachable tries deleted within a single write transaction by
+/// [`LmdbGlobalState::collect_garbage`].
+const GARBAGE_COLLECTION_BATCH_SIZE: usize = 10_000;
+
 /// Global state implemented against LMDB as a backing data store.
 pub struct LmdbGlobalState {
     /// Environment for LMDB.
@@ -37,6 +49,42 @@ pub struct LmdbGlobalState {
     pub(crate) empty_root_hash: Digest,
 }
 
+/// The outcome of a trie garbage collection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GarbageCollectionStats {
+    /// The number of tries reachable from the retained state roots.
+    pub reachable_tries: u64,
+    /// The number of reachable tries missing from the store.
+    pub missing_tries: u64,
+    /// The number of unreachable tries, deleted unless the collection was a dry run.
+    pub unreachable_tries: u64,
+    /// The total size of the keys and values of the unreachable tries, in bytes.
+    pub reclaimable_bytes: u64,
+}
+
+/// An error collecting unreachable tries.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum GarbageCollectionError {
+    /// Error accessing the trie store.
+    #[error(transparent)]
+    Storage(#[from] error::Error),
+    /// No state roots to retain were given, so all tries would be deleted.
+    #[error("refusing to delete all tries: no state roots to retain")]
+    NoRoots,
+    /// Some tries reachable from the retained state roots are missing, e.g. because the state was
+    /// only partially synchronized, so deleting could lose the parts already present.
+    #[error(
+        "refusing to delete tries: {0} tries reachable from the retained state roots are missing"
+    )]
+    MissingTries(u64),
+}
+
+impl From<lmdb::Error> for GarbageCollectionError {
+    fn from(error: lmdb::Error) -> Self {
+        GarbageCollectionError::Storage(error.into())
+    }
+}
+
 /// Represents a "view" of global state at a particular root hash.
 pub struct LmdbGlobalStateView {
     /// Environment for LMDB.
@@ -127,6 +175,175 @@ impl LmdbGlobalState {
     pub fn empty_state_root_hash(&self) -> Digest {
         self.empty_root_hash
     }
+
+    /// Returns up to `limit` differences between the global states at `old_root` and `new_root`,
+    /// in the order of their serialized keys, starting after `start_after` if given.
+    pub fn diff(
+        &self,
+        correlation_id: CorrelationId,
+        old_root: Digest,
+        new_root: Digest,
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<DiffResult<Key, StoredValue>, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = diff::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, error::Error>(
+            correlation_id,
+            &txn,
+            self.trie_store.deref(),
+            &old_root,
+            &new_root,
+            start_after,
+            limit,
+        )?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    /// Returns up to `limit` keys in the global state at `root` whose serialized forms start with
+    /// `prefix` and whose entries satisfy `filter`, in the order of their serialized forms,
+    /// starting after `start_after` if given.  At most `max_scanned` entries are read.
+    #[allow(clippy::too_many_arguments)]
+    pub fn list_keys<F>(
+        &self,
+        correlation_id: CorrelationId,
+        root: Digest,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+        max_scanned: usize,
+        filter: F,
+    ) -> Result<ListKeysResult<Key>, error::Error>
+    where
+        F: FnMut(&Key, &StoredValue) -> bool,
+    {
+        let txn = self.environment.create_read_txn()?;
+        let result =
+            list_keys::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, error::Error, F>(
+                correlation_id,
+                &txn,
+                self.trie_store.deref(),
+                &root,
+                prefix,
+                start_after,
+                limit,
+                max_scanned,
+                filter,
+            )?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    /// Checks the integrity of every trie reachable from `root`, collecting all the violations
+    /// found rather than stopping at the first one.
+    pub fn check_integrity(
+        &self,
+        correlation_id: CorrelationId,
+        root: Digest,
+    ) -> Result<IntegrityReport, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let report = check_trie_integrity::<
+            Key,
+            StoredValue,
+            lmdb::RoTransaction,
+            LmdbTrieStore,
+            error::Error,
+        >(correlation_id, &txn, self.trie_store.deref(), &root)?;
+        txn.commit()?;
+        Ok(report)
+    }
+
+    /// Deletes all tries which aren't reachable from any of `roots` from the trie store, or only
+    /// counts them if `dry_run` is set.  The empty trie is always retained.
+    ///
+    /// Unless `force` is set, nothing is deleted and an error is returned if `roots` is empty or if
+    /// any tries reachable from them are missing.  Dry runs always succeed, to report the stats.
+    ///
+    /// Tries written while the collection is running may be deleted, so this must only be used
+    /// while nothing else is writing to the global state, e.g. while the node is stopped.  LMDB
+    /// reuses the freed pages for later writes, but doesn't shrink the database file itself.
+    pub fn collect_garbage(
+        &self,
+        correlation_id: CorrelationId,
+        roots: &[Digest],
+        dry_run: bool,
+        force: bool,
+    ) -> Result<GarbageCollectionStats, GarbageCollectionError> {
+        if roots.is_empty() && !dry_run && !force {
+            return Err(GarbageCollectionError::NoRoots);
+        }
+        let mut reachable = HashSet::new();
+        reachable.insert(self.empty_root_hash);
+        let txn = self.environment.create_read_txn()?;
+        let missing =
+            mark_reachable::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, error::Error>(
+                correlation_id,
+                &txn,
+                self.trie_store.deref(),
+                roots,
+                &mut reachable,
+            )?;
+        txn.commit()?;
+
+        let mut stats = GarbageCollectionStats {
+            reachable_tries: reachable.len() as u64,
+            missing_tries: missing.len() as u64,
+            ..Default::default()
+        };
+        if stats.missing_tries > 0 && !dry_run && !force {
+            return Err(GarbageCollectionError::MissingTries(stats.missing_tries));
+        }
+        let db = self.trie_store.get_db();
+        let mut resume_after: Option<Vec<u8>> = None;
+        loop {
+            let mut unreachable = Vec::new();
+            let txn = self.environment.create_read_txn()?;
+            {
+                let mut cursor = lmdb::Transaction::open_ro_cursor(&txn, db)?;
+                let entries = match &resume_after {
+                    Some(last_key) => cursor.iter_from(last_key),
+                    None => cursor.iter_start(),
+                };
+                for entry in entries {
+                    let (key, value) = entry?;
+                    if resume_after.as_deref() == Some(key) {
+                        continue;
+                    }
+                    let is_reachable = Digest::try_from(key)
+                        .map(|trie_key| reachable.contains(&trie_key))
+                        .unwrap_or(false);
+                    if is_reachable {
+                        continue;
+                    }
+                    stats.unreachable_tries += 1;
+                    stats.reclaimable_bytes += (key.len() + value.len()) as u64;
+                    unreachable.push(key.to_vec());
+                    if unreachable.len() == GARBAGE_COLLECTION_BATCH_SIZE {
+                        break;
+                    }
+                }
+            }
+            txn.commit()?;
+
+            if !dry_run && !unreachable.is_empty() {
+                let mut txn = self.environment.create_read_write_txn()?;
+                for key in &unreachable {
+                    txn.del(db, key, None)?;
+                }
+                txn.commit()?;
+            }
+
+            if unreachable.len() < GARBAGE_COLLECTION_BATCH_SIZE {
+                break;
+            }
+            resume_after = unreachable.pop();
+        }
+
+        if !dry_run && self.environment.is_manual_sync_enabled() {
+            self.environment.sync()?;
+        }
+        Ok(stats)
+    }
 }
 
 fn compute_empty_root_hash() -> Result<(Digest, Trie<Key, StoredValue>), error::Error> {
@@ -337,7 +554,7 @@ mod tests {
 
     use super::*;
     use crate::storage::{
-        trie_store::operations::{write, WriteResult},
+        trie_store::operations::{write, IntegrityError, WriteResult},
         DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
     };
 
@@ -505,4 +722,150 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn collect_garbage_deletes_only_unreachable_tries() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs_updated = create_test_pairs_updated();
+
+        let (state, root_hash) = create_test_state(create_test_pairs);
+
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &test_pairs_updated {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+
+        let updated_hash = state.commit(correlation_id, root_hash, effects).unwrap();
+
+        let dry_run_stats = state
+            .collect_garbage(correlation_id, &[updated_hash], true, false)
+            .unwrap();
+        assert_eq!(dry_run_stats.missing_tries, 0);
+        assert!(dry_run_stats.unreachable_tries > 0);
+        assert!(dry_run_stats.reclaimable_bytes > 0);
+        assert!(state.checkout(root_hash).unwrap().is_some());
+
+        let stats = state
+            .collect_garbage(correlation_id, &[updated_hash], false, false)
+            .unwrap();
+        assert_eq!(stats, dry_run_stats);
+        assert!(state.checkout(root_hash).unwrap().is_none());
+        assert!(state.checkout(state.empty_root_hash).unwrap().is_some());
+
+        let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+        for TestPair { key, value } in test_pairs_updated.iter().cloned() {
+            assert_eq!(
+                Some(value),
+                updated_checkout.read(correlation_id, &key).unwrap()
+            );
+        }
+
+        let stats = state
+            .collect_garbage(correlation_id, &[updated_hash], false, false)
+            .unwrap();
+        assert_eq!(stats.unreachable_tries, 0);
+        assert_eq!(stats.reclaimable_bytes, 0);
+    }
+
+    #[test]
+    fn check_integrity_reports_missing_and_corrupted_tries() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+
+        let report = state.check_integrity(correlation_id, root_hash).unwrap();
+        assert!(report.errors.is_empty());
+        assert!(report.tries_checked > 1);
+
+        let leaves: Vec<Digest> = create_test_pairs()
+            .iter()
+            .map(|TestPair { key, value }| Trie::leaf(*key, value.clone()).trie_hash().unwrap())
+            .collect();
+
+        // Delete one leaf and overwrite the other with garbage.
+        let mut txn = state.environment.create_read_write_txn().unwrap();
+        txn.del(state.trie_store.get_db(), &leaves[0], None)
+            .unwrap();
+        txn.put(
+            state.trie_store.get_db(),
+            &leaves[1],
+            &[0xff_u8; 3],
+            lmdb::WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        let report = state.check_integrity(correlation_id, root_hash).unwrap();
+        assert_eq!(report.errors.len(), 3);
+        assert!(report
+            .errors
+            .contains(&IntegrityError::MissingTrie(leaves[0])));
+        assert!(report.errors.iter().any(|error| matches!(
+            error,
+            IntegrityError::HashMismatch { trie_key, .. } if *trie_key == leaves[1]
+        )));
+        assert!(report.errors.iter().any(|error| matches!(
+            error,
+            IntegrityError::Malformed { trie_key, .. } if *trie_key == leaves[1]
+        )));
+    }
+
+    #[test]
+    fn collect_garbage_refuses_without_roots() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+
+        let dry_run_stats = state
+            .collect_garbage(correlation_id, &[], true, false)
+            .unwrap();
+        assert!(dry_run_stats.unreachable_tries > 0);
+
+        assert_eq!(
+            Err(GarbageCollectionError::NoRoots),
+            state.collect_garbage(correlation_id, &[], false, false)
+        );
+        assert!(state.checkout(root_hash).unwrap().is_some());
+
+        let stats = state
+            .collect_garbage(correlation_id, &[], false, true)
+            .unwrap();
+        assert_eq!(stats, dry_run_stats);
+        assert!(state.checkout(root_hash).unwrap().is_none());
+        assert!(state.checkout(state.empty_root_hash).unwrap().is_some());
+    }
+
+    #[test]
+    fn collect_garbage_refuses_with_missing_tries() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+
+        let effects: AdditiveMap<Key, Transform> = create_test_pairs_updated()
+            .into_iter()
+            .map(|TestPair { key, value }| (key, Transform::Write(value)))
+            .collect();
+        let updated_hash = state.commit(correlation_id, root_hash, effects).unwrap();
+        let missing_root = Digest::hash(b"a state root that was never synchronized");
+        let roots = [updated_hash, missing_root];
+
+        let dry_run_stats = state
+            .collect_garbage(correlation_id, &roots, true, false)
+            .unwrap();
+        assert_eq!(dry_run_stats.missing_tries, 1);
+        assert!(dry_run_stats.unreachable_tries > 0);
+
+        assert_eq!(
+            Err(GarbageCollectionError::MissingTries(1)),
+            state.collect_garbage(correlation_id, &roots, false, false)
+        );
+        assert!(state.checkout(root_hash).unwrap().is_some());
+
+        let stats = state
+            .collect_garbage(correlation_id, &roots, false, true)
+            .unwrap();
+        assert_eq!(stats, dry_run_stats);
+        assert!(state.checkout(root_hash).unwrap().is_none());
+        assert!(state.checkout(updated_hash).unwrap().is_some());
+    }
 }
@@ -6,6 +6,9 @@ pub mod in_memory;
 /// Lmdb implementation of global state.
 pub mod lmdb;
 
+/// Wrapper of global state recording the keys read.
+pub mod recording;
+
 /// Lmdb implementation of global state with cache.
 pub mod scratch;
 
@@ -55,6 +58,12 @@ pub trait StateReader<K, V> {
         correlation_id: CorrelationId,
         prefix: &[u8],
     ) -> Result<Vec<K>, Self::Error>;
+
+    /// Notes that the value under `key` was read by an execution, as opposed to only being used to
+    /// add to it.
+    ///
+    /// Does nothing by default.
+    fn note_read(&self, _key: &K) {}
 }
 
 /// An error emitted by the execution engine on commit
@@ -0,0 +1,257 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+
+use casper_hashing::Digest;
+use casper_types::{Key, StoredValue};
+
+use crate::{
+    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    storage::{
+        global_state::{CommitProvider, StateProvider, StateReader},
+        trie::{merkle_proof::TrieMerkleProof, TrieRaw},
+        trie_store::operations::DeleteResult,
+    },
+};
+
+/// The reads made by an execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadSet {
+    /// The values read from the wrapped state, or `None` for keys which were not found.
+    values: BTreeMap<Key, Option<StoredValue>>,
+    /// The keys whose values were read by the execution, rather than only added to.
+    noted: BTreeSet<Key>,
+}
+
+impl ReadSet {
+    /// Returns the keys the execution depends on, with the values found in the wrapped state.
+    ///
+    /// This excludes existing purse balances which the execution only added to: Adding to a
+    /// balance commutes with other changes to it, so the result of the execution doesn't depend on
+    /// its value.
+    pub fn dependencies(&self) -> impl Iterator<Item = (&Key, &Option<StoredValue>)> {
+        self.values.iter().filter(move |(key, value)| {
+            !matches!(key, Key::Balance(_)) || value.is_none() || self.noted.contains(key)
+        })
+    }
+}
+
+type SharedReadSet = Arc<Mutex<ReadSet>>;
+
+/// Global state wrapper recording the values read from the wrapped state, including the keys which
+/// were not found.
+///
+/// Used to detect whether an execution could have been affected by writes made by other executions
+/// against the same [`ScratchGlobalState`](super::scratch::ScratchGlobalState).
+pub struct RecordingGlobalState<S> {
+    state: S,
+    read_set: SharedReadSet,
+}
+
+/// Represents a "view" of the wrapped global state at a particular root hash.
+pub struct RecordingGlobalStateView<R> {
+    reader: R,
+    read_set: SharedReadSet,
+}
+
+impl<S> RecordingGlobalState<S> {
+    /// Wraps the given state.
+    pub fn new(state: S) -> Self {
+        RecordingGlobalState {
+            state,
+            read_set: Arc::new(Mutex::new(ReadSet::default())),
+        }
+    }
+
+    /// Returns the reads made so far through any view of this state.
+    pub fn read_set(&self) -> ReadSet {
+        self.read_set.lock().unwrap().clone()
+    }
+}
+
+impl<R> RecordingGlobalStateView<R> {
+    fn record(&self, key: &Key, value: Option<&StoredValue>) {
+        self.read_set
+            .lock()
+            .unwrap()
+            .values
+            .entry(*key)
+            .or_insert_with(|| value.cloned());
+    }
+}
+
+impl<R> StateReader<Key, StoredValue> for RecordingGlobalStateView<R>
+where
+    R: StateReader<Key, StoredValue>,
+{
+    type Error = R::Error;
+
+    fn read(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Self::Error> {
+        let maybe_value = self.reader.read(correlation_id, key)?;
+        self.record(key, maybe_value.as_ref());
+        Ok(maybe_value)
+    }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        let maybe_proof = self.reader.read_with_proof(correlation_id, key)?;
+        self.record(key, maybe_proof.as_ref().map(TrieMerkleProof::value));
+        self.note_read(key);
+        Ok(maybe_proof)
+    }
+
+    fn keys_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+    ) -> Result<Vec<Key>, Self::Error> {
+        // Not recorded: enumerating keys of a `ScratchGlobalState` ignores its cached writes, so
+        // the result can't be affected by other executions against the same scratch state.
+        self.reader.keys_with_prefix(correlation_id, prefix)
+    }
+
+    fn note_read(&self, key: &Key) {
+        self.read_set.lock().unwrap().noted.insert(*key);
+    }
+}
+
+impl<S> CommitProvider for RecordingGlobalState<S>
+where
+    S: CommitProvider,
+{
+    fn commit(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Digest,
+        effects: AdditiveMap<Key, Transform>,
+    ) -> Result<Digest, Self::Error> {
+        self.state.commit(correlation_id, state_hash, effects)
+    }
+}
+
+impl<S> StateProvider for RecordingGlobalState<S>
+where
+    S: StateProvider,
+{
+    type Error = S::Error;
+
+    type Reader = RecordingGlobalStateView<S::Reader>;
+
+    fn checkout(&self, state_hash: Digest) -> Result<Option<Self::Reader>, Self::Error> {
+        let maybe_reader = self.state.checkout(state_hash)?;
+        Ok(maybe_reader.map(|reader| RecordingGlobalStateView {
+            reader,
+            read_set: Arc::clone(&self.read_set),
+        }))
+    }
+
+    fn empty_root(&self) -> Digest {
+        self.state.empty_root()
+    }
+
+    fn get_trie_full(
+        &self,
+        correlation_id: CorrelationId,
+        trie_key: &Digest,
+    ) -> Result<Option<TrieRaw>, Self::Error> {
+        self.state.get_trie_full(correlation_id, trie_key)
+    }
+
+    fn put_trie(&self, correlation_id: CorrelationId, trie: &[u8]) -> Result<Digest, Self::Error> {
+        self.state.put_trie(correlation_id, trie)
+    }
+
+    fn missing_children(
+        &self,
+        correlation_id: CorrelationId,
+        trie_raw: &[u8],
+    ) -> Result<Vec<Digest>, Self::Error> {
+        self.state.missing_children(correlation_id, trie_raw)
+    }
+
+    fn delete_keys(
+        &self,
+        correlation_id: CorrelationId,
+        root: Digest,
+        keys_to_delete: &[Key],
+    ) -> Result<DeleteResult, Self::Error> {
+        self.state.delete_keys(correlation_id, root, keys_to_delete)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{account::AccountHash, CLValue, U512};
+
+    use super::*;
+    use crate::{
+        core::tracking_copy::TrackingCopy, storage::global_state::in_memory::InMemoryGlobalState,
+    };
+
+    #[test]
+    fn should_record_found_and_missing_keys() {
+        let correlation_id = CorrelationId::new();
+        let present_key = Key::Account(AccountHash::new([1; 32]));
+        let missing_key = Key::Account(AccountHash::new([2; 32]));
+        let present_value = StoredValue::CLValue(CLValue::from_t(1_i32).unwrap());
+        let (state, root_hash) = InMemoryGlobalState::from_pairs(
+            correlation_id,
+            &[(present_key, present_value.clone())],
+        )
+        .unwrap();
+
+        let recording_state = RecordingGlobalState::new(state);
+        let view = recording_state.checkout(root_hash).unwrap().unwrap();
+        assert!(view.read(correlation_id, &present_key).unwrap().is_some());
+        assert!(view.read(correlation_id, &missing_key).unwrap().is_none());
+
+        let read_set = recording_state.read_set();
+        assert_eq!(
+            read_set.dependencies().collect::<Vec<_>>(),
+            vec![(&present_key, &Some(present_value)), (&missing_key, &None)]
+        );
+    }
+
+    #[test]
+    fn should_not_depend_on_balances_only_added_to() {
+        let correlation_id = CorrelationId::new();
+        let balance =
+            |amount: u64| StoredValue::CLValue(CLValue::from_t(U512::from(amount)).unwrap());
+        let added_key = Key::Balance([1; 32]);
+        let read_key = Key::Balance([2; 32]);
+        let (state, root_hash) = InMemoryGlobalState::from_pairs(
+            correlation_id,
+            &[(added_key, balance(1)), (read_key, balance(2))],
+        )
+        .unwrap();
+
+        let recording_state = RecordingGlobalState::new(state);
+        let view = recording_state.checkout(root_hash).unwrap().unwrap();
+        let mut tracking_copy = TrackingCopy::new(view);
+        tracking_copy
+            .add(correlation_id, added_key, balance(10))
+            .unwrap();
+        tracking_copy
+            .add(correlation_id, read_key, balance(10))
+            .unwrap();
+        // Reading the new value reveals the old one.
+        assert_eq!(
+            tracking_copy.read(correlation_id, &read_key).unwrap(),
+            Some(balance(12))
+        );
+
+        let read_set = recording_state.read_set();
+        assert_eq!(
+            read_set.dependencies().collect::<Vec<_>>(),
+            vec![(&read_key, &Some(balance(2)))]
+        );
+    }
+}
@@ -64,6 +64,9 @@ impl Cache {
 }
 
 /// Global state implemented against LMDB as a backing data store.
+///
+/// Clones share the same cache.
+#[derive(Clone)]
 pub struct ScratchGlobalState {
     /// Underlying, cached stored values.
     cache: SharedCache,
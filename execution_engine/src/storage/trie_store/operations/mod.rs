@@ -1,9 +1,13 @@
 #[cfg(test)]
 mod tests;
 
-#[cfg(test)]
-use std::collections::HashSet;
-use std::{borrow::Cow, cmp, collections::VecDeque, convert::TryInto, mem};
+use std::{
+    borrow::Cow,
+    cmp,
+    collections::{HashSet, VecDeque},
+    convert::TryInto,
+    mem,
+};
 
 use either::Either;
 use num_traits::FromPrimitive;
@@ -1200,6 +1204,637 @@ where
     }
 }
 
+/// A difference between the leaves of two tries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieDiff<K, V> {
+    /// A leaf only present in the new trie.
+    Added {
+        /// Leaf key.
+        key: K,
+        /// Leaf value.
+        value: V,
+    },
+    /// A leaf only present in the old trie.
+    Removed {
+        /// Leaf key.
+        key: K,
+        /// Leaf value.
+        value: V,
+    },
+    /// A leaf present in both tries with different values.
+    Modified {
+        /// Leaf key.
+        key: K,
+        /// Leaf value in the old trie.
+        old_value: V,
+        /// Leaf value in the new trie.
+        new_value: V,
+    },
+}
+
+impl<K, V> TrieDiff<K, V> {
+    /// Returns the key of the leaf which differs.
+    pub fn key(&self) -> &K {
+        match self {
+            TrieDiff::Added { key, .. }
+            | TrieDiff::Removed { key, .. }
+            | TrieDiff::Modified { key, .. } => key,
+        }
+    }
+}
+
+/// The result of diffing two tries.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffResult<K, V> {
+    /// The differences, in the order of their serialized keys.
+    Found(Vec<TrieDiff<K, V>>),
+    /// A root or descendant trie was not found in the store.
+    TrieNotFound(Digest),
+}
+
+/// The result of listing the keys of a trie.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ListKeysResult<K> {
+    /// The keys, in the order of their serialized forms.
+    Found {
+        /// The keys found.
+        keys: Vec<K>,
+        /// If the scanning budget ran out before `limit` keys were found, the last key scanned,
+        /// after which the listing can be resumed.
+        scan_stopped_after: Option<K>,
+    },
+    /// The root or a descendant trie was not found in the store.
+    TrieNotFound(Digest),
+}
+
+enum TrieCursorItem<K, V> {
+    Leaf {
+        key_bytes: Vec<u8>,
+        key: K,
+        value: V,
+    },
+    Subtrie {
+        path: Vec<u8>,
+        trie_key: Digest,
+    },
+}
+
+/// A cursor over the leaves of a trie in the order of their serialized keys, which only loads
+/// subtries when they are expanded.
+struct TrieCursor<K, V> {
+    /// The unvisited leaves and subtries, with the next one in key order last.
+    stack: Vec<TrieCursorItem<K, V>>,
+}
+
+impl<K, V> TrieCursor<K, V>
+where
+    K: ToBytes + FromBytes,
+    V: ToBytes + FromBytes,
+{
+    fn new(root: Digest) -> Self {
+        TrieCursor {
+            stack: vec![TrieCursorItem::Subtrie {
+                path: vec![],
+                trie_key: root,
+            }],
+        }
+    }
+
+    fn peek(&self) -> Option<&TrieCursorItem<K, V>> {
+        self.stack.last()
+    }
+
+    fn pop(&mut self) -> Option<TrieCursorItem<K, V>> {
+        self.stack.pop()
+    }
+
+    /// Replaces the next item, which must be a subtrie, with its children.
+    ///
+    /// Returns the key of the subtrie if it is missing from the store.
+    fn expand_next<T, S, E>(&mut self, txn: &T, store: &S) -> Result<Option<Digest>, E>
+    where
+        T: Readable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error>,
+    {
+        let (path, trie_key) = match self.stack.pop() {
+            Some(TrieCursorItem::Subtrie { path, trie_key }) => (path, trie_key),
+            Some(leaf @ TrieCursorItem::Leaf { .. }) => {
+                self.stack.push(leaf);
+                return Ok(None);
+            }
+            None => return Ok(None),
+        };
+        match store.get(txn, &trie_key)? {
+            None => return Ok(Some(trie_key)),
+            Some(Trie::Leaf { key, value }) => {
+                let key_bytes = key.to_bytes()?;
+                self.stack.push(TrieCursorItem::Leaf {
+                    key_bytes,
+                    key,
+                    value,
+                });
+            }
+            Some(Trie::Node { pointer_block }) => {
+                let indexed_pointers: Vec<_> = pointer_block.as_indexed_pointers().collect();
+                for (index, pointer) in indexed_pointers.into_iter().rev() {
+                    let mut child_path = path.clone();
+                    child_path.push(index);
+                    self.stack.push(TrieCursorItem::Subtrie {
+                        path: child_path,
+                        trie_key: pointer.into_hash(),
+                    });
+                }
+            }
+            Some(Trie::Extension { affix, pointer }) => {
+                let mut child_path = path;
+                child_path.extend_from_slice(&affix);
+                self.stack.push(TrieCursorItem::Subtrie {
+                    path: child_path,
+                    trie_key: pointer.into_hash(),
+                });
+            }
+        }
+        Ok(None)
+    }
+
+    /// Drops all leaves whose serialized keys are not greater than `start_after`, expanding only
+    /// the subtries which may contain both such leaves and others.
+    ///
+    /// Returns the key of a subtrie if it is missing from the store.
+    fn skip_to<T, S, E>(
+        &mut self,
+        txn: &T,
+        store: &S,
+        start_after: &[u8],
+    ) -> Result<Option<Digest>, E>
+    where
+        T: Readable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error>,
+    {
+        loop {
+            match self.peek() {
+                Some(TrieCursorItem::Leaf { key_bytes, .. })
+                    if key_bytes.as_slice() <= start_after =>
+                {
+                    self.pop();
+                }
+                Some(TrieCursorItem::Subtrie { path, .. }) if path.as_slice() <= start_after => {
+                    if start_after.starts_with(path) {
+                        if let Some(missing) = self.expand_next::<_, _, E>(txn, store)? {
+                            return Ok(Some(missing));
+                        }
+                    } else {
+                        self.pop();
+                    }
+                }
+                _ => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Returns up to `limit` differences between the leaves of the tries at `old_root` and `new_root`,
+/// in the order of their serialized keys.
+///
+/// If `start_after` is given, only leaves whose serialized keys are greater than it are compared,
+/// so that the differences can be paginated.  Subtries with the same key at the same position in
+/// both tries are skipped without being read.
+pub fn diff<K, V, T, S, E>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    old_root: &Digest,
+    new_root: &Digest,
+    start_after: Option<&K>,
+    limit: usize,
+) -> Result<DiffResult<K, V>, E>
+where
+    K: ToBytes + FromBytes + Eq,
+    V: ToBytes + FromBytes + Eq,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    for root in [old_root, new_root] {
+        if store.get(txn, root)?.is_none() {
+            return Ok(DiffResult::TrieNotFound(*root));
+        }
+    }
+
+    let mut old = TrieCursor::<K, V>::new(*old_root);
+    let mut new = TrieCursor::<K, V>::new(*new_root);
+    if let Some(start_after) = start_after {
+        let start_after = start_after.to_bytes()?;
+        for cursor in [&mut old, &mut new] {
+            if let Some(missing) = cursor.skip_to::<_, _, E>(txn, store, &start_after)? {
+                return Ok(DiffResult::TrieNotFound(missing));
+            }
+        }
+    }
+
+    let mut diffs = Vec::new();
+    while diffs.len() < limit {
+        let maybe_missing = match (old.peek(), new.peek()) {
+            (None, None) => break,
+            (Some(TrieCursorItem::Leaf { .. }), None) => {
+                if let Some(TrieCursorItem::Leaf { key, value, .. }) = old.pop() {
+                    diffs.push(TrieDiff::Removed { key, value });
+                }
+                None
+            }
+            (None, Some(TrieCursorItem::Leaf { .. })) => {
+                if let Some(TrieCursorItem::Leaf { key, value, .. }) = new.pop() {
+                    diffs.push(TrieDiff::Added { key, value });
+                }
+                None
+            }
+            (Some(TrieCursorItem::Subtrie { .. }), None) => {
+                old.expand_next::<_, _, E>(txn, store)?
+            }
+            (None, Some(TrieCursorItem::Subtrie { .. })) => {
+                new.expand_next::<_, _, E>(txn, store)?
+            }
+            (
+                Some(TrieCursorItem::Subtrie {
+                    path: old_path,
+                    trie_key: old_trie_key,
+                }),
+                Some(TrieCursorItem::Subtrie {
+                    path: new_path,
+                    trie_key: new_trie_key,
+                }),
+            ) => {
+                if old_path == new_path && old_trie_key == new_trie_key {
+                    old.pop();
+                    new.pop();
+                    None
+                } else if old_path.len() <= new_path.len() {
+                    old.expand_next::<_, _, E>(txn, store)?
+                } else {
+                    new.expand_next::<_, _, E>(txn, store)?
+                }
+            }
+            (
+                Some(TrieCursorItem::Leaf { key_bytes, .. }),
+                Some(TrieCursorItem::Subtrie { path, .. }),
+            ) => {
+                // All leaves of the subtrie have keys starting with its path.
+                if key_bytes < path {
+                    if let Some(TrieCursorItem::Leaf { key, value, .. }) = old.pop() {
+                        diffs.push(TrieDiff::Removed { key, value });
+                    }
+                    None
+                } else {
+                    new.expand_next::<_, _, E>(txn, store)?
+                }
+            }
+            (
+                Some(TrieCursorItem::Subtrie { path, .. }),
+                Some(TrieCursorItem::Leaf { key_bytes, .. }),
+            ) => {
+                if key_bytes < path {
+                    if let Some(TrieCursorItem::Leaf { key, value, .. }) = new.pop() {
+                        diffs.push(TrieDiff::Added { key, value });
+                    }
+                    None
+                } else {
+                    old.expand_next::<_, _, E>(txn, store)?
+                }
+            }
+            (
+                Some(TrieCursorItem::Leaf {
+                    key_bytes: old_key_bytes,
+                    ..
+                }),
+                Some(TrieCursorItem::Leaf {
+                    key_bytes: new_key_bytes,
+                    ..
+                }),
+            ) => {
+                match old_key_bytes.cmp(new_key_bytes) {
+                    cmp::Ordering::Less => {
+                        if let Some(TrieCursorItem::Leaf { key, value, .. }) = old.pop() {
+                            diffs.push(TrieDiff::Removed { key, value });
+                        }
+                    }
+                    cmp::Ordering::Greater => {
+                        if let Some(TrieCursorItem::Leaf { key, value, .. }) = new.pop() {
+                            diffs.push(TrieDiff::Added { key, value });
+                        }
+                    }
+                    cmp::Ordering::Equal => {
+                        if let (
+                            Some(TrieCursorItem::Leaf {
+                                key,
+                                value: old_value,
+                                ..
+                            }),
+                            Some(TrieCursorItem::Leaf {
+                                value: new_value, ..
+                            }),
+                        ) = (old.pop(), new.pop())
+                        {
+                            if old_value != new_value {
+                                diffs.push(TrieDiff::Modified {
+                                    key,
+                                    old_value,
+                                    new_value,
+                                });
+                            }
+                        }
+                    }
+                }
+                None
+            }
+        };
+        if let Some(missing) = maybe_missing {
+            return Ok(DiffResult::TrieNotFound(missing));
+        }
+    }
+    Ok(DiffResult::Found(diffs))
+}
+
+/// Returns up to `limit` keys of the leaves of the trie at `root` whose serialized keys start with
+/// `prefix` and which satisfy `filter`, in the order of their serialized keys.
+///
+/// If `start_after` is given, only keys whose serialized forms are greater than it are returned, so
+/// that the keys can be paginated.  Subtries which can't contain matching keys are not read.
+///
+/// At most `max_scanned` leaves are read, whether or not they satisfy `filter`, so that a selective
+/// filter can't cause the whole trie to be read.  If the budget runs out, the last leaf read is
+/// returned as the point to resume from.
+#[allow(clippy::too_many_arguments)]
+pub fn list_keys<K, V, T, S, E, F>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Digest,
+    prefix: &[u8],
+    start_after: Option<&K>,
+    limit: usize,
+    max_scanned: usize,
+    mut filter: F,
+) -> Result<ListKeysResult<K>, E>
+where
+    K: ToBytes + FromBytes + Clone,
+    V: ToBytes + FromBytes,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+    F: FnMut(&K, &V) -> bool,
+{
+    if store.get(txn, root)?.is_none() {
+        return Ok(ListKeysResult::TrieNotFound(*root));
+    }
+
+    let mut cursor = TrieCursor::<K, V>::new(*root);
+    if let Some(start_after) = start_after {
+        let start_after = start_after.to_bytes()?;
+        if let Some(missing) = cursor.skip_to::<_, _, E>(txn, store, &start_after)? {
+            return Ok(ListKeysResult::TrieNotFound(missing));
+        }
+    }
+
+    let mut keys = Vec::new();
+    let mut scanned = 0;
+    let mut last_scanned = None;
+    while keys.len() < limit {
+        let maybe_missing = match cursor.peek() {
+            None => break,
+            Some(TrieCursorItem::Leaf { key_bytes, .. }) => {
+                if !key_bytes.starts_with(prefix) && key_bytes.as_slice() > prefix {
+                    // All following leaves are past the keys starting with `prefix`.
+                    break;
+                }
+                if scanned == max_scanned {
+                    return Ok(ListKeysResult::Found {
+                        keys,
+                        scan_stopped_after: last_scanned,
+                    });
+                }
+                if let Some(TrieCursorItem::Leaf {
+                    key_bytes,
+                    key,
+                    value,
+                }) = cursor.pop()
+                {
+                    scanned += 1;
+                    if key_bytes.starts_with(prefix) && filter(&key, &value) {
+                        keys.push(key.clone());
+                    }
+                    last_scanned = Some(key);
+                }
+                None
+            }
+            Some(TrieCursorItem::Subtrie { path, .. }) => {
+                if path.starts_with(prefix) || prefix.starts_with(path) {
+                    cursor.expand_next::<_, _, E>(txn, store)?
+                } else if path.as_slice() > prefix {
+                    break;
+                } else {
+                    cursor.pop();
+                    None
+                }
+            }
+        };
+        if let Some(missing) = maybe_missing {
+            return Ok(ListKeysResult::TrieNotFound(missing));
+        }
+    }
+    Ok(ListKeysResult::Found {
+        keys,
+        scan_stopped_after: None,
+    })
+}
+
+/// Adds the keys of all tries reachable from `roots` to `reachable`, returning the keys of the
+/// reachable tries which are missing from the store.
+///
+/// Tries which are already in `reachable` aren't descended into again, so subtries shared between
+/// roots are only visited once.  Leaves are marked without being read, hence missing leaves aren't
+/// reported.
+pub fn mark_reachable<K, V, T, S, E>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    roots: &[Digest],
+    reachable: &mut HashSet<Digest>,
+) -> Result<Vec<Digest>, E>
+where
+    K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let mut missing = Vec::new();
+    let mut trie_keys_to_visit = roots.to_vec();
+    while let Some(trie_key) = trie_keys_to_visit.pop() {
+        if !reachable.insert(trie_key) {
+            continue;
+        }
+        let trie_raw = match store.get_raw(txn, &trie_key)? {
+            Some(trie_raw) => trie_raw,
+            None => {
+                missing.push(trie_key);
+                continue;
+            }
+        };
+        // Optimization: Don't deserialize leaves as they have no descendants.
+        if let Some(TrieTag::Leaf) = trie_raw.first().copied().and_then(TrieTag::from_u8) {
+            continue;
+        }
+        match bytesrepr::deserialize_from_slice(trie_raw)? {
+            Trie::<K, V>::Leaf { .. } => {}
+            Trie::Node { pointer_block } => {
+                for (_, pointer) in pointer_block.as_indexed_pointers() {
+                    match pointer {
+                        Pointer::LeafPointer(leaf_key) => {
+                            reachable.insert(leaf_key);
+                        }
+                        Pointer::NodePointer(node_key) => trie_keys_to_visit.push(node_key),
+                    }
+                }
+            }
+            Trie::Extension { pointer, .. } => trie_keys_to_visit.push(pointer.into_hash()),
+        }
+    }
+    Ok(missing)
+}
+
+/// A violation of the integrity of a trie store.
+#[derive(Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The trie was not found in the store.
+    MissingTrie(Digest),
+    /// The trie stored under `trie_key` doesn't hash to it.
+    HashMismatch {
+        /// The key under which the trie is stored.
+        trie_key: Digest,
+        /// The actual hash of the stored trie.
+        actual_hash: Digest,
+    },
+    /// The trie stored under `trie_key` couldn't be parsed.
+    Malformed {
+        /// The key under which the trie is stored.
+        trie_key: Digest,
+        /// The parsing error.
+        error: bytesrepr::Error,
+    },
+    /// The key of the leaf stored under `trie_key` doesn't start with the path leading to it.
+    MisplacedLeaf {
+        /// The key under which the leaf is stored.
+        trie_key: Digest,
+        /// The path leading to the leaf.
+        path: Vec<u8>,
+    },
+}
+
+/// The outcome of checking the integrity of a trie.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The number of tries which were checked.
+    pub tries_checked: u64,
+    /// The integrity violations found, in the order they were encountered.
+    pub errors: Vec<IntegrityError>,
+}
+
+/// Checks the integrity of every trie reachable from `root`.
+///
+/// Unlike the walks performed when reading from or writing to the store, this doesn't stop at the
+/// first problem: every trie which is present is checked to hash to its key and to be well-formed,
+/// and every child reported by [`missing_children`] is recorded as missing.
+pub fn check_trie_integrity<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Digest,
+) -> Result<IntegrityReport, E>
+where
+    K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let mut report = IntegrityReport::default();
+    if store.get_raw(txn, root)?.is_none() {
+        report.errors.push(IntegrityError::MissingTrie(*root));
+        return Ok(report);
+    }
+
+    let mut trie_keys_to_visit = vec![(Vec::new(), *root)];
+    while let Some((path, trie_key)) = trie_keys_to_visit.pop() {
+        // Only present tries are queued, as missing ones are reported by their parents.
+        let trie_raw = match store.get_raw(txn, &trie_key)? {
+            Some(trie_raw) => trie_raw,
+            None => continue,
+        };
+        report.tries_checked += 1;
+
+        let actual_hash = Digest::hash_into_chunks_if_necessary(&trie_raw);
+        if actual_hash != trie_key {
+            report.errors.push(IntegrityError::HashMismatch {
+                trie_key,
+                actual_hash,
+            });
+        }
+
+        let trie: Trie<K, V> = match bytesrepr::deserialize_from_slice(&trie_raw) {
+            Ok(trie) => trie,
+            Err(error) => {
+                report
+                    .errors
+                    .push(IntegrityError::Malformed { trie_key, error });
+                continue;
+            }
+        };
+
+        let missing = missing_children::<K, V, T, S, E>(correlation_id, txn, store, &trie_raw)?;
+        report
+            .errors
+            .extend(missing.iter().copied().map(IntegrityError::MissingTrie));
+        let is_present = |child_key: &Digest| !missing.contains(child_key);
+
+        match trie {
+            Trie::Leaf { key, .. } => {
+                if !key.to_bytes()?.starts_with(&path) {
+                    report
+                        .errors
+                        .push(IntegrityError::MisplacedLeaf { trie_key, path });
+                }
+            }
+            Trie::Node { pointer_block } => {
+                for (byte, pointer) in pointer_block.as_indexed_pointers() {
+                    let child_key = pointer.into_hash();
+                    if is_present(&child_key) {
+                        let mut child_path = path.clone();
+                        child_path.push(byte);
+                        trie_keys_to_visit.push((child_path, child_key));
+                    }
+                }
+            }
+            Trie::Extension { affix, pointer } => {
+                let child_key = pointer.into_hash();
+                if is_present(&child_key) {
+                    let mut child_path = path;
+                    child_path.extend_from_slice(&affix);
+                    trie_keys_to_visit.push((child_path, child_key));
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
 /// Returns the iterator over the keys at a given root hash.
 ///
 /// The root should be the apex of the trie.
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use casper_hashing::Digest;
+
+use super::*;
+use crate::storage::trie_store::operations::{DiffResult, TrieDiff};
+
+const OLD_KEYS: std::ops::Range<u8> = 0..40;
+const NEW_KEYS: std::ops::Range<u8> = 20..60;
+const MODIFIED_KEYS: std::ops::Range<u8> = 30..35;
+
+/// Returns a test key sharing varying prefixes with other test keys, so that the trie contains
+/// both nodes and extensions.
+fn test_key(index: u8) -> TestKey {
+    TestKey([index % 3, index / 3 % 4, 0, 0, 0, index / 12, index])
+}
+
+fn old_pairs() -> Vec<(TestKey, TestValue)> {
+    OLD_KEYS
+        .map(|index| (test_key(index), TestValue([1; TEST_VAL_LENGTH])))
+        .collect()
+}
+
+fn new_pairs() -> Vec<(TestKey, TestValue)> {
+    NEW_KEYS
+        .map(|index| {
+            let value = if MODIFIED_KEYS.contains(&index) {
+                TestValue([2; TEST_VAL_LENGTH])
+            } else {
+                TestValue([1; TEST_VAL_LENGTH])
+            };
+            (test_key(index), value)
+        })
+        .collect()
+}
+
+fn expected_diffs() -> Vec<TrieDiff<TestKey, TestValue>> {
+    let old: BTreeMap<_, _> = old_pairs().into_iter().collect();
+    let new: BTreeMap<_, _> = new_pairs().into_iter().collect();
+    let mut diffs = BTreeMap::new();
+    for (key, value) in &old {
+        match new.get(key) {
+            None => {
+                diffs.insert(
+                    *key,
+                    TrieDiff::Removed {
+                        key: *key,
+                        value: *value,
+                    },
+                );
+            }
+            Some(new_value) if new_value != value => {
+                diffs.insert(
+                    *key,
+                    TrieDiff::Modified {
+                        key: *key,
+                        old_value: *value,
+                        new_value: *new_value,
+                    },
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, value) in &new {
+        if !old.contains_key(key) {
+            diffs.insert(
+                *key,
+                TrieDiff::Added {
+                    key: *key,
+                    value: *value,
+                },
+            );
+        }
+    }
+    diffs.into_values().collect()
+}
+
+/// Writes the old and new pairs on top of an empty trie, returning the two resulting roots.
+fn write_old_and_new(context: &InMemoryTestContext, empty_root_hash: &Digest) -> (Digest, Digest) {
+    let correlation_id = CorrelationId::new();
+    let old_root = *write_pairs::<_, _, _, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        empty_root_hash,
+        &old_pairs(),
+    )
+    .unwrap()
+    .last()
+    .unwrap();
+    let new_root = *write_pairs::<_, _, _, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        empty_root_hash,
+        &new_pairs(),
+    )
+    .unwrap()
+    .last()
+    .unwrap();
+    (old_root, new_root)
+}
+
+fn diff(
+    context: &InMemoryTestContext,
+    old_root: &Digest,
+    new_root: &Digest,
+    start_after: Option<&TestKey>,
+    limit: usize,
+) -> DiffResult<TestKey, TestValue> {
+    let txn = context.environment.create_read_txn().unwrap();
+    let result = operations::diff::<TestKey, TestValue, _, _, in_memory::Error>(
+        CorrelationId::new(),
+        &txn,
+        &context.store,
+        old_root,
+        new_root,
+        start_after,
+        limit,
+    )
+    .unwrap();
+    txn.commit().unwrap();
+    result
+}
+
+#[test]
+fn should_diff_tries() {
+    let (empty_root_hash, tries) = create_0_leaf_trie().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+    let (old_root, new_root) = write_old_and_new(&context, &empty_root_hash);
+
+    let expected = expected_diffs();
+    assert_eq!(
+        diff(&context, &old_root, &new_root, None, usize::MAX),
+        DiffResult::Found(expected)
+    );
+    assert_eq!(
+        diff(&context, &old_root, &old_root, None, usize::MAX),
+        DiffResult::Found(vec![])
+    );
+}
+
+#[test]
+fn should_paginate_diff() {
+    let (empty_root_hash, tries) = create_0_leaf_trie().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+    let (old_root, new_root) = write_old_and_new(&context, &empty_root_hash);
+
+    let mut actual = vec![];
+    let mut start_after = None;
+    loop {
+        let page = match diff(&context, &old_root, &new_root, start_after.as_ref(), 7) {
+            DiffResult::Found(page) => page,
+            DiffResult::TrieNotFound(trie_key) => panic!("missing trie {:?}", trie_key),
+        };
+        match page.last() {
+            Some(last) => start_after = Some(*last.key()),
+            None => break,
+        }
+        assert!(page.len() <= 7);
+        actual.extend(page);
+    }
+    assert_eq!(actual, expected_diffs());
+}
+
+#[test]
+fn should_report_missing_root() {
+    let (empty_root_hash, tries) = create_0_leaf_trie().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+    let missing_root = Digest::hash([1]);
+
+    assert_eq!(
+        diff(&context, &empty_root_hash, &missing_root, None, usize::MAX),
+        DiffResult::TrieNotFound(missing_root)
+    );
+}
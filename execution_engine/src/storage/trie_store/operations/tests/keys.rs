@@ -314,3 +314,227 @@ mod keys_with_prefix_iterator {
         test_prefix(&[0, 0, 0, 0, 0, 0, 1]); // 1 leaf
     }
 }
+
+mod list_keys {
+    use casper_hashing::Digest;
+
+    use crate::{
+        shared::newtypes::CorrelationId,
+        storage::{
+            error::in_memory,
+            transaction_source::{Transaction, TransactionSource},
+            trie::Trie,
+            trie_store::operations::{
+                self,
+                tests::{create_6_leaf_trie, InMemoryTestContext, TestKey, TestValue, TEST_LEAVES},
+                ListKeysResult,
+            },
+        },
+    };
+
+    const PREFIXES: &[&[u8]] = &[
+        &[],
+        &[0],
+        &[0, 1],
+        &[0, 1, 1],
+        &[0, 0],
+        &[0, 0, 2],
+        &[0, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 0, 1],
+        &[1],
+    ];
+
+    fn expected_keys(prefix: &[u8]) -> Vec<TestKey> {
+        let mut tmp = TEST_LEAVES
+            .iter()
+            .filter_map(Trie::key)
+            .filter(|key| key.0.starts_with(prefix))
+            .cloned()
+            .collect::<Vec<TestKey>>();
+        tmp.sort();
+        tmp
+    }
+
+    fn list_keys<F: FnMut(&TestKey, &TestValue) -> bool>(
+        context: &InMemoryTestContext,
+        root_hash: &Digest,
+        prefix: &[u8],
+        start_after: Option<&TestKey>,
+        limit: usize,
+        filter: F,
+    ) -> Vec<TestKey> {
+        let (keys, scan_stopped_after) = list_keys_scanning_at_most(
+            context,
+            root_hash,
+            prefix,
+            start_after,
+            limit,
+            usize::MAX,
+            filter,
+        );
+        assert!(scan_stopped_after.is_none());
+        keys
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn list_keys_scanning_at_most<F: FnMut(&TestKey, &TestValue) -> bool>(
+        context: &InMemoryTestContext,
+        root_hash: &Digest,
+        prefix: &[u8],
+        start_after: Option<&TestKey>,
+        limit: usize,
+        max_scanned: usize,
+        filter: F,
+    ) -> (Vec<TestKey>, Option<TestKey>) {
+        let txn = context.environment.create_read_txn().unwrap();
+        let result = operations::list_keys::<TestKey, TestValue, _, _, in_memory::Error, _>(
+            CorrelationId::new(),
+            &txn,
+            &context.store,
+            root_hash,
+            prefix,
+            start_after,
+            limit,
+            max_scanned,
+            filter,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        match result {
+            ListKeysResult::Found {
+                keys,
+                scan_stopped_after,
+            } => (keys, scan_stopped_after),
+            ListKeysResult::TrieNotFound(trie_key) => panic!("missing trie {:?}", trie_key),
+        }
+    }
+
+    #[test]
+    fn should_list_keys_with_prefix() {
+        let (root_hash, tries) = create_6_leaf_trie().unwrap();
+        let context = InMemoryTestContext::new(&tries).unwrap();
+        for prefix in PREFIXES {
+            let actual = list_keys(&context, &root_hash, prefix, None, usize::MAX, |_, _| true);
+            assert_eq!(actual, expected_keys(prefix), "prefix {:?}", prefix);
+        }
+    }
+
+    #[test]
+    fn should_paginate_keys() {
+        let (root_hash, tries) = create_6_leaf_trie().unwrap();
+        let context = InMemoryTestContext::new(&tries).unwrap();
+        for prefix in PREFIXES {
+            let mut actual = vec![];
+            let mut start_after = None;
+            loop {
+                let page = list_keys(
+                    &context,
+                    &root_hash,
+                    prefix,
+                    start_after.as_ref(),
+                    2,
+                    |_, _| true,
+                );
+                assert!(page.len() <= 2);
+                match page.last() {
+                    Some(last) => start_after = Some(*last),
+                    None => break,
+                }
+                actual.extend(page);
+            }
+            assert_eq!(actual, expected_keys(prefix), "prefix {:?}", prefix);
+        }
+    }
+
+    #[test]
+    fn should_filter_keys() {
+        let (root_hash, tries) = create_6_leaf_trie().unwrap();
+        let context = InMemoryTestContext::new(&tries).unwrap();
+        let expected: Vec<_> = expected_keys(&[])
+            .into_iter()
+            .filter(|key| key.0[6] % 2 == 0)
+            .collect();
+        let actual = list_keys(&context, &root_hash, &[], None, usize::MAX, |key, _| {
+            key.0[6] % 2 == 0
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_stop_scanning_when_budget_exhausted() {
+        let (root_hash, tries) = create_6_leaf_trie().unwrap();
+        let context = InMemoryTestContext::new(&tries).unwrap();
+        let all_keys = expected_keys(&[]);
+
+        // A filter matching only the last key shouldn't cause all the keys to be read at once.
+        let last_key = *all_keys.last().unwrap();
+        let (keys, scan_stopped_after) =
+            list_keys_scanning_at_most(&context, &root_hash, &[], None, 1, 2, |key, _| {
+                *key == last_key
+            });
+        assert!(keys.is_empty());
+        assert_eq!(scan_stopped_after, Some(all_keys[1]));
+
+        // Resuming from where the scan stopped should eventually find it.
+        let mut found = vec![];
+        let mut start_after = None;
+        loop {
+            let (keys, scan_stopped_after) = list_keys_scanning_at_most(
+                &context,
+                &root_hash,
+                &[],
+                start_after.as_ref(),
+                1,
+                2,
+                |key, _| *key == last_key,
+            );
+            found.extend(keys);
+            match scan_stopped_after {
+                Some(key) => start_after = Some(key),
+                None => break,
+            }
+        }
+        assert_eq!(found, vec![last_key]);
+
+        // Matching keys found within the budget are returned along with where the scan stopped.
+        let (keys, scan_stopped_after) =
+            list_keys_scanning_at_most(&context, &root_hash, &[], None, usize::MAX, 3, |_, _| true);
+        assert_eq!(keys, all_keys[..3]);
+        assert_eq!(scan_stopped_after, Some(all_keys[2]));
+
+        // No resumption point is given if the scan ends exactly on the budget.
+        let (keys, scan_stopped_after) = list_keys_scanning_at_most(
+            &context,
+            &root_hash,
+            &[],
+            None,
+            usize::MAX,
+            all_keys.len(),
+            |_, _| true,
+        );
+        assert_eq!(keys, all_keys);
+        assert!(scan_stopped_after.is_none());
+    }
+
+    #[test]
+    fn should_report_missing_root() {
+        let (_, tries) = create_6_leaf_trie().unwrap();
+        let context = InMemoryTestContext::new(&tries).unwrap();
+        let missing_root = Digest::hash([1]);
+        let txn = context.environment.create_read_txn().unwrap();
+        let result = operations::list_keys::<TestKey, TestValue, _, _, in_memory::Error, _>(
+            CorrelationId::new(),
+            &txn,
+            &context.store,
+            &missing_root,
+            &[],
+            None,
+            usize::MAX,
+            usize::MAX,
+            |_, _| true,
+        )
+        .unwrap();
+        assert_eq!(result, ListKeysResult::TrieNotFound(missing_root));
+    }
+}
@@ -1,4 +1,5 @@
 mod delete;
+mod diff;
 mod ee_699;
 mod keys;
 mod proptests;
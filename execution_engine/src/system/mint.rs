@@ -240,7 +240,8 @@ pub trait Mint: RuntimeProvider + StorageProvider + SystemProvider {
         if amount > source_balance {
             return Err(Error::InsufficientFunds);
         }
-        if self.read_balance(target)?.is_none() {
+        // The target's balance itself doesn't matter: Adding to it commutes with other transfers.
+        if !self.balance_exists(target)? {
             return Err(Error::DestNotFound);
         }
         if self.get_caller() != PublicKey::System.to_account_hash()
@@ -21,6 +21,9 @@ pub trait StorageProvider {
     /// Read balance.
     fn read_balance(&mut self, uref: URef) -> Result<Option<U512>, Error>;
 
+    /// Returns whether the balance exists, without depending on its value.
+    fn balance_exists(&mut self, uref: URef) -> Result<bool, Error>;
+
     /// Write balance.
     fn write_balance(&mut self, uref: URef, balance: U512) -> Result<(), Error>;
 
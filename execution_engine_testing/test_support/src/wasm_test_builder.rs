@@ -15,6 +15,8 @@ use log::LevelFilter;
 use num_rational::Ratio;
 use num_traits::CheckedMul;
 
+#[cfg(feature = "compiled-wasm-engine")]
+use casper_execution_engine::core::wasm_engine::WasmEngineKind;
 use casper_execution_engine::{
     core::{
         engine_state::{
@@ -90,6 +92,17 @@ const DEFAULT_MAX_READERS: u32 = 512;
 /// This is appended to the data dir path provided to the `LmdbWasmTestBuilder`".
 const GLOBAL_STATE_DIR: &str = "global_state";
 
+/// Selects the Wasm engine the tests are run with, as per the `compiled-wasm-engine` feature.
+fn with_test_wasm_engine(engine_config: EngineConfig) -> EngineConfig {
+    #[cfg(feature = "compiled-wasm-engine")]
+    let engine_config = {
+        let mut engine_config = engine_config;
+        engine_config.set_wasm_engine(WasmEngineKind::Compiled);
+        engine_config
+    };
+    engine_config
+}
+
 /// Wasm test builder where state is held entirely in memory.
 pub type InMemoryWasmTestBuilder = WasmTestBuilder<InMemoryGlobalState>;
 /// Wasm test builder where state is held in LMDB.
@@ -164,7 +177,7 @@ impl InMemoryWasmTestBuilder {
 
         let genesis_hash = global_state.empty_root();
 
-        let engine_state = EngineState::new(global_state, engine_config);
+        let engine_state = EngineState::new(global_state, with_test_wasm_engine(engine_config));
 
         WasmTestBuilder {
             exec_results: Vec::new(),
@@ -189,7 +202,7 @@ impl InMemoryWasmTestBuilder {
         maybe_post_state_hash: Option<Digest>,
     ) -> Self {
         Self::initialize_logging();
-        let engine_state = EngineState::new(global_state, engine_config);
+        let engine_state = EngineState::new(global_state, with_test_wasm_engine(engine_config));
         WasmTestBuilder {
             engine_state: Rc::new(engine_state),
             exec_results: Vec::new(),
@@ -267,7 +280,7 @@ impl LmdbWasmTestBuilder {
         upgrade_config.with_pre_state_hash(pre_state_hash);
 
         let engine_state = Rc::get_mut(&mut self.engine_state).unwrap();
-        engine_state.update_config(engine_config);
+        engine_state.update_config(with_test_wasm_engine(engine_config));
 
         let scratch_state = self.engine_state.get_scratch_engine_state();
         let pre_state_hash = upgrade_config.pre_state_hash();
@@ -327,7 +340,7 @@ impl LmdbWasmTestBuilder {
         let global_state =
             LmdbGlobalState::empty(environment, trie_store).expect("should create LmdbGlobalState");
 
-        let engine_state = EngineState::new(global_state, engine_config);
+        let engine_state = EngineState::new(global_state, with_test_wasm_engine(engine_config));
         WasmTestBuilder {
             engine_state: Rc::new(engine_state),
             exec_results: Vec::new(),
@@ -409,7 +422,7 @@ impl LmdbWasmTestBuilder {
         let global_state =
             LmdbGlobalState::empty(environment, trie_store).expect("should create LmdbGlobalState");
 
-        let engine_state = EngineState::new(global_state, engine_config);
+        let engine_state = EngineState::new(global_state, with_test_wasm_engine(engine_config));
 
         let mut builder = WasmTestBuilder {
             engine_state: Rc::new(engine_state),
@@ -788,7 +801,7 @@ where
 
         let engine_state_mut =
             Rc::get_mut(&mut self.engine_state).expect("should have unique ownership");
-        engine_state_mut.update_config(engine_config);
+        engine_state_mut.update_config(with_test_wasm_engine(engine_config));
 
         let result = engine_state_mut.commit_upgrade(CorrelationId::new(), upgrade_config.clone());
 
@@ -85,6 +85,22 @@ impl ChunkWithProof {
         })
     }
 
+    /// Constructs the [`ChunkWithProof`] for `chunk`, which is the chunk with the given index of
+    /// data whose chunks have the given hashes.
+    ///
+    /// Unlike [`ChunkWithProof::new`], this doesn't require all of the data to be held in memory at
+    /// once.  The resulting proof is only valid if `chunk` hashes to `chunk_hashes[index]`.
+    pub fn from_chunk_hashes(
+        chunk: &[u8],
+        chunk_hashes: &[Digest],
+        index: u64,
+    ) -> Result<Self, MerkleConstructionError> {
+        Ok(ChunkWithProof {
+            proof: IndexedMerkleProof::new(chunk_hashes.iter().copied(), index)?,
+            chunk: Bytes::from(chunk),
+        })
+    }
+
     /// Get a reference to the `ChunkWithProof`'s chunk.
     pub fn chunk(&self) -> &[u8] {
         self.chunk.as_slice()
@@ -235,6 +251,25 @@ mod tests {
         }
     }
 
+    #[proptest]
+    fn constructs_same_chunks_from_chunk_hashes(test_data: TestDataSizeAtLeastTwoChunks) {
+        let data = prepare_bytes(test_data.0);
+        let chunk_hashes: Vec<_> = data
+            .chunks(ChunkWithProof::CHUNK_SIZE_BYTES)
+            .map(Digest::hash)
+            .collect();
+
+        for (index, chunk) in data.chunks(ChunkWithProof::CHUNK_SIZE_BYTES).enumerate() {
+            let chunk_with_proof =
+                ChunkWithProof::from_chunk_hashes(chunk, &chunk_hashes, index as u64).unwrap();
+            assert!(chunk_with_proof.verify().is_ok());
+            assert_eq!(
+                chunk_with_proof,
+                ChunkWithProof::new(data.as_slice(), index as u64).unwrap()
+            );
+        }
+    }
+
     #[proptest]
     fn verifies_chunk_with_proofs(test_data: TestDataSize) {
         for data in [prepare_bytes(test_data.0), vec![0u8; test_data.0]] {
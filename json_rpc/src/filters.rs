@@ -8,8 +8,8 @@ mod tests;
 
 use bytes::Bytes;
 use http::{header::CONTENT_TYPE, HeaderMap, StatusCode};
-use serde_json::{json, Map, Value};
-use tracing::{debug, trace, warn};
+use serde_json::json;
+use tracing::{trace, warn};
 use warp::{
     body,
     filters::BoxedFilter,
@@ -19,11 +19,8 @@ use warp::{
 };
 
 use crate::{
-    error::{Error, ReservedErrorCode},
     rejections::{BodyTooLarge, MissingContentTypeHeader, MissingId, UnsupportedMediaType},
-    request::{ErrorOrRejection, Request},
     request_handlers::RequestHandlers,
-    response::Response,
 };
 
 const CONTENT_TYPE_VALUE: &str = "application/json";
@@ -66,49 +63,19 @@ pub fn base_filter<P: AsRef<str>>(path: P, max_body_bytes: u32) -> BoxedFilter<(
         .boxed()
 }
 
-/// Handles parsing a JSON-RPC request from the given HTTP body, executing it using the appropriate
-/// handler, and providing a JSON-RPC response (which could be a success or failure).
-///
-/// Returns an `Err(Rejection)` only if the request is a Notification as per the JSON-RPC
-/// specification, i.e. the request doesn't contain an "id" field.  In this case, no JSON-RPC
-/// response is sent to the client.
-///
-/// If `allow_unknown_fields` is `false`, requests with unknown fields will cause the server to
-/// respond with an error.
-async fn handle_body(
-    body: Bytes,
-    handlers: RequestHandlers,
-    allow_unknown_fields: bool,
-) -> Result<Response, Rejection> {
-    let response = match serde_json::from_slice::<Map<String, Value>>(&body) {
-        Ok(unvalidated_request) => match Request::new(unvalidated_request, allow_unknown_fields) {
-            Ok(request) => handlers.handle_request(request).await,
-            Err(ErrorOrRejection::Error { id, error }) => {
-                debug!(?error, "got an invalid request");
-                Response::new_failure(id, error)
-            }
-            Err(ErrorOrRejection::Rejection(rejection)) => {
-                debug!(?rejection, "rejecting an invalid request");
-                return Err(rejection);
-            }
-        },
-        Err(error) => {
-            debug!(%error, "got bad json");
-            let error = Error::new(ReservedErrorCode::ParseError, error.to_string());
-            Response::new_failure(Value::Null, error)
-        }
-    };
-    Ok(response)
-}
-
 /// Returns a boxed warp filter which handles parsing a JSON-RPC request from the given HTTP body,
 /// executing it using the appropriate handler, and providing a reply.
 ///
 /// The reply will normally be built from a JSON-RPC response (which could be a success or failure).
 ///
+/// If the body holds a batch (an array of requests), the requests are executed concurrently and the
+/// reply is built from an array of their responses.  The maximum number of requests allowed in a
+/// batch is set via [`set_max_batch_size`](crate::RequestHandlersBuilder::set_max_batch_size).
+///
 /// However, the reply could be built from a [`Rejection`] if the request is a Notification as per
-/// the JSON-RPC specification, i.e. the request doesn't contain an "id" field.  In this case, no
-/// JSON-RPC response is sent to the client, only an HTTP response.
+/// the JSON-RPC specification, i.e. the request doesn't contain an "id" field, or if the request is
+/// a batch consisting only of Notifications.  In this case, no JSON-RPC response is sent to the
+/// client, only an HTTP response.
 ///
 /// If `allow_unknown_fields` is `false`, requests with unknown fields will cause the server to
 /// respond with an error.
@@ -117,9 +84,9 @@ pub fn main_filter(
     allow_unknown_fields: bool,
 ) -> BoxedFilter<(WithStatus<reply::Json>,)> {
     body::bytes()
-        .and_then(move |body| {
+        .and_then(move |body: Bytes| {
             let handlers = handlers.clone();
-            async move { handle_body(body, handlers, allow_unknown_fields).await }
+            async move { handlers.handle_body(&body, allow_unknown_fields).await }
         })
         .map(|response| reply::with_status(reply::json(&response), StatusCode::OK))
         .boxed()
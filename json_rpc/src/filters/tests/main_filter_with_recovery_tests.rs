@@ -1,5 +1,9 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
+use futures::future;
 use http::StatusCode;
 use serde::{
     ser::{Error as _, Serializer},
@@ -14,6 +18,8 @@ use crate::{
     Error, Params, RequestHandlersBuilder, ReservedErrorCode, Response,
 };
 
+const MAX_BATCH_SIZE: usize = 2;
+
 const GET_GOOD_THING: &str = "get good thing";
 const GET_BAD_THING: &str = "get bad thing";
 
@@ -50,9 +56,16 @@ async fn from_http_response(response: http::Response<hyper::Body>) -> Response {
 }
 
 fn main_filter_with_recovery() -> BoxedFilter<(impl Reply,)> {
+    main_filter_with_recovery_and_max_batch_size(0)
+}
+
+fn main_filter_with_recovery_and_max_batch_size(
+    max_batch_size: usize,
+) -> BoxedFilter<(impl Reply,)> {
     let mut handlers = RequestHandlersBuilder::new();
     handlers.register_handler(GET_GOOD_THING, Arc::new(get_good_thing));
     handlers.register_handler(GET_BAD_THING, Arc::new(get_bad_thing));
+    handlers.set_max_batch_size(max_batch_size);
     let handlers = handlers.build();
 
     main_filter(handlers, false)
@@ -318,3 +331,98 @@ async fn should_handle_invalid_json() {
         )
     );
 }
+
+#[tokio::test]
+async fn should_handle_batch_request() {
+    let _ = env_logger::try_init();
+
+    let filter = main_filter_with_recovery_and_max_batch_size(MAX_BATCH_SIZE);
+
+    // This should get handled by `filters::handle_body` and return an array of responses, one for
+    // each request in the batch, in the same order.
+    let http_response = warp::test::request()
+        .body(
+            r#"[{"jsonrpc":"2.0","id":1,"method":"get good thing","params":["one"]},
+                {"jsonrpc":"2.0","id":"a","method":"get good thing"}]"#,
+        )
+        .filter(&filter)
+        .await
+        .unwrap()
+        .into_response();
+
+    assert_eq!(http_response.status(), StatusCode::OK);
+    let body_bytes = hyper::body::to_bytes(http_response.into_body())
+        .await
+        .unwrap();
+    let rpc_responses: Vec<Response> = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(rpc_responses.len(), 2);
+    assert_eq!(rpc_responses[0].id(), 1);
+    assert_eq!(
+        rpc_responses[0].result(),
+        Some(GoodThing {
+            good_thing: "one".to_string()
+        })
+    );
+    assert_eq!(rpc_responses[1].id(), "a");
+    assert_eq!(
+        rpc_responses[1].error().unwrap(),
+        &Error::new(ReservedErrorCode::InvalidParams, "no params")
+    );
+}
+
+#[tokio::test]
+async fn should_handle_batch_request_when_batches_disabled() {
+    let _ = env_logger::try_init();
+
+    let filter = main_filter_with_recovery();
+
+    // This should get handled by `filters::handle_body` and return a single Response::Failure
+    // (invalid request) to the client as batches are not supported.
+    let http_response = warp::test::request()
+        .body(r#"[{"jsonrpc":"2.0","id":1,"method":"get good thing","params":["one"]}]"#)
+        .filter(&filter)
+        .await
+        .unwrap()
+        .into_response();
+
+    assert_eq!(http_response.status(), StatusCode::OK);
+    let rpc_response = from_http_response(http_response).await;
+    assert_eq!(rpc_response.id(), &Value::Null);
+    assert_eq!(
+        rpc_response.error().unwrap(),
+        &Error::new(
+            ReservedErrorCode::InvalidRequest,
+            "Batch requests are not supported"
+        )
+    );
+}
+
+#[tokio::test]
+async fn should_limit_each_request_in_batch_request() {
+    let _ = env_logger::try_init();
+
+    let limited_count = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&limited_count);
+    let mut handlers = RequestHandlersBuilder::new();
+    handlers.register_handler(GET_GOOD_THING, Arc::new(get_good_thing));
+    handlers.set_max_batch_size(MAX_BATCH_SIZE);
+    let handlers = handlers.build().with_limiter(move || {
+        count.fetch_add(1, Ordering::SeqCst);
+        future::ready(())
+    });
+    let filter = main_filter(handlers, false).recover(handle_rejection);
+
+    // Each request in the batch should be counted by the limiter, not just the batch as a whole.
+    let http_response = warp::test::request()
+        .body(
+            r#"[{"jsonrpc":"2.0","id":1,"method":"get good thing","params":["one"]},
+                {"jsonrpc":"2.0","id":2,"method":"get good thing","params":["two"]}]"#,
+        )
+        .filter(&filter)
+        .await
+        .unwrap()
+        .into_response();
+
+    assert_eq!(http_response.status(), StatusCode::OK);
+    assert_eq!(limited_count.load(Ordering::SeqCst), MAX_BATCH_SIZE);
+}
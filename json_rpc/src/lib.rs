@@ -90,8 +90,8 @@ use warp::{filters::BoxedFilter, Filter, Reply};
 
 pub use error::{Error, ErrorCodeT, ReservedErrorCode};
 pub use request::Params;
-pub use request_handlers::{RequestHandlers, RequestHandlersBuilder};
-pub use response::Response;
+pub use request_handlers::{RequestHandlers, RequestHandlersBuilder, DEFAULT_MAX_BATCH_SIZE};
+pub use response::{Response, ResponseBody};
 
 const JSON_RPC_VERSION: &str = "2.0";
 
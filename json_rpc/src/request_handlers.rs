@@ -1,29 +1,58 @@
 use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc};
 
-use futures::FutureExt;
+use futures::{future, FutureExt};
 use serde::Serialize;
 use serde_json::Value;
 use tracing::{debug, error};
+use warp::reject::{self, Rejection};
 
 use crate::{
     error::{Error, ReservedErrorCode},
-    request::{Params, Request},
-    response::Response,
+    rejections::MissingId,
+    request::{ErrorOrRejection, Params, Request},
+    response::{Response, ResponseBody},
 };
 
+/// The default maximum number of requests allowed in a single batch.  Batch requests are not
+/// supported by default.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 0;
+
 /// A boxed future of `Result<Value, Error>`; the return type of a request-handling closure.
 type HandleRequestFuture = Pin<Box<dyn Future<Output = Result<Value, Error>> + Send>>;
 /// A request-handling closure.
 type RequestHandler = Arc<dyn Fn(Option<Params>) -> HandleRequestFuture + Send + Sync>;
+/// A closure returning a future which completes once the next request may be handled.
+type RequestLimiter = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
 
 /// A collection of request-handlers, indexed by the JSON-RPC "method" applicable to each.
 ///
 /// There needs to be a unique handler for each JSON-RPC request "method" to be handled.  Handlers
 /// are added via a [`RequestHandlersBuilder`].
 #[derive(Clone)]
-pub struct RequestHandlers(Arc<HashMap<&'static str, RequestHandler>>);
+pub struct RequestHandlers {
+    handlers: Arc<HashMap<&'static str, RequestHandler>>,
+    max_batch_size: usize,
+    limiter: Option<RequestLimiter>,
+}
 
 impl RequestHandlers {
+    /// Returns a copy of these handlers which wait for the future returned by `limiter` to complete
+    /// before handling each request, replacing any limiter already set.
+    ///
+    /// Every request in a batch is limited individually, so this can be used to rate-limit
+    /// requests regardless of how they're batched.
+    pub fn with_limiter<F, Fut>(&self, limiter: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        RequestHandlers {
+            handlers: Arc::clone(&self.handlers),
+            max_batch_size: self.max_batch_size,
+            limiter: Some(Arc::new(move || limiter().boxed())),
+        }
+    }
+
     /// Finds the relevant handler for the given request's "method" field, and invokes it with the
     /// given "params" value.
     ///
@@ -33,7 +62,11 @@ impl RequestHandlers {
     ///
     /// Otherwise a [`Response::Success`] is returned.
     pub(crate) async fn handle_request(&self, request: Request) -> Response {
-        let handler = match self.0.get(request.method.as_str()) {
+        if let Some(limiter) = &self.limiter {
+            limiter().await;
+        }
+
+        let handler = match self.handlers.get(request.method.as_str()) {
             Some(handler) => Arc::clone(handler),
             None => {
                 debug!(requested_method = %request.method.as_str(), "failed to get handler");
@@ -53,14 +86,169 @@ impl RequestHandlers {
             Err(error) => Response::new_failure(request.id, error),
         }
     }
+
+    /// Handles a single element of a batch request.
+    ///
+    /// Returns `None` if the element is a Notification as per the JSON-RPC specification, i.e. it
+    /// doesn't contain an "id" field.
+    async fn handle_batch_element(
+        &self,
+        element: Value,
+        allow_unknown_fields: bool,
+    ) -> Option<Response> {
+        let unvalidated_request = match element {
+            Value::Object(unvalidated_request) => unvalidated_request,
+            _ => {
+                debug!("got a batch element which is not an object");
+                let error = Error::new(
+                    ReservedErrorCode::InvalidRequest,
+                    "Expected each batch element to be a JSON-RPC request object",
+                );
+                return Some(Response::new_failure(Value::Null, error));
+            }
+        };
+        match Request::new(unvalidated_request, allow_unknown_fields) {
+            Ok(request) => Some(self.handle_request(request).await),
+            Err(ErrorOrRejection::Error { id, error }) => {
+                debug!(?error, "got an invalid request in batch");
+                Some(Response::new_failure(id, error))
+            }
+            Err(ErrorOrRejection::Rejection(rejection)) => {
+                debug!(?rejection, "ignoring notification in batch");
+                None
+            }
+        }
+    }
+
+    /// Handles a batch request, executing all the requests in it concurrently.
+    ///
+    /// As per the JSON-RPC specification, the returned batch holds no responses for Notifications.
+    /// If the batch holds only Notifications, an `Err(Rejection)` is returned.
+    async fn handle_batch(
+        &self,
+        elements: Vec<Value>,
+        allow_unknown_fields: bool,
+    ) -> Result<ResponseBody, Rejection> {
+        if elements.is_empty() {
+            let error = Error::new(ReservedErrorCode::InvalidRequest, "Empty batch");
+            return Ok(ResponseBody::Single(Response::new_failure(
+                Value::Null,
+                error,
+            )));
+        }
+
+        if elements.len() > self.max_batch_size {
+            debug!(batch_size = elements.len(), "got an oversized batch");
+            let message = if self.max_batch_size == 0 {
+                "Batch requests are not supported".to_string()
+            } else {
+                format!(
+                    "Batch contains {} requests, but the maximum allowed is {}",
+                    elements.len(),
+                    self.max_batch_size
+                )
+            };
+            let error = Error::new(ReservedErrorCode::InvalidRequest, message);
+            return Ok(ResponseBody::Single(Response::new_failure(
+                Value::Null,
+                error,
+            )));
+        }
+
+        let responses: Vec<Response> = future::join_all(
+            elements
+                .into_iter()
+                .map(|element| self.handle_batch_element(element, allow_unknown_fields)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if responses.is_empty() {
+            return Err(reject::custom(MissingId));
+        }
+        Ok(ResponseBody::Batch(responses))
+    }
+
+    /// Parses a JSON-RPC request or batch of requests from the given raw bytes, executes them
+    /// using the appropriate handlers, and provides the JSON-RPC response(s) (each of which could
+    /// be a success or failure).
+    ///
+    /// Returns an `Err(Rejection)` only if the request is a Notification as per the JSON-RPC
+    /// specification, i.e. the request doesn't contain an "id" field, or if the request is a batch
+    /// consisting only of Notifications.
+    pub(crate) async fn handle_body(
+        &self,
+        body: &[u8],
+        allow_unknown_fields: bool,
+    ) -> Result<ResponseBody, Rejection> {
+        let response = match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Object(unvalidated_request)) => {
+                match Request::new(unvalidated_request, allow_unknown_fields) {
+                    Ok(request) => self.handle_request(request).await,
+                    Err(ErrorOrRejection::Error { id, error }) => {
+                        debug!(?error, "got an invalid request");
+                        Response::new_failure(id, error)
+                    }
+                    Err(ErrorOrRejection::Rejection(rejection)) => {
+                        debug!(?rejection, "rejecting an invalid request");
+                        return Err(rejection);
+                    }
+                }
+            }
+            Ok(Value::Array(elements)) => {
+                return self.handle_batch(elements, allow_unknown_fields).await
+            }
+            Ok(_) => {
+                debug!("got json which is neither an object nor an array");
+                let error = Error::new(
+                    ReservedErrorCode::InvalidRequest,
+                    "Expected a JSON-RPC request object or an array of them",
+                );
+                Response::new_failure(Value::Null, error)
+            }
+            Err(error) => {
+                debug!(%error, "got bad json");
+                let error = Error::new(ReservedErrorCode::ParseError, error.to_string());
+                Response::new_failure(Value::Null, error)
+            }
+        };
+        Ok(ResponseBody::Single(response))
+    }
+
+    /// Parses a JSON-RPC request or batch of requests from the given raw message, executes them
+    /// using the appropriate handlers, and provides the JSON-RPC response(s) (each of which could
+    /// be a success or failure).
+    ///
+    /// This is intended for use by transports other than HTTP, e.g. WebSockets, where each message
+    /// holds a single JSON-RPC request or batch.
+    ///
+    /// Returns `None` if the request is a Notification as per the JSON-RPC specification, i.e. the
+    /// request doesn't contain an "id" field, or if the request is a batch consisting only of
+    /// Notifications.  In this case, no JSON-RPC response should be sent to the client.
+    ///
+    /// If `allow_unknown_fields` is `false`, requests with unknown fields will cause an error
+    /// response.
+    pub async fn handle_raw_request(
+        &self,
+        raw_request: &[u8],
+        allow_unknown_fields: bool,
+    ) -> Option<ResponseBody> {
+        self.handle_body(raw_request, allow_unknown_fields)
+            .await
+            .ok()
+    }
 }
 
 /// A builder for [`RequestHandlers`].
 //
 // This builder exists so the internal `HashMap` can be populated before it is made immutable behind
 // the `Arc` in the `RequestHandlers`.
-#[derive(Default)]
-pub struct RequestHandlersBuilder(HashMap<&'static str, RequestHandler>);
+pub struct RequestHandlersBuilder {
+    handlers: HashMap<&'static str, RequestHandler>,
+    max_batch_size: usize,
+}
 
 impl RequestHandlersBuilder {
     /// Returns a new builder.
@@ -68,6 +256,16 @@ impl RequestHandlersBuilder {
         Self::default()
     }
 
+    /// Sets the maximum number of requests allowed in a single batch request.
+    ///
+    /// Batches holding more requests than this are responded to with a single
+    /// [`ReservedErrorCode::InvalidRequest`] error.  A value of 0 disables batch requests.
+    ///
+    /// Defaults to [`DEFAULT_MAX_BATCH_SIZE`].
+    pub fn set_max_batch_size(&mut self, max_batch_size: usize) {
+        self.max_batch_size = max_batch_size;
+    }
+
     /// Adds a new request-handler which will be called to handle all JSON-RPC requests with the
     /// given "method" field.
     ///
@@ -100,7 +298,11 @@ impl RequestHandlersBuilder {
             }
             .boxed()
         };
-        if self.0.insert(method, Arc::new(wrapped_handler)).is_some() {
+        if self
+            .handlers
+            .insert(method, Arc::new(wrapped_handler))
+            .is_some()
+        {
             error!(
                 method,
                 "already registered a handler for this json-rpc request method"
@@ -110,6 +312,165 @@ impl RequestHandlersBuilder {
 
     /// Finalize building by converting `self` to a [`RequestHandlers`].
     pub fn build(self) -> RequestHandlers {
-        RequestHandlers(Arc::new(self.0))
+        RequestHandlers {
+            handlers: Arc::new(self.handlers),
+            max_batch_size: self.max_batch_size,
+            limiter: None,
+        }
+    }
+}
+
+impl Default for RequestHandlersBuilder {
+    fn default() -> Self {
+        RequestHandlersBuilder {
+            handlers: HashMap::new(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    const MAX_BATCH_SIZE: usize = 4;
+
+    fn handlers() -> RequestHandlers {
+        let mut handlers = RequestHandlersBuilder::new();
+        let handler = |_params| async move { Ok::<_, Error>("pong") };
+        handlers.register_handler("ping", Arc::new(handler));
+        handlers.set_max_batch_size(MAX_BATCH_SIZE);
+        handlers.build()
+    }
+
+    async fn handle_single(raw_request: &[u8]) -> Response {
+        match handlers().handle_raw_request(raw_request, false).await {
+            Some(ResponseBody::Single(response)) => response,
+            other => panic!("expected single response, got {:?}", other),
+        }
+    }
+
+    async fn handle_batch(raw_request: &[u8]) -> Vec<Response> {
+        match handlers().handle_raw_request(raw_request, false).await {
+            Some(ResponseBody::Batch(responses)) => responses,
+            other => panic!("expected batch response, got {:?}", other),
+        }
+    }
+
+    fn error_code(response: &Response) -> Value {
+        let error = response.error().expect("should be failure");
+        serde_json::to_value(error).unwrap()["code"].clone()
+    }
+
+    #[tokio::test]
+    async fn should_handle_raw_request() {
+        let raw_request = br#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let response = handle_single(raw_request).await;
+        assert_eq!(response.result::<String>(), Some("pong".to_string()));
+        assert_eq!(response.id(), &Value::from(1));
+    }
+
+    #[tokio::test]
+    async fn should_return_parse_error_for_bad_raw_request() {
+        let response = handle_single(b"{").await;
+        assert_eq!(
+            error_code(&response),
+            Value::from(ReservedErrorCode::ParseError as i64)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_respond_to_raw_notification() {
+        let raw_request = br#"{"jsonrpc":"2.0","method":"ping"}"#;
+        assert!(handlers()
+            .handle_raw_request(raw_request, false)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_handle_batch_with_per_element_responses() {
+        let raw_request = br#"[
+            {"jsonrpc":"2.0","id":1,"method":"ping"},
+            {"jsonrpc":"2.0","method":"ping"},
+            {"jsonrpc":"2.0","id":2,"method":"pong"},
+            3
+        ]"#;
+        let responses = handle_batch(raw_request).await;
+        assert_eq!(responses.len(), 3);
+
+        assert_eq!(responses[0].id(), &Value::from(1));
+        assert_eq!(responses[0].result::<String>(), Some("pong".to_string()));
+
+        assert_eq!(responses[1].id(), &Value::from(2));
+        assert_eq!(
+            error_code(&responses[1]),
+            Value::from(ReservedErrorCode::MethodNotFound as i64)
+        );
+
+        assert_eq!(responses[2].id(), &Value::Null);
+        assert_eq!(
+            error_code(&responses[2]),
+            Value::from(ReservedErrorCode::InvalidRequest as i64)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_empty_batch() {
+        let response = handle_single(b"[]").await;
+        assert_eq!(response.id(), &Value::Null);
+        assert_eq!(
+            error_code(&response),
+            Value::from(ReservedErrorCode::InvalidRequest as i64)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_oversized_batch() {
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let raw_request = format!("[{}]", [request; MAX_BATCH_SIZE].join(","));
+        assert_eq!(
+            handle_batch(raw_request.as_bytes()).await.len(),
+            MAX_BATCH_SIZE
+        );
+
+        let raw_request = format!("[{}]", [request; MAX_BATCH_SIZE + 1].join(","));
+        let response = handle_single(raw_request.as_bytes()).await;
+        assert_eq!(
+            error_code(&response),
+            Value::from(ReservedErrorCode::InvalidRequest as i64)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_respond_to_batch_of_notifications() {
+        let raw_request = br#"[{"jsonrpc":"2.0","method":"ping"},{"jsonrpc":"2.0","method":"a"}]"#;
+        assert!(handlers()
+            .handle_raw_request(raw_request, false)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_limit_each_request_in_batch() {
+        let limited_count = Arc::new(AtomicUsize::new(0));
+        let count = Arc::clone(&limited_count);
+        let handlers = handlers().with_limiter(move || {
+            count.fetch_add(1, Ordering::SeqCst);
+            future::ready(())
+        });
+
+        let raw_request = br#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let _ = handlers.handle_raw_request(raw_request, false).await;
+        assert_eq!(limited_count.load(Ordering::SeqCst), 1);
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let raw_request = format!("[{}]", [request; MAX_BATCH_SIZE].join(","));
+        let _ = handlers
+            .handle_raw_request(raw_request.as_bytes(), false)
+            .await;
+        assert_eq!(limited_count.load(Ordering::SeqCst), 1 + MAX_BATCH_SIZE);
     }
 }
@@ -101,6 +101,18 @@ impl Response {
     }
 }
 
+/// The body of the reply to a JSON-RPC request: either a single response, or the responses to the
+/// requests in a batch.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ResponseBody {
+    /// The response to a single request, or a single error response to an invalid batch.
+    Single(Response),
+    /// The responses to the requests in a batch, in the same order as the requests.  Notifications
+    /// have no corresponding response.
+    Batch(Vec<Response>),
+}
+
 fn set_jsonrpc_field<'de, D: Deserializer<'de>>(
     _deserializer: D,
 ) -> Result<Cow<'static, str>, D::Error> {
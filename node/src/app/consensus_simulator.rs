@@ -0,0 +1,64 @@
+//! # Casper consensus simulator
+//!
+//! Runs a network of simulated validators with the consensus parameters of a given chainspec, and
+//! prints statistics about finality, eras and fault detection as JSON. Run with `--help` to see
+//! available command-line arguments.
+
+use std::{fs, path::PathBuf, process};
+
+use anyhow::Context;
+use structopt::StructOpt;
+
+use casper_node::{
+    consensus::simulator::{self, SimulationConfig, SimulationReport},
+    types::{chainspec::ChainspecRawBytes, Chainspec},
+    utils::Loadable,
+};
+
+/// Command-line arguments of the simulator.
+#[derive(Debug, StructOpt)]
+struct Args {
+    /// Path to the directory containing the `chainspec.toml` to simulate.
+    #[structopt(parse(from_os_str))]
+    chainspec_dir: PathBuf,
+    /// Path to the TOML file describing the simulated validators and network.
+    #[structopt(parse(from_os_str))]
+    simulation: PathBuf,
+    /// Overrides the seed configured in the simulation file.
+    #[structopt(long)]
+    seed: Option<u64>,
+}
+
+/// Loads the chainspec and simulation config and runs the simulation.
+fn run(args: &Args) -> anyhow::Result<SimulationReport> {
+    let (chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_path(&args.chainspec_dir)
+        .with_context(|| {
+            format!(
+                "could not load chainspec from {}",
+                args.chainspec_dir.display()
+            )
+        })?;
+    let contents = fs::read_to_string(&args.simulation)
+        .with_context(|| format!("could not read {}", args.simulation.display()))?;
+    let mut config: SimulationConfig =
+        toml::from_str(&contents).context("could not parse simulation config")?;
+    if let Some(seed) = args.seed {
+        config.seed = seed;
+    }
+    simulator::simulate(chainspec, config).context("invalid simulation config")
+}
+
+/// Main function.
+fn main() {
+    let args = Args::from_args();
+    match run(&args) {
+        Ok(report) => {
+            let json = serde_json::to_string_pretty(&report).expect("should serialize report");
+            println!("{}", json);
+        }
+        Err(error) => {
+            eprintln!("{:#}", error);
+            process::exit(1);
+        }
+    }
+}
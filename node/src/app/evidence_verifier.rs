@@ -0,0 +1,98 @@
+//! # Casper evidence verifier
+//!
+//! Checks an equivocation evidence bundle, as written by a node to its `evidence` folder or
+//! returned by the `info_get_equivocation_evidence` RPC, without access to the chain. Run with
+//! `--help` to see available command-line arguments.
+
+use std::{fs, path::PathBuf, process};
+
+use anyhow::{bail, Context};
+use structopt::StructOpt;
+
+use casper_hashing::Digest;
+use casper_node::{consensus::EvidenceBundle, types::BlockHash};
+use casper_types::{AsymmetricType, EraId, PublicKey};
+
+/// Command-line arguments of the verifier.
+#[derive(Debug, StructOpt)]
+struct Args {
+    /// Path to the JSON file containing the evidence bundle.
+    #[structopt(parse(from_os_str))]
+    bundle: PathBuf,
+    /// The hex-encoded public key of the accused validator.
+    #[structopt(long, parse(try_from_str = PublicKey::from_hex))]
+    public_key: PublicKey,
+    /// The era in which the validator is accused of equivocating.
+    #[structopt(long)]
+    era_id: Option<u64>,
+    /// The hex-encoded hash of the chainspec the era was run with.
+    #[structopt(long, parse(try_from_str = Digest::from_hex))]
+    chainspec_hash: Option<Digest>,
+    /// The hex-encoded hash of the era's key block, i.e. the previous era's switch block.
+    #[structopt(long, parse(try_from_str = Digest::from_hex))]
+    key_block_hash: Option<Digest>,
+}
+
+/// Checks that the bundle matches the expected parameters, and that the evidence is valid.
+fn verify(args: &Args) -> anyhow::Result<EvidenceBundle> {
+    let bytes = fs::read(&args.bundle)
+        .with_context(|| format!("could not read {}", args.bundle.display()))?;
+    let bundle: EvidenceBundle =
+        serde_json::from_slice(&bytes).context("could not parse evidence bundle")?;
+
+    if bundle.public_key != args.public_key {
+        bail!("the evidence is against {}", bundle.public_key.to_hex());
+    }
+    if let Some(era_id) = args.era_id.map(EraId::new) {
+        if bundle.era_id != era_id {
+            bail!("the evidence is from era {}", bundle.era_id.value());
+        }
+    }
+    if let Some(chainspec_hash) = args.chainspec_hash {
+        if bundle.chainspec_hash != chainspec_hash {
+            bail!(
+                "the evidence has chainspec hash {:?}",
+                bundle.chainspec_hash
+            );
+        }
+    }
+    if let Some(key_block_hash) = args.key_block_hash.map(BlockHash::new) {
+        if bundle.key_block_hash != key_block_hash {
+            bail!(
+                "the evidence has key block hash {:?}",
+                bundle.key_block_hash.inner()
+            );
+        }
+    }
+    bundle.verify().context("invalid evidence")?;
+    Ok(bundle)
+}
+
+/// Main function.
+fn main() {
+    let args = Args::from_args();
+    match verify(&args) {
+        Ok(bundle) => {
+            println!(
+                "valid: {} equivocated in era {} (chainspec hash {:?}, key block hash {:?})",
+                bundle.public_key.to_hex(),
+                bundle.era_id.value(),
+                bundle.chainspec_hash,
+                bundle.key_block_hash.inner()
+            );
+            if args.era_id.is_none()
+                || args.chainspec_hash.is_none()
+                || args.key_block_hash.is_none()
+            {
+                println!(
+                    "note: check that the era ID and hashes match the era on your chain, or \
+                    pass them as arguments"
+                );
+            }
+        }
+        Err(error) => {
+            eprintln!("{:#}", error);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! # Casper reference signer
+//!
+//! Holds a validator's secret key and signs consensus messages and finality signatures on behalf
+//! of a node configured with `consensus.remote_signer`. Refuses to sign anything that would make
+//! the validator equivocate. Run with `--help` to see available command-line arguments.
+
+use std::{fs, io, os::unix::net::UnixListener, path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use structopt::StructOpt;
+
+use casper_node::{
+    logging::{self, LoggingConfig},
+    signer::daemon::SignerDaemon,
+};
+use casper_types::SecretKey;
+
+/// Command-line arguments of the signer.
+#[derive(Debug, StructOpt)]
+struct Args {
+    /// Path to the validator's secret key PEM file.
+    #[structopt(long)]
+    secret_key: PathBuf,
+    /// Path of the Unix socket to listen on.
+    #[structopt(long)]
+    socket: PathBuf,
+    /// Path of the file recording what has been signed, to protect against equivocations.
+    #[structopt(long)]
+    slashing_protection: PathBuf,
+}
+
+/// Main function.
+fn main() -> anyhow::Result<()> {
+    logging::init_with_config(&LoggingConfig::default())?;
+    let args = Args::from_args();
+
+    let secret_key = SecretKey::from_file(&args.secret_key)
+        .with_context(|| format!("could not load secret key {}", args.secret_key.display()))?;
+    let daemon = SignerDaemon::new(secret_key, args.slashing_protection)
+        .context("could not load slashing protection data")?;
+
+    // A socket left behind by a previous run would make binding fail.
+    match fs::remove_file(&args.socket) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+            return Err(error).context("could not remove stale socket");
+        }
+        _ => (),
+    }
+    let listener = UnixListener::bind(&args.socket)
+        .with_context(|| format!("could not bind to {}", args.socket.display()))?;
+
+    Arc::new(daemon).serve(listener)?;
+    Ok(())
+}
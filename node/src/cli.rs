@@ -78,6 +78,27 @@ pub enum Cli {
         #[structopt(long)]
         new_config: PathBuf,
     },
+    /// Delete global state tries which are unreachable from recent blocks and switch blocks.
+    ///
+    /// The node must not be running while this is in progress.
+    CompactGlobalState {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Number of highest blocks whose global state is retained.
+        #[structopt(long, default_value = "1000")]
+        keep_blocks: u64,
+        /// Number of highest switch blocks whose global state is retained.  All are retained if
+        /// not given.
+        #[structopt(long)]
+        keep_switch_blocks: Option<u64>,
+        /// Only report the number and size of the unreachable tries without deleting them.
+        #[structopt(long)]
+        dry_run: bool,
+        /// Delete the unreachable tries even if no blocks are retained, or if tries reachable
+        /// from the retained blocks are missing, e.g. because global state is not fully synced.
+        #[structopt(long)]
+        force: bool,
+    },
 }
 
 #[derive(Debug)]
@@ -237,6 +258,33 @@ impl Cli {
                 )?;
                 Ok(ExitCode::Success as i32)
             }
+            Cli::CompactGlobalState {
+                config,
+                keep_blocks,
+                keep_switch_blocks,
+                dry_run,
+                force,
+            } => {
+                let config = Self::init(&config, vec![])?;
+
+                info!(build_version = %crate::VERSION_STRING.as_str(), "compacting global state");
+                let stats = crate::trie_compaction::compact_global_state(
+                    config,
+                    keep_blocks,
+                    keep_switch_blocks,
+                    dry_run,
+                    force,
+                )?;
+                println!(
+                    "reachable tries: {}, missing tries: {}, unreachable tries: {}, {} bytes: {}",
+                    stats.reachable_tries,
+                    stats.missing_tries,
+                    stats.unreachable_tries,
+                    if dry_run { "reclaimable" } else { "reclaimed" },
+                    stats.reclaimable_bytes
+                );
+                Ok(ExitCode::Success as i32)
+            }
         }
     }
 
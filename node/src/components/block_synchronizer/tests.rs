@@ -27,6 +27,7 @@ use crate::{
     },
     effect::Effect,
     reactor::{EventQueueHandle, QueueKind, Scheduler},
+    signer::Signer,
     tls::KeyFingerprint,
     types::{
         chainspec::LegacyRequiredFinality, BlockExecutionResultsOrChunkId, DeployId,
@@ -148,8 +149,7 @@ impl TestEnv {
             Ratio::new(1, 3),
             None,
             EraId::from(0),
-            self.validator_keys[0].clone(),
-            PublicKey::from(self.validator_keys[0].as_ref()),
+            Signer::local(self.validator_keys[0].clone()),
             1,
         );
         validator_matrix
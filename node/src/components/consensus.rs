@@ -5,6 +5,7 @@
 mod cl_context;
 mod config;
 mod consensus_protocol;
+mod equivocation;
 mod era_supervisor;
 #[macro_use]
 pub mod highway_core;
@@ -12,6 +13,8 @@ pub(crate) mod error;
 mod leader_sequence;
 mod metrics;
 pub mod protocols;
+pub mod simulator;
+mod status;
 #[cfg(test)]
 pub(crate) mod tests;
 mod traits;
@@ -30,7 +33,8 @@ use derive_more::From;
 use serde::{Deserialize, Serialize};
 use tracing::{info, trace};
 
-use casper_types::{EraId, Timestamp};
+use casper_hashing::Digest;
+use casper_types::{EraId, Signature, Timestamp};
 
 use crate::{
     components::Component,
@@ -58,8 +62,9 @@ use protocols::{highway::HighwayProtocol, zug::Zug};
 use traits::Context;
 
 pub use cl_context::ClContext;
-pub(crate) use config::{ChainspecConsensusExt, Config};
+pub(crate) use config::{ChainspecConsensusExt, Config, LoadSignerError};
 pub(crate) use consensus_protocol::{BlockContext, EraReport, ProposedBlock};
+pub use equivocation::{Equivocation, EquivocationError, EvidenceBundle};
 pub(crate) use era_supervisor::{debug::EraDump, EraSupervisor, SerializedMessage};
 #[cfg(test)]
 pub(crate) use highway_core::highway::Vertex as HighwayVertex;
@@ -67,6 +72,7 @@ pub(crate) use leader_sequence::LeaderSequence;
 pub(crate) use protocols::highway::max_rounds_per_era;
 #[cfg(test)]
 pub(crate) use protocols::highway::HighwayMessage;
+pub use status::{ConsensusStatus, ParticipationStatus, RoundLeader, ValidatorStatus};
 pub(crate) use validator_change::ValidatorChange;
 
 const COMPONENT_NAME: &str = "consensus";
@@ -161,6 +167,12 @@ pub(crate) enum Event {
     },
     /// A queued action to be handled by a specific era.
     Action { era_id: EraId, action_id: ActionId },
+    /// The remote signer answered a request to sign a consensus message in the given era.
+    SignatureCreated {
+        era_id: EraId,
+        hash: Digest,
+        signature: Option<Signature>,
+    },
     /// We are receiving the data we require to propose a new block.
     NewBlockPayload(NewBlockPayload),
     #[from]
@@ -258,6 +270,21 @@ impl Display for Event {
             Event::Action { era_id, action_id } => {
                 write!(f, "action (ID {}) for {}", action_id.0, era_id)
             }
+            Event::SignatureCreated {
+                era_id,
+                hash,
+                signature,
+            } => write!(
+                f,
+                "signature of {} for {}: {}",
+                hash,
+                era_id,
+                if signature.is_some() {
+                    "signed"
+                } else {
+                    "failed"
+                },
+            ),
             Event::NewBlockPayload(NewBlockPayload {
                 era_id,
                 block_payload,
@@ -433,6 +460,11 @@ where
             Event::Action { era_id, action_id } => {
                 self.handle_action(effect_builder, rng, era_id, action_id)
             }
+            Event::SignatureCreated {
+                era_id,
+                hash,
+                signature,
+            } => self.handle_signature(effect_builder, rng, era_id, hash, signature),
             Event::Incoming(ConsensusMessageIncoming { sender, message }) => {
                 let delay_by = self.message_delay_failpoint.fire(rng).cloned();
                 if let Some(delay) = delay_by {
@@ -473,6 +505,13 @@ where
                 let validator_changes = self.get_validator_changes();
                 responder.respond(validator_changes).ignore()
             }
+            Event::ConsensusRequest(ConsensusRequest::EquivocationEvidence(responder)) => {
+                responder.respond(self.get_equivocation_evidence()).ignore()
+            }
+            Event::ConsensusRequest(ConsensusRequest::ConsensusStatus(responder)) => {
+                let status = self.get_consensus_status(Timestamp::now());
+                responder.respond(status).ignore()
+            }
             Event::DumpState(req @ DumpConsensusStateRequest { era_id, .. }) => {
                 let current_era = match self.current_era() {
                     None => {
@@ -2,40 +2,38 @@ use std::sync::Arc;
 
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 
 use casper_hashing::Digest;
 use casper_types::{crypto, PublicKey, SecretKey, Signature};
 
 use crate::{
-    components::consensus::traits::{ConsensusValueT, Context, ValidatorSecret},
+    components::consensus::traits::{
+        ConsensusValueT, Context, SignedData, Signing, ValidatorSecret,
+    },
+    signer::{SignRequest, Signer},
     types::BlockPayload,
 };
 
 #[derive(DataSize)]
 pub struct Keypair {
-    secret_key: Arc<SecretKey>,
-    public_key: PublicKey,
+    signer: Signer,
 }
 
 impl Keypair {
-    pub(crate) fn new(secret_key: Arc<SecretKey>, public_key: PublicKey) -> Self {
-        Self {
-            secret_key,
-            public_key,
-        }
+    pub(crate) fn new(signer: Signer) -> Self {
+        Self { signer }
     }
 
     #[cfg(test)]
     pub(crate) fn public_key(&self) -> &PublicKey {
-        &self.public_key
+        self.signer.public_key()
     }
 }
 
 impl From<Arc<SecretKey>> for Keypair {
     fn from(secret_key: Arc<SecretKey>) -> Self {
-        let public_key: PublicKey = secret_key.as_ref().into();
-        Self::new(secret_key, public_key)
+        Self::new(Signer::local(secret_key))
     }
 }
 
@@ -43,8 +41,32 @@ impl ValidatorSecret for Keypair {
     type Hash = Digest;
     type Signature = Signature;
 
-    fn sign(&self, hash: &Digest) -> Signature {
-        crypto::sign(hash, self.secret_key.as_ref(), &self.public_key)
+    fn sign(&self, hash: &Digest, data: SignedData) -> Signing<Signature> {
+        // A remote signer can take a while to answer, so it's never called synchronously.
+        if self.signer.is_remote() {
+            return Signing::Deferred(data);
+        }
+        let request = sign_request(data);
+        debug_assert_eq!(request.bytes_to_sign(), hash.as_ref());
+        match self.signer.sign(request) {
+            Ok(signature) => Signing::Signed(signature),
+            Err(error) => {
+                warn!(%error, %hash, "could not sign consensus message");
+                Signing::Refused
+            }
+        }
+    }
+}
+
+/// Returns the request to sign `data` with the validator's key.
+pub(crate) fn sign_request(data: SignedData) -> SignRequest {
+    match data {
+        SignedData::HighwayUnit(wire_unit) => SignRequest::HighwayUnit { wire_unit },
+        SignedData::HighwayPing(fields) => SignRequest::HighwayPing { fields },
+        SignedData::HighwayEndorsement(endorsement) => {
+            SignRequest::HighwayEndorsement { endorsement }
+        }
+        SignedData::ZugMessage(fields) => SignRequest::ZugMessage { fields },
     }
 }
 
@@ -82,17 +104,12 @@ impl Context for ClContext {
 mod specimen_support {
     use super::Keypair;
     use crate::utils::specimen::{Cache, LargestSpecimen, SizeEstimator};
-    use casper_types::{PublicKey, SecretKey};
+    use casper_types::SecretKey;
     use std::sync::Arc;
 
     impl LargestSpecimen for Keypair {
         fn largest_specimen<E: SizeEstimator>(estimator: &E, cache: &mut Cache) -> Self {
-            let secret_key = SecretKey::largest_specimen(estimator, cache);
-            let public_key = PublicKey::from(&secret_key);
-            Keypair {
-                secret_key: Arc::new(secret_key),
-                public_key,
-            }
+            Keypair::from(Arc::new(SecretKey::largest_specimen(estimator, cache)))
         }
     }
 }
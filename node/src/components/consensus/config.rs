@@ -2,8 +2,9 @@ use std::{path::Path, sync::Arc};
 
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use casper_types::{PublicKey, SecretKey};
+use casper_types::SecretKey;
 
 use crate::{
     components::consensus::{
@@ -11,6 +12,7 @@ use crate::{
         protocols::{highway::config::Config as HighwayConfig, zug::config::Config as ZugConfig},
         EraId,
     },
+    signer::{RemoteSignerConfig, Signer, SignerError},
     types::Chainspec,
     utils::{External, LoadError, Loadable},
 };
@@ -22,8 +24,16 @@ const DEFAULT_MAX_EXECUTION_DELAY: u64 = 3;
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub struct Config {
-    /// Path to secret key file.
+    /// Path to secret key file. Not used if `remote_signer` is set.
+    #[serde(default)]
     pub secret_key_path: External,
+    /// A signer process holding the secret key, which is then never loaded by the node itself.
+    ///
+    /// A signer process signs the consensus certificates proving validator status to peers in a
+    /// form which older versions reject, so this must not be set until every node in the network
+    /// runs a version supporting it.
+    #[serde(default)]
+    pub remote_signer: Option<RemoteSignerConfig>,
     /// The maximum number of blocks by which execution is allowed to lag behind finalization.
     /// If it is more than that, consensus will pause, and resume once the executor has caught up.
     pub max_execution_delay: u64,
@@ -39,6 +49,7 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             secret_key_path: External::Missing,
+            remote_signer: None,
             max_execution_delay: DEFAULT_MAX_EXECUTION_DELAY,
             highway: HighwayConfig::default(),
             zug: ZugConfig::default(),
@@ -48,15 +59,30 @@ impl Default for Config {
 
 type LoadKeyError = LoadError<<Arc<SecretKey> as Loadable>::Error>;
 
+/// An error creating the validator's signer.
+#[derive(Debug, Error)]
+pub(crate) enum LoadSignerError {
+    /// Could not load the secret key.
+    #[error("could not load secret key: {0}")]
+    SecretKey(#[from] LoadKeyError),
+    /// Could not connect to the remote signer.
+    #[error(transparent)]
+    RemoteSigner(#[from] SignerError),
+}
+
 impl Config {
-    /// Loads the secret key from the configuration file and derives the public key.
-    pub(crate) fn load_keys<P: AsRef<Path>>(
-        &self,
-        root: P,
-    ) -> Result<(Arc<SecretKey>, PublicKey), LoadKeyError> {
+    /// Connects to the remote signer if configured, otherwise loads the secret key from the
+    /// configured file.
+    pub(crate) fn load_signer<P: AsRef<Path>>(&self, root: P) -> Result<Signer, LoadSignerError> {
+        if let Some(remote_signer) = &self.remote_signer {
+            let config = RemoteSignerConfig {
+                socket_path: root.as_ref().join(&remote_signer.socket_path),
+                ..remote_signer.clone()
+            };
+            return Ok(Signer::remote(&config)?);
+        }
         let secret_signing_key: Arc<SecretKey> = self.secret_key_path.clone().load(root)?;
-        let public_key: PublicKey = PublicKey::from(secret_signing_key.as_ref());
-        Ok((secret_signing_key, public_key))
+        Ok(Signer::local(secret_signing_key))
     }
 }
 
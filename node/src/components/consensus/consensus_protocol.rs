@@ -12,7 +12,11 @@ use casper_hashing::Digest;
 use casper_types::{bytesrepr::ToBytes, TimeDiff, Timestamp};
 
 use crate::{
-    components::consensus::{traits::Context, ActionId, TimerId},
+    components::consensus::{
+        status::ProtocolStatus,
+        traits::{Context, SignedData},
+        ActionId, Equivocation, TimerId,
+    },
     types::NodeId,
     NodeRng,
 };
@@ -231,6 +235,9 @@ pub(crate) enum ProtocolOutcome<C: Context> {
     /// This is used to inform the deploy buffer, so we don't propose the same deploys again.
     /// Does not need to be raised for proposals this node created itself.
     HandledProposedBlock(ProposedBlock<C>),
+    /// Request a signature of the given data from the validator's remote signer, and pass it to
+    /// `ConsensusProtocol::handle_signature`.
+    RequestSignature(C::Hash, SignedData),
 }
 
 /// An API for a single instance of the consensus.
@@ -273,6 +280,15 @@ pub(crate) trait ConsensusProtocol<C: Context>: Send {
     /// Triggers a queued action.
     fn handle_action(&mut self, action_id: ActionId, now: Timestamp) -> ProtocolOutcomes<C>;
 
+    /// Continues creating the message with the given hash, now that the remote signer answered a
+    /// `RequestSignature`. The signature is `None` if the signer refused or failed.
+    fn handle_signature(
+        &mut self,
+        hash: C::Hash,
+        signature: Option<C::Signature>,
+        now: Timestamp,
+    ) -> ProtocolOutcomes<C>;
+
     /// Proposes a new value for consensus.
     fn propose(&mut self, proposed_block: ProposedBlock<C>, now: Timestamp) -> ProtocolOutcomes<C>;
 
@@ -291,7 +307,7 @@ pub(crate) trait ConsensusProtocol<C: Context>: Send {
         our_id: C::ValidatorId,
         secret: C::ValidatorSecret,
         timestamp: Timestamp,
-        unit_hash_file: Option<PathBuf>,
+        wal_file: Option<PathBuf>,
     ) -> ProtocolOutcomes<C>;
 
     /// Turns this instance into a passive observer, that does not create any new vertices.
@@ -309,6 +325,9 @@ pub(crate) trait ConsensusProtocol<C: Context>: Send {
     /// Sends evidence for a faulty of validator `vid` to the `sender` of the request.
     fn send_evidence(&self, sender: NodeId, vid: &C::ValidatorId) -> ProtocolOutcomes<C>;
 
+    /// Returns the evidence against validator `vid`, if this instance has any.
+    fn equivocation(&self, vid: &C::ValidatorId) -> Option<Equivocation<C>>;
+
     /// Sets the pause status: While paused we don't create consensus messages other than pings.
     fn set_paused(&mut self, paused: bool, now: Timestamp) -> ProtocolOutcomes<C>;
 
@@ -323,4 +342,8 @@ pub(crate) trait ConsensusProtocol<C: Context>: Send {
 
     // TODO: Make this less Highway-specific.
     fn next_round_length(&self) -> Option<TimeDiff>;
+
+    /// Returns a summary of the current round, the upcoming leaders and the validators'
+    /// participation.
+    fn protocol_status(&self, now: Timestamp) -> ProtocolStatus<C>;
 }
@@ -0,0 +1,290 @@
+//! Portable evidence of equivocations.
+//!
+//! When a validator signs two conflicting consensus messages, the protocol instance keeps the two
+//! messages as evidence. An [`EvidenceBundle`] packages them together with the era's parameters,
+//! so that anyone can verify the fault with only the validator's public key, e.g. for governance
+//! decisions, without access to the chain or the consensus state.
+
+use std::sync::Arc;
+
+use datasize::DataSize;
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_hashing::Digest;
+use casper_types::{EraId, PublicKey, SecretKey};
+
+use crate::{
+    components::consensus::{
+        cl_context::{ClContext, Keypair},
+        era_supervisor,
+        highway_core::{Evidence, EvidenceError},
+        protocols::zug::{Content, SignedMessage},
+        traits::Context,
+        utils::ValidatorIndex,
+    },
+    rpcs::docs::DocExample,
+    types::{Block, BlockHash},
+};
+
+static EVIDENCE_BUNDLE: Lazy<EvidenceBundle> = Lazy::new(|| {
+    let secret_key = SecretKey::ed25519_from_bytes([0; 32]).unwrap();
+    let public_key = PublicKey::from(&secret_key);
+    let keypair = Keypair::from(Arc::new(secret_key));
+    let era_id = EraId::new(1);
+    let chainspec_hash = Digest::from([42; Digest::LENGTH]);
+    let key_block_hash = *Block::doc_example().hash();
+    let instance_id = era_supervisor::instance_id(chainspec_hash, era_id, key_block_hash);
+    let vote = |vote| {
+        SignedMessage::sign_new(
+            0,
+            instance_id,
+            Content::Vote(vote),
+            ValidatorIndex(0),
+            &keypair,
+        )
+        .expect("should sign with local key")
+    };
+    let false_vote = vote(false);
+    EvidenceBundle {
+        public_key,
+        era_id,
+        chainspec_hash,
+        key_block_hash,
+        equivocation: Equivocation::zug(vote(true), *false_vote.content(), *false_vote.signature()),
+    }
+});
+
+/// Two conflicting messages signed by the same validator, in the format of the consensus protocol
+/// that detected them.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "C::Hash: Serialize",
+    deserialize = "C::Hash: Deserialize<'de>",
+))]
+pub struct Equivocation<C>(ProtocolEvidence<C>)
+where
+    C: Context;
+
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "C::Hash: Serialize",
+    deserialize = "C::Hash: Deserialize<'de>",
+))]
+#[serde(rename_all = "snake_case")]
+enum ProtocolEvidence<C>
+where
+    C: Context,
+{
+    /// Highway evidence: two units with the same sequence number, or conflicting endorsements.
+    Highway(Evidence<C>),
+    /// Two contradicting Zug echoes or votes in the same round.
+    Zug {
+        /// The first signed message.
+        message: SignedMessage<C>,
+        /// The content of the second message, which otherwise agrees with the first one.
+        content2: Content<C>,
+        /// The signature of the second message.
+        signature2: C::Signature,
+    },
+}
+
+impl<C: Context> Equivocation<C> {
+    /// Creates an equivocation from Highway evidence.
+    pub(crate) fn highway(evidence: Evidence<C>) -> Self {
+        Equivocation(ProtocolEvidence::Highway(evidence))
+    }
+
+    /// Creates an equivocation from a Zug message and the content and signature of a conflicting
+    /// one.
+    pub(crate) fn zug(
+        message: SignedMessage<C>,
+        content2: Content<C>,
+        signature2: C::Signature,
+    ) -> Self {
+        Equivocation(ProtocolEvidence::Zug {
+            message,
+            content2,
+            signature2,
+        })
+    }
+
+    /// Returns `Ok(())` if the messages were signed by `validator_id` in the protocol instance
+    /// `instance_id`, and contradict each other.
+    pub fn verify(
+        &self,
+        validator_id: &C::ValidatorId,
+        instance_id: &C::InstanceId,
+    ) -> Result<(), EquivocationError> {
+        match &self.0 {
+            ProtocolEvidence::Highway(evidence) => evidence
+                .validate_perpetrator(validator_id, instance_id)
+                .map_err(EquivocationError::Highway),
+            ProtocolEvidence::Zug {
+                message,
+                content2,
+                signature2,
+            } => {
+                if message.instance_id() != instance_id {
+                    return Err(EquivocationError::InstanceId);
+                }
+                if !message.content().contradicts(content2) {
+                    return Err(EquivocationError::NoConflict);
+                }
+                if !message.verify_signature(validator_id)
+                    || !message
+                        .with(*content2, *signature2)
+                        .verify_signature(validator_id)
+                {
+                    return Err(EquivocationError::Signature);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An error due to an invalid [`Equivocation`] or [`EvidenceBundle`].
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum EquivocationError {
+    /// The Highway evidence is invalid.
+    #[error("invalid Highway evidence: {0}")]
+    Highway(EvidenceError),
+    /// The messages were created for a different instance ID.
+    #[error("the messages were created for a different instance ID")]
+    InstanceId,
+    /// The messages don't contradict each other.
+    #[error("the messages don't conflict")]
+    NoConflict,
+    /// A signature is invalid.
+    #[error("invalid signature")]
+    Signature,
+}
+
+/// Self-contained evidence that a validator equivocated in an era.
+///
+/// The messages are signed over the era's instance ID, which is derived from the chainspec hash,
+/// the era ID and the key block hash. Verification only checks that the evidence is consistent
+/// with those parameters; whoever relies on it must also check that they are the actual
+/// parameters of the era on their chain, and that `public_key` was a validator in it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EvidenceBundle {
+    /// The public key of the equivocating validator.
+    pub public_key: PublicKey,
+    /// The era in which the validator equivocated.
+    pub era_id: EraId,
+    /// The hash of the chainspec the era was run with.
+    pub chainspec_hash: Digest,
+    /// The hash of the era's key block, i.e. the switch block of the previous era.
+    pub key_block_hash: BlockHash,
+    /// The conflicting signed consensus messages.
+    #[schemars(with = "serde_json::Value")]
+    pub equivocation: Equivocation<ClContext>,
+}
+
+impl EvidenceBundle {
+    /// Returns the ID of the consensus protocol instance the messages must belong to.
+    pub fn instance_id(&self) -> Digest {
+        era_supervisor::instance_id(self.chainspec_hash, self.era_id, self.key_block_hash)
+    }
+
+    /// Returns `Ok(())` if the bundle proves that `public_key` signed conflicting messages in the
+    /// era.
+    pub fn verify(&self) -> Result<(), EquivocationError> {
+        self.equivocation
+            .verify(&self.public_key, &self.instance_id())
+    }
+}
+
+impl DocExample for EvidenceBundle {
+    fn doc_example() -> &'static Self {
+        &EVIDENCE_BUNDLE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, sync::Arc};
+
+    use casper_types::Timestamp;
+
+    use super::*;
+    use crate::components::consensus::highway_core::{
+        highway::{SignedWireUnit, WireUnit},
+        Panorama,
+    };
+
+    #[test]
+    fn verify_zug_evidence() {
+        let bundle = EvidenceBundle::doc_example().clone();
+        assert_eq!(Ok(()), bundle.verify());
+
+        // The bundle survives a round trip through its JSON representation.
+        let json = serde_json::to_string(&bundle).unwrap();
+        let deserialized: EvidenceBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(bundle, deserialized);
+        assert_eq!(Ok(()), deserialized.verify());
+
+        // Changing any of the era parameters changes the instance ID.
+        let mut wrong_era = bundle.clone();
+        wrong_era.era_id = EraId::new(2);
+        assert_eq!(Err(EquivocationError::InstanceId), wrong_era.verify());
+
+        // The signatures are not by a different validator.
+        let mut wrong_key = bundle;
+        wrong_key.public_key = PublicKey::from(&SecretKey::ed25519_from_bytes([1; 32]).unwrap());
+        assert_eq!(Err(EquivocationError::Signature), wrong_key.verify());
+    }
+
+    #[test]
+    fn verify_highway_evidence() {
+        let mut rng = crate::new_rng();
+        let secret_key = SecretKey::random(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        let keypair = Keypair::from(Arc::new(secret_key));
+        let era_id = EraId::new(3);
+        let chainspec_hash = Digest::hash([1]);
+        let key_block_hash = BlockHash::random(&mut rng);
+        let instance_id = era_supervisor::instance_id(chainspec_hash, era_id, key_block_hash);
+
+        let unit = |timestamp: u64| {
+            let wunit = WireUnit::<ClContext> {
+                panorama: Panorama::new(1),
+                creator: ValidatorIndex(0),
+                instance_id,
+                value: None,
+                seq_number: 0,
+                timestamp: Timestamp::from(timestamp),
+                round_exp: 14,
+                endorsed: BTreeSet::new(),
+            };
+            SignedWireUnit::new(wunit.into_hashed(), &keypair).unwrap()
+        };
+        let bundle = EvidenceBundle {
+            public_key,
+            era_id,
+            chainspec_hash,
+            key_block_hash,
+            equivocation: Equivocation::highway(Evidence::Equivocation(unit(1000), unit(2000))),
+        };
+        assert_eq!(Ok(()), bundle.verify());
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let deserialized: EvidenceBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(Ok(()), deserialized.verify());
+
+        let same_unit = EvidenceBundle {
+            equivocation: Equivocation::highway(Evidence::Equivocation(unit(1000), unit(1000))),
+            ..bundle
+        };
+        assert_eq!(
+            Err(EquivocationError::Highway(
+                EvidenceError::EquivocationSameUnit
+            )),
+            same_unit.verify()
+        );
+    }
+}
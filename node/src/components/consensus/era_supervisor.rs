@@ -31,12 +31,12 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tracing::{debug, error, info, trace, warn};
 
 use casper_hashing::Digest;
-use casper_types::{AsymmetricType, EraId, PublicKey, SecretKey, TimeDiff, Timestamp};
+use casper_types::{AsymmetricType, EraId, PublicKey, Signature, TimeDiff, Timestamp};
 
 use crate::{
     components::{
         consensus::{
-            cl_context::{ClContext, Keypair},
+            cl_context::{self, ClContext, Keypair},
             consensus_protocol::{
                 ConsensusProtocol, EraReport, FinalizedBlock as CpFinalizedBlock, ProposedBlock,
                 ProtocolOutcome,
@@ -44,7 +44,8 @@ use crate::{
             metrics::Metrics,
             validator_change::{ValidatorChange, ValidatorChanges},
             ActionId, ChainspecConsensusExt, Config, ConsensusMessage, ConsensusRequestMessage,
-            Event, HighwayProtocol, NewBlockPayload, ReactorEventT, ResolveValidity, TimerId, Zug,
+            ConsensusStatus, Event, EvidenceBundle, HighwayProtocol, NewBlockPayload,
+            ReactorEventT, ResolveValidity, TimerId, Zug,
         },
         network::blocklist::BlocklistJustification,
     },
@@ -55,6 +56,7 @@ use crate::{
     },
     failpoints::Failpoint,
     fatal, protocol,
+    signer::Signer,
     types::{
         chainspec::ConsensusProtocolName, BlockHash, BlockHeader, Chainspec, Deploy, DeployHash,
         DeployOrTransferHash, FinalizedApprovals, FinalizedBlock, MetaBlockState, NodeId,
@@ -96,7 +98,7 @@ pub struct EraSupervisor {
     /// Since eras at or before the most recent activation point are never instantiated, shortly
     /// after that there can temporarily be fewer than three entries in the map.
     open_eras: BTreeMap<EraId, Era>,
-    secret_signing_key: Arc<SecretKey>,
+    signer: Signer,
     public_signing_key: PublicKey,
     chainspec: Arc<Chainspec>,
     config: Config,
@@ -113,6 +115,8 @@ pub struct EraSupervisor {
     metrics: Metrics,
     /// The path to the folder where unit files will be stored.
     unit_files_folder: PathBuf,
+    /// The path to the folder where evidence of equivocations will be stored.
+    evidence_folder: PathBuf,
     last_progress: Timestamp,
 
     /// Failpoints
@@ -132,26 +136,29 @@ impl EraSupervisor {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         storage_dir: &Path,
-        secret_signing_key: Arc<SecretKey>,
-        public_signing_key: PublicKey,
+        signer: Signer,
         config: Config,
         chainspec: Arc<Chainspec>,
         registry: &Registry,
     ) -> Result<Self, Error> {
         let unit_files_folder = storage_dir.join("unit_files");
         std::fs::create_dir_all(&unit_files_folder)?;
+        let evidence_folder = storage_dir.join("evidence");
+        std::fs::create_dir_all(&evidence_folder)?;
+        let public_signing_key = signer.public_key().clone();
         info!(our_id = %public_signing_key, "EraSupervisor pubkey",);
         let metrics = Metrics::new(registry)?;
 
         let era_supervisor = Self {
             open_eras: Default::default(),
-            secret_signing_key,
+            signer,
             public_signing_key,
             chainspec,
             config,
             next_block_height: 0,
             metrics,
             unit_files_folder,
+            evidence_folder,
             next_executed_height: 0,
             last_progress: Timestamp::now(),
             message_delay_failpoint: Failpoint::new("consensus.message_delay"),
@@ -259,6 +266,30 @@ impl EraSupervisor {
         result
     }
 
+    /// Returns the evidence of all equivocations known in the open eras.
+    pub(super) fn get_equivocation_evidence(&self) -> Vec<EvidenceBundle> {
+        self.open_eras
+            .iter()
+            .flat_map(|(era_id, era)| {
+                era.consensus
+                    .validators_with_evidence()
+                    .into_iter()
+                    .filter_map(move |public_key| self.evidence_bundle(*era_id, public_key))
+            })
+            .collect()
+    }
+
+    /// Returns a summary of the current era's consensus state.
+    pub(super) fn get_consensus_status(&self, now: Timestamp) -> Option<ConsensusStatus> {
+        let (era_id, era) = self.open_eras.iter().last()?;
+        Some(ConsensusStatus::new(
+            *era_id,
+            era,
+            &self.public_signing_key,
+            now,
+        ))
+    }
+
     fn era_seed(booking_block_hash: BlockHash, key_block_seed: Digest) -> u64 {
         let result = Digest::hash_pair(booking_block_hash, key_block_seed).value();
         u64::from_le_bytes(result[0..std::mem::size_of::<u64>()].try_into().unwrap())
@@ -384,15 +415,12 @@ impl EraSupervisor {
             vec![]
         } else {
             info!(era = era_id.value(), %our_id, "start voting");
-            let secret = Keypair::new(self.secret_signing_key.clone(), our_id.clone());
+            let secret = Keypair::new(self.signer.clone());
             let instance_id = self.era(era_id).consensus.instance_id();
-            let unit_hash_file = self.unit_file(instance_id);
-            self.era_mut(era_id).consensus.activate_validator(
-                our_id,
-                secret,
-                now,
-                Some(unit_hash_file),
-            )
+            let wal_file = self.unit_file(instance_id);
+            self.era_mut(era_id)
+                .consensus
+                .activate_validator(our_id, secret, now, Some(wal_file))
         };
         self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes)
     }
@@ -539,6 +567,7 @@ impl EraSupervisor {
             consensus,
             start_time,
             start_height,
+            key_block_hash,
             faulty,
             inactive,
             validators.clone(),
@@ -619,6 +648,42 @@ impl EraSupervisor {
         ))
     }
 
+    /// Returns a self-contained bundle with the evidence against `public_key` in the given era, if
+    /// the era is open and has any.
+    fn evidence_bundle(&self, era_id: EraId, public_key: &PublicKey) -> Option<EvidenceBundle> {
+        let era = self.open_eras.get(&era_id)?;
+        Some(EvidenceBundle {
+            public_key: public_key.clone(),
+            era_id,
+            chainspec_hash: self.chainspec.hash(),
+            key_block_hash: era.key_block_hash,
+            equivocation: era.consensus.equivocation(public_key)?,
+        })
+    }
+
+    /// Writes the evidence against `public_key` in the given era to the evidence folder.
+    fn write_evidence_bundle(&self, era_id: EraId, public_key: &PublicKey) {
+        let bundle = match self.evidence_bundle(era_id, public_key) {
+            Some(bundle) => bundle,
+            None => {
+                debug!(%public_key, era = era_id.value(), "no evidence to write");
+                return;
+            }
+        };
+        let path = self.evidence_folder.join(format!(
+            "evidence_{}_{}.json",
+            era_id.value(),
+            public_key.to_hex()
+        ));
+        let result = serde_json::to_vec_pretty(&bundle)
+            .map_err(io::Error::from)
+            .and_then(|bytes| fs::write(&path, bytes));
+        match result {
+            Ok(()) => info!(path = %path.display(), "wrote equivocation evidence"),
+            Err(error) => warn!(%error, path = %path.display(), "could not write evidence"),
+        }
+    }
+
     /// Applies `f` to the consensus protocol of the specified era.
     fn delegate_to_era<REv: ReactorEventT, F>(
         &mut self,
@@ -691,6 +756,19 @@ impl EraSupervisor {
         })
     }
 
+    pub(super) fn handle_signature<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        era_id: EraId,
+        hash: Digest,
+        signature: Option<Signature>,
+    ) -> Effects<Event> {
+        self.delegate_to_era(effect_builder, rng, era_id, move |consensus, _| {
+            consensus.handle_signature(hash, signature, Timestamp::now())
+        })
+    }
+
     pub(super) fn handle_message<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -1199,6 +1277,7 @@ impl EraSupervisor {
                 .ignore(),
             ProtocolOutcome::NewEvidence(pub_key) => {
                 info!(%pub_key, era = era_id.value(), "validator equivocated");
+                self.write_evidence_bundle(era_id, &pub_key);
                 let mut effects = effect_builder
                     .announce_fault_event(era_id, pub_key.clone(), Timestamp::now())
                     .ignore();
@@ -1231,6 +1310,24 @@ impl EraSupervisor {
                 .collect(),
             ProtocolOutcome::WeAreFaulty => Default::default(),
             ProtocolOutcome::DoppelgangerDetected => Default::default(),
+            ProtocolOutcome::RequestSignature(hash, data) => {
+                let signer = self.signer.clone();
+                let request = cl_context::sign_request(data);
+                async move {
+                    match signer.sign_async(request).await {
+                        Ok(signature) => Some(signature),
+                        Err(error) => {
+                            warn!(%error, %hash, "could not sign consensus message");
+                            None
+                        }
+                    }
+                }
+                .event(move |signature| Event::SignatureCreated {
+                    era_id,
+                    hash,
+                    signature,
+                })
+            }
             ProtocolOutcome::FttExceeded => effect_builder
                 .set_timeout(Duration::from_millis(FTT_EXCEEDED_SHUTDOWN_DELAY_MILLIS))
                 .then(move |_| fatal!(effect_builder, "too many faulty validators"))
@@ -1377,7 +1474,11 @@ async fn execute_finalized_block<REv>(
 }
 
 /// Computes the instance ID for an era, given the era ID and the chainspec hash.
-fn instance_id(chainspec_hash: Digest, era_id: EraId, key_block_hash: BlockHash) -> Digest {
+pub(super) fn instance_id(
+    chainspec_hash: Digest,
+    era_id: EraId,
+    key_block_hash: BlockHash,
+) -> Digest {
     Digest::hash_pair(
         key_block_hash.inner().value(),
         Digest::hash_pair(chainspec_hash, era_id.to_le_bytes()).value(),
@@ -10,10 +10,13 @@ use tracing::{debug, warn};
 
 use casper_types::{PublicKey, Timestamp, U512};
 
-use crate::components::consensus::{
-    cl_context::ClContext,
-    consensus_protocol::{ConsensusProtocol, ProposedBlock},
-    protocols::{highway::HighwayProtocol, zug::Zug},
+use crate::{
+    components::consensus::{
+        cl_context::ClContext,
+        consensus_protocol::{ConsensusProtocol, ProposedBlock},
+        protocols::{highway::HighwayProtocol, zug::Zug},
+    },
+    types::BlockHash,
 };
 
 const CASPER_ENABLE_DETAILED_CONSENSUS_METRICS_ENV_VAR: &str =
@@ -50,6 +53,8 @@ pub struct Era {
     pub(crate) start_time: Timestamp,
     /// The height of this era's first block.
     pub(crate) start_height: u64,
+    /// The hash of the switch block that started this era.
+    pub(crate) key_block_hash: BlockHash,
     /// Pending blocks, waiting for validation and dependencies.
     pub(crate) validation_states: HashMap<ProposedBlock<ClContext>, ValidationState>,
     /// Validators banned in this and the next BONDED_ERAS eras, because they were faulty in the
@@ -68,6 +73,7 @@ impl Era {
         consensus: Box<dyn ConsensusProtocol<ClContext>>,
         start_time: Timestamp,
         start_height: u64,
+        key_block_hash: BlockHash,
         faulty: HashSet<PublicKey>,
         cannot_propose: HashSet<PublicKey>,
         validators: BTreeMap<PublicKey, U512>,
@@ -76,6 +82,7 @@ impl Era {
             consensus,
             start_time,
             start_height,
+            key_block_hash,
             validation_states: HashMap::new(),
             faulty,
             cannot_propose,
@@ -163,6 +170,7 @@ impl DataSize for Era {
             consensus,
             start_time,
             start_height,
+            key_block_hash,
             validation_states,
             faulty,
             cannot_propose,
@@ -207,6 +215,7 @@ impl DataSize for Era {
         consensus_heap_size
             .saturating_add(start_time.estimate_heap_size())
             .saturating_add(start_height.estimate_heap_size())
+            .saturating_add(key_block_hash.estimate_heap_size())
             .saturating_add(validation_states.estimate_heap_size())
             .saturating_add(faulty.estimate_heap_size())
             .saturating_add(cannot_propose.estimate_heap_size())
@@ -42,7 +42,9 @@ mod endorsement;
 mod evidence;
 #[cfg(test)]
 pub(crate) mod highway_testing;
+mod wal;
 
+pub(crate) use evidence::{Evidence, EvidenceError};
 pub use state::{Observation, Panorama, State};
 
 // Enables the endorsement mechanism.
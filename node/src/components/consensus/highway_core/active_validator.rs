@@ -1,9 +1,8 @@
 use std::{
+    collections::BTreeMap,
     fmt::{self, Debug},
-    fs::{self, File},
-    io::{self, Read, Write},
     iter,
-    path::{Path, PathBuf},
+    path::PathBuf,
 };
 
 use datasize::DataSize;
@@ -14,15 +13,16 @@ use casper_types::{TimeDiff, Timestamp};
 use super::{
     endorsement::{Endorsement, SignedEndorsement},
     evidence::Evidence,
-    highway::{Ping, ValidVertex, Vertex, WireUnit},
+    highway::{HashedWireUnit, Ping, ValidVertex, Vertex, WireUnit},
     state::{self, Panorama, State, Unit},
+    wal::{self, Entry, UnitWal},
     ENABLE_ENDORSEMENTS,
 };
 
 use crate::components::consensus::{
     consensus_protocol::BlockContext,
     highway_core::{highway::SignedWireUnit, state::Fault},
-    traits::{Context, ValidatorSecret},
+    traits::{Context, SignedData, Signing, ValidatorSecret},
     utils::{ValidatorIndex, Weight},
 };
 
@@ -42,6 +42,20 @@ pub(crate) enum Effect<C: Context> {
     ///
     /// When this is returned, the validator automatically deactivates.
     WeAreFaulty(Fault<C>),
+    /// The remote signer needs to sign the data, and `on_signature` needs to be called with the
+    /// result.
+    RequestSignature(C::Hash, SignedData),
+}
+
+/// A vertex waiting to be signed by a remote signer.
+#[derive(DataSize, Debug)]
+enum Unsigned<C>
+where
+    C: Context,
+{
+    Unit(HashedWireUnit<C>),
+    Ping(Timestamp, C::InstanceId),
+    Endorsement(Endorsement<C>),
 }
 
 /// A validator that actively participates in consensus by creating new vertices.
@@ -73,8 +87,12 @@ where
     next_timer: Timestamp,
     /// Panorama and context for a block we are about to propose when we get a consensus value.
     next_proposal: Option<(BlockContext<C>, Panorama<C>)>,
-    /// The path to the file storing the hash of our latest known unit (if any).
-    unit_file: Option<PathBuf>,
+    /// The Write-Ahead Log storing every unit we signed, if any.
+    wal: Option<UnitWal<C>>,
+    /// Whether reading from or writing to the WAL failed. If so, we don't create any new units.
+    wal_failed: bool,
+    /// The highest sequence number of any unit we signed, according to the WAL.
+    max_signed_seq_number: Option<u64>,
     /// The last known unit created by us.
     own_last_unit: Option<SignedWireUnit<C>>,
     /// The target fault tolerance threshold. The validator pauses (i.e. doesn't create new units)
@@ -82,6 +100,8 @@ where
     target_ftt: Weight,
     /// If this flag is set we don't create new units and just send pings instead.
     paused: bool,
+    /// Vertices we requested a signature for from the remote signer, by hash.
+    pending_signatures: BTreeMap<C::Hash, Unsigned<C>>,
 }
 
 impl<C: Context> Debug for ActiveValidator<C> {
@@ -104,36 +124,68 @@ impl<C: Context> ActiveValidator<C> {
         current_time: Timestamp,
         start_time: Timestamp,
         state: &State<C>,
-        unit_file: Option<PathBuf>,
+        wal_file: Option<PathBuf>,
         target_ftt: Weight,
         instance_id: C::InstanceId,
     ) -> (Self, Vec<Effect<C>>) {
-        let own_last_unit = unit_file
-            .as_ref()
-            .map(read_last_unit)
-            .transpose()
-            .map_err(|err| match err.kind() {
-                io::ErrorKind::NotFound => (),
-                _ => panic!("got an error reading unit file {:?}: {:?}", unit_file, err),
-            })
-            .ok()
-            .flatten();
         let mut av = ActiveValidator {
             vidx,
             secret,
             next_round_len: state.params().init_round_len(),
             next_timer: state.params().start_timestamp(),
             next_proposal: None,
-            unit_file,
-            own_last_unit,
+            wal: None,
+            wal_failed: false,
+            max_signed_seq_number: None,
+            own_last_unit: None,
             target_ftt,
             paused: false,
+            pending_signatures: BTreeMap::new(),
         };
+        if let Some(wal_file) = wal_file {
+            av.replay_wal(&wal_file, instance_id);
+        }
         let mut effects = av.schedule_timer(start_time, state);
-        effects.push(av.send_ping(current_time, instance_id));
+        effects.extend(av.send_ping(current_time, instance_id));
         (av, effects)
     }
 
+    /// Reads the units we signed from the WAL at `wal_file`, and opens it for recording new ones.
+    ///
+    /// If the WAL can't be read or opened, the validator won't create any units, since it can't
+    /// know which units it must not contradict.
+    fn replay_wal(&mut self, wal_file: &PathBuf, instance_id: C::InstanceId) {
+        let (entries, write_wal) = match wal::open::<C>(wal_file) {
+            Ok(result) => result,
+            Err(err) => {
+                error!(?wal_file, %err, "could not open WAL; not creating any units");
+                self.wal_failed = true;
+                return;
+            }
+        };
+        for entry in entries {
+            match entry {
+                Entry::Unit(swunit) => {
+                    let wunit = swunit.wire_unit();
+                    if wunit.creator != self.vidx || wunit.instance_id != instance_id {
+                        warn!(
+                            ?wal_file,
+                            ?swunit,
+                            "ignoring WAL entry by a different validator"
+                        );
+                        continue;
+                    }
+                    if self.max_signed_seq_number >= Some(wunit.seq_number) {
+                        continue;
+                    }
+                    self.max_signed_seq_number = Some(wunit.seq_number);
+                    self.own_last_unit = Some(swunit);
+                }
+            }
+        }
+        self.wal = Some(write_wal);
+    }
+
     /// Returns whether validator's protocol state is fully synchronized and it's safe to start
     /// creating units.
     ///
@@ -141,9 +193,11 @@ impl<C: Context> ActiveValidator<C> {
     /// cannot start creating new units until its state is fully synchronized, otherwise it will
     /// most likely equivocate.
     fn can_vote(&self, state: &State<C>) -> bool {
-        self.own_last_unit
-            .as_ref()
-            .map_or(true, |swunit| state.has_unit(&swunit.hash()))
+        !self.wal_failed
+            && self
+                .own_last_unit
+                .as_ref()
+                .map_or(true, |swunit| state.has_unit(&swunit.hash()))
     }
 
     /// Returns whether validator's protocol state is synchronized up until the panorama of its own
@@ -199,16 +253,14 @@ impl<C: Context> ActiveValidator<C> {
                 return effects;
             } else if timestamp == r_id.saturating_add(self.witness_offset(r_len)) {
                 let panorama = self.panorama_at(state, timestamp);
-                if let Some(witness_unit) =
-                    self.new_unit(panorama, timestamp, None, state, instance_id)
-                {
+                if let Some(effect) = self.new_unit(panorama, timestamp, None, state, instance_id) {
                     if self
                         .latest_unit(state)
                         .map_or(true, |latest_unit| latest_unit.round_id() != r_id)
                     {
                         info!(round_id = %r_id, "sending witness in round with no proposal");
                     }
-                    effects.push(Effect::NewVertex(ValidVertex(Vertex::Unit(witness_unit))));
+                    effects.push(effect);
                     return effects;
                 }
             }
@@ -221,15 +273,98 @@ impl<C: Context> ActiveValidator<C> {
             one_max_round_ago.saturating_add(TimeDiff::from_millis(1)),
         ) {
             warn!(%timestamp, "too many validators offline, sending ping");
-            effects.push(self.send_ping(timestamp, instance_id));
+            effects.extend(self.send_ping(timestamp, instance_id));
         }
         effects
     }
 
-    /// Creates a Ping vertex.
-    pub(crate) fn send_ping(&self, timestamp: Timestamp, instance_id: C::InstanceId) -> Effect<C> {
-        let ping = Ping::new(self.vidx, timestamp, instance_id, &self.secret);
-        Effect::NewVertex(ValidVertex(Vertex::Ping(ping)))
+    /// Creates a Ping vertex, unless the signer refused to sign it.
+    pub(crate) fn send_ping(
+        &mut self,
+        timestamp: Timestamp,
+        instance_id: C::InstanceId,
+    ) -> Option<Effect<C>> {
+        let (hash, data) = Ping::<C>::signed_data(self.vidx, timestamp, instance_id);
+        match self.secret.sign(&hash, data) {
+            Signing::Signed(signature) => {
+                let ping = Ping::with_signature(self.vidx, timestamp, instance_id, signature);
+                Some(Effect::NewVertex(ValidVertex(Vertex::Ping(ping))))
+            }
+            Signing::Refused => None,
+            Signing::Deferred(data) => {
+                self.defer(hash, Unsigned::Ping(timestamp, instance_id), data)
+            }
+        }
+    }
+
+    /// Records a vertex waiting for the remote signer, and returns the effect requesting the
+    /// signature.
+    fn defer(
+        &mut self,
+        hash: C::Hash,
+        unsigned: Unsigned<C>,
+        data: SignedData,
+    ) -> Option<Effect<C>> {
+        self.pending_signatures.insert(hash, unsigned);
+        Some(Effect::RequestSignature(hash, data))
+    }
+
+    /// Returns whether we are waiting for the remote signer to sign one of our units.
+    fn has_pending_unit(&self) -> bool {
+        self.pending_signatures
+            .values()
+            .any(|unsigned| matches!(unsigned, Unsigned::Unit(_)))
+    }
+
+    /// Returns the vertex with the given hash, now that the remote signer signed it.
+    ///
+    /// If the signer failed, or our own unit was superseded in the meantime, nothing is created.
+    pub(crate) fn on_signature(
+        &mut self,
+        hash: &C::Hash,
+        signature: Option<C::Signature>,
+        state: &State<C>,
+    ) -> Vec<Effect<C>> {
+        let unsigned = match self.pending_signatures.remove(hash) {
+            Some(unsigned) => unsigned,
+            None => {
+                warn!(%hash, "received a signature we didn't request");
+                return vec![];
+            }
+        };
+        let signature = match signature {
+            Some(signature) => signature,
+            None => return vec![],
+        };
+        let vertex = match unsigned {
+            Unsigned::Unit(hashed_wire_unit) => {
+                if self.is_faulty(state)
+                    || hashed_wire_unit.wire_unit().panorama[self.vidx]
+                        != state.panorama()[self.vidx]
+                {
+                    warn!(%hash, "our unit is outdated by the time it was signed; dropping it");
+                    return vec![];
+                }
+                let swunit = SignedWireUnit {
+                    hashed_wire_unit,
+                    signature,
+                };
+                match self.record_unit(swunit) {
+                    Some(swunit) => Vertex::Unit(swunit),
+                    None => return vec![],
+                }
+            }
+            Unsigned::Ping(timestamp, instance_id) => Vertex::Ping(Ping::with_signature(
+                self.vidx,
+                timestamp,
+                instance_id,
+                signature,
+            )),
+            Unsigned::Endorsement(endorsement) => {
+                Vertex::Endorsements(SignedEndorsement::new(endorsement, signature).into())
+            }
+        };
+        vec![Effect::NewVertex(ValidVertex(vertex))]
     }
 
     /// Returns whether enough validators are online to finalize values with the target fault
@@ -264,17 +399,11 @@ impl<C: Context> ActiveValidator<C> {
         if self.should_send_confirmation(uhash, now, state) {
             let panorama = state.confirmation_panorama(self.vidx, uhash);
             if panorama.has_correct() {
-                if let Some(confirmation_unit) =
-                    self.new_unit(panorama, now, None, state, instance_id)
-                {
-                    let vv = ValidVertex(Vertex::Unit(confirmation_unit));
-                    effects.push(Effect::NewVertex(vv));
-                }
+                effects.extend(self.new_unit(panorama, now, None, state, instance_id));
             }
         };
         if self.should_endorse(uhash, state) {
-            let endorsement = self.endorse(uhash);
-            effects.extend(vec![Effect::NewVertex(ValidVertex(endorsement))]);
+            effects.extend(self.endorse(uhash));
         }
         effects
     }
@@ -297,8 +426,7 @@ impl<C: Context> ActiveValidator<C> {
                 let unit = state.unit(v);
                 unit.new_hash_obs(state, vidx)
             })
-            .map(|v| self.endorse(v))
-            .map(|endorsement| Effect::NewVertex(ValidVertex(endorsement)))
+            .filter_map(|v| self.endorse(v))
             .collect()
     }
 
@@ -321,9 +449,7 @@ impl<C: Context> ActiveValidator<C> {
         let maybe_parent_hash = state.fork_choice(&panorama);
         // If the parent is a terminal block, just create a unit without a new block.
         if maybe_parent_hash.map_or(false, |hash| state.is_terminal_block(hash)) {
-            return self
-                .new_unit(panorama, timestamp, None, state, instance_id)
-                .map(|proposal_unit| Effect::NewVertex(ValidVertex(Vertex::Unit(proposal_unit))));
+            return self.new_unit(panorama, timestamp, None, state, instance_id);
         }
         // Otherwise we need to request a new consensus value to propose.
         let ancestor_values = match maybe_parent_hash {
@@ -366,7 +492,6 @@ impl<C: Context> ActiveValidator<C> {
             return vec![];
         }
         self.new_unit(panorama, timestamp, Some(value), state, instance_id)
-            .map(|proposal_unit| Effect::NewVertex(ValidVertex(Vertex::Unit(proposal_unit))))
             .into_iter()
             .collect()
     }
@@ -415,7 +540,8 @@ impl<C: Context> ActiveValidator<C> {
         true
     }
 
-    /// Returns a new unit with the given data, and the correct sequence number.
+    /// Returns the effect creating a new unit with the given data, and the correct sequence
+    /// number, or requesting its signature.
     ///
     /// Returns `None` if it's not possible to create a valid unit with the given panorama.
     fn new_unit(
@@ -425,7 +551,7 @@ impl<C: Context> ActiveValidator<C> {
         value: Option<C::ConsensusValue>,
         state: &State<C>,
         instance_id: C::InstanceId,
-    ) -> Option<SignedWireUnit<C>> {
+    ) -> Option<Effect<C>> {
         if value.is_none() && !panorama.has_correct() {
             return None; // Wait for the first proposal before creating a unit without a value.
         }
@@ -433,6 +559,10 @@ impl<C: Context> ActiveValidator<C> {
             info!(?self.own_last_unit, "not voting - last own unit unknown");
             return None;
         }
+        if self.has_pending_unit() {
+            info!("not voting - previous unit not signed yet");
+            return None;
+        }
         if let Some((prop_context, _)) = self.next_proposal.take() {
             warn!(?prop_context, "canceling proposal due to unit");
         }
@@ -453,6 +583,14 @@ impl<C: Context> ActiveValidator<C> {
             return None;
         }
         let seq_number = panorama.next_seq_num(state, self.vidx);
+        if self.max_signed_seq_number >= Some(seq_number) {
+            error!(
+                seq_number,
+                ?self.max_signed_seq_number,
+                "already signed a unit with this sequence number; canceling unit creation"
+            );
+            return None;
+        }
         let endorsed = state.seen_endorsed(&panorama);
         #[allow(clippy::arithmetic_side_effects)] // min_round_length is guaranteed to be > 0.
         let round_exp = (self.round_len(state, timestamp) / state.params().min_round_length())
@@ -468,13 +606,33 @@ impl<C: Context> ActiveValidator<C> {
             endorsed,
         }
         .into_hashed();
-        let swunit = SignedWireUnit::new(hwunit, &self.secret);
-        write_last_unit(&self.unit_file, swunit.clone()).unwrap_or_else(|err| {
-            panic!(
-                "should successfully write unit's hash to {:?}, got {:?}",
-                self.unit_file, err
-            )
-        });
+        let hash = hwunit.hash();
+        match self.secret.sign(&hash, hwunit.signed_data()) {
+            Signing::Signed(signature) => {
+                let swunit = SignedWireUnit {
+                    hashed_wire_unit: hwunit,
+                    signature,
+                };
+                let swunit = self.record_unit(swunit)?;
+                Some(Effect::NewVertex(ValidVertex(Vertex::Unit(swunit))))
+            }
+            Signing::Refused => None,
+            Signing::Deferred(data) => self.defer(hash, Unsigned::Unit(hwunit), data),
+        }
+    }
+
+    /// Records a newly signed unit in the WAL. Returns `None` if that failed.
+    fn record_unit(&mut self, swunit: SignedWireUnit<C>) -> Option<SignedWireUnit<C>> {
+        // The unit must be on disk before anyone can see it, so we never contradict it.
+        if let Some(wal) = self.wal.as_mut() {
+            if let Err(err) = wal.record_entry(&Entry::Unit(swunit.clone())) {
+                error!(%err, "could not record unit in WAL; not creating any more units");
+                self.wal = None;
+                self.wal_failed = true;
+                return None;
+            }
+        }
+        self.max_signed_seq_number = Some(swunit.wire_unit().seq_number);
         Some(swunit)
     }
 
@@ -581,11 +739,17 @@ impl<C: Context> ActiveValidator<C> {
                 .any(|(vidx, _)| state.is_faulty(vidx) && unit.new_hash_obs(state, vidx))
     }
 
-    /// Creates endorsement of the `vhash`.
-    fn endorse(&self, vhash: &C::Hash) -> Vertex<C> {
+    /// Creates endorsement of the `vhash`, unless the signer refused to sign it.
+    fn endorse(&mut self, vhash: &C::Hash) -> Option<Effect<C>> {
         let endorsement = Endorsement::new(*vhash, self.vidx);
-        let signature = self.secret.sign(&endorsement.hash());
-        Vertex::Endorsements(SignedEndorsement::new(endorsement, signature).into())
+        let hash = endorsement.hash();
+        match self.secret.sign(&hash, endorsement.signed_data()) {
+            Signing::Signed(signature) => Some(Effect::NewVertex(ValidVertex(
+                Vertex::Endorsements(SignedEndorsement::new(endorsement, signature).into()),
+            ))),
+            Signing::Refused => None,
+            Signing::Deferred(data) => self.defer(hash, Unsigned::Endorsement(endorsement), data),
+        }
     }
 
     /// Returns a panorama that is valid to use in our own unit at the given timestamp.
@@ -640,40 +804,6 @@ impl<C: Context> ActiveValidator<C> {
     }
 }
 
-pub(crate) fn read_last_unit<C, P>(path: P) -> io::Result<SignedWireUnit<C>>
-where
-    C: Context,
-    P: AsRef<Path>,
-{
-    let mut file = File::open(path)?;
-    let mut bytes = Vec::new();
-    file.read_to_end(&mut bytes)?;
-    Ok(serde_json::from_slice(&bytes)?)
-}
-
-pub(crate) fn write_last_unit<C: Context>(
-    unit_file: &Option<PathBuf>,
-    swunit: SignedWireUnit<C>,
-) -> io::Result<()> {
-    // If there is no unit_file set, do not write to it
-    let unit_file = if let Some(file) = unit_file.as_ref() {
-        file
-    } else {
-        return Ok(());
-    };
-
-    // Create the file (and its parents) as necessary
-    if let Some(parent_directory) = unit_file.parent() {
-        fs::create_dir_all(parent_directory)?;
-    }
-    let mut file = File::create(unit_file)?;
-
-    // Finally, write the data to file we created
-    let bytes = serde_json::to_vec(&swunit)?;
-
-    file.write_all(&bytes)
-}
-
 #[cfg(test)]
 #[allow(clippy::arithmetic_side_effects)] // Overflows in tests panic anyway.
 mod tests {
@@ -1010,7 +1140,8 @@ mod tests {
             TEST_INSTANCE_ID,
         );
 
-        let ping = Vertex::Ping(Ping::new(ALICE, 500.into(), TEST_INSTANCE_ID, &ALICE_SEC));
+        let ping =
+            Vertex::Ping(Ping::new(ALICE, 500.into(), TEST_INSTANCE_ID, &ALICE_SEC).unwrap());
 
         // The ping is suspicious if it is newer than the latest ping (or unit) that has been added
         // to the state.
@@ -1040,14 +1171,11 @@ mod tests {
         // Clean state. We want Alice to synchronize first.
         state.retain_evidence_only();
 
-        let unit_file = {
-            let tmp_dir = tempdir().unwrap();
-            let unit_files_folder = tmp_dir.path().to_path_buf();
-            Some(unit_files_folder.join(format!("unit_{:?}.dat", instance_id)))
-        };
+        let tmp_dir = tempdir().unwrap();
+        let wal_file = tmp_dir.path().join(format!("unit_{:?}.dat", instance_id));
 
         // Store `a2` unit as the Alice's last unit.
-        write_last_unit(&unit_file, a2.clone()).expect("storing unit should succeed");
+        write_wal(&wal_file, &[&a2]);
 
         // Alice's last unit is `a2` but `State` is empty. She must synchronize first.
         let (mut alice, alice_init_effects) = ActiveValidator::new(
@@ -1056,7 +1184,7 @@ mod tests {
             410.into(),
             410.into(),
             &state,
-            unit_file,
+            Some(wal_file.clone()),
             Weight(2),
             TEST_INSTANCE_ID,
         );
@@ -1096,10 +1224,113 @@ mod tests {
             panorama!(a2.hash()),
             "new unit should cite the latest unit"
         );
+        assert_eq!(
+            read_wal(&wal_file),
+            vec![a2, proposal_wunit],
+            "new unit should be recorded in the WAL"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn never_reuses_signed_seq_number() -> Result<(), AddUnitError<TestContext>> {
+        let instance_id = TEST_INSTANCE_ID;
+        let mut state = State::new_test(&[Weight(3)], 0);
+        let a0 = {
+            let a0 = add_unit!(state, ALICE, 0xB0; N)?;
+            state.wire_unit(&a0, instance_id).unwrap()
+        };
+        let a1 = {
+            let a1 = add_unit!(state, ALICE, None; a0.hash())?;
+            state.wire_unit(&a1, instance_id).unwrap()
+        };
+        let a2 = {
+            let a2 = add_unit!(state, ALICE, None; a1.hash())?;
+            state.wire_unit(&a2, instance_id).unwrap()
+        };
+        // Alice signed `a2` before crashing, but no one else ever saw it.
+        state.retain_evidence_only();
+        state.add_unit(a0.clone())?;
+        state.add_unit(a1.clone())?;
+
+        let tmp_dir = tempdir().unwrap();
+        let wal_file = tmp_dir.path().join(format!("unit_{:?}.dat", instance_id));
+        write_wal(&wal_file, &[&a0, &a2, &a1]);
+
+        let (mut alice, alice_init_effects) = ActiveValidator::new(
+            ALICE,
+            TestSecret(ALICE.0),
+            410.into(),
+            410.into(),
+            &state,
+            Some(wal_file.clone()),
+            Weight(2),
+            TEST_INSTANCE_ID,
+        );
+        assert_eq!(alice.own_last_unit, Some(a2.clone()));
+        let next_proposal_timer = match &*alice_init_effects {
+            &[Effect::ScheduleTimer(timestamp), Effect::NewVertex(ValidVertex(Vertex::Ping(_)))] => {
+                timestamp
+            }
+            other => panic!("unexpected effects {:?}", other),
+        };
+
+        // Even if she lost track of `a2`, she must not sign another unit with its sequence number.
+        alice.take_own_last_unit();
+        assert_no_proposal(&mut alice, &state, instance_id, next_proposal_timer);
+        assert_eq!(read_wal(&wal_file), vec![a0, a2, a1]);
+        Ok(())
+    }
+
+    #[test]
+    fn converts_legacy_unit_file() -> Result<(), AddUnitError<TestContext>> {
+        let instance_id = TEST_INSTANCE_ID;
+        let mut state = State::new_test(&[Weight(3)], 0);
+        let a0 = {
+            let a0 = add_unit!(state, ALICE, 0xB0; N)?;
+            state.wire_unit(&a0, instance_id).unwrap()
+        };
+
+        // Earlier versions stored only the last unit, as JSON.
+        let tmp_dir = tempdir().unwrap();
+        let wal_file = tmp_dir.path().join(format!("unit_{:?}.dat", instance_id));
+        std::fs::write(&wal_file, serde_json::to_vec(&a0).unwrap()).unwrap();
 
+        let (alice, _) = ActiveValidator::new(
+            ALICE,
+            TestSecret(ALICE.0),
+            410.into(),
+            410.into(),
+            &state,
+            Some(wal_file.clone()),
+            Weight(2),
+            TEST_INSTANCE_ID,
+        );
+        assert!(!alice.wal_failed);
+        assert_eq!(alice.own_last_unit, Some(a0.clone()));
+        assert_eq!(read_wal(&wal_file), vec![a0]);
         Ok(())
     }
 
+    fn write_wal(wal_file: &PathBuf, units: &[&SignedWireUnit<TestContext>]) {
+        let mut wal = UnitWal::new(wal_file).expect("should open WAL");
+        for swunit in units {
+            wal.record_entry(&Entry::Unit((*swunit).clone()))
+                .expect("storing unit should succeed");
+        }
+    }
+
+    fn read_wal(wal_file: &PathBuf) -> Vec<SignedWireUnit<TestContext>> {
+        let (entries, _) = wal::open::<TestContext>(wal_file).expect("should read WAL");
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                Entry::Unit(swunit) => swunit,
+            })
+            .collect()
+    }
+
     // Triggers new proposal by `validator` and verifies that it's empty – no block was proposed.
     // Captures the next witness timer and calls the `validator` with that to return the timer for
     // the next proposal.
@@ -2,7 +2,10 @@ use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::components::consensus::{traits::Context, utils::ValidatorIndex};
+use crate::components::consensus::{
+    traits::{Context, SignedData},
+    utils::ValidatorIndex,
+};
 
 /// An error due to an invalid endorsement.
 #[derive(Debug, Error, Eq, PartialEq)]
@@ -44,9 +47,17 @@ impl<C: Context> Endorsement<C> {
 
     /// Returns the hash of the endorsement.
     pub fn hash(&self) -> C::Hash {
-        <C as Context>::hash(
-            &bincode::serialize(&(self.unit, self.creator)).expect("serialize endorsement"),
-        )
+        <C as Context>::hash(&self.serialize_fields())
+    }
+
+    /// Serializes the fields the endorsement's hash is computed from.
+    fn serialize_fields(&self) -> Vec<u8> {
+        bincode::serialize(&(self.unit, self.creator)).expect("serialize endorsement")
+    }
+
+    /// Returns the serialized endorsement, to be signed by its creator.
+    pub(crate) fn signed_data(&self) -> SignedData {
+        SignedData::HighwayEndorsement(self.serialize_fields())
     }
 }
 
@@ -101,10 +101,51 @@ impl<C: Context> Evidence<C> {
         validators: &Validators<C::ValidatorId>,
         instance_id: &C::InstanceId,
         params: &Params,
+    ) -> Result<(), EvidenceError> {
+        let unit_creator = match self {
+            Evidence::Equivocation(..) => None,
+            Evidence::Endorsements {
+                unit1, swimlane2, ..
+            } => {
+                if swimlane2.len() as u64 > params.endorsement_evidence_limit() {
+                    return Err(EvidenceError::EndorsementTooManyUnits);
+                }
+                let creator_id = validators
+                    .id(unit1.wire_unit().creator)
+                    .ok_or(EvidenceError::UnknownPerpetrator)?;
+                Some(creator_id)
+            }
+        };
+        let v_id = validators
+            .id(self.perpetrator())
+            .ok_or(EvidenceError::UnknownPerpetrator)?;
+        self.validate_signatures(v_id, unit_creator, instance_id)
+    }
+
+    /// Validates the evidence against the perpetrator's ID alone, without knowing the rest of the
+    /// validator set, e.g. to verify it outside of the consensus protocol instance.
+    ///
+    /// For endorsement evidence the signatures of the endorsed units are not checked: They were
+    /// created by a different validator and don't affect the perpetrator's fault.
+    pub fn validate_perpetrator(
+        &self,
+        v_id: &C::ValidatorId,
+        instance_id: &C::InstanceId,
+    ) -> Result<(), EvidenceError> {
+        self.validate_signatures(v_id, None, instance_id)
+    }
+
+    /// Validates evidence against the perpetrator `v_id`. For endorsement evidence, the endorsed
+    /// units' signatures are only checked if their creator's ID `unit_creator` is given.
+    fn validate_signatures(
+        &self,
+        v_id: &C::ValidatorId,
+        unit_creator: Option<&C::ValidatorId>,
+        instance_id: &C::InstanceId,
     ) -> Result<(), EvidenceError> {
         match self {
             Evidence::Equivocation(unit1, unit2) => {
-                Self::validate_equivocation(unit1, unit2, instance_id, validators)
+                Self::validate_equivocation(unit1, unit2, instance_id, Some(v_id))
             }
             Evidence::Endorsements {
                 endorsement1,
@@ -113,12 +154,6 @@ impl<C: Context> Evidence<C> {
                 unit2,
                 swimlane2,
             } => {
-                if swimlane2.len() as u64 > params.endorsement_evidence_limit() {
-                    return Err(EvidenceError::EndorsementTooManyUnits);
-                }
-                let v_id = validators
-                    .id(endorsement1.validator_idx())
-                    .ok_or(EvidenceError::UnknownPerpetrator)?;
                 if *endorsement1.unit() != unit1.hash() || *endorsement2.unit() != unit2.hash() {
                     return Err(EvidenceError::EndorsementWrongHash);
                 }
@@ -134,7 +169,7 @@ impl<C: Context> Evidence<C> {
                     unit1,
                     swimlane2.last().unwrap_or(unit2),
                     instance_id,
-                    validators,
+                    unit_creator,
                 )?;
                 if !C::verify_signature(&endorsement1.hash(), v_id, endorsement1.signature())
                     || !C::verify_signature(&endorsement2.hash(), v_id, endorsement2.signature())
@@ -146,17 +181,16 @@ impl<C: Context> Evidence<C> {
         }
     }
 
+    /// Validates two units with the same creator and sequence number. If the creator's ID is
+    /// given, their signatures are checked, too.
     fn validate_equivocation(
         unit1: &SignedWireUnit<C>,
         unit2: &SignedWireUnit<C>,
         instance_id: &C::InstanceId,
-        validators: &Validators<C::ValidatorId>,
+        creator_id: Option<&C::ValidatorId>,
     ) -> Result<(), EvidenceError> {
         let wunit1 = unit1.wire_unit();
         let wunit2 = unit2.wire_unit();
-        let v_id = validators
-            .id(wunit1.creator)
-            .ok_or(EvidenceError::UnknownPerpetrator)?;
         if wunit1.creator != wunit2.creator {
             return Err(EvidenceError::EquivocationDifferentCreators);
         }
@@ -169,10 +203,12 @@ impl<C: Context> Evidence<C> {
         if unit1 == unit2 {
             return Err(EvidenceError::EquivocationSameUnit);
         }
-        if !C::verify_signature(&unit1.hash(), v_id, &unit1.signature)
-            || !C::verify_signature(&unit2.hash(), v_id, &unit2.signature)
-        {
-            return Err(EvidenceError::Signature);
+        if let Some(v_id) = creator_id {
+            if !C::verify_signature(&unit1.hash(), v_id, &unit1.signature)
+                || !C::verify_signature(&unit2.hash(), v_id, &unit2.signature)
+            {
+                return Err(EvidenceError::Signature);
+            }
         }
         Ok(())
     }
@@ -190,7 +190,7 @@ impl<C: Context> Highway<C> {
         id: C::ValidatorId,
         secret: C::ValidatorSecret,
         current_time: Timestamp,
-        unit_hash_file: Option<PathBuf>,
+        wal_file: Option<PathBuf>,
         target_ftt: Weight,
     ) -> Vec<Effect<C>> {
         if self.active_validator.is_some() {
@@ -211,7 +211,7 @@ impl<C: Context> Highway<C> {
             current_time,
             start_time,
             &self.state,
-            unit_hash_file,
+            wal_file,
             target_ftt,
             self.instance_id,
         );
@@ -457,6 +457,20 @@ impl<C: Context> Highway<C> {
         })
     }
 
+    /// Creates the vertex with the given hash, now that the remote signer answered.
+    pub(crate) fn handle_signature(
+        &mut self,
+        hash: &C::Hash,
+        signature: Option<C::Signature>,
+        now: Timestamp,
+    ) -> Vec<Effect<C>> {
+        self.map_active_validator(|av, state| av.on_signature(hash, signature, state), now)
+            .unwrap_or_else(|| {
+                debug!(%hash, "ignoring signature: validator has been deactivated");
+                vec![]
+            })
+    }
+
     pub(crate) fn validators(&self) -> &Validators<C::ValidatorId> {
         &self.validators
     }
@@ -547,7 +561,9 @@ impl<C: Context> Highway<C> {
                     result.extend(self.add_valid_vertex(vv.clone(), timestamp))
                 }
                 Effect::WeAreFaulty(_) => self.deactivate_validator(),
-                Effect::ScheduleTimer(_) | Effect::RequestNewBlock(_, _) => (),
+                Effect::ScheduleTimer(_)
+                | Effect::RequestNewBlock(_, _)
+                | Effect::RequestSignature(_, _) => (),
             }
         }
         result.extend(effects);
@@ -826,12 +842,7 @@ pub(crate) mod tests {
         let expected = (invalid_vertex.clone(), err);
         assert_eq!(Err(expected), highway.pre_validate_vertex(invalid_vertex));
 
-        let hwunit = wunit.into_hashed();
-        let valid_signature = CAROL_SEC.sign(&hwunit.hash());
-        let correct_signature_unit = SignedWireUnit {
-            hashed_wire_unit: hwunit,
-            signature: valid_signature,
-        };
+        let correct_signature_unit = SignedWireUnit::new(wunit.into_hashed(), &CAROL_SEC).unwrap();
         let valid_vertex = Vertex::Unit(correct_signature_unit);
         let pvv = highway.pre_validate_vertex(valid_vertex).unwrap();
         assert_eq!(None, highway.missing_dependency(&pvv));
@@ -921,9 +932,9 @@ pub(crate) mod tests {
                         wunit1: &WireUnit<TestContext>,
                         signer1: &TestSecret| {
             let hwunit0 = wunit0.clone().into_hashed();
-            let swunit0 = SignedWireUnit::new(hwunit0, signer0);
+            let swunit0 = SignedWireUnit::new(hwunit0, signer0).unwrap();
             let hwunit1 = wunit1.clone().into_hashed();
-            let swunit1 = SignedWireUnit::new(hwunit1, signer1);
+            let swunit1 = SignedWireUnit::new(hwunit1, signer1).unwrap();
             let evidence = Evidence::Equivocation(swunit0, swunit1);
             let vertex = Vertex::Evidence(evidence);
             highway
@@ -1017,7 +1028,7 @@ pub(crate) mod tests {
         // Ping by validator that is not bonded, with an index that is outside of boundaries of the
         // state.
         let ping: Vertex<TestContext> =
-            Vertex::Ping(Ping::new(DAN, now, TEST_INSTANCE_ID, &DAN_SEC));
+            Vertex::Ping(Ping::new(DAN, now, TEST_INSTANCE_ID, &DAN_SEC).unwrap());
         assert!(
             DAN.0 >= WEIGHTS.len() as u32,
             "should use validator that is not bonded"
@@ -1043,9 +1054,9 @@ pub(crate) mod tests {
         let _effects =
             highway.activate_validator(ALICE.0, ALICE_SEC.clone(), now, None, target_ftt);
 
-        let ping = Vertex::Ping(Ping::new(ALICE, now, TEST_INSTANCE_ID, &ALICE_SEC));
+        let ping = Vertex::Ping(Ping::new(ALICE, now, TEST_INSTANCE_ID, &ALICE_SEC).unwrap());
         assert!(!highway.is_doppelganger_vertex(&ping));
-        let ping = Vertex::Ping(Ping::new(ALICE, later, TEST_INSTANCE_ID, &ALICE_SEC));
+        let ping = Vertex::Ping(Ping::new(ALICE, later, TEST_INSTANCE_ID, &ALICE_SEC).unwrap());
         assert!(highway.is_doppelganger_vertex(&ping));
     }
 }
@@ -5,13 +5,15 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use casper_types::Timestamp;
 
+#[cfg(test)]
+use crate::components::consensus::traits::ValidatorSecret;
 use crate::components::consensus::{
     highway_core::{
         endorsement::SignedEndorsement,
         highway::{PingError, VertexError},
         state::Panorama,
     },
-    traits::{Context, ValidatorSecret},
+    traits::{Context, SignedData},
     utils::{ValidatorIndex, Validators},
 };
 
@@ -318,15 +320,19 @@ where
 }
 
 impl<C: Context> SignedWireUnit<C> {
+    /// Signs the unit. Returns `None` if the signer refused or works asynchronously.
+    #[cfg(test)]
     pub(crate) fn new(
         hashed_wire_unit: HashedWireUnit<C>,
         secret_key: &C::ValidatorSecret,
-    ) -> Self {
-        let signature = secret_key.sign(&hashed_wire_unit.hash);
-        SignedWireUnit {
+    ) -> Option<Self> {
+        let signature = secret_key
+            .sign(&hashed_wire_unit.hash, hashed_wire_unit.signed_data())
+            .signed()?;
+        Some(SignedWireUnit {
             hashed_wire_unit,
             signature,
-        }
+        })
     }
 
     /// Returns the inner `WireUnit`.
@@ -380,6 +386,11 @@ where
     pub(crate) fn new_with_hash(wire_unit: WireUnit<C>, hash: C::Hash) -> Self {
         HashedWireUnit { hash, wire_unit }
     }
+
+    /// Returns the serialized unit, to be signed by its creator.
+    pub(crate) fn signed_data(&self) -> SignedData {
+        SignedData::HighwayUnit(bincode::serialize(&self.wire_unit).expect("serialize WireUnit"))
+    }
 }
 
 impl<C: Context> Serialize for HashedWireUnit<C> {
@@ -518,14 +529,31 @@ where
 }
 
 impl<C: Context> Ping<C> {
-    /// Creates a new signed ping.
+    /// Creates a new signed ping. Returns `None` if the signer refused or works asynchronously.
+    #[cfg(test)]
     pub(crate) fn new(
         creator: ValidatorIndex,
         timestamp: Timestamp,
         instance_id: C::InstanceId,
         sk: &C::ValidatorSecret,
+    ) -> Option<Self> {
+        let (hash, data) = Self::signed_data(creator, timestamp, instance_id);
+        let signature = sk.sign(&hash, data).signed()?;
+        Some(Ping::with_signature(
+            creator,
+            timestamp,
+            instance_id,
+            signature,
+        ))
+    }
+
+    /// Creates a ping with the given signature.
+    pub(crate) fn with_signature(
+        creator: ValidatorIndex,
+        timestamp: Timestamp,
+        instance_id: C::InstanceId,
+        signature: C::Signature,
     ) -> Self {
-        let signature = sk.sign(&Self::hash(creator, timestamp, instance_id));
         Ping {
             creator,
             timestamp,
@@ -534,6 +562,17 @@ impl<C: Context> Ping<C> {
         }
     }
 
+    /// Returns the hash of a ping with the given fields, and the data to sign.
+    pub(crate) fn signed_data(
+        creator: ValidatorIndex,
+        timestamp: Timestamp,
+        instance_id: C::InstanceId,
+    ) -> (C::Hash, SignedData) {
+        let bytes = Self::serialize_fields(creator, timestamp, instance_id);
+        let hash = <C as Context>::hash(&bytes);
+        (hash, SignedData::HighwayPing(bytes))
+    }
+
     /// The creator who signals that it is online.
     pub fn creator(&self) -> ValidatorIndex {
         self.creator
@@ -569,7 +608,15 @@ impl<C: Context> Ping<C> {
 
     /// Computes the hash of a ping, i.e. of the creator and timestamp.
     fn hash(creator: ValidatorIndex, timestamp: Timestamp, instance_id: C::InstanceId) -> C::Hash {
-        let bytes = bincode::serialize(&(creator, timestamp, instance_id)).expect("serialize Ping");
-        <C as Context>::hash(&bytes)
+        <C as Context>::hash(&Self::serialize_fields(creator, timestamp, instance_id))
+    }
+
+    /// Serializes the fields the ping's hash is computed from.
+    fn serialize_fields(
+        creator: ValidatorIndex,
+        timestamp: Timestamp,
+        instance_id: C::InstanceId,
+    ) -> Vec<u8> {
+        bincode::serialize(&(creator, timestamp, instance_id)).expect("serialize Ping")
     }
 }
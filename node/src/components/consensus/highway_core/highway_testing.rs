@@ -34,7 +34,7 @@ use crate::{
             },
             queue::QueueEntry,
         },
-        traits::{ConsensusValueT, Context, ValidatorSecret},
+        traits::{ConsensusValueT, Context, SignedData, Signing, ValidatorSecret},
         utils::{Validators, Weight},
         BlockContext,
     },
@@ -124,6 +124,7 @@ impl From<Effect<TestContext>> for HighwayMessage {
                 HighwayMessage::RequestBlock(block_context)
             }
             Effect::WeAreFaulty(fault) => HighwayMessage::WeAreFaulty(Box::new(fault)),
+            Effect::RequestSignature(..) => unreachable!("test secrets sign synchronously"),
         }
     }
 }
@@ -285,7 +286,7 @@ impl HighwayValidator {
                                 }
                                 let secret = TestSecret(wunit2.creator.0.into());
                                 let hwunit2 = wunit2.into_hashed();
-                                let swunit2 = SignedWireUnit::new(hwunit2, &secret);
+                                let swunit2 = SignedWireUnit::new(hwunit2, &secret).unwrap();
                                 let vertex2 = Box::new(Vertex::Unit(swunit2));
                                 vec![msg, HighwayMessage::NewVertex(vertex2)]
                             }
@@ -1039,8 +1040,8 @@ impl ValidatorSecret for TestSecret {
     type Hash = HashWrapper;
     type Signature = SignatureWrapper;
 
-    fn sign(&self, data: &Self::Hash) -> Self::Signature {
-        SignatureWrapper(data.0 + self.0)
+    fn sign(&self, data: &Self::Hash, _signed_data: SignedData) -> Signing<Self::Signature> {
+        Signing::Signed(SignatureWrapper(data.0 + self.0))
     }
 }
 
@@ -0,0 +1,86 @@
+//! The Write-Ahead Log of an active Highway validator, storing every unit it signed.
+
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use crate::components::consensus::{
+    highway_core::highway::SignedWireUnit,
+    traits::Context,
+    utils::wal::{ReadWal, ReadWalError, WriteWal, WriteWalError},
+};
+
+/// An entry in the Write-Ahead Log, storing a unit we signed.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(bound(
+    serialize = "C::Hash: Serialize",
+    deserialize = "C::Hash: Deserialize<'de>",
+))]
+pub(crate) enum Entry<C: Context> {
+    /// A unit signed by us.
+    Unit(SignedWireUnit<C>),
+}
+
+/// The Write-Ahead Log the units we sign are recorded in.
+pub(crate) type UnitWal<C> = WriteWal<Entry<C>>;
+
+#[derive(Error, Debug)]
+pub(crate) enum WalError {
+    #[error("could not read WAL: {0}")]
+    Read(#[from] ReadWalError),
+    #[error("could not open WAL for writing: {0}")]
+    Write(#[from] WriteWalError),
+    #[error("could not convert legacy unit file: {0}")]
+    LegacyConversion(io::Error),
+}
+
+/// Reads all entries from the WAL at `path` and opens it for appending new ones.
+///
+/// Earlier versions stored only the last signed unit, as JSON, at the same path. Such a file is
+/// converted into a WAL containing that unit.
+pub(crate) fn open<C: Context>(path: &PathBuf) -> Result<(Vec<Entry<C>>, UnitWal<C>), WalError> {
+    convert_legacy_unit_file::<C>(path).map_err(WalError::LegacyConversion)?;
+    let mut read_wal = ReadWal::<Entry<C>>::new(path)?;
+    let mut entries = Vec::new();
+    while let Some(entry) = read_wal.read_next_entry()? {
+        entries.push(entry);
+    }
+    let write_wal = WriteWal::new(path)?;
+    Ok((entries, write_wal))
+}
+
+/// If `path` contains a unit in the legacy JSON format, atomically replaces it with a WAL
+/// containing that unit.
+fn convert_legacy_unit_file<C: Context>(path: &Path) -> io::Result<()> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let swunit: SignedWireUnit<C> = match serde_json::from_slice(&bytes) {
+        Ok(swunit) => swunit,
+        Err(_) => return Ok(()), // Not a legacy unit file.
+    };
+    info!(?path, "converting legacy unit file to WAL");
+    let tmp_path = path.with_extension("tmp");
+    // Remove leftovers of an earlier, interrupted conversion.
+    if let Err(err) = fs::remove_file(&tmp_path) {
+        if err.kind() != io::ErrorKind::NotFound {
+            return Err(err);
+        }
+    }
+    WriteWal::new(&tmp_path)
+        .and_then(|mut wal| wal.record_entry(&Entry::Unit(swunit)))
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    fs::rename(&tmp_path, path)?;
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+        _ => Ok(()),
+    }
+}
@@ -23,10 +23,12 @@ use casper_types::{system::auction::BLOCK_REWARD, TimeDiff, Timestamp, U512};
 
 use crate::{
     components::consensus::{
+        self,
         config::Config,
         consensus_protocol::{
             BlockContext, ConsensusProtocol, ProposedBlock, ProtocolOutcome, ProtocolOutcomes,
         },
+        equivocation::Equivocation,
         era_supervisor::SerializedMessage,
         highway_core::{
             active_validator::Effect as AvEffect,
@@ -35,10 +37,11 @@ use crate::{
                 Dependency, GetDepOutcome, Highway, Params, PreValidatedVertex, ValidVertex,
                 Vertex, VertexError,
             },
-            state::{IndexObservation, IndexPanorama, Observation},
+            state::{self, IndexObservation, IndexPanorama, Observation},
             synchronizer::Synchronizer,
         },
         protocols,
+        status::{ProtocolStatus, UPCOMING_LEADERS},
         traits::{ConsensusValueT, Context},
         utils::ValidatorIndex,
         ActionId, TimerId,
@@ -258,6 +261,9 @@ impl<C: Context + 'static> HighwayProtocol<C> {
                 error!("this validator is faulty: {:?}", fault);
                 vec![ProtocolOutcome::WeAreFaulty]
             }
+            AvEffect::RequestSignature(hash, data) => {
+                vec![ProtocolOutcome::RequestSignature(hash, data)]
+            }
         }
     }
 
@@ -377,7 +383,7 @@ impl<C: Context + 'static> HighwayProtocol<C> {
     fn calculate_round_length(&mut self, vv: &ValidVertex<C>, now: Timestamp) {
         let new_round_len = self
             .round_success_meter
-            .calculate_new_length(self.highway.state());
+            .calculate_new_length(self.highway.state(), now);
         // If the vertex contains a proposal, register it in the success meter.
         // It's important to do this _after_ the calculation above - otherwise we might try to
         // register the proposal before the meter is aware that a new round has started, and it
@@ -1011,6 +1017,16 @@ where
         }
     }
 
+    fn handle_signature(
+        &mut self,
+        hash: C::Hash,
+        signature: Option<C::Signature>,
+        now: Timestamp,
+    ) -> ProtocolOutcomes<C> {
+        let effects = self.highway.handle_signature(&hash, signature, now);
+        self.process_av_effects(effects, now)
+    }
+
     fn propose(&mut self, proposed_block: ProposedBlock<C>, now: Timestamp) -> ProtocolOutcomes<C> {
         let (value, block_context) = proposed_block.destructure();
         let effects = self.highway.propose(value, block_context);
@@ -1061,12 +1077,12 @@ where
         our_id: C::ValidatorId,
         secret: C::ValidatorSecret,
         now: Timestamp,
-        unit_hash_file: Option<PathBuf>,
+        wal_file: Option<PathBuf>,
     ) -> ProtocolOutcomes<C> {
         let ftt = self.finality_detector.fault_tolerance_threshold();
         let av_effects = self
             .highway
-            .activate_validator(our_id, secret, now, unit_hash_file, ftt);
+            .activate_validator(our_id, secret, now, wal_file, ftt);
         self.process_av_effects(av_effects, now)
     }
 
@@ -1111,6 +1127,12 @@ where
             .collect()
     }
 
+    fn equivocation(&self, vid: &C::ValidatorId) -> Option<Equivocation<C>> {
+        let vidx = self.highway.validators().get_index(vid)?;
+        let evidence = self.highway.state().maybe_evidence(vidx)?;
+        Some(Equivocation::highway(evidence.clone()))
+    }
+
     /// Sets the pause status: While paused we don't create any new units, just pings.
     fn set_paused(&mut self, paused: bool, _now: Timestamp) -> ProtocolOutcomes<C> {
         self.highway.set_paused(paused);
@@ -1136,6 +1158,49 @@ where
     fn next_round_length(&self) -> Option<TimeDiff> {
         self.highway.next_round_length()
     }
+
+    fn protocol_status(&self, now: Timestamp) -> ProtocolStatus<C> {
+        let state = self.highway.state();
+        // If we are not a validator, use the round length of the latest unit we have seen.
+        let round_length = self.highway.next_round_length().or_else(|| {
+            let latest_unit = state
+                .panorama()
+                .iter_correct(state)
+                .max_by_key(|unit| unit.timestamp)?;
+            Some(latest_unit.round_len)
+        });
+        let current_round = round_length.map(|round_len| state::round_id(now, round_len));
+        let upcoming_leaders = current_round
+            .zip(round_length)
+            .into_iter()
+            .flat_map(|(r_id, round_len)| {
+                (0..UPCOMING_LEADERS).filter_map(move |i| {
+                    let offset = round_len.millis().checked_mul(u64::from(i))?;
+                    Some(Timestamp::from(r_id.millis().checked_add(offset)?))
+                })
+            })
+            .filter_map(|timestamp| {
+                let v_id = self.highway.validators().id(state.leader(timestamp))?;
+                Some((timestamp.millis(), v_id.clone()))
+            })
+            .collect();
+        let participation = self
+            .highway
+            .validators()
+            .enumerate_ids()
+            .map(|(idx, v_id)| {
+                let status = participation::Status::for_index(idx, state, now)
+                    .map_or(consensus::ParticipationStatus::Active, Into::into);
+                (v_id.clone(), status)
+            })
+            .collect();
+        ProtocolStatus {
+            current_round: current_round.map(|r_id| r_id.millis()),
+            round_length,
+            upcoming_leaders,
+            participation,
+        }
+    }
 }
 
 /// Maximum possible rounds in one era.
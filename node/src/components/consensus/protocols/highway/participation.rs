@@ -4,6 +4,7 @@ use casper_types::Timestamp;
 
 use crate::{
     components::consensus::{
+        self,
         highway_core::{
             highway::Highway,
             state::{Fault, State},
@@ -16,7 +17,7 @@ use crate::{
 
 /// A validator's participation status: whether they are faulty or inactive.
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
-enum Status {
+pub(super) enum Status {
     LastSeenSecondsAgo(u64),
     Inactive,
     EquivocatedInOtherEra,
@@ -25,7 +26,7 @@ enum Status {
 
 impl Status {
     /// Returns a `Status` for a validator unless they are honest and online.
-    fn for_index<C: Context>(
+    pub(super) fn for_index<C: Context>(
         idx: ValidatorIndex,
         state: &State<C>,
         now: Timestamp,
@@ -51,6 +52,19 @@ impl Status {
     }
 }
 
+impl From<Status> for consensus::ParticipationStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::LastSeenSecondsAgo(seconds) => {
+                consensus::ParticipationStatus::LastSeenSecondsAgo(seconds)
+            }
+            Status::Inactive => consensus::ParticipationStatus::Inactive,
+            Status::EquivocatedInOtherEra => consensus::ParticipationStatus::EquivocatedInOtherEra,
+            Status::Equivocated => consensus::ParticipationStatus::Equivocated,
+        }
+    }
+}
+
 /// A map of status (faulty, inactive) by validator ID.
 #[derive(Debug)]
 // False positive, as the fields of this struct are all used in logging validator participation.
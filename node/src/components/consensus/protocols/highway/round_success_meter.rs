@@ -102,8 +102,7 @@ impl<C: Context> RoundSuccessMeter<C> {
     /// successful, we return a higher round length for the future.
     /// If the length shouldn't grow, and the round ID is divisible by a certain number, a lower
     /// round length is returned.
-    pub fn calculate_new_length(&mut self, state: &State<C>) -> TimeDiff {
-        let now = Timestamp::now();
+    pub fn calculate_new_length(&mut self, state: &State<C>, now: Timestamp) -> TimeDiff {
         // if the round hasn't finished, just return whatever we have now
         if state::round_id(now, self.current_round_len) <= self.current_round_id {
             return self.new_length();
@@ -85,29 +85,35 @@ use casper_types::{system::auction::BLOCK_REWARD, TimeDiff, Timestamp, U512};
 
 use crate::{
     components::consensus::{
+        self,
         config::Config,
         consensus_protocol::{
             BlockContext, ConsensusProtocol, FinalizedBlock, ProposedBlock, ProtocolOutcome,
             ProtocolOutcomes, TerminalBlockData,
         },
+        equivocation::Equivocation,
         era_supervisor::SerializedMessage,
         protocols,
-        traits::{ConsensusValueT, Context},
-        utils::{ValidatorIndex, ValidatorMap, Validators, Weight},
+        status::{ProtocolStatus, UPCOMING_LEADERS},
+        traits::{ConsensusValueT, Context, Signing, ValidatorSecret},
+        utils::{
+            wal::{ReadWal, WriteWal},
+            ValidatorIndex, ValidatorMap, Validators, Weight,
+        },
         ActionId, LeaderSequence, TimerId,
     },
     types::{Chainspec, NodeId},
     utils, NodeRng,
 };
 use fault::Fault;
-use message::{Content, SignedMessage, SyncResponse};
+use message::SyncResponse;
 use params::Params;
 use participation::{Participation, ParticipationStatus};
 use proposal::{HashedProposal, Proposal};
 use round::Round;
-use wal::{Entry, ReadWal, WriteWal};
+use wal::Entry;
 
-pub(crate) use message::{Message, SyncRequest};
+pub(crate) use message::{Content, ContentDiscriminants, Message, SignedMessage, SyncRequest};
 
 /// The timer for syncing with a random peer.
 const TIMER_ID_SYNC_PEER: TimerId = TimerId(0);
@@ -135,6 +141,20 @@ where
 {
     idx: ValidatorIndex,
     secret: C::ValidatorSecret,
+    /// Messages we requested a signature for from the remote signer, by hash.
+    pending_signatures: BTreeMap<C::Hash, PendingMessage<C>>,
+}
+
+/// An echo or vote waiting to be signed by the remote signer.
+#[derive(DataSize)]
+struct PendingMessage<C>
+where
+    C: Context,
+{
+    round_id: RoundId,
+    content: Content<C>,
+    /// Our own new proposal, if this is its echo.
+    proposal: Option<HashedProposal<C>>,
 }
 
 impl<C: Context> Debug for ActiveValidator<C> {
@@ -198,7 +218,7 @@ where
     /// `update`.
     next_scheduled_update: Timestamp,
     /// The write-ahead log to prevent honest nodes from double-signing upon restart.
-    write_wal: Option<WriteWal<C>>,
+    write_wal: Option<WriteWal<Entry<C>>>,
     /// The rewards based on the finalized rounds so far.
     rewards: BTreeMap<C::ValidatorId, u64>,
 }
@@ -626,47 +646,126 @@ impl<C: Context + 'static> Zug<C> {
         self.leader_sequence.leader(u64::from(round_id))
     }
 
+    /// Signs, records and gossips our own message, like `create_and_gossip_message`. If
+    /// `proposal` is given, `content` is its echo, and the proposal is gossiped with it.
+    ///
+    /// If the key is held by a remote signer, the signature is requested instead, and the message
+    /// is created in `handle_signature`.
     fn create_message(
         &mut self,
         round_id: RoundId,
         content: Content<C>,
-    ) -> Option<SignedMessage<C>> {
-        let (validator_idx, secret_key) = if let Some(active_validator) = &self.active_validator {
-            (active_validator.idx, &active_validator.secret)
+        proposal: Option<HashedProposal<C>>,
+    ) -> ProtocolOutcomes<C> {
+        let validator_idx = if let Some(active_validator) = &self.active_validator {
+            active_validator.idx
         } else {
-            return None;
+            return vec![];
         };
         if self.paused {
-            return None;
+            return vec![];
         }
         let already_signed = match &content {
             Content::Echo(_) => self.has_echoed(round_id, validator_idx),
             Content::Vote(_) => self.has_voted(round_id, validator_idx),
         };
-        if already_signed {
-            return None;
+        if already_signed || self.is_signature_pending(round_id, &content) {
+            return vec![];
         }
-        let signed_msg = SignedMessage::sign_new(
-            round_id,
-            *self.instance_id(),
-            content,
-            validator_idx,
-            secret_key,
-        );
-        // We only return the new message if we are able to record it. If that fails we
+        let instance_id = *self.instance_id();
+        let (hash, data) =
+            SignedMessage::signed_data(round_id, &instance_id, &content, validator_idx);
+        let active_validator = match self.active_validator.as_mut() {
+            Some(active_validator) => active_validator,
+            None => return vec![],
+        };
+        match active_validator.secret.sign(&hash, data) {
+            Signing::Signed(signature) => {
+                let signed_msg = SignedMessage {
+                    round_id,
+                    instance_id,
+                    content,
+                    validator_idx,
+                    signature,
+                };
+                self.add_own_message(signed_msg, proposal)
+            }
+            Signing::Refused => vec![],
+            Signing::Deferred(data) => {
+                let pending = PendingMessage {
+                    round_id,
+                    content,
+                    proposal,
+                };
+                active_validator.pending_signatures.insert(hash, pending);
+                vec![ProtocolOutcome::RequestSignature(hash, data)]
+            }
+        }
+    }
+
+    /// Returns whether we are waiting for the remote signer to sign a message of the same kind as
+    /// `content` in that round.
+    fn is_signature_pending(&self, round_id: RoundId, content: &Content<C>) -> bool {
+        self.active_validator
+            .as_ref()
+            .map_or(false, |active_validator| {
+                active_validator.pending_signatures.values().any(|pending| {
+                    pending.round_id == round_id
+                        && ContentDiscriminants::from(&pending.content)
+                            == ContentDiscriminants::from(content)
+                })
+            })
+    }
+
+    /// Records our own new message and adds it to the protocol state, and gossips it together
+    /// with the proposal it echoes, if any.
+    fn add_own_message(
+        &mut self,
+        signed_msg: SignedMessage<C>,
+        proposal: Option<HashedProposal<C>>,
+    ) -> ProtocolOutcomes<C> {
+        let round_id = signed_msg.round_id;
+        // We only gossip the new message if we are able to record it. If that fails we
         // wouldn't know about our own message after a restart and risk double-signing.
-        if self.record_entry(&Entry::SignedMessage(signed_msg.clone()))
-            && self.add_content(signed_msg.clone())
+        if !self.record_entry(&Entry::SignedMessage(signed_msg.clone()))
+            || !self.add_content(signed_msg.clone())
         {
-            Some(signed_msg)
-        } else {
             debug!(
                 our_idx = self.our_idx(),
                 %round_id,
-                ?content,
+                content = ?signed_msg.content,
                 "couldn't record a signed message in the WAL or add it to the protocol state"
             );
-            None
+            return vec![];
+        }
+        let hashed_prop = match proposal {
+            None => {
+                let message = Message::Signed(signed_msg);
+                return vec![ProtocolOutcome::CreatedGossipMessage(
+                    SerializedMessage::from_message(&message),
+                )];
+            }
+            Some(hashed_prop) => hashed_prop,
+        };
+        let prop_msg = Message::Proposal {
+            round_id,
+            proposal: hashed_prop.inner().clone(),
+            instance_id: *self.instance_id(),
+            echo: signed_msg,
+        };
+        if !self.record_entry(&Entry::Proposal(hashed_prop.inner().clone(), round_id)) {
+            error!(
+                our_idx = self.our_idx(),
+                "could not record own proposal in WAL"
+            );
+            vec![]
+        } else if self.round_mut(round_id).insert_proposal(hashed_prop) {
+            self.mark_dirty(round_id);
+            vec![ProtocolOutcome::CreatedGossipMessage(
+                SerializedMessage::from_message(&prop_msg),
+            )]
+        } else {
+            vec![]
         }
     }
 
@@ -679,14 +778,7 @@ impl<C: Context + 'static> Zug<C> {
         round_id: RoundId,
         content: Content<C>,
     ) -> ProtocolOutcomes<C> {
-        let maybe_signed_msg = self.create_message(round_id, content);
-        maybe_signed_msg
-            .into_iter()
-            .map(|signed_msg| {
-                let message = Message::Signed(signed_msg);
-                ProtocolOutcome::CreatedGossipMessage(SerializedMessage::from_message(&message))
-            })
-            .collect()
+        self.create_message(round_id, content, None)
     }
 
     /// When we receive evidence for a fault, we must notify the rest of the network of this
@@ -1316,7 +1408,7 @@ impl<C: Context + 'static> Zug<C> {
     pub(crate) fn open_wal(&mut self, wal_file: PathBuf, now: Timestamp) -> ProtocolOutcomes<C> {
         let our_idx = self.our_idx();
         // Open the file for reading.
-        let mut read_wal = match ReadWal::<C>::new(&wal_file) {
+        let mut read_wal = match ReadWal::<Entry<C>>::new(&wal_file) {
             Ok(read_wal) => read_wal,
             Err(err) => {
                 error!(our_idx, %err, "could not create a ReadWal using this file");
@@ -1427,6 +1519,7 @@ impl<C: Context + 'static> Zug<C> {
                             | ProtocolOutcome::ScheduleTimer(_, _)
                             | ProtocolOutcome::QueueAction(_)
                             | ProtocolOutcome::CreateNewBlock(_, _)
+                            | ProtocolOutcome::RequestSignature(_, _)
                             | ProtocolOutcome::DoppelgangerDetected
                             | ProtocolOutcome::Disconnect(_) => false,
                         }));
@@ -1899,34 +1992,9 @@ impl<C: Context + 'static> Zug<C> {
     /// Creates a new proposal message in the current round, and a corresponding signed echo,
     /// inserts them into our protocol state and gossips them.
     fn create_echo_and_proposal(&mut self, proposal: Proposal<C>) -> ProtocolOutcomes<C> {
-        let round_id = self.current_round;
-        let hashed_prop = HashedProposal::new(proposal.clone());
+        let hashed_prop = HashedProposal::new(proposal);
         let echo_content = Content::Echo(*hashed_prop.hash());
-        let echo = if let Some(echo) = self.create_message(round_id, echo_content) {
-            echo
-        } else {
-            return vec![];
-        };
-        let prop_msg = Message::Proposal {
-            round_id,
-            proposal,
-            instance_id: *self.instance_id(),
-            echo,
-        };
-        if !self.record_entry(&Entry::Proposal(hashed_prop.inner().clone(), round_id)) {
-            error!(
-                our_idx = self.our_idx(),
-                "could not record own proposal in WAL"
-            );
-            vec![]
-        } else if self.round_mut(round_id).insert_proposal(hashed_prop) {
-            self.mark_dirty(round_id);
-            vec![ProtocolOutcome::CreatedGossipMessage(
-                SerializedMessage::from_message(&prop_msg),
-            )]
-        } else {
-            vec![]
-        }
+        self.create_message(self.current_round, echo_content, Some(hashed_prop))
     }
 
     /// Returns a parent if a block with that parent could be proposed in the current round, and the
@@ -2232,6 +2300,41 @@ where
         vec![]
     }
 
+    fn handle_signature(
+        &mut self,
+        hash: C::Hash,
+        signature: Option<C::Signature>,
+        now: Timestamp,
+    ) -> ProtocolOutcomes<C> {
+        let (validator_idx, pending) = match self.active_validator.as_mut() {
+            Some(active_validator) => match active_validator.pending_signatures.remove(&hash) {
+                Some(pending) => (active_validator.idx, pending),
+                None => {
+                    warn!(%hash, "received a signature we didn't request");
+                    return vec![];
+                }
+            },
+            None => {
+                debug!(%hash, "ignoring signature: validator has been deactivated");
+                return vec![];
+            }
+        };
+        let signature = match signature {
+            Some(signature) => signature,
+            None => return vec![],
+        };
+        let signed_msg = SignedMessage {
+            round_id: pending.round_id,
+            instance_id: *self.instance_id(),
+            content: pending.content,
+            validator_idx,
+            signature,
+        };
+        let mut outcomes = self.add_own_message(signed_msg, pending.proposal);
+        outcomes.extend(self.update(now));
+        outcomes
+    }
+
     fn propose(&mut self, proposed_block: ProposedBlock<C>, now: Timestamp) -> ProtocolOutcomes<C> {
         let maybe_parent_round_id = if let Some((block_context, round_id, maybe_parent_round_id)) =
             self.pending_proposal.take()
@@ -2324,7 +2427,11 @@ where
                 return outcomes;
             }
             info!(our_idx = idx.0, "start voting");
-            self.active_validator = Some(ActiveValidator { idx, secret });
+            self.active_validator = Some(ActiveValidator {
+                idx,
+                secret,
+                pending_signatures: BTreeMap::new(),
+            });
             debug!(
                 our_idx = idx.0,
                 %now,
@@ -2382,6 +2489,16 @@ where
             .unwrap_or_default()
     }
 
+    fn equivocation(&self, vid: &C::ValidatorId) -> Option<Equivocation<C>> {
+        let idx = self.validators.get_index(vid)?;
+        match self.faults.get(&idx)? {
+            Fault::Direct(msg, content, sign) => {
+                Some(Equivocation::zug(msg.clone(), *content, *sign))
+            }
+            Fault::Banned | Fault::Indirect => None,
+        }
+    }
+
     fn set_paused(&mut self, paused: bool, now: Timestamp) -> ProtocolOutcomes<C> {
         if self.paused && !paused {
             info!(
@@ -2430,6 +2547,31 @@ where
     fn next_round_length(&self) -> Option<TimeDiff> {
         Some(self.params.min_block_time())
     }
+
+    fn protocol_status(&self, _now: Timestamp) -> ProtocolStatus<C> {
+        let upcoming_leaders = (0..UPCOMING_LEADERS)
+            .filter_map(|i| {
+                let round_id = self.current_round.checked_add(i)?;
+                let v_id = self.validators.id(self.leader(round_id))?;
+                Some((u64::from(round_id), v_id.clone()))
+            })
+            .collect();
+        let participation = self
+            .validators
+            .enumerate_ids()
+            .map(|(idx, v_id)| {
+                let status = ParticipationStatus::for_index(idx, self)
+                    .map_or(consensus::ParticipationStatus::Active, Into::into);
+                (v_id.clone(), status)
+            })
+            .collect();
+        ProtocolStatus {
+            current_round: Some(u64::from(self.current_round)),
+            round_length: self.next_round_length(),
+            upcoming_leaders,
+            participation,
+        }
+    }
 }
 
 mod specimen_support {
@@ -2556,6 +2698,7 @@ mod specimen_support {
                 LargestSpecimen::largest_specimen(estimator, cache),
                 &LargestSpecimen::largest_specimen(estimator, cache),
             )
+            .expect("should sign with local key")
         }
     }
 
@@ -8,7 +8,7 @@ use either::Either;
 use crate::{
     components::consensus::{
         protocols::zug::{Proposal, RoundId},
-        traits::{ConsensusNetworkMessage, Context, ValidatorSecret},
+        traits::{ConsensusNetworkMessage, Context, SignedData, ValidatorSecret},
         utils::ValidatorIndex,
     },
     utils::ds,
@@ -128,22 +128,36 @@ where
 }
 
 impl<C: Context> SignedMessage<C> {
-    /// Creates a new signed message with a valid signature.
+    /// Creates a new signed message with a valid signature. Returns `None` if the signer refused or
+    /// works asynchronously.
     pub(crate) fn sign_new(
         round_id: RoundId,
         instance_id: C::InstanceId,
         content: Content<C>,
         validator_idx: ValidatorIndex,
         secret: &C::ValidatorSecret,
-    ) -> SignedMessage<C> {
-        let hash = Self::hash_fields(round_id, &instance_id, &content, validator_idx);
-        SignedMessage {
+    ) -> Option<SignedMessage<C>> {
+        let (hash, data) = Self::signed_data(round_id, &instance_id, &content, validator_idx);
+        let signature = secret.sign(&hash, data).signed()?;
+        Some(SignedMessage {
             round_id,
             instance_id,
             content,
             validator_idx,
-            signature: secret.sign(&hash),
-        }
+            signature,
+        })
+    }
+
+    /// Returns the hash of a message with the given fields, and the data to sign.
+    pub(crate) fn signed_data(
+        round_id: RoundId,
+        instance_id: &C::InstanceId,
+        content: &Content<C>,
+        validator_idx: ValidatorIndex,
+    ) -> (C::Hash, SignedData) {
+        let bytes = Self::serialize_fields(round_id, instance_id, content, validator_idx);
+        let hash = <C as Context>::hash(&bytes);
+        (hash, SignedData::ZugMessage(bytes))
     }
 
     /// Creates a new signed message with the alternative content and signature.
@@ -155,6 +169,21 @@ impl<C: Context> SignedMessage<C> {
         }
     }
 
+    /// Returns the ID of the protocol instance this message belongs to.
+    pub(crate) fn instance_id(&self) -> &C::InstanceId {
+        &self.instance_id
+    }
+
+    /// Returns the signed echo or vote.
+    pub(crate) fn content(&self) -> &Content<C> {
+        &self.content
+    }
+
+    /// Returns the validator's signature.
+    pub(crate) fn signature(&self) -> &C::Signature {
+        &self.signature
+    }
+
     /// Returns whether the signature is valid.
     pub(crate) fn verify_signature(&self, validator_id: &C::ValidatorId) -> bool {
         let hash = Self::hash_fields(
@@ -173,10 +202,23 @@ impl<C: Context> SignedMessage<C> {
         content: &Content<C>,
         validator_idx: ValidatorIndex,
     ) -> C::Hash {
-        let serialized_fields =
-            bincode::serialize(&(round_id, instance_id, content, validator_idx))
-                .expect("failed to serialize fields");
-        <C as Context>::hash(&serialized_fields)
+        <C as Context>::hash(&Self::serialize_fields(
+            round_id,
+            instance_id,
+            content,
+            validator_idx,
+        ))
+    }
+
+    /// Serializes all fields except the signature, for computing the hash.
+    fn serialize_fields(
+        round_id: RoundId,
+        instance_id: &C::InstanceId,
+        content: &Content<C>,
+        validator_idx: ValidatorIndex,
+    ) -> Vec<u8> {
+        bincode::serialize(&(round_id, instance_id, content, validator_idx))
+            .expect("failed to serialize fields")
     }
 }
 
@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
 use crate::components::consensus::{
+    self,
     protocols::zug::{Fault, RoundId, Zug},
     traits::Context,
     utils::ValidatorIndex,
@@ -58,3 +59,18 @@ impl ParticipationStatus {
         Some(ParticipationStatus::Inactive)
     }
 }
+
+impl From<ParticipationStatus> for consensus::ParticipationStatus {
+    fn from(status: ParticipationStatus) -> Self {
+        match status {
+            ParticipationStatus::LastSeenInRound(r_id) => {
+                consensus::ParticipationStatus::LastSeenInRound(r_id.into())
+            }
+            ParticipationStatus::Inactive => consensus::ParticipationStatus::Inactive,
+            ParticipationStatus::EquivocatedInOtherEra => {
+                consensus::ParticipationStatus::EquivocatedInOtherEra
+            }
+            ParticipationStatus::Equivocated => consensus::ParticipationStatus::Equivocated,
+        }
+    }
+}
@@ -77,7 +77,7 @@ fn create_signed_message(
 ) -> SignedMessage<ClContext> {
     let validator_idx = validators.get_index(keypair.public_key()).unwrap();
     let instance_id = ClContext::hash(INSTANCE_ID_DATA);
-    SignedMessage::sign_new(round_id, instance_id, content, validator_idx, keypair)
+    SignedMessage::sign_new(round_id, instance_id, content, validator_idx, keypair).unwrap()
 }
 
 /// Creates a `Message::Signed`.
@@ -1037,3 +1037,135 @@ fn update_proposal_timeout() {
         );
     }
 }
+
+/// Tests that the status summary reports the upcoming leaders and which validators are active,
+/// inactive or faulty.
+#[test]
+fn zug_protocol_status() {
+    let mut rng = crate::new_rng();
+    let (weights, validators) = abc_weights(50, 20, 30);
+    let alice_idx = validators.get_index(&*ALICE_PUBLIC_KEY).unwrap();
+    let bob_idx = validators.get_index(&*BOB_PUBLIC_KEY).unwrap();
+    let carol_idx = validators.get_index(&*CAROL_PUBLIC_KEY).unwrap();
+
+    // The first round leaders are Carol, Alice, Bob.
+    let mut zug = new_test_zug(weights, vec![], &[carol_idx, alice_idx, bob_idx]);
+
+    let alice_kp = Keypair::from(ALICE_SECRET_KEY.clone());
+    let bob_kp = Keypair::from(BOB_SECRET_KEY.clone());
+
+    let sender = *ALICE_NODE_ID;
+    let timestamp = Timestamp::now();
+
+    // Alice votes in round 0, and Bob equivocates. We never hear from Carol.
+    let msg = create_message(&validators, 0, vote(false), &alice_kp);
+    expect_no_gossip_block_finalized(zug.handle_message(&mut rng, sender, msg, timestamp));
+    let msg = create_message(&validators, 0, vote(true), &bob_kp);
+    expect_no_gossip_block_finalized(zug.handle_message(&mut rng, sender, msg, timestamp));
+    let msg = create_message(&validators, 0, vote(false), &bob_kp);
+    zug.handle_message(&mut rng, sender, msg, timestamp);
+
+    // Since Bob counts towards every quorum, round 0 is skippable and we move on to round 1.
+    let status = zug.protocol_status(timestamp);
+    assert_eq!(Some(1), status.current_round);
+    assert_eq!(Some(zug.params.min_block_time()), status.round_length);
+    assert_eq!(UPCOMING_LEADERS as usize, status.upcoming_leaders.len());
+    assert_eq!(
+        [(1, ALICE_PUBLIC_KEY.clone()), (2, BOB_PUBLIC_KEY.clone())],
+        status.upcoming_leaders[..2]
+    );
+    let participation: BTreeMap<_, _> = status.participation.into_iter().collect();
+    assert_eq!(
+        Some(&consensus::ParticipationStatus::Active),
+        participation.get(&*ALICE_PUBLIC_KEY)
+    );
+    assert_eq!(
+        Some(&consensus::ParticipationStatus::Equivocated),
+        participation.get(&*BOB_PUBLIC_KEY)
+    );
+    assert_eq!(
+        Some(&consensus::ParticipationStatus::Inactive),
+        participation.get(&*CAROL_PUBLIC_KEY)
+    );
+}
+
+#[test]
+fn zug_resumes_after_remote_signature() {
+    use std::{os::unix::net::UnixListener, thread};
+
+    use crate::{
+        components::consensus::cl_context,
+        signer::{daemon::SignerDaemon, RemoteSignerConfig, Signer},
+    };
+
+    testing::init_logging();
+    let mut rng = crate::new_rng();
+    let (weights, validators) = abc_weights(60, 30, 10);
+    let bob_idx = validators.get_index(&*BOB_PUBLIC_KEY).unwrap();
+    let carol_idx = validators.get_index(&*CAROL_PUBLIC_KEY).unwrap();
+    let sender = *ALICE_NODE_ID;
+
+    // Carol's key is held by a signer process.
+    let dir = tempdir().unwrap();
+    let socket_path = dir.path().join("signer.socket");
+    let carol_secret_key = SecretKey::ed25519_from_bytes([2; SecretKey::ED25519_LENGTH]).unwrap();
+    let daemon = Arc::new(
+        SignerDaemon::new(
+            carol_secret_key,
+            dir.path().join("slashing_protection.json"),
+        )
+        .unwrap(),
+    );
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    thread::spawn(move || daemon.serve(listener));
+    let signer = Signer::remote(&RemoteSignerConfig {
+        socket_path,
+        request_timeout: "5 seconds".parse().unwrap(),
+    })
+    .unwrap();
+    assert_eq!(*signer.public_key(), *CAROL_PUBLIC_KEY);
+
+    let mut timestamp = Timestamp::from(100000);
+    let mut sc_c = new_test_zug(weights, vec![], &[bob_idx]);
+    sc_c.open_wal(dir.path().join("wal"), timestamp);
+    sc_c.activate_validator(
+        CAROL_PUBLIC_KEY.clone(),
+        Keypair::new(signer.clone()),
+        Timestamp::now(),
+        None,
+    );
+
+    let proposal0 = Proposal::<ClContext> {
+        timestamp,
+        maybe_block: Some(new_payload(false)),
+        maybe_parent_round_id: None,
+        inactive: None,
+    };
+    let hash0 = proposal0.hash();
+    timestamp += sc_c.params.min_block_time();
+
+    // Carol wants to echo Bob's proposal, but has to wait for the signer.
+    let bob_kp = Keypair::from(BOB_SECRET_KEY.clone());
+    let msg = create_proposal_message(0, &proposal0, &validators, &bob_kp);
+    let mut outcomes = sc_c.handle_message(&mut rng, sender, msg.clone(), timestamp);
+    assert!(remove_gossip(&validators, &mut outcomes).is_empty());
+    let mut requests = outcomes.into_iter().filter_map(|outcome| match outcome {
+        ProtocolOutcome::RequestSignature(hash, data) => Some((hash, data)),
+        _ => None,
+    });
+    let (hash, data) = requests.next().expect("expected a signature request");
+    assert!(requests.next().is_none());
+
+    // While the signature is pending, she doesn't request another one for the same echo.
+    let outcomes = sc_c.handle_message(&mut rng, sender, msg, timestamp);
+    assert!(!outcomes
+        .iter()
+        .any(|outcome| matches!(outcome, ProtocolOutcome::RequestSignature(..))));
+
+    // Once it arrives, she records and gossips the echo.
+    let signature = signer.sign(cl_context::sign_request(data)).unwrap();
+    let mut outcomes = sc_c.handle_signature(hash, Some(signature), timestamp);
+    let mut gossip = remove_gossip(&validators, &mut outcomes);
+    assert!(remove_signed(&mut gossip, 0, carol_idx, echo(hash0)));
+    assert!(gossip.is_empty(), "unexpected gossip: {:?}", gossip);
+}
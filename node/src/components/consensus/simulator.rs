@@ -0,0 +1,782 @@
+//! A deterministic simulator of a network of validators running consensus.
+//!
+//! All validators run in a single thread and are connected by a simulated network with
+//! configurable latency, message loss and partitions. Time is simulated as well, so the outcome
+//! only depends on the chainspec and the [`SimulationConfig`], including its seed. This allows
+//! evaluating chainspec parameters such as the era duration or block time before launching a
+//! network.
+
+mod config;
+mod report;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    io,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use rand::{seq::IteratorRandom, Rng, SeedableRng};
+use tempfile::TempDir;
+use thiserror::Error;
+use tracing::debug;
+
+use casper_hashing::Digest;
+use casper_types::{EraId, PublicKey, SecretKey, TimeDiff, Timestamp, U512};
+
+use crate::{
+    components::consensus::{
+        cl_context::{ClContext, Keypair},
+        consensus_protocol::{
+            ConsensusProtocol, FinalizedBlock, ProtocolOutcome, ProtocolOutcomes,
+        },
+        era_supervisor::{self, SerializedMessage},
+        protocols::{highway::HighwayProtocol, zug::Zug},
+        ActionId, BlockContext, Config, ProposedBlock, TimerId,
+    },
+    tls::KeyFingerprint,
+    types::{chainspec::ConsensusProtocolName, BlockHash, BlockPayload, Chainspec, NodeId},
+    NodeRng,
+};
+
+pub use config::{
+    Behavior, LatencyDistribution, NetworkConfig, PartitionConfig, SimulationConfig,
+    ValidatorConfig,
+};
+pub use report::{EraReport, FaultReport, LatencyStats, MessageStats, SimulationReport};
+
+/// An error setting up a simulation.
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    /// No validators are configured.
+    #[error("no validators configured")]
+    NoValidators,
+    /// The number of eras to simulate is zero.
+    #[error("the number of eras must be positive")]
+    NoEras,
+    /// A validator has zero weight.
+    #[error("validator {0} has zero weight")]
+    ZeroWeight(usize),
+    /// The message loss rate is not a probability.
+    #[error("the loss rate must be at least 0 and less than 1, got {0}")]
+    InvalidLossRate(f64),
+    /// The latency distribution's parameters are inconsistent.
+    #[error("invalid latency distribution: {0}")]
+    InvalidLatency(&'static str),
+    /// A network partition is invalid.
+    #[error("invalid partition: it {0}")]
+    InvalidPartition(String),
+    /// Could not create the directory for the simulated validators' write-ahead logs.
+    #[error("could not create WAL directory: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Runs the simulation and returns its results.
+pub fn simulate(
+    chainspec: Chainspec,
+    config: SimulationConfig,
+) -> Result<SimulationReport, SimulationError> {
+    Ok(Simulation::new(chainspec, config)?.run())
+}
+
+/// A message, timer or other input to one of a node's protocol instances.
+enum EventKind {
+    /// A network message from another node.
+    Message {
+        from: usize,
+        request: bool,
+        msg: SerializedMessage,
+    },
+    /// A timer scheduled by the protocol.
+    Timer {
+        timestamp: Timestamp,
+        timer_id: TimerId,
+    },
+    /// An action queued by the protocol.
+    Action(ActionId),
+    /// It is the node's turn to propose a block.
+    Propose(BlockContext<ClContext>),
+    /// A proposed block needs to be validated.
+    Validate(ProposedBlock<ClContext>),
+}
+
+struct Event {
+    node: usize,
+    era: u64,
+    kind: EventKind,
+}
+
+/// A node's protocol instance for one era.
+struct NodeEra {
+    consensus: Box<dyn ConsensusProtocol<ClContext>>,
+    start_time: Timestamp,
+    /// The validators reported as equivocators in the era's finalized blocks.
+    equivocators: BTreeSet<PublicKey>,
+    /// Whether the era's switch block has been finalized.
+    finished: bool,
+}
+
+/// A simulated node. Every validator runs one node, except equivocating ones, which run two.
+struct Node {
+    /// The index of the node's validator.
+    validator: usize,
+    node_id: NodeId,
+    eras: BTreeMap<u64, NodeEra>,
+    /// Messages received for eras the node hasn't started yet.
+    pending: BTreeMap<u64, Vec<(usize, bool, SerializedMessage)>>,
+}
+
+struct Simulation {
+    config: SimulationConfig,
+    chainspec: Chainspec,
+    consensus_config: Config,
+    rng: NodeRng,
+    secret_keys: Vec<Arc<SecretKey>>,
+    public_keys: Vec<PublicKey>,
+    validator_indices: HashMap<PublicKey, usize>,
+    validator_weights: BTreeMap<PublicKey, U512>,
+    nodes: Vec<Node>,
+    node_indices: HashMap<NodeId, usize>,
+    /// The scheduled events, by time and order of creation.
+    queue: BTreeMap<(Timestamp, u64), Event>,
+    next_event: u64,
+    start_time: Timestamp,
+    now: Timestamp,
+    wal_dir: TempDir,
+    eras: BTreeMap<u64, EraReport>,
+    latencies: Vec<u64>,
+    /// For each validator, the time at which each honest node found evidence against it.
+    detections: BTreeMap<usize, BTreeMap<usize, Timestamp>>,
+    false_accusations: u64,
+    ftt_exceeded: u64,
+    messages: MessageStats,
+}
+
+impl Simulation {
+    fn new(mut chainspec: Chainspec, config: SimulationConfig) -> Result<Self, SimulationError> {
+        config.validate()?;
+        if let Some(protocol) = config.protocol {
+            chainspec.core_config.consensus_protocol = protocol;
+        }
+        let mut rng = NodeRng::seed_from_u64(config.seed);
+        let secret_keys: Vec<_> = config
+            .validators
+            .iter()
+            .map(|_| {
+                let secret_key = SecretKey::ed25519_from_bytes(rng.gen::<[u8; 32]>())
+                    .expect("any 32 bytes should be a valid Ed25519 secret key");
+                Arc::new(secret_key)
+            })
+            .collect();
+        let public_keys: Vec<_> = secret_keys
+            .iter()
+            .map(|secret_key| PublicKey::from(secret_key.as_ref()))
+            .collect();
+        let validator_indices = public_keys
+            .iter()
+            .enumerate()
+            .map(|(idx, public_key)| (public_key.clone(), idx))
+            .collect();
+        let validator_weights = public_keys
+            .iter()
+            .zip(&config.validators)
+            .map(|(public_key, validator)| (public_key.clone(), U512::from(validator.weight)))
+            .collect();
+        let nodes: Vec<_> = config
+            .validators
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, validator)| {
+                let count = match validator.behavior {
+                    Behavior::Equivocate => 2,
+                    Behavior::Honest | Behavior::Silent { .. } => 1,
+                };
+                itertools::repeat_n(idx, count)
+            })
+            .enumerate()
+            .map(|(node_idx, validator)| {
+                let mut id_bytes = [0; KeyFingerprint::LENGTH];
+                id_bytes[..8].copy_from_slice(&(node_idx as u64).to_le_bytes());
+                Node {
+                    validator,
+                    node_id: NodeId::from(KeyFingerprint::from(id_bytes)),
+                    eras: BTreeMap::new(),
+                    pending: BTreeMap::new(),
+                }
+            })
+            .collect();
+        let node_indices = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (node.node_id, idx))
+            .collect();
+        let start_time = Timestamp::zero();
+        Ok(Simulation {
+            config,
+            chainspec,
+            consensus_config: Config::default(),
+            rng,
+            secret_keys,
+            public_keys,
+            validator_indices,
+            validator_weights,
+            nodes,
+            node_indices,
+            queue: BTreeMap::new(),
+            next_event: 0,
+            start_time,
+            now: start_time,
+            wal_dir: tempfile::tempdir()?,
+            eras: BTreeMap::new(),
+            latencies: Vec::new(),
+            detections: BTreeMap::new(),
+            false_accusations: 0,
+            ftt_exceeded: 0,
+            messages: MessageStats::default(),
+        })
+    }
+
+    /// Runs the simulation until all honest validators have finished the last era, or the
+    /// maximum duration has passed.
+    fn run(mut self) -> SimulationReport {
+        for node in 0..self.nodes.len() {
+            self.start_era(node, 0, self.start_time, HashSet::new(), HashSet::new());
+        }
+        let end_time = self.start_time.saturating_add(self.config.max_duration);
+        while !self.is_complete() {
+            let ((time, _), event) = match self.queue.pop_first() {
+                Some(entry) => entry,
+                None => break,
+            };
+            if time > end_time {
+                break;
+            }
+            self.now = time;
+            self.handle_event(event);
+        }
+        self.report()
+    }
+
+    /// Returns whether all honest validators have finished the last era.
+    fn is_complete(&self) -> bool {
+        let last_era = self.config.eras.saturating_sub(1);
+        self.nodes
+            .iter()
+            .filter(|node| self.is_honest(node.validator))
+            .all(|node| node.eras.get(&last_era).map_or(false, |era| era.finished))
+    }
+
+    fn is_honest(&self, validator: usize) -> bool {
+        self.config.validators[validator].behavior == Behavior::Honest
+    }
+
+    fn elapsed(&self) -> TimeDiff {
+        self.now.saturating_diff(self.start_time)
+    }
+
+    fn schedule(&mut self, time: Timestamp, node: usize, era: u64, kind: EventKind) {
+        let time = time.max(self.now);
+        self.queue
+            .insert((time, self.next_event), Event { node, era, kind });
+        self.next_event = self.next_event.saturating_add(1);
+    }
+
+    /// Creates the node's protocol instance for the given era, and activates it as a validator.
+    fn start_era(
+        &mut self,
+        node: usize,
+        era: u64,
+        start_time: Timestamp,
+        inactive: HashSet<PublicKey>,
+        faulty: HashSet<PublicKey>,
+    ) {
+        // All nodes must agree on the instance ID and leader sequence; they are derived from the
+        // seed instead of actual blocks.
+        let key_block_hash = BlockHash::new(Digest::hash(
+            [self.config.seed.to_le_bytes(), era.to_le_bytes()].concat(),
+        ));
+        let instance_id =
+            era_supervisor::instance_id(self.chainspec.hash(), EraId::new(era), key_block_hash);
+        let mut seed_bytes = [0; 8];
+        seed_bytes.copy_from_slice(&key_block_hash.inner().value()[..8]);
+        let seed = u64::from_le_bytes(seed_bytes);
+        let wal_file = self.wal_file(node, era);
+
+        let validator = self.nodes[node].validator;
+        let prev_era = era
+            .checked_sub(1)
+            .and_then(|prev_era| self.nodes[node].eras.get(&prev_era));
+        let prev_cp = prev_era.map(|prev_era| &*prev_era.consensus);
+        let validators = self.validator_weights.clone();
+        let (mut consensus, mut outcomes) = match self.chainspec.core_config.consensus_protocol {
+            ConsensusProtocolName::Highway => HighwayProtocol::new_boxed(
+                instance_id,
+                validators,
+                &faulty,
+                &inactive,
+                &self.chainspec,
+                &self.consensus_config,
+                prev_cp,
+                start_time,
+                seed,
+                self.now,
+            ),
+            ConsensusProtocolName::Zug => Zug::new_boxed(
+                instance_id,
+                validators,
+                &faulty,
+                &inactive,
+                &self.chainspec,
+                &self.consensus_config,
+                prev_cp,
+                start_time,
+                seed,
+                self.now,
+                wal_file.clone(),
+            ),
+        };
+        let validators_with_evidence: Vec<PublicKey> = prev_era
+            .into_iter()
+            .flat_map(|prev_era| prev_era.consensus.validators_with_evidence())
+            .cloned()
+            .collect();
+        for public_key in &validators_with_evidence {
+            consensus.mark_faulty(public_key);
+        }
+        outcomes.extend(consensus.handle_is_current(self.now));
+        outcomes.extend(consensus.activate_validator(
+            self.public_keys[validator].clone(),
+            Keypair::from(Arc::clone(&self.secret_keys[validator])),
+            self.now,
+            Some(wal_file),
+        ));
+        let node_era = NodeEra {
+            consensus,
+            start_time,
+            equivocators: BTreeSet::new(),
+            finished: false,
+        };
+        self.nodes[node].eras.insert(era, node_era);
+        self.handle_outcomes(node, era, outcomes);
+
+        let pending = self.nodes[node].pending.remove(&era).unwrap_or_default();
+        for (from, request, msg) in pending {
+            let kind = EventKind::Message { from, request, msg };
+            self.schedule(self.now, node, era, kind);
+        }
+    }
+
+    fn wal_file(&self, node: usize, era: u64) -> PathBuf {
+        self.wal_dir
+            .path()
+            .join(format!("node_{}_era_{}.wal", node, era))
+    }
+
+    fn handle_event(&mut self, Event { node, era, kind }: Event) {
+        let now = self.now;
+        let sender_id = match &kind {
+            EventKind::Message { from, .. } => Some(self.nodes[*from].node_id),
+            _ => None,
+        };
+        let rng = &mut self.rng;
+        let node_era = match self.nodes[node].eras.get_mut(&era) {
+            Some(node_era) => node_era,
+            None => {
+                if let EventKind::Message { from, request, msg } = kind {
+                    let pending = self.nodes[node].pending.entry(era).or_default();
+                    pending.push((from, request, msg));
+                }
+                return;
+            }
+        };
+        let consensus = &mut node_era.consensus;
+        let outcomes = match (kind, sender_id) {
+            (EventKind::Message { request, msg, .. }, Some(sender)) => {
+                if request {
+                    let (mut outcomes, response) =
+                        consensus.handle_request_message(rng, sender, msg, now);
+                    outcomes.extend(
+                        response.map(|msg| ProtocolOutcome::CreatedTargetedMessage(msg, sender)),
+                    );
+                    outcomes
+                } else {
+                    consensus.handle_message(rng, sender, msg, now)
+                }
+            }
+            (EventKind::Message { .. }, None) => vec![],
+            (
+                EventKind::Timer {
+                    timestamp,
+                    timer_id,
+                },
+                _,
+            ) => consensus.handle_timer(timestamp, now, timer_id, rng),
+            (EventKind::Action(action_id), _) => consensus.handle_action(action_id, now),
+            (EventKind::Propose(block_context), _) => {
+                let accusations = consensus
+                    .validators_with_evidence()
+                    .into_iter()
+                    .cloned()
+                    .collect();
+                let payload = BlockPayload::new(vec![], vec![], accusations, rng.gen());
+                let proposed_block = ProposedBlock::new(Arc::new(payload), block_context);
+                consensus.propose(proposed_block, now)
+            }
+            (EventKind::Validate(proposed_block), _) => {
+                consensus.resolve_validity(proposed_block, true, now)
+            }
+        };
+        self.handle_outcomes(node, era, outcomes);
+    }
+
+    fn handle_outcomes(&mut self, node: usize, era: u64, outcomes: ProtocolOutcomes<ClContext>) {
+        let mut outcomes = VecDeque::from(outcomes);
+        while let Some(outcome) = outcomes.pop_front() {
+            match outcome {
+                ProtocolOutcome::CreatedGossipMessage(msg) => {
+                    for peer in 0..self.nodes.len() {
+                        if peer != node {
+                            self.send(node, peer, era, false, msg.clone());
+                        }
+                    }
+                }
+                ProtocolOutcome::CreatedTargetedMessage(msg, target) => {
+                    if let Some(peer) = self.node_indices.get(&target).copied() {
+                        self.send(node, peer, era, false, msg);
+                    }
+                }
+                ProtocolOutcome::CreatedMessageToRandomPeer(msg) => {
+                    if let Some(peer) = self.random_peer(node) {
+                        self.send(node, peer, era, false, msg);
+                    }
+                }
+                ProtocolOutcome::CreatedRequestToRandomPeer(msg) => {
+                    if let Some(peer) = self.random_peer(node) {
+                        self.send(node, peer, era, true, msg);
+                    }
+                }
+                ProtocolOutcome::ScheduleTimer(timestamp, timer_id) => {
+                    let kind = EventKind::Timer {
+                        timestamp,
+                        timer_id,
+                    };
+                    self.schedule(timestamp, node, era, kind);
+                }
+                ProtocolOutcome::QueueAction(action_id) => {
+                    self.schedule(self.now, node, era, EventKind::Action(action_id));
+                }
+                ProtocolOutcome::CreateNewBlock(block_context, _expiry) => {
+                    let time = block_context.timestamp();
+                    self.schedule(time, node, era, EventKind::Propose(block_context));
+                }
+                ProtocolOutcome::FinalizedBlock(finalized_block) => {
+                    self.handle_finalized_block(node, era, finalized_block);
+                }
+                ProtocolOutcome::ValidateConsensusValue { proposed_block, .. } => {
+                    self.schedule(self.now, node, era, EventKind::Validate(proposed_block));
+                }
+                ProtocolOutcome::NewEvidence(public_key) => {
+                    self.record_evidence(node, &public_key);
+                }
+                ProtocolOutcome::SendEvidence(target, public_key) => {
+                    if let Some(node_era) = self.nodes[node].eras.get(&era) {
+                        outcomes.extend(node_era.consensus.send_evidence(target, &public_key));
+                    }
+                }
+                ProtocolOutcome::FttExceeded => {
+                    if self.is_honest(self.nodes[node].validator) {
+                        self.ftt_exceeded = self.ftt_exceeded.saturating_add(1);
+                    }
+                }
+                ProtocolOutcome::RequestSignature(..) => {
+                    unreachable!("simulated validators sign with local keys")
+                }
+                ProtocolOutcome::WeAreFaulty
+                | ProtocolOutcome::DoppelgangerDetected
+                | ProtocolOutcome::Disconnect(_)
+                | ProtocolOutcome::HandledProposedBlock(_) => {}
+            }
+        }
+    }
+
+    fn random_peer(&mut self, node: usize) -> Option<usize> {
+        (0..self.nodes.len())
+            .filter(|peer| *peer != node)
+            .choose(&mut self.rng)
+    }
+
+    /// Sends a message over the simulated network, unless the sender is silent, or the message is
+    /// lost or blocked by a partition.
+    fn send(&mut self, from: usize, to: usize, era: u64, request: bool, msg: SerializedMessage) {
+        let elapsed = self.elapsed();
+        let (from_validator, to_validator) = (self.nodes[from].validator, self.nodes[to].validator);
+        let stats = &mut self.messages;
+        if self.config.validators[from_validator]
+            .behavior
+            .is_silent(elapsed)
+        {
+            stats.suppressed = stats.suppressed.saturating_add(1);
+            return;
+        }
+        stats.sent = stats.sent.saturating_add(1);
+        let network = &self.config.network;
+        if network.is_partitioned(elapsed, from_validator, to_validator) {
+            stats.partitioned = stats.partitioned.saturating_add(1);
+            return;
+        }
+        if self.rng.gen_bool(network.loss_rate) {
+            stats.lost = stats.lost.saturating_add(1);
+            return;
+        }
+        let delivery_time = self
+            .now
+            .saturating_add(network.latency.sample(&mut self.rng));
+        let kind = EventKind::Message { from, request, msg };
+        self.schedule(delivery_time, to, era, kind);
+    }
+
+    fn handle_finalized_block(
+        &mut self,
+        node: usize,
+        era: u64,
+        finalized_block: FinalizedBlock<ClContext>,
+    ) {
+        let honest = self.is_honest(self.nodes[node].validator);
+        if honest {
+            let latency = self.now.saturating_diff(finalized_block.timestamp);
+            self.latencies.push(latency.millis());
+        }
+        let node_era = match self.nodes[node].eras.get_mut(&era) {
+            Some(node_era) => node_era,
+            None => return,
+        };
+        node_era
+            .equivocators
+            .extend(finalized_block.equivocators.iter().cloned());
+        node_era
+            .equivocators
+            .extend(finalized_block.value.accusations().iter().cloned());
+        let terminal_block_data = match finalized_block.terminal_block_data {
+            Some(terminal_block_data) => terminal_block_data,
+            None => return,
+        };
+        node_era.finished = true;
+        let era_start_time = node_era.start_time;
+        let equivocators: HashSet<PublicKey> = node_era.equivocators.iter().cloned().collect();
+        debug!(node, era, "finalized switch block");
+
+        if honest && !self.eras.contains_key(&era) {
+            let indices = |public_keys: &mut dyn Iterator<Item = &PublicKey>| {
+                let mut indices: Vec<usize> = public_keys
+                    .filter_map(|public_key| self.validator_indices.get(public_key).copied())
+                    .collect();
+                indices.sort_unstable();
+                indices
+            };
+            let era_report = EraReport {
+                era,
+                blocks: finalized_block.relative_height.saturating_add(1),
+                start_ms: era_start_time.saturating_diff(self.start_time).millis(),
+                switch_block_ms: finalized_block
+                    .timestamp
+                    .saturating_diff(self.start_time)
+                    .millis(),
+                finalized_ms: self.elapsed().millis(),
+                inactive_validators: indices(&mut terminal_block_data.inactive_validators.iter()),
+                equivocators: indices(&mut equivocators.iter()),
+            };
+            self.eras.insert(era, era_report);
+        }
+
+        let next_era = era.saturating_add(1);
+        if next_era < self.config.eras {
+            let inactive = terminal_block_data
+                .inactive_validators
+                .into_iter()
+                .collect();
+            self.start_era(
+                node,
+                next_era,
+                finalized_block.timestamp,
+                inactive,
+                equivocators,
+            );
+        }
+    }
+
+    /// Records that the node found evidence against the validator.
+    fn record_evidence(&mut self, node: usize, public_key: &PublicKey) {
+        if !self.is_honest(self.nodes[node].validator) {
+            return;
+        }
+        let accused = match self.validator_indices.get(public_key) {
+            Some(accused) => *accused,
+            None => return,
+        };
+        if self.is_honest(accused) {
+            self.false_accusations = self.false_accusations.saturating_add(1);
+        } else {
+            let now = self.now;
+            self.detections
+                .entry(accused)
+                .or_default()
+                .entry(node)
+                .or_insert(now);
+        }
+    }
+
+    fn report(self) -> SimulationReport {
+        let since_start =
+            |timestamp: &Timestamp| timestamp.saturating_diff(self.start_time).millis();
+        let faults = self
+            .config
+            .validators
+            .iter()
+            .enumerate()
+            .filter(|(_, validator)| validator.behavior != Behavior::Honest)
+            .map(|(idx, validator)| {
+                let detections = self.detections.get(&idx);
+                let times = || detections.into_iter().flat_map(BTreeMap::values);
+                FaultReport {
+                    validator: idx,
+                    behavior: validator.behavior,
+                    detected_by: detections.map_or(0, BTreeMap::len),
+                    first_detection_ms: times().min().map(since_start),
+                    last_detection_ms: times().max().map(since_start),
+                    reported_inactive_in_eras: self
+                        .eras
+                        .values()
+                        .filter(|era| era.inactive_validators.contains(&idx))
+                        .map(|era| era.era)
+                        .collect(),
+                }
+            })
+            .collect();
+        SimulationReport {
+            protocol: self.chainspec.core_config.consensus_protocol,
+            seed: self.config.seed,
+            complete: self.is_complete(),
+            duration_ms: self.elapsed().millis(),
+            eras: self.eras.values().cloned().collect(),
+            finality_latency: LatencyStats::new(self.latencies.clone()),
+            faults,
+            false_accusations: self.false_accusations,
+            ftt_exceeded: self.ftt_exceeded,
+            messages: self.messages.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{types::ChainspecRawBytes, utils::Loadable};
+
+    /// Returns the local chainspec with the given protocol, and eras with three blocks.
+    fn test_chainspec(protocol: ConsensusProtocolName) -> Chainspec {
+        let (mut chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_resources("local");
+        chainspec.core_config.consensus_protocol = protocol;
+        chainspec.core_config.minimum_era_height = 3;
+        chainspec.core_config.era_duration = TimeDiff::from_millis(0);
+        chainspec
+    }
+
+    fn test_config(behaviors: &[Behavior]) -> SimulationConfig {
+        SimulationConfig {
+            protocol: None,
+            seed: 42,
+            eras: 2,
+            max_duration: TimeDiff::from_seconds(3600),
+            validators: behaviors
+                .iter()
+                .map(|behavior| ValidatorConfig {
+                    weight: 100,
+                    behavior: *behavior,
+                })
+                .collect(),
+            network: NetworkConfig::default(),
+        }
+    }
+
+    fn honest_validators_finish_all_eras(protocol: ConsensusProtocolName) {
+        let config = test_config(&[Behavior::Honest; 4]);
+        let report = simulate(test_chainspec(protocol), config).expect("simulation failed");
+        assert!(report.complete);
+        assert_eq!(2, report.eras.len());
+        assert!(report.eras.iter().all(|era| era.blocks >= 3));
+        assert!(report
+            .eras
+            .iter()
+            .all(|era| era.inactive_validators.is_empty()));
+        assert!(report.finality_latency.is_some());
+        assert!(report.faults.is_empty());
+        assert_eq!(0, report.false_accusations);
+        assert_eq!(0, report.ftt_exceeded);
+        assert_eq!(0, report.messages.lost);
+        assert_eq!(0, report.messages.partitioned);
+    }
+
+    #[test]
+    fn zug_honest_validators_finish_all_eras() {
+        honest_validators_finish_all_eras(ConsensusProtocolName::Zug);
+    }
+
+    #[test]
+    fn highway_honest_validators_finish_all_eras() {
+        honest_validators_finish_all_eras(ConsensusProtocolName::Highway);
+    }
+
+    #[test]
+    fn simulation_is_deterministic() {
+        let mut config = test_config(&[Behavior::Honest; 4]);
+        config.network.latency = LatencyDistribution::Exponential {
+            min: TimeDiff::from_millis(10),
+            mean: TimeDiff::from_millis(200),
+        };
+        config.network.loss_rate = 0.05;
+        // Only one `TestRng` can exist per thread, so the simulations run in separate threads.
+        let run = |config: SimulationConfig| {
+            std::thread::spawn(move || {
+                simulate(test_chainspec(ConsensusProtocolName::Zug), config)
+                    .expect("simulation failed")
+            })
+            .join()
+            .expect("simulation panicked")
+        };
+        let report0 = run(config.clone());
+        let report1 = run(config);
+        assert!(report0.messages.lost > 0);
+        assert_eq!(report0, report1);
+    }
+
+    #[test]
+    fn silent_validator_is_reported_inactive() {
+        let silent = Behavior::Silent {
+            from: None,
+            till: None,
+        };
+        let config = test_config(&[Behavior::Honest, Behavior::Honest, Behavior::Honest, silent]);
+        let report = simulate(test_chainspec(ConsensusProtocolName::Zug), config)
+            .expect("simulation failed");
+        assert!(report.complete);
+        assert_eq!(vec![0, 1], report.faults[0].reported_inactive_in_eras);
+        assert_eq!(0, report.faults[0].detected_by);
+    }
+
+    #[test]
+    fn equivocation_is_detected() {
+        let config = test_config(&[
+            Behavior::Honest,
+            Behavior::Honest,
+            Behavior::Honest,
+            Behavior::Equivocate,
+        ]);
+        let report = simulate(test_chainspec(ConsensusProtocolName::Zug), config)
+            .expect("simulation failed");
+        assert!(report.complete);
+        assert_eq!(3, report.faults[0].detected_by);
+        assert_eq!(vec![3], report.eras[0].equivocators);
+        assert_eq!(0, report.false_accusations);
+    }
+}
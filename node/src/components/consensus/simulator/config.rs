@@ -0,0 +1,285 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use casper_types::TimeDiff;
+
+use super::SimulationError;
+use crate::{types::chainspec::ConsensusProtocolName, NodeRng};
+
+const DEFAULT_ERAS: u64 = 1;
+const DEFAULT_MAX_DURATION: &str = "1 day";
+const DEFAULT_LATENCY: &str = "100 ms";
+
+/// The configuration of a simulated network.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+// Disallow unknown fields to ensure config files contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct SimulationConfig {
+    /// The consensus protocol to run. Defaults to the one configured in the chainspec.
+    #[serde(default)]
+    pub protocol: Option<ConsensusProtocolName>,
+    /// The seed for all random choices, i.e. keys, leader sequences and network behavior.
+    #[serde(default)]
+    pub seed: u64,
+    /// The number of eras to simulate.
+    #[serde(default = "default_eras")]
+    pub eras: u64,
+    /// The simulation stops after this much simulated time, even if not all eras are finished.
+    #[serde(default = "default_max_duration")]
+    pub max_duration: TimeDiff,
+    /// The validators, in the order in which they are referred to in the report.
+    pub validators: Vec<ValidatorConfig>,
+    /// The behavior of the network connecting the validators.
+    #[serde(default)]
+    pub network: NetworkConfig,
+}
+
+fn default_eras() -> u64 {
+    DEFAULT_ERAS
+}
+
+fn default_max_duration() -> TimeDiff {
+    DEFAULT_MAX_DURATION.parse().unwrap()
+}
+
+impl SimulationConfig {
+    /// Returns an error if the configuration is inconsistent.
+    pub(super) fn validate(&self) -> Result<(), SimulationError> {
+        if self.validators.is_empty() {
+            return Err(SimulationError::NoValidators);
+        }
+        if self.eras == 0 {
+            return Err(SimulationError::NoEras);
+        }
+        if let Some(idx) = self.validators.iter().position(|v| v.weight == 0) {
+            return Err(SimulationError::ZeroWeight(idx));
+        }
+        self.network.validate(self.validators.len())
+    }
+}
+
+/// A simulated validator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorConfig {
+    /// The validator's weight.
+    pub weight: u64,
+    /// Whether the validator follows the protocol, or how it deviates from it.
+    #[serde(default)]
+    pub behavior: Behavior,
+}
+
+/// How a simulated validator behaves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Behavior {
+    /// The validator follows the protocol.
+    #[default]
+    Honest,
+    /// The validator doesn't send any messages in the given interval, measured from the start of
+    /// the simulation. If `from` is missing it is silent from the start, if `till` is missing it
+    /// stays silent until the end.
+    Silent {
+        /// The beginning of the silent interval.
+        #[serde(default)]
+        from: Option<TimeDiff>,
+        /// The end of the silent interval.
+        #[serde(default)]
+        till: Option<TimeDiff>,
+    },
+    /// The validator's key is used by two nodes that are otherwise honest, but unaware of each
+    /// other, so that they sign conflicting messages.
+    Equivocate,
+}
+
+impl Behavior {
+    /// Returns whether the validator is silent at the given time since the start.
+    pub(super) fn is_silent(&self, elapsed: TimeDiff) -> bool {
+        match self {
+            Behavior::Silent { from, till } => {
+                from.map_or(true, |from| from <= elapsed)
+                    && till.map_or(true, |till| elapsed < till)
+            }
+            Behavior::Honest | Behavior::Equivocate => false,
+        }
+    }
+}
+
+/// The behavior of the simulated network.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// The distribution of the delay between sending and receiving a message.
+    #[serde(default)]
+    pub latency: LatencyDistribution,
+    /// The probability that a message gets lost, between 0 (inclusive) and 1 (exclusive).
+    #[serde(default)]
+    pub loss_rate: f64,
+    /// Intervals in which the network is split into groups that can't communicate with each other.
+    #[serde(default)]
+    pub partitions: Vec<PartitionConfig>,
+}
+
+impl NetworkConfig {
+    fn validate(&self, validator_count: usize) -> Result<(), SimulationError> {
+        if !(0.0..1.0).contains(&self.loss_rate) {
+            return Err(SimulationError::InvalidLossRate(self.loss_rate));
+        }
+        self.latency.validate()?;
+        for partition in &self.partitions {
+            if partition.end < partition.start {
+                return Err(SimulationError::InvalidPartition(
+                    "ends before it starts".to_string(),
+                ));
+            }
+            for idx in partition.groups.iter().flatten() {
+                if *idx >= validator_count {
+                    return Err(SimulationError::InvalidPartition(format!(
+                        "refers to unknown validator {}",
+                        idx
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether messages between the two validators are blocked by a partition at the
+    /// given time since the start.
+    pub(super) fn is_partitioned(&self, elapsed: TimeDiff, v0: usize, v1: usize) -> bool {
+        self.partitions
+            .iter()
+            .filter(|partition| partition.start <= elapsed && elapsed < partition.end)
+            .any(|partition| partition.group(v0) != partition.group(v1))
+    }
+}
+
+/// A network partition.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PartitionConfig {
+    /// When the partition begins, measured from the start of the simulation.
+    pub start: TimeDiff,
+    /// When the partition is healed, measured from the start of the simulation.
+    pub end: TimeDiff,
+    /// The groups of validator indices that can only communicate among themselves. Validators not
+    /// listed in any group form one additional group.
+    pub groups: Vec<Vec<usize>>,
+}
+
+impl PartitionConfig {
+    /// Returns the index of the validator's group, or `None` if it is not listed.
+    fn group(&self, idx: usize) -> Option<usize> {
+        self.groups.iter().position(|group| group.contains(&idx))
+    }
+}
+
+/// A distribution of message delays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LatencyDistribution {
+    /// Every message takes the same time.
+    Constant {
+        /// The delay of every message.
+        delay: TimeDiff,
+    },
+    /// The delays are uniformly distributed between `min` and `max`, inclusive.
+    Uniform {
+        /// The minimum delay.
+        min: TimeDiff,
+        /// The maximum delay.
+        max: TimeDiff,
+    },
+    /// The delays are `min` plus an exponentially distributed value, i.e. most messages are fast
+    /// but there is a long tail of slow ones.
+    Exponential {
+        /// The minimum delay.
+        min: TimeDiff,
+        /// The mean of the delay, including `min`.
+        mean: TimeDiff,
+    },
+}
+
+impl Default for LatencyDistribution {
+    fn default() -> Self {
+        LatencyDistribution::Constant {
+            delay: DEFAULT_LATENCY.parse().unwrap(),
+        }
+    }
+}
+
+impl LatencyDistribution {
+    fn validate(&self) -> Result<(), SimulationError> {
+        match self {
+            LatencyDistribution::Constant { .. } => Ok(()),
+            LatencyDistribution::Uniform { min, max } if min > max => Err(
+                SimulationError::InvalidLatency("the minimum exceeds the maximum"),
+            ),
+            LatencyDistribution::Exponential { min, mean } if min > mean => Err(
+                SimulationError::InvalidLatency("the minimum exceeds the mean"),
+            ),
+            LatencyDistribution::Uniform { .. } | LatencyDistribution::Exponential { .. } => Ok(()),
+        }
+    }
+
+    /// Returns a random delay.
+    pub(super) fn sample(&self, rng: &mut NodeRng) -> TimeDiff {
+        match self {
+            LatencyDistribution::Constant { delay } => *delay,
+            LatencyDistribution::Uniform { min, max } => {
+                TimeDiff::from_millis(rng.gen_range(min.millis()..=max.millis()))
+            }
+            LatencyDistribution::Exponential { min, mean } => {
+                let excess_mean = mean.millis().saturating_sub(min.millis()) as f64;
+                // Inverse transform sampling: `-ln(1 - u)` is exponentially distributed with
+                // mean 1, for `u` uniformly distributed in `[0, 1)`.
+                let excess = -(1.0 - rng.gen::<f64>()).ln() * excess_mean;
+                TimeDiff::from_millis(min.millis().saturating_add(excess as u64))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: SimulationConfig = toml::from_str(
+            r#"
+            protocol = 'Zug'
+            seed = 7
+            eras = 2
+            validators = [
+                { weight = 100 },
+                { weight = 50, behavior = 'equivocate' },
+                { weight = 20, behavior = { silent = { from = '1 minute' } } },
+            ]
+
+            [network]
+            latency = { distribution = 'uniform', min = '10 ms', max = '300 ms' }
+            loss_rate = 0.05
+
+            [[network.partitions]]
+            start = '10 seconds'
+            end = '20 seconds'
+            groups = [[0], [1]]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(Some(ConsensusProtocolName::Zug), config.protocol);
+        assert_eq!(Behavior::Equivocate, config.validators[1].behavior);
+        let silent = config.validators[2].behavior;
+        assert!(!silent.is_silent(TimeDiff::from_seconds(59)));
+        assert!(silent.is_silent(TimeDiff::from_seconds(60)));
+        assert!(config.validate().is_ok());
+
+        let network = &config.network;
+        let at = TimeDiff::from_seconds;
+        assert!(!network.is_partitioned(at(5), 0, 1));
+        assert!(network.is_partitioned(at(15), 0, 1));
+        assert!(network.is_partitioned(at(15), 1, 2));
+        assert!(!network.is_partitioned(at(20), 0, 2));
+    }
+}
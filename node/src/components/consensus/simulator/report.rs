@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::chainspec::ConsensusProtocolName;
+
+use super::config::Behavior;
+
+/// The results of a simulation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulationReport {
+    /// The simulated consensus protocol.
+    pub protocol: ConsensusProtocolName,
+    /// The seed the simulation was run with.
+    pub seed: u64,
+    /// Whether all honest validators finished all eras before the maximum duration.
+    pub complete: bool,
+    /// The simulated time that passed, in milliseconds.
+    pub duration_ms: u64,
+    /// The eras that ended, as seen by the first honest validator to finish each of them.
+    pub eras: Vec<EraReport>,
+    /// The time from a block's proposal until an honest validator finalized it.
+    pub finality_latency: Option<LatencyStats>,
+    /// For every validator that is not honest, how the others detected that.
+    pub faults: Vec<FaultReport>,
+    /// The number of times an honest validator found evidence against another honest one.
+    pub false_accusations: u64,
+    /// The number of times an honest validator considered the fault tolerance threshold exceeded.
+    pub ftt_exceeded: u64,
+    /// Statistics about the simulated network traffic.
+    pub messages: MessageStats,
+}
+
+/// The outcome of an era.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EraReport {
+    /// The era, counted from 0.
+    pub era: u64,
+    /// The number of blocks finalized in the era, including the switch block.
+    pub blocks: u64,
+    /// The time since the start of the simulation at which the era began, in milliseconds.
+    pub start_ms: u64,
+    /// The timestamp of the switch block, relative to the start of the simulation, in
+    /// milliseconds.
+    pub switch_block_ms: u64,
+    /// The time since the start of the simulation at which the switch block was first finalized,
+    /// in milliseconds.
+    pub finalized_ms: u64,
+    /// The indices of the validators the switch block reports as inactive.
+    pub inactive_validators: Vec<usize>,
+    /// The indices of the validators the era's blocks report as equivocators.
+    pub equivocators: Vec<usize>,
+}
+
+/// How a faulty validator was detected.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultReport {
+    /// The index of the validator.
+    pub validator: usize,
+    /// The configured behavior.
+    pub behavior: Behavior,
+    /// The number of honest validators that found direct evidence of an equivocation.
+    pub detected_by: usize,
+    /// The time since the start of the simulation at which the first honest validator found
+    /// evidence, in milliseconds.
+    pub first_detection_ms: Option<u64>,
+    /// The time since the start of the simulation at which the last honest validator found
+    /// evidence, in milliseconds.
+    pub last_detection_ms: Option<u64>,
+    /// The eras whose switch blocks report the validator as inactive.
+    pub reported_inactive_in_eras: Vec<u64>,
+}
+
+/// Statistics about a set of durations.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LatencyStats {
+    /// The number of samples.
+    pub samples: usize,
+    /// The shortest duration, in milliseconds.
+    pub min_ms: u64,
+    /// The average duration, in milliseconds.
+    pub mean_ms: f64,
+    /// The median duration, in milliseconds.
+    pub median_ms: u64,
+    /// The 90th percentile, in milliseconds.
+    pub p90_ms: u64,
+    /// The longest duration, in milliseconds.
+    pub max_ms: u64,
+}
+
+impl LatencyStats {
+    /// Returns the statistics of the given samples, or `None` if there are none.
+    pub(super) fn new(mut samples: Vec<u64>) -> Option<Self> {
+        samples.sort_unstable();
+        let (min_ms, max_ms) = (*samples.first()?, *samples.last()?);
+        // `samples` is not empty, and the index is at most `samples.len() - 1` for `p <= 100`.
+        #[allow(clippy::arithmetic_side_effects)]
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+        Some(LatencyStats {
+            samples: samples.len(),
+            min_ms,
+            mean_ms: samples.iter().map(|ms| *ms as f64).sum::<f64>() / samples.len() as f64,
+            median_ms: percentile(50),
+            p90_ms: percentile(90),
+            max_ms,
+        })
+    }
+}
+
+/// Statistics about the simulated network traffic.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessageStats {
+    /// The number of messages sent, counting each recipient separately.
+    pub sent: u64,
+    /// The number of messages that were lost.
+    pub lost: u64,
+    /// The number of messages blocked by a network partition.
+    pub partitioned: u64,
+    /// The number of messages not sent because the sender was silent.
+    pub suppressed: u64,
+}
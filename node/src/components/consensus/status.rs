@@ -0,0 +1,181 @@
+//! A read-only summary of the current era's consensus state, for monitoring.
+
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{EraId, PublicKey, TimeDiff, Timestamp, U512};
+
+use super::{era_supervisor::Era, traits::Context};
+use crate::rpcs::docs::DocExample;
+
+/// The number of upcoming rounds for which the leaders are included in the status.
+pub(crate) const UPCOMING_LEADERS: u32 = 10;
+
+static CONSENSUS_STATUS: Lazy<ConsensusStatus> = Lazy::new(|| {
+    let public_key = PublicKey::doc_example().clone();
+    ConsensusStatus {
+        era_id: EraId::new(1),
+        start_time: Timestamp::from(1_600_000_000_000),
+        start_height: 100,
+        current_round: Some(12),
+        round_length: Some(TimeDiff::from_millis(1 << 16)),
+        upcoming_leaders: vec![RoundLeader {
+            round_id: 12,
+            public_key: public_key.clone(),
+        }],
+        validators: vec![ValidatorStatus {
+            public_key: public_key.clone(),
+            weight: U512::from(1_000),
+            participation: ParticipationStatus::Active,
+        }],
+        faulty: vec![],
+        cannot_propose: vec![],
+        our_public_key: Some(public_key),
+        our_participation: Some(ParticipationStatus::Active),
+        active: true,
+    }
+});
+
+/// A validator's participation in an era, as observed by this node.
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum ParticipationStatus {
+    /// We have seen messages from the validator in the last few rounds.
+    Active,
+    /// The validator's last message we have seen was in the given Zug round.
+    LastSeenInRound(u64),
+    /// The validator's last Highway unit we have seen was created this many seconds ago.
+    LastSeenSecondsAgo(u64),
+    /// We haven't seen any messages from the validator in this era.
+    Inactive,
+    /// The validator equivocated in a previous era, or was banned.
+    EquivocatedInOtherEra,
+    /// The validator equivocated in this era.
+    Equivocated,
+}
+
+/// The protocol-specific part of the consensus status.
+pub(crate) struct ProtocolStatus<C>
+where
+    C: Context,
+{
+    /// The current round: the round number in Zug, or the round's start time in milliseconds in
+    /// Highway.
+    pub(crate) current_round: Option<u64>,
+    /// The current round length.
+    pub(crate) round_length: Option<TimeDiff>,
+    /// The leaders of the current and the next few rounds.
+    pub(crate) upcoming_leaders: Vec<(u64, C::ValidatorId)>,
+    /// The participation status of every validator.
+    pub(crate) participation: Vec<(C::ValidatorId, ParticipationStatus)>,
+}
+
+/// The leader of a round.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RoundLeader {
+    /// The round: the round number in Zug, or the round's start time in milliseconds in Highway.
+    pub round_id: u64,
+    /// The public key of the round's leader.
+    pub public_key: PublicKey,
+}
+
+/// A validator's weight and participation in the era.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorStatus {
+    /// The validator's public key.
+    pub public_key: PublicKey,
+    /// The validator's weight in the era.
+    pub weight: U512,
+    /// Whether we have recently seen messages from the validator, or evidence against them.
+    pub participation: ParticipationStatus,
+}
+
+/// A summary of the current era's consensus state.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConsensusStatus {
+    /// The current era.
+    pub era_id: EraId,
+    /// The scheduled starting time of the era.
+    pub start_time: Timestamp,
+    /// The height of the era's first block.
+    pub start_height: u64,
+    /// The current round: the round number in Zug, or the round's start time in milliseconds in
+    /// Highway.
+    pub current_round: Option<u64>,
+    /// The current round length.
+    pub round_length: Option<TimeDiff>,
+    /// The leaders of the current and the next few rounds.
+    pub upcoming_leaders: Vec<RoundLeader>,
+    /// The era's validators and their participation.
+    pub validators: Vec<ValidatorStatus>,
+    /// Validators that have been faulty in any of the recent bonded eras.
+    pub faulty: Vec<PublicKey>,
+    /// Validators that are excluded from proposing new blocks.
+    pub cannot_propose: Vec<PublicKey>,
+    /// Our public key, if we are a validator in the era.
+    pub our_public_key: Option<PublicKey>,
+    /// Our own participation, as seen by ourselves, if we are a validator in the era.
+    pub our_participation: Option<ParticipationStatus>,
+    /// Whether we are actively participating in the era, i.e. creating and signing messages.
+    pub active: bool,
+}
+
+impl ConsensusStatus {
+    /// Creates the status of the given era, with our own public key `our_id`.
+    pub(super) fn new(era_id: EraId, era: &Era, our_id: &PublicKey, now: Timestamp) -> Self {
+        let ProtocolStatus {
+            current_round,
+            round_length,
+            upcoming_leaders,
+            participation,
+        } = era.consensus.protocol_status(now);
+        let upcoming_leaders = upcoming_leaders
+            .into_iter()
+            .map(|(round_id, public_key)| RoundLeader {
+                round_id,
+                public_key,
+            })
+            .collect();
+        let validators: Vec<ValidatorStatus> = participation
+            .into_iter()
+            .map(|(public_key, participation)| ValidatorStatus {
+                weight: era.validators.get(&public_key).copied().unwrap_or_default(),
+                public_key,
+                participation,
+            })
+            .collect();
+        let our_participation = validators
+            .iter()
+            .find(|validator| validator.public_key == *our_id)
+            .map(|validator| validator.participation);
+        let mut faulty: Vec<PublicKey> = era.faulty.iter().cloned().collect();
+        faulty.sort();
+        let mut cannot_propose: Vec<PublicKey> = era.cannot_propose.iter().cloned().collect();
+        cannot_propose.sort();
+        ConsensusStatus {
+            era_id,
+            start_time: era.start_time,
+            start_height: era.start_height,
+            current_round,
+            round_length,
+            upcoming_leaders,
+            validators,
+            faulty,
+            cannot_propose,
+            our_public_key: our_participation.map(|_| our_id.clone()),
+            our_participation,
+            active: era.consensus.is_active(),
+        }
+    }
+}
+
+impl DocExample for ConsensusStatus {
+    fn doc_example() -> &'static Self {
+        &CONSENSUS_STATUS
+    }
+}
@@ -39,13 +39,53 @@ impl<H> HashT for H where
 {
 }
 
+/// A message a validator signs, in the serialization its hash is computed from.
+///
+/// Signers holding the key outside of the node decode the message, refuse to sign messages that
+/// contradict ones they signed before, and compute the hash themselves.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DataSize)]
+pub enum SignedData {
+    /// A Highway unit.
+    HighwayUnit(Vec<u8>),
+    /// The fields of a Highway ping.
+    HighwayPing(Vec<u8>),
+    /// A Highway endorsement.
+    HighwayEndorsement(Vec<u8>),
+    /// The fields of a Zug echo or vote.
+    ZugMessage(Vec<u8>),
+}
+
+/// The result of asking a `ValidatorSecret` for a signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Signing<S> {
+    /// The message was signed.
+    Signed(S),
+    /// The message can't be signed, e.g. because a remote signer refused it.
+    Refused,
+    /// The key is held by a remote signer, which must not be waited for. The protocol returns a
+    /// `ProtocolOutcome::RequestSignature` with the data, and continues once the signature is
+    /// passed to `ConsensusProtocol::handle_signature`.
+    Deferred(SignedData),
+}
+
+impl<S> Signing<S> {
+    /// Returns the signature, or `None` if it isn't available immediately.
+    pub fn signed(self) -> Option<S> {
+        match self {
+            Signing::Signed(signature) => Some(signature),
+            Signing::Refused | Signing::Deferred(_) => None,
+        }
+    }
+}
+
 /// A validator's secret signing key.
 pub trait ValidatorSecret: Send + DataSize {
     type Hash: DataSize;
 
     type Signature: Eq + PartialEq + Clone + Debug + Hash + Serialize + DeserializeOwned + DataSize;
 
-    fn sign(&self, hash: &Self::Hash) -> Self::Signature;
+    /// Signs `hash`, the hash of `data`.
+    fn sign(&self, hash: &Self::Hash, data: SignedData) -> Signing<Self::Signature>;
 }
 
 /// The collection of types the user can choose for cryptography, IDs, transactions, etc.
@@ -1,6 +1,7 @@
 //! Various utilities relevant to consensus.
 
 mod validators;
+pub(crate) mod wal;
 mod weight;
 
 pub use validators::{Validator, ValidatorIndex, ValidatorMap, Validators};
@@ -0,0 +1,171 @@
+//! A Write-Ahead Log, used by the consensus protocols to persist what they signed, so that they
+//! don't contradict it after a restart.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    marker::PhantomData,
+    mem,
+    path::{Path, PathBuf},
+};
+
+use datasize::DataSize;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+/// A Write-Ahead Log to store entries on disk before acting on them.
+///
+/// Every entry is written to disk before `record_entry` returns, so it survives a crash or power
+/// loss.
+#[derive(Debug)]
+pub(crate) struct WriteWal<E> {
+    writer: BufWriter<File>,
+    phantom_entry: PhantomData<E>,
+}
+
+impl<E> DataSize for WriteWal<E> {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    fn estimate_heap_size(&self) -> usize {
+        self.writer.capacity()
+    }
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum WriteWalError {
+    #[error("Could not get serialized message size: {0}")]
+    CouldntGetSerializedSize(bincode::Error),
+    #[error("Could not serialize size: {0}")]
+    CouldntSerializeSizeIntoWriter(io::Error),
+    #[error("Could not serialize message: {0}")]
+    CouldntSerializeMessageIntoWriter(bincode::Error),
+    #[error("Could not flush message to disk: {0}")]
+    CouldntFlushMessageToDisk(io::Error),
+    #[error("Could not open file: {0}")]
+    FileCouldntBeOpened(io::Error),
+}
+
+impl<E: Serialize> WriteWal<E> {
+    pub(crate) fn new(wal_path: &PathBuf) -> Result<Self, WriteWalError> {
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(wal_path)
+            .map_err(WriteWalError::FileCouldntBeOpened)?;
+        // Make sure the file itself, not only its contents, persists if it was just created.
+        sync_parent_dir(wal_path).map_err(WriteWalError::FileCouldntBeOpened)?;
+        Ok(WriteWal {
+            writer: BufWriter::new(file),
+            phantom_entry: PhantomData,
+        })
+    }
+
+    pub(crate) fn record_entry(&mut self, entry: &E) -> Result<(), WriteWalError> {
+        // First write the size of the entry as a serialized u64.
+        let entry_size =
+            bincode::serialized_size(entry).map_err(WriteWalError::CouldntGetSerializedSize)?;
+        self.writer
+            .write_all(&entry_size.to_le_bytes())
+            .map_err(WriteWalError::CouldntSerializeSizeIntoWriter)?;
+        // Write the serialized entry itself.
+        bincode::serialize_into(&mut self.writer, entry)
+            .map_err(WriteWalError::CouldntSerializeMessageIntoWriter)?;
+        self.writer
+            .flush()
+            .map_err(WriteWalError::CouldntFlushMessageToDisk)?;
+        self.writer
+            .get_ref()
+            .sync_data()
+            .map_err(WriteWalError::CouldntFlushMessageToDisk)?;
+        Ok(())
+    }
+}
+
+/// A buffer to read a Write-Ahead Log from disk and deserialize its entries.
+#[derive(Debug)]
+pub(crate) struct ReadWal<E> {
+    pub(crate) reader: BufReader<File>,
+    pub(crate) phantom_entry: PhantomData<E>,
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum ReadWalError {
+    #[error("Could not create file at {0}: {1}")]
+    FileCouldntBeCreated(PathBuf, io::Error),
+    #[error(transparent)]
+    OtherIOError(#[from] io::Error),
+    #[error("could not deserialize WAL entry: {0}")]
+    CouldNotDeserialize(bincode::Error),
+}
+
+impl<E: DeserializeOwned> ReadWal<E> {
+    pub(crate) fn new(wal_path: &PathBuf) -> Result<Self, ReadWalError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .open(wal_path)
+            .map_err(|err| ReadWalError::FileCouldntBeCreated(wal_path.clone(), err))?;
+        Ok(ReadWal {
+            reader: BufReader::new(file),
+            phantom_entry: PhantomData,
+        })
+    }
+
+    /// Reads the next entry from the WAL, or returns an error.
+    /// If there are 0 bytes left it returns `Ok(None)`.
+    pub(crate) fn read_next_entry(&mut self) -> Result<Option<E>, ReadWalError> {
+        // Remember the current position: If we encounter an unreadable entry we trim the file at
+        // this point so we can continue appending entries after it.
+        let position = self.reader.stream_position()?;
+
+        // Deserialize the size of the entry, in bytes, as a u64.
+        let mut entry_size_buf = [0u8; mem::size_of::<u64>()];
+        if let Err(err) = self.reader.read_exact(&mut entry_size_buf) {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                self.trim_file(position)?;
+                return Ok(None);
+            }
+            return Err(ReadWalError::OtherIOError(err));
+        }
+        let entry_size = u64::from_le_bytes(entry_size_buf) as usize;
+
+        // Read the serialized entry itself.
+        let mut entry_buf = vec![0; entry_size];
+        if let Err(err) = self.reader.read_exact(&mut entry_buf) {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                self.trim_file(position)?;
+                return Ok(None);
+            }
+            return Err(ReadWalError::OtherIOError(err));
+        }
+
+        // Deserialize and return the entry.
+        let entry = bincode::deserialize(&entry_buf).map_err(ReadWalError::CouldNotDeserialize)?;
+        Ok(Some(entry))
+    }
+
+    /// Trims the file to the given length and logs a warning if any bytes were removed.
+    ///
+    /// This should be called with the position where the last complete entry ended. Incomplete
+    /// entries can safely be removed because we only act on entries after writing them and
+    /// syncing them to disk, so we won't remove any entries that we already acted upon.
+    fn trim_file(&mut self, position: u64) -> Result<(), ReadWalError> {
+        if self.reader.stream_position()? > position {
+            warn!("removing incomplete entry from WAL");
+            self.reader.get_mut().set_len(position)?;
+        }
+        Ok(())
+    }
+}
+
+/// Syncs the directory containing `path`, so that a newly created file at `path` persists.
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+        _ => Ok(()),
+    }
+}
@@ -28,21 +28,29 @@ use thiserror::Error;
 use tracing::{debug, error, info, trace};
 
 use casper_execution_engine::{
-    core::engine_state::{
-        self,
-        engine_config::{FeeHandling, RefundHandling},
-        genesis::GenesisError,
-        ChainspecRegistry, DeployItem, EngineConfigBuilder, EngineState, GenesisSuccess,
-        SystemContractRegistry, UpgradeConfig, UpgradeSuccess,
+    core::{
+        engine_state::{
+            self,
+            engine_config::{FeeHandling, RefundHandling},
+            genesis::GenesisError,
+            ChainspecRegistry, DeployItem, EngineConfigBuilder, EngineState, GenesisSuccess,
+            SystemContractRegistry, UpgradeConfig, UpgradeSuccess,
+        },
+        runtime::ExecutionTrace,
+    },
+    shared::{
+        newtypes::CorrelationId, system_config::SystemConfig, wasm_config::WasmConfig,
+        wasm_module_cache::WasmModuleCache,
     },
-    shared::{newtypes::CorrelationId, system_config::SystemConfig, wasm_config::WasmConfig},
     storage::{
         global_state::lmdb::LmdbGlobalState, transaction_source::lmdb::LmdbEnvironment,
         trie_store::lmdb::LmdbTrieStore,
     },
 };
 use casper_hashing::Digest;
-use casper_types::{bytesrepr::Bytes, EraId, ProtocolVersion, PublicKey, Timestamp};
+use casper_types::{
+    bytesrepr::Bytes, EraId, ExecutionResult, ProtocolVersion, PublicKey, Timestamp,
+};
 
 use crate::{
     components::{fetcher::FetchResponse, Component, ComponentState},
@@ -67,9 +75,11 @@ pub(crate) use config::Config;
 pub(crate) use error::{BlockExecutionError, ConfigError};
 use metrics::Metrics;
 pub use operations::execute_finalized_block;
-use operations::execute_only;
+use operations::{
+    estimate_gas, execute_only, execute_only_sequence, execute_only_with_state_overrides,
+};
 pub(crate) use types::{
-    BlockAndExecutionResults, EraValidatorsRequest, StepEffectAndUpcomingEraValidators,
+    BlockAndExecutionResults, EraValidatorsRequest, GasEstimate, StepEffectAndUpcomingEraValidators,
 };
 
 const COMPONENT_NAME: &str = "contract_runtime";
@@ -128,6 +138,10 @@ pub struct SpeculativeExecutionState {
     pub protocol_version: ProtocolVersion,
 }
 
+/// The result of speculatively executing a deploy, along with the trace of its host function calls
+/// if one was requested.
+pub type SpeculativeExecutionResult = (ExecutionResult, Option<ExecutionTrace>);
+
 /// State to use to construct the next block in the blockchain. Includes the state root hash for the
 /// execution engine as well as certain values the next header will be based on.
 #[derive(DataSize, Debug, Clone, Serialize)]
@@ -212,6 +226,10 @@ pub(crate) struct ContractRuntime {
     system_contract_registry: Option<SystemContractRegistry>,
     activation_point: ActivationPoint,
     prune_batch_size: u64,
+    /// The maximum gas which can be consumed by a single block, bounding gas estimation.
+    block_gas_limit: u64,
+    /// The number of threads used to execute the deploys of a block.
+    parallel_execution_threads: usize,
 }
 
 impl Debug for ContractRuntime {
@@ -494,6 +512,7 @@ impl ContractRuntime {
                         let shared_pre_state = Arc::clone(&self.execution_pre_state);
                         let activation_point = self.activation_point;
                         let prune_batch_size = self.prune_batch_size;
+                        let parallel_execution_threads = self.parallel_execution_threads;
                         effects.extend(
                             Self::execute_finalized_block_or_requeue(
                                 engine_state,
@@ -508,6 +527,7 @@ impl ContractRuntime {
                                 activation_point,
                                 key_block_height_for_activation_point,
                                 prune_batch_size,
+                                parallel_execution_threads,
                                 meta_block_state,
                             )
                             .ignore(),
@@ -567,20 +587,145 @@ impl ContractRuntime {
                 responder.respond(result).ignore()
             }
             ContractRuntimeRequest::SpeculativeDeployExecution {
+                execution_prestate,
+                deploy,
+                state_overrides,
+                trace,
+                responder,
+            } => {
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_intensive_task(move || {
+                        let deploy_item = DeployItem::from((*deploy).clone());
+                        if state_overrides.is_empty() {
+                            execute_only(
+                                engine_state.as_ref(),
+                                execution_prestate,
+                                deploy_item,
+                                trace,
+                            )
+                        } else {
+                            execute_only_with_state_overrides(
+                                engine_state.as_ref(),
+                                execution_prestate,
+                                deploy_item,
+                                state_overrides,
+                                trace,
+                            )
+                        }
+                    })
+                    .await;
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::SpeculativeDeploySequenceExecution {
+                execution_prestate,
+                deploys,
+                responder,
+            } => {
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_intensive_task(move || {
+                        execute_only_sequence(
+                            engine_state.as_ref(),
+                            execution_prestate,
+                            deploys
+                                .iter()
+                                .map(|deploy| DeployItem::from((**deploy).clone()))
+                                .collect(),
+                        )
+                    })
+                    .await;
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::EstimateGas {
                 execution_prestate,
                 deploy,
                 responder,
             } => {
                 let engine_state = Arc::clone(&self.engine_state);
+                let block_gas_limit = self.block_gas_limit;
                 async move {
                     let result = run_intensive_task(move || {
-                        execute_only(
+                        estimate_gas(
                             engine_state.as_ref(),
                             execution_prestate,
                             DeployItem::from((*deploy).clone()),
+                            block_gas_limit,
+                        )
+                    })
+                    .await;
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::GetStateDiff {
+                old_state_root_hash,
+                new_state_root_hash,
+                start_after,
+                limit,
+                responder,
+            } => {
+                trace!(
+                    %old_state_root_hash,
+                    %new_state_root_hash,
+                    ?start_after,
+                    limit,
+                    "get_state_diff request"
+                );
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_intensive_task(move || {
+                        engine_state.get_state_diff(
+                            CorrelationId::new(),
+                            old_state_root_hash,
+                            new_state_root_hash,
+                            start_after.as_ref(),
+                            limit,
+                        )
+                    })
+                    .await;
+                    trace!(?result, "get_state_diff response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::ListKeys {
+                state_root_hash,
+                prefix,
+                dictionary_seed_uref,
+                start_after,
+                limit,
+                max_scanned,
+                responder,
+            } => {
+                trace!(
+                    %state_root_hash,
+                    ?prefix,
+                    ?dictionary_seed_uref,
+                    ?start_after,
+                    limit,
+                    max_scanned,
+                    "list_keys request"
+                );
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_intensive_task(move || {
+                        engine_state.list_keys(
+                            CorrelationId::new(),
+                            state_root_hash,
+                            &prefix,
+                            dictionary_seed_uref,
+                            start_after.as_ref(),
+                            limit,
+                            max_scanned,
                         )
                     })
                     .await;
+                    trace!(?result, "list_keys response");
                     responder.respond(result).await
                 }
                 .ignore()
@@ -611,6 +756,7 @@ impl ContractRuntime {
         allow_unrestricted_transfers: bool,
         refund_handling: RefundHandling,
         fee_handling: FeeHandling,
+        block_gas_limit: u64,
     ) -> Result<Self, ConfigError> {
         // TODO: This is bogus, get rid of this
         let execution_pre_state = Arc::new(Mutex::new(ExecutionPreState {
@@ -649,9 +795,14 @@ impl ContractRuntime {
             .with_allow_unrestricted_transfers(allow_unrestricted_transfers)
             .with_refund_handling(refund_handling)
             .with_fee_handling(fee_handling)
+            .with_wasm_engine(contract_runtime_config.wasm_engine_or_default())
             .build();
 
-        let engine_state = Arc::new(EngineState::new(global_state, engine_config));
+        let wasm_module_cache =
+            WasmModuleCache::new(contract_runtime_config.wasm_module_cache_size_or_default());
+        let engine_state = Arc::new(
+            EngineState::new(global_state, engine_config).with_wasm_module_cache(wasm_module_cache),
+        );
 
         let metrics = Arc::new(Metrics::new(registry)?);
 
@@ -665,6 +816,9 @@ impl ContractRuntime {
             system_contract_registry: None,
             activation_point,
             prune_batch_size,
+            block_gas_limit,
+            parallel_execution_threads: contract_runtime_config
+                .parallel_execution_threads_or_default(),
         })
     }
 
@@ -756,6 +910,7 @@ impl ContractRuntime {
         activation_point: ActivationPoint,
         key_block_height_for_activation_point: u64,
         prune_batch_size: u64,
+        parallel_execution_threads: usize,
         mut meta_block_state: MetaBlockState,
     ) where
         REv: From<ContractRuntimeRequest>
@@ -784,6 +939,7 @@ impl ContractRuntime {
                 activation_point.era_id(),
                 key_block_height_for_activation_point,
                 prune_batch_size,
+                parallel_execution_threads,
             )
         })
         .await
@@ -1071,6 +1227,7 @@ mod trie_chunking_tests {
             true,
             DEFAULT_REFUND_HANDLING,
             DEFAULT_FEE_HANDLING,
+            10_000_000_000_000,
         )
         .unwrap();
         let empty_state_root = contract_runtime
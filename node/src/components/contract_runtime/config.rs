@@ -1,12 +1,14 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
-use casper_execution_engine::shared::utils;
+use casper_execution_engine::{core::wasm_engine::WasmEngineKind, shared::utils};
 
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_MAX_READERS: u32 = 512;
 const DEFAULT_MAX_QUERY_DEPTH: u64 = 5;
 const DEFAULT_MANUAL_SYNC_ENABLED: bool = true;
+const DEFAULT_PARALLEL_EXECUTION_THREADS: usize = 1;
+const DEFAULT_WASM_MODULE_CACHE_SIZE: usize = 256;
 
 /// Contract runtime configuration.
 #[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
@@ -31,6 +33,22 @@ pub struct Config {
     ///
     /// Defaults to `true`.
     pub enable_manual_sync: Option<bool>,
+    /// The number of threads used to execute the deploys of a block in parallel.
+    ///
+    /// Deploys are executed optimistically, and re-executed in order if they conflict with
+    /// preceding deploys, so the results are the same as those of sequential execution.
+    ///
+    /// Defaults to 1, i.e. sequential execution.
+    pub parallel_execution_threads: Option<usize>,
+    /// The maximum number of deserialized stored contract modules kept in memory.
+    ///
+    /// Defaults to 256.  Setting it to 0 disables the cache.
+    pub wasm_module_cache_size: Option<usize>,
+    /// The engine used to execute Wasm: "interpreter", or "compiled" if the node was built with
+    /// the `compiled-wasm-engine` feature.
+    ///
+    /// Defaults to "interpreter".
+    pub wasm_engine: Option<WasmEngineKind>,
 }
 
 impl Config {
@@ -58,6 +76,23 @@ impl Config {
         self.enable_manual_sync
             .unwrap_or(DEFAULT_MANUAL_SYNC_ENABLED)
     }
+
+    /// Number of threads used to execute the deploys of a block.
+    pub fn parallel_execution_threads_or_default(&self) -> usize {
+        self.parallel_execution_threads
+            .unwrap_or(DEFAULT_PARALLEL_EXECUTION_THREADS)
+    }
+
+    /// Maximum number of cached stored contract modules.
+    pub fn wasm_module_cache_size_or_default(&self) -> usize {
+        self.wasm_module_cache_size
+            .unwrap_or(DEFAULT_WASM_MODULE_CACHE_SIZE)
+    }
+
+    /// The engine used to execute Wasm.
+    pub fn wasm_engine_or_default(&self) -> WasmEngineKind {
+        self.wasm_engine.unwrap_or_default()
+    }
 }
 
 impl Default for Config {
@@ -67,6 +102,9 @@ impl Default for Config {
             max_readers: Some(DEFAULT_MAX_READERS),
             max_query_depth: Some(DEFAULT_MAX_QUERY_DEPTH),
             enable_manual_sync: Some(DEFAULT_MANUAL_SYNC_ENABLED),
+            parallel_execution_threads: Some(DEFAULT_PARALLEL_EXECUTION_THREADS),
+            wasm_module_cache_size: Some(DEFAULT_WASM_MODULE_CACHE_SIZE),
+            wasm_engine: Some(WasmEngineKind::default()),
         }
     }
 }
@@ -1,4 +1,4 @@
-use prometheus::{self, Gauge, Histogram, IntGauge, Registry};
+use prometheus::{self, Gauge, Histogram, IntCounter, IntGauge, Registry};
 
 use crate::{unregister_metric, utils};
 
@@ -51,6 +51,18 @@ const EXEC_BLOCK_HELP: &str = "time in seconds to execute all deploys in a block
 const LATEST_COMMIT_STEP_NAME: &str = "contract_runtime_latest_commit_step";
 const LATEST_COMMIT_STEP_HELP: &str = "duration in seconds of latest commit step at era end";
 
+const DEPLOY_REEXECUTIONS_NAME: &str = "contract_runtime_deploy_reexecutions";
+const DEPLOY_REEXECUTIONS_HELP: &str =
+    "number of deploys executed in parallel which had to be re-executed due to conflicts";
+
+const WASM_MODULE_CACHE_HITS_NAME: &str = "contract_runtime_wasm_module_cache_hits";
+const WASM_MODULE_CACHE_HITS_HELP: &str =
+    "number of stored contract calls served by the wasm module cache";
+
+const WASM_MODULE_CACHE_MISSES_NAME: &str = "contract_runtime_wasm_module_cache_misses";
+const WASM_MODULE_CACHE_MISSES_HELP: &str =
+    "number of stored contract calls which had to deserialize the contract's wasm module";
+
 const EXEC_QUEUE_SIZE_NAME: &str = "execution_queue_size";
 const EXEC_QUEUE_SIZE_HELP: &str =
     "number of blocks that are currently enqueued and waiting for execution";
@@ -70,6 +82,9 @@ pub struct Metrics {
     pub(super) get_trie: Histogram,
     pub(super) exec_block: Histogram,
     pub(super) latest_commit_step: Gauge,
+    pub(super) deploy_reexecutions: IntCounter,
+    pub(super) wasm_module_cache_hits: IntCounter,
+    pub(super) wasm_module_cache_misses: IntCounter,
     pub(super) exec_queue_size: IntGauge,
     registry: Registry,
 }
@@ -92,6 +107,18 @@ impl Metrics {
         let latest_commit_step = Gauge::new(LATEST_COMMIT_STEP_NAME, LATEST_COMMIT_STEP_HELP)?;
         registry.register(Box::new(latest_commit_step.clone()))?;
 
+        let deploy_reexecutions =
+            IntCounter::new(DEPLOY_REEXECUTIONS_NAME, DEPLOY_REEXECUTIONS_HELP)?;
+        registry.register(Box::new(deploy_reexecutions.clone()))?;
+
+        let wasm_module_cache_hits =
+            IntCounter::new(WASM_MODULE_CACHE_HITS_NAME, WASM_MODULE_CACHE_HITS_HELP)?;
+        registry.register(Box::new(wasm_module_cache_hits.clone()))?;
+
+        let wasm_module_cache_misses =
+            IntCounter::new(WASM_MODULE_CACHE_MISSES_NAME, WASM_MODULE_CACHE_MISSES_HELP)?;
+        registry.register(Box::new(wasm_module_cache_misses.clone()))?;
+
         let exec_queue_size = IntGauge::new(EXEC_QUEUE_SIZE_NAME, EXEC_QUEUE_SIZE_HELP)?;
         registry.register(Box::new(exec_queue_size.clone()))?;
 
@@ -163,6 +190,9 @@ impl Metrics {
                 common_buckets,
             )?,
             latest_commit_step,
+            deploy_reexecutions,
+            wasm_module_cache_hits,
+            wasm_module_cache_misses,
             exec_queue_size,
             registry: registry.clone(),
         })
@@ -183,6 +213,9 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.get_trie);
         unregister_metric!(self.registry, self.exec_block);
         unregister_metric!(self.registry, self.latest_commit_step);
+        unregister_metric!(self.registry, self.deploy_reexecutions);
+        unregister_metric!(self.registry, self.wasm_module_cache_hits);
+        unregister_metric!(self.registry, self.wasm_module_cache_misses);
         unregister_metric!(self.registry, self.exec_queue_size);
     }
 }
@@ -1,4 +1,15 @@
-use std::{cmp, collections::BTreeMap, ops::Range, sync::Arc, time::Instant};
+use std::{
+    cmp,
+    collections::BTreeMap,
+    iter,
+    ops::Range,
+    sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
 
 use itertools::Itertools;
 use tracing::{debug, error, info, trace, warn};
@@ -6,19 +17,26 @@ use tracing::{debug, error, info, trace, warn};
 use casper_execution_engine::{
     core::{
         engine_state::{
-            self, execution_result::ExecutionResults, step::EvictItem, ChecksumRegistry,
-            DeployItem, EngineState, ExecuteRequest, ExecutionResult as EngineExecutionResult,
-            GetEraValidatorsRequest, PruneConfig, PruneResult, RewardItem, StepError, StepRequest,
+            self, execution_result::ExecutionResults, step::EvictItem, BalanceResult,
+            ChecksumRegistry, DeployItem, EngineState, ExecutableDeployItem, ExecuteRequest,
+            ExecutionResult as EngineExecutionResult, GetEraValidatorsRequest, PruneConfig,
+            PruneResult, QueryRequest, QueryResult, RewardItem, StepError, StepRequest,
             StepSuccess,
         },
         execution,
+        runtime::ExecutionTrace,
     },
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
-    storage::global_state::{lmdb::LmdbGlobalState, CommitProvider, StateProvider},
+    storage::global_state::{
+        lmdb::LmdbGlobalState, recording::ReadSet, scratch::ScratchGlobalState, CommitProvider,
+        StateProvider,
+    },
 };
 use casper_hashing::Digest;
 use casper_types::{
-    CLValue, DeployHash, EraId, ExecutionResult, Key, ProtocolVersion, PublicKey, U512,
+    account::AccountHash, bytesrepr::Bytes, runtime_args, system::standard_payment, CLValue,
+    DeployHash, EraId, ExecutionResult, Gas, Key, Motes, ProtocolVersion, PublicKey, RuntimeArgs,
+    StoredValue, U512,
 };
 
 use crate::{
@@ -26,8 +44,9 @@ use crate::{
         consensus::EraReport,
         contract_runtime::{
             error::BlockExecutionError, types::StepEffectAndUpcomingEraValidators,
-            BlockAndExecutionResults, ExecutionPreState, Metrics, SpeculativeExecutionState,
-            APPROVALS_CHECKSUM_NAME, EXECUTION_RESULTS_CHECKSUM_NAME,
+            BlockAndExecutionResults, ExecutionPreState, GasEstimate, Metrics,
+            SpeculativeExecutionResult, SpeculativeExecutionState, APPROVALS_CHECKSUM_NAME,
+            EXECUTION_RESULTS_CHECKSUM_NAME,
         },
         fetcher::FetchItem,
     },
@@ -37,6 +56,10 @@ use crate::{
     },
 };
 
+/// The maximum number of re-executions used to search for the smallest sufficient payment amount
+/// when estimating gas.
+const MAX_GAS_ESTIMATION_ATTEMPTS: usize = 16;
+
 fn generate_range_by_index(
     highest_era: u64,
     batch_size: u64,
@@ -99,6 +122,7 @@ pub fn execute_finalized_block(
     activation_point_era_id: EraId,
     key_block_height_for_activation_point: u64,
     prune_batch_size: u64,
+    parallel_execution_threads: usize,
 ) -> Result<BlockAndExecutionResults, BlockExecutionError> {
     if finalized_block.height() != execution_pre_state.next_block_height {
         return Err(BlockExecutionError::WrongBlockHeight {
@@ -125,14 +149,43 @@ pub fn execute_finalized_block(
     // Create a new EngineState that reads from LMDB but only caches changes in memory.
     let scratch_state = engine_state.get_scratch_engine_state();
 
+    let deploys = deploys
+        .into_iter()
+        .map(|deploy| {
+            (
+                *deploy.hash(),
+                deploy.header().clone(),
+                DeployItem::from(deploy),
+            )
+        })
+        .collect_vec();
+
+    // If enabled, optimistically execute all deploys in parallel on top of the pre-state.  Each
+    // result is only used if the preceding deploys didn't change any of the values it depends on,
+    // in which case it is identical to the result of sequential execution.
+    let parallel_execution = parallel_execution_threads > 1 && deploys.len() > 1;
+    let mut optimistic_results = if parallel_execution {
+        execute_in_parallel(
+            &scratch_state,
+            parallel_execution_threads,
+            state_root_hash,
+            block_time,
+            protocol_version,
+            *finalized_block.proposer(),
+            deploys.iter().map(|(_, _, deploy_item)| deploy_item),
+        )
+    } else {
+        vec![]
+    }
+    .into_iter();
+    let mut written_values = WrittenValues::default();
+
     // WARNING: Do not change the order of `deploys` as it will result in a different root hash.
-    for deploy in deploys {
-        let deploy_hash = *deploy.hash();
-        let deploy_header = deploy.header().clone();
+    for (deploy_hash, deploy_header, deploy_item) in deploys {
         let execute_request = ExecuteRequest::new(
             state_root_hash,
             block_time,
-            vec![DeployItem::from(deploy)],
+            vec![deploy_item],
             protocol_version,
             *finalized_block.proposer(),
         );
@@ -142,7 +195,21 @@ pub fn execute_finalized_block(
         // mapping between deploy_hash and execution result, and this outer logic is
         // enriching it with the deploy hash. If we were passing multiple deploys per exec
         // the relation between the deploy and the execution results would be lost.
-        let result = execute(&scratch_state, metrics.clone(), execute_request)?;
+        let result = match optimistic_results.next().flatten() {
+            Some((result, read_set)) if !written_values.affect(&read_set) => result,
+            maybe_conflicting_result => {
+                if maybe_conflicting_result.is_some() {
+                    trace!(?deploy_hash, "re-executing conflicting deploy");
+                    if let Some(metrics) = metrics.as_ref() {
+                        metrics.deploy_reexecutions.inc();
+                    }
+                }
+                execute(&scratch_state, metrics.clone(), execute_request)?
+            }
+        };
+        if parallel_execution {
+            written_values.record(&result);
+        }
 
         trace!(?deploy_hash, ?result, "deploy execution result");
         // As for now a given state is expected to exist.
@@ -183,6 +250,11 @@ pub fn execute_finalized_block(
 
     if let Some(metrics) = metrics.as_ref() {
         metrics.exec_block.observe(start.elapsed().as_secs_f64());
+        if let Some(wasm_module_cache) = engine_state.wasm_module_cache() {
+            let stats = wasm_module_cache.take_stats();
+            metrics.wasm_module_cache_hits.inc_by(stats.hits);
+            metrics.wasm_module_cache_misses.inc_by(stats.misses);
+        }
     }
 
     // If the finalized block has an era report, run the auction contract and get the upcoming era
@@ -335,6 +407,110 @@ pub fn execute_finalized_block(
     })
 }
 
+/// The values written by the deploys of a block executed so far.
+#[derive(Default)]
+struct WrittenValues(BTreeMap<Key, Option<StoredValue>>);
+
+impl WrittenValues {
+    /// Records the keys written by the execution, with the new values if they are known.
+    ///
+    /// Values aren't known if the execution added to them.
+    fn record(&mut self, execution_results: &ExecutionResults) {
+        for result in execution_results {
+            let effects: AdditiveMap<Key, Transform> = result.execution_journal().clone().into();
+            for (key, transform) in effects {
+                match transform {
+                    Transform::Identity => {}
+                    Transform::Write(value) => {
+                        self.0.insert(key, Some(value));
+                    }
+                    _ => {
+                        self.0.insert(key, None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns whether an execution against the pre-state could have been affected by the values
+    /// written since.
+    ///
+    /// Keys which were changed back to their value in the pre-state, like the payment purse's
+    /// balance, don't affect it.
+    fn affect(&self, read_set: &ReadSet) -> bool {
+        read_set
+            .dependencies()
+            .any(|(key, read_value)| match self.0.get(key) {
+                None => false,
+                Some(Some(written_value)) => read_value.as_ref() != Some(written_value),
+                Some(None) => true,
+            })
+    }
+}
+
+/// Executes each of the deploys on its own on top of `state_root_hash`, using up to `thread_count`
+/// threads.
+///
+/// Returns the results in the same order as `deploy_items`, along with the keys read by each
+/// execution, or `None` for deploys whose execution returned an error.
+fn execute_in_parallel<'a>(
+    scratch_state: &EngineState<ScratchGlobalState>,
+    thread_count: usize,
+    state_root_hash: Digest,
+    block_time: u64,
+    protocol_version: ProtocolVersion,
+    proposer: PublicKey,
+    deploy_items: impl Iterator<Item = &'a DeployItem>,
+) -> Vec<Option<(ExecutionResults, ReadSet)>> {
+    let deploy_items = deploy_items.collect_vec();
+    let next_index = AtomicUsize::new(0);
+    let mut results = iter::repeat_with(|| None)
+        .take(deploy_items.len())
+        .collect_vec();
+
+    thread::scope(|scope| {
+        let workers = (0..cmp::min(thread_count, deploy_items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut worker_results = vec![];
+                    loop {
+                        let index = next_index.fetch_add(1, atomic::Ordering::Relaxed);
+                        let deploy_item = match deploy_items.get(index) {
+                            Some(deploy_item) => (*deploy_item).clone(),
+                            None => break worker_results,
+                        };
+                        let deploy_hash = deploy_item.deploy_hash;
+                        let execute_request = ExecuteRequest::new(
+                            state_root_hash,
+                            block_time,
+                            vec![deploy_item],
+                            protocol_version,
+                            proposer.clone(),
+                        );
+                        let result = scratch_state
+                            .run_execute_recording_reads(CorrelationId::new(), execute_request)
+                            .map_err(|error| {
+                                debug!(?deploy_hash, %error, "optimistic execution failed");
+                            })
+                            .ok();
+                        worker_results.push((index, result));
+                    }
+                })
+            })
+            .collect_vec();
+        for worker in workers {
+            let worker_results = worker
+                .join()
+                .expect("deploy execution thread should not panic");
+            for (index, result) in worker_results {
+                results[index] = result;
+            }
+        }
+    });
+
+    results
+}
+
 /// Commits the execution results.
 fn commit_execution_results<S>(
     engine_state: &EngineState<S>,
@@ -407,12 +583,14 @@ where
 /// Execute the transaction without commiting the effects.
 /// Intended to be used for discovery operations on read-only nodes.
 ///
-/// Returns effects of the execution.
+/// Returns effects of the execution, along with the trace of its host function calls if `trace` is
+/// set.
 pub fn execute_only<S>(
     engine_state: &EngineState<S>,
     execution_state: SpeculativeExecutionState,
     deploy: DeployItem,
-) -> Result<Option<ExecutionResult>, engine_state::Error>
+    trace: bool,
+) -> Result<Option<SpeculativeExecutionResult>, engine_state::Error>
 where
     S: StateProvider + CommitProvider,
     S::Error: Into<execution::Error>,
@@ -430,23 +608,222 @@ where
         protocol_version,
         PublicKey::System,
     );
-    let results = execute(engine_state, None, execute_request);
-    results.map(|mut execution_results| {
-        let len = execution_results.len();
-        if len != 1 {
+    let (execution_results, mut execution_traces) = if trace {
+        let (execution_results, execution_traces) =
+            execute_with_trace(engine_state, execute_request)?;
+        (execution_results, Some(execution_traces))
+    } else {
+        (execute(engine_state, None, execute_request)?, None)
+    };
+    let execution_result = match execution_results.into_iter().exactly_one() {
+        Ok(execution_result) => execution_result,
+        Err(execution_results) => {
             warn!(
                 ?deploy_hash,
-                "got more ({}) execution results from a single transaction", len
+                "got more ({}) execution results from a single transaction",
+                execution_results.len()
             );
-            None
-        } else {
-            // We know it must be 1, we could unwrap and then wrap
-            // with `Some(_)` but `pop_front` already returns an `Option`.
-            // We need to transform the `engine_state::ExecutionResult` into
-            // `casper_types::ExecutionResult` as well.
-            execution_results.pop_front().map(Into::into)
+            return Ok(None);
         }
-    })
+    };
+    let execution_trace = execution_traces.as_mut().and_then(Vec::pop);
+    Ok(Some((execution_result.into(), execution_trace)))
+}
+
+/// Execute the transaction without commiting the effects, on top of the given overrides of global
+/// state values.
+///
+/// The overrides are applied to a scratch copy of global state which is discarded afterwards.
+///
+/// Returns effects of the execution, along with the trace of its host function calls if `trace` is
+/// set.
+pub fn execute_only_with_state_overrides(
+    engine_state: &EngineState<LmdbGlobalState>,
+    execution_state: SpeculativeExecutionState,
+    deploy: DeployItem,
+    state_overrides: Vec<(Key, StoredValue)>,
+    trace: bool,
+) -> Result<Option<SpeculativeExecutionResult>, engine_state::Error> {
+    // Create a new EngineState that reads from LMDB but only caches changes in memory.  It is never
+    // written back to LMDB.
+    let scratch_state = engine_state.get_scratch_engine_state();
+
+    let mut effects = AdditiveMap::new();
+    for (key, stored_value) in state_overrides {
+        let _ = effects.insert(key, Transform::Write(stored_value));
+    }
+    let state_root_hash = commit_transforms(
+        &scratch_state,
+        None,
+        execution_state.state_root_hash,
+        effects,
+    )?;
+
+    execute_only(
+        &scratch_state,
+        SpeculativeExecutionState {
+            state_root_hash,
+            ..execution_state
+        },
+        deploy,
+        trace,
+    )
+}
+
+/// Execute the transactions in order without commiting their effects, each on top of the effects of
+/// the previous ones.
+///
+/// The effects are applied to a scratch copy of global state which is discarded afterwards.
+///
+/// Returns effects of each execution, in the same order as `deploys`.
+pub fn execute_only_sequence(
+    engine_state: &EngineState<LmdbGlobalState>,
+    execution_state: SpeculativeExecutionState,
+    deploys: Vec<DeployItem>,
+) -> Result<Option<Vec<ExecutionResult>>, engine_state::Error> {
+    let SpeculativeExecutionState {
+        mut state_root_hash,
+        block_time,
+        protocol_version,
+    } = execution_state;
+
+    // Create a new EngineState that reads from LMDB but only caches changes in memory.  It is never
+    // written back to LMDB.
+    let scratch_state = engine_state.get_scratch_engine_state();
+
+    let mut execution_results = Vec::with_capacity(deploys.len());
+    for deploy in deploys {
+        let deploy_hash = deploy.deploy_hash;
+        let execute_request = ExecuteRequest::new(
+            state_root_hash,
+            block_time.millis(),
+            vec![deploy],
+            protocol_version,
+            PublicKey::System,
+        );
+        let ee_execution_result = match execute(&scratch_state, None, execute_request)?
+            .into_iter()
+            .exactly_one()
+        {
+            Ok(ee_execution_result) => ee_execution_result,
+            Err(execution_results) => {
+                warn!(
+                    ?deploy_hash,
+                    "got more ({}) execution results from a single transaction",
+                    execution_results.len()
+                );
+                return Ok(None);
+            }
+        };
+        execution_results.push(ExecutionResult::from(&ee_execution_result));
+
+        let execution_effect: AdditiveMap<Key, Transform> = match ee_execution_result {
+            EngineExecutionResult::Success {
+                execution_journal, ..
+            }
+            | EngineExecutionResult::Failure {
+                execution_journal, ..
+            } => execution_journal,
+        }
+        .into();
+        state_root_hash =
+            commit_transforms(&scratch_state, None, state_root_hash, execution_effect)?;
+    }
+    Ok(Some(execution_results))
+}
+
+/// Estimates the gas required to execute the transaction, which must use standard payment, without
+/// commiting its effects.
+///
+/// The transaction is first executed with the largest payment amount the account can afford,
+/// capped at `block_gas_limit`.  The smallest sufficient payment amount is then searched for by
+/// re-executing it, starting from the gas consumed, so that each attempt is subject to the
+/// configured refund and fee handling.
+pub(crate) fn estimate_gas(
+    engine_state: &EngineState<LmdbGlobalState>,
+    execution_state: SpeculativeExecutionState,
+    deploy: DeployItem,
+    block_gas_limit: u64,
+) -> Result<Option<GasEstimate>, engine_state::Error> {
+    let gas_price = deploy.gas_price;
+    let mut max_payment_amount = Motes::from_gas(Gas::from(block_gas_limit), gas_price)
+        .map(|motes| motes.value())
+        .unwrap_or_else(U512::max_value);
+    if let Some(balance) = get_main_purse_balance(
+        engine_state,
+        execution_state.state_root_hash,
+        deploy.address,
+    )? {
+        max_payment_amount = cmp::min(max_payment_amount, balance);
+    }
+
+    let execute_with_payment_amount = |payment_amount: U512, trace: bool| {
+        let mut deploy = deploy.clone();
+        deploy.payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: runtime_args! {
+                standard_payment::ARG_AMOUNT => payment_amount,
+            },
+        };
+        execute_only(engine_state, execution_state.clone(), deploy, trace)
+    };
+
+    let (execution_result, execution_trace) =
+        match execute_with_payment_amount(max_payment_amount, true)? {
+            Some((execution_result, Some(execution_trace))) => (execution_result, execution_trace),
+            _ => return Ok(None),
+        };
+
+    let recommended_payment_amount = match &execution_result {
+        ExecutionResult::Failure { .. } => None,
+        ExecutionResult::Success { cost, .. } => {
+            // A payment amount smaller than the cost of the gas consumed can never be sufficient.
+            let gas_consumed_amount = cost.saturating_mul(U512::from(gas_price));
+            let mut insufficient = gas_consumed_amount.saturating_sub(U512::one());
+            let mut sufficient = max_payment_amount;
+            let mut candidate = gas_consumed_amount;
+            for _ in 0..MAX_GAS_ESTIMATION_ATTEMPTS {
+                if candidate <= insufficient || candidate >= sufficient {
+                    break;
+                }
+                match execute_with_payment_amount(candidate, false)? {
+                    Some((ExecutionResult::Success { .. }, _)) => sufficient = candidate,
+                    Some((ExecutionResult::Failure { .. }, _)) => insufficient = candidate,
+                    None => return Ok(None),
+                }
+                candidate = insufficient + (sufficient - insufficient) / 2;
+            }
+            Some(sufficient)
+        }
+    };
+
+    Ok(Some(GasEstimate {
+        execution_result,
+        payment_gas: execution_trace.payment_cost,
+        session_gas: execution_trace.session_cost,
+        recommended_payment_amount,
+    }))
+}
+
+/// Returns the balance of the main purse of the given account, or `None` if it doesn't exist.
+fn get_main_purse_balance(
+    engine_state: &EngineState<LmdbGlobalState>,
+    state_root_hash: Digest,
+    account_hash: AccountHash,
+) -> Result<Option<U512>, engine_state::Error> {
+    let correlation_id = CorrelationId::new();
+    let query_request = QueryRequest::new(state_root_hash, Key::Account(account_hash), vec![]);
+    let main_purse = match engine_state.run_query(correlation_id, query_request)? {
+        QueryResult::Success { value, .. } => match value.as_account() {
+            Some(account) => account.main_purse(),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    match engine_state.get_purse_balance(correlation_id, state_root_hash, main_purse)? {
+        BalanceResult::Success { motes, .. } => Ok(Some(motes)),
+        BalanceResult::RootNotFound => Ok(None),
+    }
 }
 
 fn execute<S>(
@@ -469,6 +846,21 @@ where
     result
 }
 
+fn execute_with_trace<S>(
+    engine_state: &EngineState<S>,
+    execute_request: ExecuteRequest,
+) -> Result<(ExecutionResults, Vec<ExecutionTrace>), engine_state::Error>
+where
+    S: StateProvider + CommitProvider,
+    S::Error: Into<execution::Error>,
+{
+    trace!(?execute_request, "execute with trace");
+    let correlation_id = CorrelationId::new();
+    let result = engine_state.run_execute_with_trace(correlation_id, execute_request);
+    trace!(?result, "execute with trace result");
+    result
+}
+
 fn commit_step<S>(
     engine_state: &EngineState<S>,
     maybe_metrics: Option<Arc<Metrics>>,
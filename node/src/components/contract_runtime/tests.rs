@@ -1,13 +1,27 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    iter,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use derive_more::{Display, From};
+use itertools::Itertools;
 use prometheus::Registry;
 use rand::RngCore;
 use serde::Serialize;
 use tempfile::TempDir;
 
-use casper_execution_engine::core::engine_state::ExecutableDeployItem;
-use casper_types::{runtime_args, EraId, PublicKey, RuntimeArgs, SecretKey, TimeDiff, U512};
+use casper_execution_engine::{
+    core::{
+        engine_state::{BalanceResult, ExecutableDeployItem, QueryRequest, QueryResult},
+        runtime::HostFunctionArg,
+    },
+    shared::newtypes::CorrelationId,
+};
+use casper_types::{
+    bytesrepr::Bytes, runtime_args, CLValue, EraId, ExecutionResult, Key, Phase, PublicKey,
+    RuntimeArgs, SecretKey, StoredValue, TimeDiff, U512,
+};
 
 use super::*;
 use crate::{
@@ -28,6 +42,7 @@ use crate::{
 const RECENT_ERA_COUNT: u64 = 5;
 const MAX_TTL: TimeDiff = TimeDiff::from_seconds(86400);
 const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_GLOBAL_STATE_SIZE: usize = 100 * 1024 * 1024;
 
 /// Top-level event for the reactor.
 #[derive(Debug, From, Serialize, Display)]
@@ -125,6 +140,7 @@ impl reactor::Reactor for Reactor {
             chainspec.core_config.allow_unrestricted_transfers,
             chainspec.core_config.refund_handling,
             chainspec.core_config.fee_handling,
+            chainspec.deploy_config.block_gas_limit,
         )?;
 
         let reactor = Reactor {
@@ -201,6 +217,148 @@ fn execution_completed(event: &Event) -> bool {
     matches!(event, Event::MetaBlockAnnouncement(_))
 }
 
+/// Creates a native transfer deploy paying the wasmless transfer cost via standard payment.
+fn transfer_deploy(chainspec: &Chainspec, from: &SecretKey, to: PublicKey, amount: U512) -> Deploy {
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! {
+          "amount" => U512::from(chainspec.system_costs_config.wasmless_transfer_cost()),
+        },
+    };
+    let session = ExecutableDeployItem::Transfer {
+        args: runtime_args! {
+          "amount" => amount,
+          "target" => to,
+          "id" => Some(9_u64),
+        },
+    };
+    Deploy::new(
+        Timestamp::now(),
+        TimeDiff::from_seconds(100),
+        1,
+        vec![],
+        chainspec.network_config.name.clone(),
+        payment,
+        session,
+        from,
+        None,
+    )
+}
+
+/// Returns the secret key of the given node in the local chainspec, funded at genesis.
+fn node_secret_key(index: usize) -> SecretKey {
+    SecretKey::from_file(
+        RESOURCES_PATH
+            .join("local")
+            .join("secret_keys")
+            .join(format!("node-{}.pem", index)),
+    )
+    .unwrap()
+}
+
+/// Creates a deploy running the session Wasm given in text format, paying `payment_amount` via
+/// standard payment.
+fn session_deploy(
+    chainspec: &Chainspec,
+    from: &SecretKey,
+    payment_amount: U512,
+    session_wat: &str,
+) -> Deploy {
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! {
+          "amount" => payment_amount,
+        },
+    };
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::from(wat::parse_str(session_wat).unwrap()),
+        args: RuntimeArgs::new(),
+    };
+    Deploy::new(
+        Timestamp::now(),
+        TimeDiff::from_seconds(100),
+        1,
+        vec![],
+        chainspec.network_config.name.clone(),
+        payment,
+        session,
+        from,
+        None,
+    )
+}
+
+/// A contract runtime in a test reactor, with the local chainspec's genesis committed.
+struct TestFixture {
+    runner: Runner<ConditionCheckReactor<Reactor>>,
+    chainspec: Arc<Chainspec>,
+    post_commit_genesis_state_hash: Digest,
+}
+
+impl TestFixture {
+    async fn new(rng: &mut NodeRng, max_global_state_size: usize) -> Self {
+        testing::init_logging();
+
+        let config = Config {
+            max_global_state_size: Some(max_global_state_size),
+            ..Config::default()
+        };
+        let (chainspec, chainspec_raw_bytes) =
+            <(Chainspec, ChainspecRawBytes)>::from_resources("local");
+        let chainspec = Arc::new(chainspec);
+        let chainspec_raw_bytes = Arc::new(chainspec_raw_bytes);
+
+        let runner: Runner<ConditionCheckReactor<Reactor>> = Runner::new(
+            config,
+            Arc::clone(&chainspec),
+            Arc::clone(&chainspec_raw_bytes),
+            rng,
+        )
+        .await
+        .unwrap();
+
+        let post_commit_genesis_state_hash = runner
+            .reactor()
+            .inner()
+            .contract_runtime
+            .commit_genesis(chainspec.as_ref(), chainspec_raw_bytes.as_ref())
+            .unwrap()
+            .post_state_hash;
+
+        TestFixture {
+            runner,
+            chainspec,
+            post_commit_genesis_state_hash,
+        }
+    }
+
+    fn contract_runtime(&self) -> &ContractRuntime {
+        &self.runner.reactor().inner().contract_runtime
+    }
+
+    fn engine_state(&self) -> &EngineState<LmdbGlobalState> {
+        self.contract_runtime().engine_state.as_ref()
+    }
+
+    /// The pre-state for executing the first block on top of genesis.
+    fn genesis_pre_state(&self) -> ExecutionPreState {
+        ExecutionPreState::new(
+            0,
+            self.post_commit_genesis_state_hash,
+            BlockHash::default(),
+            Digest::default(),
+        )
+    }
+
+    /// The state for executing deploys speculatively on top of genesis.
+    fn genesis_execution_state(&self) -> SpeculativeExecutionState {
+        SpeculativeExecutionState {
+            state_root_hash: self.post_commit_genesis_state_hash,
+            block_time: Timestamp::now(),
+            protocol_version: self.chainspec.protocol_version(),
+        }
+    }
+}
+
 #[tokio::test]
 async fn should_not_set_shared_pre_state_to_lower_block_height() {
     testing::init_logging();
@@ -392,3 +550,450 @@ async fn should_not_set_shared_pre_state_to_lower_block_height() {
         next_block_height
     );
 }
+
+#[tokio::test]
+async fn should_chain_effects_in_speculative_sequence_execution() {
+    let mut rng = crate::new_rng();
+    let rng = &mut rng;
+    let fixture = TestFixture::new(rng, MAX_GLOBAL_STATE_SIZE).await;
+    let chainspec = &fixture.chainspec;
+
+    // Create a transfer from node-1's main account to a new account, followed by a transfer from
+    // that new account, which can only succeed if it sees the effects of the first one.
+    let new_secret_key = SecretKey::random(rng);
+    let minimum_motes = chainspec.deploy_config.native_transfer_minimum_motes;
+    let transfer_cost = chainspec.system_costs_config.wasmless_transfer_cost();
+    let deploys = vec![
+        transfer_deploy(
+            chainspec,
+            &node_secret_key(1),
+            PublicKey::from(&new_secret_key),
+            U512::from(minimum_motes) * 10 + transfer_cost,
+        ),
+        transfer_deploy(
+            chainspec,
+            &new_secret_key,
+            PublicKey::random(rng),
+            U512::from(minimum_motes),
+        ),
+    ];
+
+    let execution_state = fixture.genesis_execution_state();
+    let results = execute_only_sequence(
+        fixture.engine_state(),
+        execution_state.clone(),
+        deploys.iter().cloned().map(DeployItem::from).collect(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(results.len(), 2);
+    for result in results {
+        assert!(
+            matches!(result, ExecutionResult::Success { .. }),
+            "{:?}",
+            result
+        );
+    }
+
+    // Executed on its own, the second transfer should not succeed.
+    let result = execute_only(
+        fixture.engine_state(),
+        execution_state,
+        DeployItem::from(deploys[1].clone()),
+        false,
+    );
+    assert!(!matches!(
+        result,
+        Ok(Some((ExecutionResult::Success { .. }, _)))
+    ));
+}
+
+#[tokio::test]
+async fn should_apply_state_overrides_in_speculative_execution() {
+    let mut rng = crate::new_rng();
+    let rng = &mut rng;
+    let fixture = TestFixture::new(rng, MAX_GLOBAL_STATE_SIZE).await;
+    let engine_state = fixture.engine_state();
+    let post_commit_genesis_state_hash = fixture.post_commit_genesis_state_hash;
+
+    // Find node-1's main purse and its balance.
+    let node_1_secret_key = node_secret_key(1);
+    let node_1_account_key = Key::Account(PublicKey::from(&node_1_secret_key).to_account_hash());
+    let main_purse = match engine_state
+        .run_query(
+            CorrelationId::new(),
+            QueryRequest::new(post_commit_genesis_state_hash, node_1_account_key, vec![]),
+        )
+        .unwrap()
+    {
+        QueryResult::Success { value, .. } => value.as_account().unwrap().main_purse(),
+        _ => panic!("should find node-1's account"),
+    };
+    let balance = match engine_state
+        .get_purse_balance(
+            CorrelationId::new(),
+            post_commit_genesis_state_hash,
+            main_purse,
+        )
+        .unwrap()
+    {
+        BalanceResult::Success { motes, .. } => motes,
+        _ => panic!("should find node-1's balance"),
+    };
+
+    // Try to transfer more than node-1 holds.
+    let deploy = DeployItem::from(transfer_deploy(
+        &fixture.chainspec,
+        &node_1_secret_key,
+        PublicKey::random(rng),
+        balance + 1,
+    ));
+    let execution_state = fixture.genesis_execution_state();
+
+    let result =
+        execute_only(engine_state, execution_state.clone(), deploy.clone(), false).unwrap();
+    assert!(matches!(
+        result,
+        Some((ExecutionResult::Failure { .. }, None))
+    ));
+
+    // With node-1's balance overridden, the transfer should succeed.
+    let state_overrides = vec![(
+        Key::Balance(main_purse.addr()),
+        StoredValue::CLValue(CLValue::from_t(balance * 2).unwrap()),
+    )];
+    let result = execute_only_with_state_overrides(
+        engine_state,
+        execution_state,
+        deploy,
+        state_overrides,
+        false,
+    )
+    .unwrap();
+    assert!(
+        matches!(result, Some((ExecutionResult::Success { .. }, None))),
+        "{:?}",
+        result
+    );
+
+    // The persisted global state should be unaffected.
+    let balance_after = match engine_state
+        .get_purse_balance(
+            CorrelationId::new(),
+            post_commit_genesis_state_hash,
+            main_purse,
+        )
+        .unwrap()
+    {
+        BalanceResult::Success { motes, .. } => motes,
+        _ => panic!("should find node-1's balance"),
+    };
+    assert_eq!(balance_after, balance);
+}
+
+#[tokio::test]
+async fn should_trace_host_function_calls_in_speculative_execution() {
+    // Checks for the named key "test", then reverts with the user error 7.
+    const SESSION: &str = r#"
+        (module
+          (import "env" "casper_has_key" (func $has_key (param i32 i32) (result i32)))
+          (import "env" "casper_revert" (func $revert (param i32)))
+          (memory 1)
+          (data (i32.const 0) "\04\00\00\00test")
+          (func (export "call")
+            (drop (call $has_key (i32.const 0) (i32.const 8)))
+            (call $revert (i32.const 7))))
+    "#;
+
+    let mut rng = crate::new_rng();
+    let fixture = TestFixture::new(&mut rng, MAX_GLOBAL_STATE_SIZE).await;
+    let deploy = session_deploy(
+        &fixture.chainspec,
+        &node_secret_key(1),
+        U512::from(10_000_000_000_u64),
+        SESSION,
+    );
+
+    let (execution_result, maybe_execution_trace) = execute_only(
+        fixture.engine_state(),
+        fixture.genesis_execution_state(),
+        DeployItem::from(deploy),
+        true,
+    )
+    .unwrap()
+    .unwrap();
+    assert!(
+        matches!(execution_result, ExecutionResult::Failure { .. }),
+        "{:?}",
+        execution_result
+    );
+
+    let host_function_calls = maybe_execution_trace
+        .expect("should have trace")
+        .host_function_calls;
+    assert_eq!(host_function_calls.len(), 2, "{:?}", host_function_calls);
+    let has_key_call = &host_function_calls[0];
+    assert_eq!(has_key_call.name, "casper_has_key");
+    assert_eq!(
+        has_key_call.args,
+        vec![HostFunctionArg::Name("test".to_string())]
+    );
+    assert!(has_key_call.trap.is_none());
+    let revert_call = &host_function_calls[1];
+    assert_eq!(revert_call.name, "casper_revert");
+    assert_eq!(revert_call.args, vec![HostFunctionArg::ErrorCode(7)]);
+    assert_eq!(revert_call.phase, Phase::Session);
+    assert_eq!(revert_call.depth, 1);
+    assert!(revert_call.trap.is_some());
+}
+
+#[tokio::test]
+async fn should_estimate_gas() {
+    // Pushes a constant and drops it.
+    const SESSION: &str = r#"
+        (module
+          (memory 1)
+          (func (export "call")
+            (drop (i32.const 1))))
+    "#;
+
+    let mut rng = crate::new_rng();
+    let fixture = TestFixture::new(&mut rng, MAX_GLOBAL_STATE_SIZE).await;
+    let node_1_secret_key = node_secret_key(1);
+    let deploy_with_payment_amount = |payment_amount: U512| {
+        DeployItem::from(session_deploy(
+            &fixture.chainspec,
+            &node_1_secret_key,
+            payment_amount,
+            SESSION,
+        ))
+    };
+    let execution_state = fixture.genesis_execution_state();
+
+    let gas_estimate = estimate_gas(
+        fixture.engine_state(),
+        execution_state.clone(),
+        deploy_with_payment_amount(U512::one()),
+        fixture.chainspec.deploy_config.block_gas_limit,
+    )
+    .unwrap()
+    .unwrap();
+    let cost = match &gas_estimate.execution_result {
+        ExecutionResult::Success { cost, .. } => *cost,
+        ExecutionResult::Failure { .. } => panic!("{:?}", gas_estimate.execution_result),
+    };
+    assert!(!gas_estimate.session_gas.value().is_zero());
+    assert_eq!(
+        gas_estimate.payment_gas.value() + gas_estimate.session_gas.value(),
+        cost
+    );
+
+    // The recommended payment amount should be the smallest one sufficient to execute the deploy.
+    let recommended_payment_amount = gas_estimate.recommended_payment_amount.unwrap();
+    let result = execute_only(
+        fixture.engine_state(),
+        execution_state.clone(),
+        deploy_with_payment_amount(recommended_payment_amount),
+        false,
+    )
+    .unwrap();
+    assert!(matches!(result, Some((ExecutionResult::Success { .. }, _))));
+    let result = execute_only(
+        fixture.engine_state(),
+        execution_state,
+        deploy_with_payment_amount(recommended_payment_amount - 1),
+        false,
+    )
+    .unwrap();
+    assert!(matches!(result, Some((ExecutionResult::Failure { .. }, _))));
+}
+
+/// Executes the deploys in a block with the given pre-state, using the given number of threads.
+///
+/// Returns the block and execution results, the duration of the execution, and the number of
+/// deploys which had to be re-executed.
+fn execute_in_block(
+    fixture: &TestFixture,
+    pre_state: ExecutionPreState,
+    block_time: Timestamp,
+    deploys: &[Deploy],
+    parallel_execution_threads: usize,
+) -> (BlockAndExecutionResults, Duration, u64) {
+    let block_payload = BlockPayload::new(
+        vec![],
+        deploys.iter().map(DeployHashWithApprovals::from).collect(),
+        vec![],
+        true,
+    );
+    let finalized_block = FinalizedBlock::new(
+        block_payload,
+        None,
+        block_time,
+        EraId::new(0),
+        pre_state.next_block_height,
+        PublicKey::System,
+    );
+    let chainspec = &fixture.chainspec;
+    let metrics = Arc::new(Metrics::new(&Registry::new()).unwrap());
+    let start = Instant::now();
+    let block_and_execution_results = execute_finalized_block(
+        fixture.engine_state(),
+        Some(Arc::clone(&metrics)),
+        chainspec.protocol_version(),
+        pre_state,
+        finalized_block,
+        deploys.to_vec(),
+        chainspec.protocol_config.activation_point.era_id(),
+        0,
+        chainspec.core_config.prune_batch_size,
+        parallel_execution_threads,
+    )
+    .unwrap();
+    (
+        block_and_execution_results,
+        start.elapsed(),
+        metrics.deploy_reexecutions.get(),
+    )
+}
+
+#[tokio::test]
+async fn should_produce_same_block_with_parallel_execution() {
+    let mut rng = crate::new_rng();
+    let rng = &mut rng;
+    let fixture = TestFixture::new(rng, MAX_GLOBAL_STATE_SIZE).await;
+    let chainspec = &fixture.chainspec;
+
+    // Create independent transfers from each of the nodes' main accounts, further transfers from
+    // node-1's main account, and a transfer from a new account which only succeeds if it sees the
+    // effects of the transfer funding it.
+    let secret_keys = (1..=5).map(node_secret_key).collect_vec();
+    let new_secret_key = SecretKey::random(rng);
+    let minimum_motes = U512::from(chainspec.deploy_config.native_transfer_minimum_motes);
+    let transfer_cost = chainspec.system_costs_config.wasmless_transfer_cost();
+    let mut deploys = secret_keys
+        .iter()
+        .chain(iter::repeat(&secret_keys[0]).take(3))
+        .map(|secret_key| {
+            transfer_deploy(chainspec, secret_key, PublicKey::random(rng), minimum_motes)
+        })
+        .collect_vec();
+    deploys.push(transfer_deploy(
+        chainspec,
+        &secret_keys[1],
+        PublicKey::from(&new_secret_key),
+        minimum_motes * 10 + transfer_cost,
+    ));
+    deploys.push(transfer_deploy(
+        chainspec,
+        &new_secret_key,
+        PublicKey::random(rng),
+        minimum_motes,
+    ));
+
+    let block_time = Timestamp::now();
+    let execute = |deploys: &[Deploy], threads| {
+        let (block_and_execution_results, _, reexecutions) = execute_in_block(
+            &fixture,
+            fixture.genesis_pre_state(),
+            block_time,
+            deploys,
+            threads,
+        );
+        (block_and_execution_results, reexecutions)
+    };
+
+    let (sequential, _) = execute(&deploys, 1);
+    for (_, _, result) in &sequential.execution_results {
+        assert!(
+            matches!(result, ExecutionResult::Success { .. }),
+            "{:?}",
+            result
+        );
+    }
+
+    // The deploys from the same accounts as earlier ones, and from the new account, conflict.
+    let (parallel, reexecutions) = execute(&deploys, 4);
+    assert_eq!(parallel.block, sequential.block);
+    assert_eq!(parallel.execution_results, sequential.execution_results);
+    assert_eq!(reexecutions, 5);
+
+    // The deploys from distinct accounts only share the payment and fee purses, so their
+    // optimistic results can all be used.
+    let independent_deploys = &deploys[..secret_keys.len()];
+    let (sequential, _) = execute(independent_deploys, 1);
+    let (parallel, reexecutions) = execute(independent_deploys, 4);
+    assert_eq!(parallel.block, sequential.block);
+    assert_eq!(parallel.execution_results, sequential.execution_results);
+    assert_eq!(reexecutions, 0);
+}
+
+/// Compares the time it takes to execute a block of independent deploys sequentially and in
+/// parallel.
+///
+/// Run with `cargo test --release -p casper-node --lib -- --ignored --nocapture
+/// bench_parallel_execution`.
+#[ignore = "benchmark"]
+#[tokio::test]
+async fn bench_parallel_execution() {
+    const ACCOUNT_COUNT: usize = 200;
+    const RUNS: usize = 3;
+
+    let mut rng = crate::new_rng();
+    let rng = &mut rng;
+    let fixture = TestFixture::new(rng, 1024 * 1024 * 1024).await;
+    let chainspec = &fixture.chainspec;
+    let block_time = Timestamp::from(1_000_000);
+
+    // Fund the accounts in a first block.
+    let secret_keys = iter::repeat_with(|| SecretKey::random(rng))
+        .take(ACCOUNT_COUNT)
+        .collect_vec();
+    let minimum_motes = U512::from(chainspec.deploy_config.native_transfer_minimum_motes);
+    let transfer_cost = chainspec.system_costs_config.wasmless_transfer_cost();
+    let funding_secret_key = node_secret_key(1);
+    let funding_deploys = secret_keys
+        .iter()
+        .map(|secret_key| {
+            transfer_deploy(
+                chainspec,
+                &funding_secret_key,
+                PublicKey::from(secret_key),
+                minimum_motes * 10 + transfer_cost,
+            )
+        })
+        .collect_vec();
+    let (funding_block, _, _) = execute_in_block(
+        &fixture,
+        fixture.genesis_pre_state(),
+        block_time,
+        &funding_deploys,
+        1,
+    );
+    let pre_state = ExecutionPreState::from_block_header(funding_block.block.header());
+
+    // Then execute a block with a transfer from each of them.
+    let deploys = secret_keys
+        .iter()
+        .map(|secret_key| {
+            transfer_deploy(chainspec, secret_key, PublicKey::random(rng), minimum_motes)
+        })
+        .collect_vec();
+    let (sequential, _, _) = execute_in_block(&fixture, pre_state.clone(), block_time, &deploys, 1);
+    for threads in [1, 2, 4, 8] {
+        let mut durations = vec![];
+        for _ in 0..RUNS {
+            let (result, duration, reexecutions) =
+                execute_in_block(&fixture, pre_state.clone(), block_time, &deploys, threads);
+            assert_eq!(result.block, sequential.block);
+            // All deploys pay to the same purses, but none of them need to be re-executed.
+            assert_eq!(reexecutions, 0);
+            durations.push(duration);
+        }
+        println!(
+            "{} deploys, {} threads: {:?}, no re-executions",
+            ACCOUNT_COUNT,
+            threads,
+            durations.into_iter().min().unwrap()
+        );
+    }
+}
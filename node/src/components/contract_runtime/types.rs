@@ -6,7 +6,7 @@ use casper_execution_engine::{
     core::engine_state::GetEraValidatorsRequest, shared::execution_journal::ExecutionJournal,
 };
 use casper_hashing::Digest;
-use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, U512};
+use casper_types::{EraId, ExecutionResult, Gas, ProtocolVersion, PublicKey, U512};
 
 use crate::types::{ApprovalsHashes, Block, DeployHash, DeployHeader};
 
@@ -107,3 +107,18 @@ pub struct BlockAndExecutionResults {
     pub(crate) maybe_step_effect_and_upcoming_era_validators:
         Option<StepEffectAndUpcomingEraValidators>,
 }
+
+/// The gas consumed by speculatively executing a deploy, and the payment amount found to be
+/// sufficient for it.
+#[derive(Clone, Debug)]
+pub(crate) struct GasEstimate {
+    /// The result of executing the deploy with the maximum payment amount.
+    pub(crate) execution_result: ExecutionResult,
+    /// The gas consumed by the payment phase.
+    pub(crate) payment_gas: Gas,
+    /// The gas consumed by the session phase.
+    pub(crate) session_gas: Gas,
+    /// The smallest standard payment amount in motes with which the deploy was verified to
+    /// execute successfully, or `None` if it fails even with the maximum payment amount.
+    pub(crate) recommended_payment_amount: Option<U512>,
+}
@@ -15,11 +15,17 @@
 //!
 //! This component uses a ring buffer for outbound events providing some robustness against
 //! unintended subscriber disconnects, if a disconnected subscriber re-subscribes before the buffer
-//! has advanced past their last received event.
+//! has advanced past their last received event.  Optionally, events can also be written to a
+//! size-bounded on-disk journal, in which case re-subscribing clients are served from that instead,
+//! including after a node restart.
+//!
+//! Subscribers can also have the server filter events relating to deploys, by the deploy's account,
+//! by the `Key`s touched in the execution effects, or by deploy hash, via the URL query string.
 
 mod config;
 mod event;
 mod event_indexer;
+mod event_journal;
 mod http_server;
 mod sse_server;
 #[cfg(test)]
@@ -49,6 +55,7 @@ use crate::{
 pub use config::Config;
 pub(crate) use event::Event;
 use event_indexer::{EventIndex, EventIndexer};
+use event_journal::EventJournal;
 use sse_server::ChannelsAndFilter;
 pub(crate) use sse_server::SseData;
 
@@ -128,6 +135,24 @@ impl EventStreamServer {
 
         let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
 
+        let mut event_indexer = EventIndexer::new(self.storage_path.clone());
+        let journal = if self.config.journal.enable {
+            match EventJournal::open(&self.storage_path, &self.config.journal) {
+                Ok(journal) => {
+                    if let Some(last_id) = journal.last_id() {
+                        event_indexer.skip_past(last_id);
+                    }
+                    Some(journal)
+                }
+                Err(error) => {
+                    warn!(%error, "failed to open sse journal, continuing without it");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let listening_address = match self.config.cors_origin.as_str() {
             "" => {
                 let (listening_address, server_with_shutdown) = warp::serve(sse_filter)
@@ -147,6 +172,7 @@ impl EventStreamServer {
                     sse_data_receiver,
                     event_broadcaster,
                     new_subscriber_info_receiver,
+                    journal,
                 ));
                 listening_address
             }
@@ -169,6 +195,7 @@ impl EventStreamServer {
                     sse_data_receiver,
                     event_broadcaster,
                     new_subscriber_info_receiver,
+                    journal,
                 ));
                 listening_address
             }
@@ -192,6 +219,7 @@ impl EventStreamServer {
                     sse_data_receiver,
                     event_broadcaster,
                     new_subscriber_info_receiver,
+                    journal,
                 ));
                 listening_address
             }
@@ -199,8 +227,6 @@ impl EventStreamServer {
 
         info!(address=%listening_address, "started event stream server");
 
-        let event_indexer = EventIndexer::new(self.storage_path.clone());
-
         self.sse_server = Some(InnerServer {
             sse_data_sender,
             event_indexer,
@@ -15,6 +15,15 @@ const DEFAULT_MAX_CONCURRENT_SUBSCRIBERS: u32 = 100;
 /// Default CORS origin.
 const DEFAULT_CORS_ORIGIN: &str = "";
 
+/// Default maximum total size of the on-disk event journal in bytes (1 GiB).
+const DEFAULT_JOURNAL_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Default maximum size of a single event journal segment file in bytes (64 MiB).
+const DEFAULT_JOURNAL_MAX_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Default maximum number of journal replays served at any one time.
+const DEFAULT_JOURNAL_MAX_CONCURRENT_REPLAYS: u32 = 4;
+
 /// SSE HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -34,6 +43,10 @@ pub struct Config {
 
     /// CORS origin.
     pub cors_origin: String,
+
+    /// On-disk event journal configuration.
+    #[serde(default)]
+    pub journal: JournalConfig,
 }
 
 impl Config {
@@ -45,6 +58,7 @@ impl Config {
             event_stream_buffer_length: DEFAULT_EVENT_STREAM_BUFFER_LENGTH,
             max_concurrent_subscribers: DEFAULT_MAX_CONCURRENT_SUBSCRIBERS,
             cors_origin: DEFAULT_CORS_ORIGIN.to_string(),
+            journal: JournalConfig::default(),
         }
     }
 }
@@ -54,3 +68,34 @@ impl Default for Config {
         Config::new()
     }
 }
+
+/// Configuration of the persistent, size-bounded event journal backing `start_from` replay.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct JournalConfig {
+    /// Setting to enable the on-disk event journal.
+    pub enable: bool,
+
+    /// Maximum total size in bytes of all journal segments.  The oldest segments are deleted once
+    /// this is exceeded.
+    pub max_size: u64,
+
+    /// Maximum size in bytes of a single journal segment before a new one is started.
+    pub max_segment_size: u64,
+
+    /// Maximum number of subscribers being served historical events from the journal at any one
+    /// time.  Further subscribers wait for a replay to finish before theirs is started.
+    pub max_concurrent_replays: u32,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        JournalConfig {
+            enable: false,
+            max_size: DEFAULT_JOURNAL_MAX_SIZE,
+            max_segment_size: DEFAULT_JOURNAL_MAX_SEGMENT_SIZE,
+            max_concurrent_replays: DEFAULT_JOURNAL_MAX_CONCURRENT_REPLAYS,
+        }
+    }
+}
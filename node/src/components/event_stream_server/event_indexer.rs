@@ -55,6 +55,18 @@ impl EventIndexer {
         index
     }
 
+    /// Ensures the next index is later than `index`.
+    ///
+    /// This is used where the event journal holds later events than the cached index accounts for,
+    /// e.g. after an unclean shutdown prevented the cache from being written.
+    pub(super) fn skip_past(&mut self, index: EventIndex) {
+        let next_index = index.wrapping_add(1);
+        if self.index.wrapping_sub(next_index) > EventIndex::MAX / 2 {
+            debug!(cached_index = %self.index, %next_index, "advancing sse index past journal");
+            self.index = next_index;
+        }
+    }
+
     #[cfg(test)]
     pub(super) fn current_index(&self) -> EventIndex {
         self.index
@@ -121,6 +133,28 @@ mod tests {
         assert_eq!(event_indexer.next_index(), 0);
     }
 
+    #[test]
+    fn should_skip_past_journaled_index() {
+        let _ = logging::init();
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let mut event_indexer = EventIndexer::new(tempdir.path().to_path_buf());
+        event_indexer.index = 10;
+
+        // Earlier indices should be ignored.
+        event_indexer.skip_past(5);
+        assert_eq!(event_indexer.current_index(), 10);
+        event_indexer.skip_past(9);
+        assert_eq!(event_indexer.current_index(), 10);
+
+        // Later ones should advance the index, including across the wrapping point.
+        event_indexer.skip_past(20);
+        assert_eq!(event_indexer.current_index(), 21);
+        event_indexer.index = EventIndex::MAX - 1;
+        event_indexer.skip_past(EventIndex::MAX);
+        assert_eq!(event_indexer.current_index(), 0);
+    }
+
     #[test]
     fn should_reset_index_on_cache_read_failure() {
         let _ = logging::init();
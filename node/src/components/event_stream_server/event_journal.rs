@@ -0,0 +1,545 @@
+//! A persistent, size-bounded journal of emitted SSEs.
+//!
+//! Events are appended to segment files in a dedicated directory under the node's storage path.
+//! Each record is laid out as `[payload length: u32 LE][event ID: u32 LE][JSON-encoded SseData]`.
+//! Once a segment reaches the configured maximum size a new one is started, and once the total
+//! size of all segments exceeds the configured maximum, the oldest segments are deleted.
+//!
+//! A partially-written record at the end of the newest segment (e.g. after a crash) is truncated
+//! away when the journal is opened.
+
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use super::{
+    config::JournalConfig,
+    sse_server::{Id, ServerSentEvent},
+    SseData,
+};
+
+const JOURNAL_DIR_NAME: &str = "sse_journal";
+const SEGMENT_FILE_EXTENSION: &str = "log";
+const RECORD_HEADER_LENGTH: usize = 8;
+
+/// A single segment file of the journal.
+#[derive(Clone, Debug)]
+struct Segment {
+    path: PathBuf,
+    /// The ID of the first event held in the segment, or `None` if the segment is empty.
+    first_id: Option<Id>,
+    size: u64,
+}
+
+/// The on-disk event journal.
+#[derive(Debug)]
+pub(super) struct EventJournal {
+    dir: PathBuf,
+    max_size: u64,
+    max_segment_size: u64,
+    /// The segments, ordered oldest first.  There is always at least one.
+    segments: VecDeque<Segment>,
+    /// The sequence number of the newest segment.
+    newest_sequence: u64,
+    /// The file handle of the newest segment, opened for appending.
+    writer: File,
+    last_id: Option<Id>,
+}
+
+impl EventJournal {
+    /// Opens the journal under `storage_path`, creating it if it doesn't exist.
+    pub(super) fn open(storage_path: &Path, config: &JournalConfig) -> io::Result<Self> {
+        let dir = storage_path.join(JOURNAL_DIR_NAME);
+        fs::create_dir_all(&dir)?;
+
+        let mut sequences = vec![];
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str())
+                != Some(SEGMENT_FILE_EXTENSION)
+            {
+                continue;
+            }
+            match path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            {
+                Some(sequence) => sequences.push(sequence),
+                None => warn!(file = %path.display(), "ignoring unexpected sse journal file"),
+            }
+        }
+        sequences.sort_unstable();
+
+        let mut segments = VecDeque::with_capacity(sequences.len().max(1));
+        let mut last_id = None;
+        let newest_sequence = sequences.last().copied().unwrap_or_default();
+        for sequence in sequences {
+            let path = segment_path(&dir, sequence);
+            let is_newest = sequence == newest_sequence;
+            let (first_id, segment_last_id, valid_length) = scan_segment(&path)?;
+            let mut size = fs::metadata(&path)?.len();
+            if valid_length < size {
+                warn!(
+                    file = %path.display(),
+                    valid_length,
+                    size,
+                    "sse journal segment has a corrupt or incomplete tail"
+                );
+                // Only the newest segment is appended to, so only truncate that one.
+                if is_newest {
+                    OpenOptions::new()
+                        .write(true)
+                        .open(&path)?
+                        .set_len(valid_length)?;
+                    size = valid_length;
+                }
+            }
+            if segment_last_id.is_some() {
+                last_id = segment_last_id;
+            }
+            segments.push_back(Segment {
+                path,
+                first_id,
+                size,
+            });
+        }
+
+        if segments.is_empty() {
+            segments.push_back(Segment {
+                path: segment_path(&dir, newest_sequence),
+                first_id: None,
+                size: 0,
+            });
+        }
+        let writer = open_for_append(&segments.back().expect("should have a segment").path)?;
+
+        debug!(
+            dir = %dir.display(),
+            segment_count = segments.len(),
+            ?last_id,
+            "opened sse journal"
+        );
+
+        let mut journal = EventJournal {
+            dir,
+            max_size: config.max_size,
+            max_segment_size: config.max_segment_size,
+            segments,
+            newest_sequence,
+            writer,
+            last_id,
+        };
+        journal.enforce_max_size()?;
+        Ok(journal)
+    }
+
+    /// Returns the ID of the most recently journaled event, if any.
+    pub(super) fn last_id(&self) -> Option<Id> {
+        self.last_id
+    }
+
+    /// Appends the given event to the journal.  Events without an ID are not journaled.
+    pub(super) fn append(&mut self, event: &ServerSentEvent) -> io::Result<()> {
+        let id = match event.id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        let payload = serde_json::to_vec(&event.data)?;
+        let payload_length = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "sse too large to journal"))?;
+        let mut record = Vec::with_capacity(RECORD_HEADER_LENGTH + payload.len());
+        record.extend_from_slice(&payload_length.to_le_bytes());
+        record.extend_from_slice(&id.to_le_bytes());
+        record.extend_from_slice(&payload);
+
+        if self.newest_segment().size > 0
+            && self.newest_segment().size + record.len() as u64 > self.max_segment_size
+        {
+            self.start_new_segment()?;
+        }
+
+        self.writer.write_all(&record)?;
+        let segment = self
+            .segments
+            .back_mut()
+            .expect("should always have a segment");
+        segment.size += record.len() as u64;
+        if segment.first_id.is_none() {
+            segment.first_id = Some(id);
+        }
+        self.last_id = Some(id);
+
+        self.enforce_max_size()
+    }
+
+    /// Returns a reader over the journal's current segments, suitable for use off the task which
+    /// appends to the journal.
+    pub(super) fn reader(&self) -> JournalReader {
+        JournalReader {
+            segments: self.segments.iter().cloned().collect(),
+            last_id: self.last_id,
+        }
+    }
+
+    fn newest_segment(&self) -> &Segment {
+        self.segments.back().expect("should always have a segment")
+    }
+
+    fn start_new_segment(&mut self) -> io::Result<()> {
+        self.writer.sync_data()?;
+        self.newest_sequence += 1;
+        let path = segment_path(&self.dir, self.newest_sequence);
+        self.writer = open_for_append(&path)?;
+        self.segments.push_back(Segment {
+            path,
+            first_id: None,
+            size: 0,
+        });
+        Ok(())
+    }
+
+    /// Deletes the oldest segments until the total size is within the limit.  The newest segment
+    /// is never deleted.
+    fn enforce_max_size(&mut self) -> io::Result<()> {
+        let mut total_size: u64 = self.segments.iter().map(|segment| segment.size).sum();
+        while total_size > self.max_size && self.segments.len() > 1 {
+            let oldest = self
+                .segments
+                .pop_front()
+                .expect("should have more than one segment");
+            total_size -= oldest.size;
+            debug!(file = %oldest.path.display(), "removing oldest sse journal segment");
+            match fs::remove_file(&oldest.path) {
+                Ok(()) => (),
+                Err(error) if error.kind() == ErrorKind::NotFound => (),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A snapshot of the journal's segments, used to replay events to a new subscriber.
+#[derive(Debug)]
+pub(super) struct JournalReader {
+    segments: Vec<Segment>,
+    last_id: Option<Id>,
+}
+
+impl JournalReader {
+    /// Sends all journaled events from `start_from` onwards to `sender`, oldest first.
+    ///
+    /// Event IDs wrap, so events are ordered by how far behind the most recent event they are.  If
+    /// `start_from` is older than the oldest journaled event, all journaled events are sent.  If
+    /// it is later than the most recent event, none are.
+    ///
+    /// Records are read one at a time and only once the previous one has been accepted by the
+    /// bounded `sender`, so a slow client holds back reading rather than buffering the journal in
+    /// memory.  This blocks the calling thread, and so must not be called on an async task.
+    pub(super) fn replay(&self, start_from: Id, sender: &mpsc::Sender<ServerSentEvent>) {
+        let last_id = match self.last_id {
+            Some(id) => id,
+            None => return,
+        };
+        let age = |id: Id| last_id.wrapping_sub(id);
+        let start_age = age(start_from);
+        if start_age > Id::MAX / 2 {
+            // `start_from` is ahead of the most recent event.
+            return;
+        }
+
+        // Skip segments which are wholly older than `start_from`.
+        let first_segment = self
+            .segments
+            .iter()
+            .rposition(|segment| {
+                segment
+                    .first_id
+                    .map_or(false, |first_id| age(first_id) >= start_age)
+            })
+            .unwrap_or_default();
+
+        for segment in &self.segments[first_segment..] {
+            let result = for_each_record(&segment.path, |id, data| {
+                if age(id) <= start_age {
+                    // The client may have disconnected already - stop reading if so.
+                    return sender
+                        .blocking_send(ServerSentEvent { id: Some(id), data })
+                        .is_ok();
+                }
+                true
+            });
+            match result {
+                Ok(true) => (),
+                Ok(false) => return,
+                // The segment may have been deleted since the snapshot was taken.
+                Err(error) if error.kind() == ErrorKind::NotFound => (),
+                Err(error) => {
+                    warn!(file = %segment.path.display(), %error, "failed to read sse journal");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn segment_path(dir: &Path, sequence: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", sequence, SEGMENT_FILE_EXTENSION))
+}
+
+fn open_for_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Returns the first and last event IDs in the segment, along with the length in bytes of the
+/// leading run of valid records.
+fn scan_segment(path: &Path) -> io::Result<(Option<Id>, Option<Id>, u64)> {
+    let mut first_id = None;
+    let mut last_id = None;
+    let valid_length = read_records(path, |id, _| {
+        first_id.get_or_insert(id);
+        last_id = Some(id);
+        true
+    })?
+    .0;
+    Ok((first_id, last_id, valid_length))
+}
+
+/// Calls `f` for each valid record in the segment, stopping early if it returns `false`.  Returns
+/// `false` if stopped early.
+fn for_each_record<F: FnMut(Id, SseData) -> bool>(path: &Path, f: F) -> io::Result<bool> {
+    read_records(path, f).map(|(_, completed)| completed)
+}
+
+/// Reads records from the segment until the end, a corrupt or incomplete record, or `f` returning
+/// `false`.  Returns the byte length of the valid records read and whether `f` accepted them all.
+fn read_records<F: FnMut(Id, SseData) -> bool>(path: &Path, mut f: F) -> io::Result<(u64, bool)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut valid_length = 0_u64;
+    let mut header = [0_u8; RECORD_HEADER_LENGTH];
+    loop {
+        match reader.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => {
+                return Ok((valid_length, true))
+            }
+            Err(error) => return Err(error),
+        }
+        let mut length_bytes = [0_u8; 4];
+        length_bytes.copy_from_slice(&header[..4]);
+        let mut id_bytes = [0_u8; 4];
+        id_bytes.copy_from_slice(&header[4..]);
+        let payload_length = u32::from_le_bytes(length_bytes) as usize;
+        let id = Id::from_le_bytes(id_bytes);
+
+        // Don't trust the length to size the buffer up front: after a crash it may be garbage.
+        let mut payload = Vec::new();
+        reader
+            .by_ref()
+            .take(payload_length as u64)
+            .read_to_end(&mut payload)?;
+        if payload.len() != payload_length {
+            return Ok((valid_length, true));
+        }
+        let data = match serde_json::from_slice::<SseData>(&payload) {
+            Ok(data) => data,
+            Err(error) => {
+                warn!(file = %path.display(), %error, "failed to parse sse journal record");
+                return Ok((valid_length, true));
+            }
+        };
+        valid_length += (RECORD_HEADER_LENGTH + payload_length) as u64;
+        if !f(id, data) {
+            return Ok((valid_length, false));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc as std_mpsc, thread, time::Duration};
+
+    use casper_types::testing::TestRng;
+
+    use super::*;
+    use crate::logging;
+
+    fn config(max_size: u64, max_segment_size: u64) -> JournalConfig {
+        JournalConfig {
+            enable: true,
+            max_size,
+            max_segment_size,
+            max_concurrent_replays: 1,
+        }
+    }
+
+    fn random_event(rng: &mut TestRng, id: Id) -> ServerSentEvent {
+        ServerSentEvent {
+            id: Some(id),
+            data: SseData::random_block_added(rng),
+        }
+    }
+
+    fn replay(journal: &EventJournal, start_from: Id) -> Vec<ServerSentEvent> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let reader = journal.reader();
+        let replayer = thread::spawn(move || reader.replay(start_from, &sender));
+        let mut events = vec![];
+        while let Some(event) = receiver.blocking_recv() {
+            events.push(event);
+        }
+        replayer.join().unwrap();
+        events
+    }
+
+    #[test]
+    fn should_replay_after_reopening() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = config(u64::MAX, 4096);
+
+        let events: Vec<_> = (0..20).map(|id| random_event(&mut rng, id)).collect();
+        {
+            let mut journal = EventJournal::open(tempdir.path(), &config).unwrap();
+            assert!(journal.last_id().is_none());
+            for event in &events {
+                journal.append(event).unwrap();
+            }
+            // Events without an ID should be ignored.
+            journal
+                .append(&ServerSentEvent::initial_event(Default::default()))
+                .unwrap();
+        }
+
+        let journal = EventJournal::open(tempdir.path(), &config).unwrap();
+        assert_eq!(journal.last_id(), Some(19));
+        assert!(journal.segments.len() > 1);
+        assert_eq!(replay(&journal, 0), events);
+        assert_eq!(replay(&journal, 15), events[15..]);
+        assert!(replay(&journal, 20).is_empty());
+    }
+
+    #[test]
+    fn should_remove_oldest_segments_when_full() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let tempdir = tempfile::tempdir().unwrap();
+        let max_size = 8192;
+        let mut journal = EventJournal::open(tempdir.path(), &config(max_size, 2048)).unwrap();
+
+        for id in 0..50 {
+            journal.append(&random_event(&mut rng, id)).unwrap();
+            let total_size: u64 = journal.segments.iter().map(|segment| segment.size).sum();
+            assert!(total_size <= max_size || journal.segments.len() == 1);
+        }
+        let file_count = fs::read_dir(tempdir.path().join(JOURNAL_DIR_NAME))
+            .unwrap()
+            .count();
+        assert_eq!(file_count, journal.segments.len());
+
+        // Requesting from before the oldest retained event should provide everything retained.
+        let replayed = replay(&journal, 0);
+        assert!(!replayed.is_empty());
+        assert!(replayed.len() < 50);
+        assert_eq!(replayed.last().unwrap().id, Some(49));
+        let first_id = replayed.first().unwrap().id.unwrap();
+        let ids: Vec<_> = replayed.iter().map(|event| event.id.unwrap()).collect();
+        assert_eq!(ids, (first_id..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_truncate_incomplete_record() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let tempdir = tempfile::tempdir().unwrap();
+        let config = config(u64::MAX, u64::MAX);
+
+        let events: Vec<_> = (0..3).map(|id| random_event(&mut rng, id)).collect();
+        let segment_path = {
+            let mut journal = EventJournal::open(tempdir.path(), &config).unwrap();
+            for event in &events {
+                journal.append(event).unwrap();
+            }
+            journal.newest_segment().path.clone()
+        };
+
+        // Simulate a crash part-way through writing a record.
+        let mut file = OpenOptions::new().append(true).open(segment_path).unwrap();
+        file.write_all(&[10, 0, 0, 0, 3, 0, 0, 0, b'{']).unwrap();
+        drop(file);
+
+        let mut journal = EventJournal::open(tempdir.path(), &config).unwrap();
+        assert_eq!(journal.last_id(), Some(2));
+        let event = random_event(&mut rng, 3);
+        journal.append(&event).unwrap();
+
+        let mut expected = events;
+        expected.push(event);
+        assert_eq!(replay(&journal, 0), expected);
+    }
+
+    #[test]
+    fn should_replay_across_id_wraparound() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut journal = EventJournal::open(tempdir.path(), &config(u64::MAX, 4096)).unwrap();
+
+        let events: Vec<_> = (Id::MAX - 4..=Id::MAX)
+            .chain(0..5)
+            .map(|id| random_event(&mut rng, id))
+            .collect();
+        for event in &events {
+            journal.append(event).unwrap();
+        }
+
+        assert_eq!(replay(&journal, Id::MAX - 10), events);
+        assert_eq!(replay(&journal, Id::MAX - 1), events[3..]);
+        assert_eq!(replay(&journal, 2), events[7..]);
+        assert!(replay(&journal, 5).is_empty());
+    }
+
+    #[test]
+    fn should_only_read_as_fast_as_client_receives() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut journal = EventJournal::open(tempdir.path(), &config(u64::MAX, 4096)).unwrap();
+
+        let events: Vec<_> = (0..20).map(|id| random_event(&mut rng, id)).collect();
+        for event in &events {
+            journal.append(event).unwrap();
+        }
+
+        let (sender, mut receiver) = mpsc::channel(2);
+        let (done_sender, done_receiver) = std_mpsc::channel();
+        let reader = journal.reader();
+        thread::spawn(move || {
+            reader.replay(0, &sender);
+            done_sender.send(()).unwrap();
+        });
+
+        // The replay should be held up by the full channel.
+        assert_eq!(
+            done_receiver.recv_timeout(Duration::from_millis(200)),
+            Err(std_mpsc::RecvTimeoutError::Timeout)
+        );
+        let buffered: Vec<_> = (0..2).map(|_| receiver.try_recv().unwrap()).collect();
+        assert_eq!(buffered, events[..2]);
+
+        // Once the client goes away, the replay should stop.
+        drop(receiver);
+        done_receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("replay should stop");
+    }
+}
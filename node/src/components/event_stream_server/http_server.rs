@@ -1,15 +1,18 @@
+use std::sync::Arc;
+
 use futures::{future, Future, FutureExt};
 use tokio::{
     select,
-    sync::{broadcast, mpsc, oneshot},
+    sync::{broadcast, mpsc, oneshot, Semaphore},
     task,
 };
-use tracing::{info, trace};
+use tracing::{info, trace, warn};
 use wheelbuf::WheelBuf;
 
 use casper_types::ProtocolVersion;
 
 use super::{
+    event_journal::EventJournal,
     sse_server::{BroadcastChannelMessage, Id, NewSubscriberInfo, ServerSentEvent},
     Config, EventIndex, SseData,
 };
@@ -25,6 +28,9 @@ use super::{
 /// * `new_subscriber_info_receiver` is used to notify the server of the details of a new client
 ///   having subscribed to the event stream.  It allows the server to populate that client's stream
 ///   with the requested number of historical events.
+/// * `journal` is the optional on-disk event journal.  If provided, all events are appended to it
+///   and historical events are replayed from it rather than from the in-memory buffer.
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn run(
     config: Config,
     api_version: ProtocolVersion,
@@ -33,6 +39,7 @@ pub(super) async fn run(
     mut data_receiver: mpsc::UnboundedReceiver<(EventIndex, SseData)>,
     broadcaster: broadcast::Sender<BroadcastChannelMessage>,
     mut new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
+    mut journal: Option<EventJournal>,
 ) {
     let server_joiner = task::spawn(server_with_shutdown);
 
@@ -42,6 +49,11 @@ pub(super) async fn run(
         config.event_stream_buffer_length as usize
     ]);
 
+    // Each journal replay occupies a blocking thread for as long as the client takes to receive it.
+    let replay_permits = Arc::new(Semaphore::new(
+        config.journal.max_concurrent_replays as usize,
+    ));
+
     // Start handling received messages from the two channels; info on new client subscribers and
     // incoming events announced by node components.
     let event_stream_fut = async {
@@ -51,12 +63,31 @@ pub(super) async fn run(
                     if let Some(subscriber) = maybe_new_subscriber {
                         // First send the client the `ApiVersion` event.  We don't care if this
                         // errors - the client may have disconnected already.
+                        // The channel is new, so has room for this event.
                         let _ = subscriber
                             .initial_events_sender
-                            .send(ServerSentEvent::initial_event(api_version));
+                            .try_send(ServerSentEvent::initial_event(api_version));
                         // If the client supplied a "start_from" index, provide the buffered events.
                         // If they requested more than is buffered, just provide the whole buffer.
-                        if let Some(start_index) = subscriber.start_from {
+                        if let (Some(start_index), Some(journal)) =
+                            (subscriber.start_from, journal.as_ref())
+                        {
+                            // Read the journal off this task, as it may hold far more events than
+                            // the in-memory buffer, and at the pace the client receives them.
+                            let reader = journal.reader();
+                            let sender = subscriber.initial_events_sender;
+                            let replay_permits = Arc::clone(&replay_permits);
+                            task::spawn(async move {
+                                let _permit = match replay_permits.acquire_owned().await {
+                                    Ok(permit) => permit,
+                                    Err(_) => return,
+                                };
+                                let _ = task::spawn_blocking(move || {
+                                    reader.replay(start_index, &sender)
+                                })
+                                .await;
+                            });
+                        } else if let Some(start_index) = subscriber.start_from {
                             // If the buffer's first event ID is in the range [0, buffer size) or
                             // (Id::MAX - buffer size, Id::MAX], then the events in the buffer are
                             // considered to have their IDs wrapping round, or that was recently the
@@ -73,17 +104,30 @@ pub(super) async fn run(
                                     id > Id::MAX - buffer_size || id < buffer_size
                                 })
                                 .unwrap_or_default();
-                            for event in buffer.iter().skip_while(|event| {
-                                if in_wraparound_zone {
-                                    event.id.unwrap().wrapping_add(buffer_size)
-                                        < start_index.wrapping_add(buffer_size)
-                                } else {
-                                    event.id.unwrap() < start_index
+                            let events: Vec<_> = buffer
+                                .iter()
+                                .skip_while(|event| {
+                                    if in_wraparound_zone {
+                                        event.id.unwrap().wrapping_add(buffer_size)
+                                            < start_index.wrapping_add(buffer_size)
+                                    } else {
+                                        event.id.unwrap() < start_index
+                                    }
+                                })
+                                .cloned()
+                                .collect();
+                            // Send them at the pace the client receives them, without holding up
+                            // this task.
+                            let sender = subscriber.initial_events_sender;
+                            task::spawn(async move {
+                                for event in events {
+                                    // As per sending `SSE_INITIAL_EVENT`, we don't care if this
+                                    // errors.
+                                    if sender.send(event).await.is_err() {
+                                        break;
+                                    }
                                 }
-                            }) {
-                                // As per sending `SSE_INITIAL_EVENT`, we don't care if this errors.
-                                let _ = subscriber.initial_events_sender.send(event.clone());
-                            }
+                            });
                         }
                     }
                 }
@@ -94,6 +138,12 @@ pub(super) async fn run(
                             // Buffer the data and broadcast it to subscribed clients.
                             trace!("Event stream server received {:?}", data);
                             let event = ServerSentEvent { id: Some(event_index), data };
+                            if let Some(journal_ref) = journal.as_mut() {
+                                if let Err(error) = journal_ref.append(&event) {
+                                    warn!(%error, "failed to write to sse journal, disabling it");
+                                    journal = None;
+                                }
+                            }
                             buffer.push(event.clone());
                             let message = BroadcastChannelMessage::ServerSentEvent(event);
                             // This can validly fail if there are no connected clients, so don't log
@@ -1,7 +1,7 @@
 //! Types and functions used by the http server to manage the event-stream.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     net::SocketAddr,
     sync::{Arc, RwLock},
 };
@@ -18,9 +18,7 @@ use tokio::sync::{
     broadcast::{self, error::RecvError},
     mpsc,
 };
-use tokio_stream::wrappers::{
-    errors::BroadcastStreamRecvError, BroadcastStream, UnboundedReceiverStream,
-};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, ReceiverStream};
 use tracing::{debug, error, info, warn};
 use warp::{
     addr,
@@ -32,10 +30,12 @@ use warp::{
     Filter, Reply,
 };
 
+use casper_hashing::Digest;
 #[cfg(test)]
 use casper_types::testing::TestRng;
 use casper_types::{
-    EraId, ExecutionEffect, ExecutionResult, ProtocolVersion, PublicKey, TimeDiff, Timestamp,
+    account::AccountHash, AsymmetricType, EraId, ExecutionEffect, ExecutionResult, Key,
+    ProtocolVersion, PublicKey, TimeDiff, Timestamp,
 };
 
 use crate::types::{BlockHash, Deploy, DeployHash, FinalitySignature, JsonBlock};
@@ -53,6 +53,19 @@ pub const SSE_API_DEPLOYS_PATH: &str = "deploys";
 pub const SSE_API_SIGNATURES_PATH: &str = "sigs";
 /// The URL query string field name.
 pub const QUERY_FIELD: &str = "start_from";
+/// The URL query string field name to filter deploy events by the deploy's account, given as a
+/// hex-encoded public key or a formatted account hash.
+pub const ACCOUNT_QUERY_FIELD: &str = "account";
+/// The URL query string field name to filter events by the formatted `Key`s touched in their
+/// execution effects.
+pub const KEY_QUERY_FIELD: &str = "key";
+/// The URL query string field name to filter deploy events by hex-encoded deploy hash.
+pub const DEPLOY_QUERY_FIELD: &str = "deploy";
+/// The separator between multiple values of a single content filter query field.
+const QUERY_VALUE_SEPARATOR: char = ',';
+/// The number of historical events which can be queued for a new subscriber before sending more
+/// waits for the client to catch up.
+const INITIAL_EVENTS_CHANNEL_CAPACITY: usize = 256;
 
 /// The filter associated with `/events/main` path.
 const MAIN_FILTER: [EventFilter; 5] = [
@@ -260,7 +273,7 @@ pub(super) struct NewSubscriberInfo {
     pub(super) start_from: Option<Id>,
     /// A channel to send the initial events to the client's handler.  This will always send the
     /// ApiVersion as the first event, and then any buffered events as indicated by `start_from`.
-    pub(super) initial_events_sender: mpsc::UnboundedSender<ServerSentEvent>,
+    pub(super) initial_events_sender: mpsc::Sender<ServerSentEvent>,
 }
 
 /// A filter for event types a client has subscribed to receive.
@@ -275,12 +288,90 @@ pub(super) enum EventFilter {
     Step,
 }
 
+/// A filter on the contents of the events a client has subscribed to receive.
+///
+/// Only events relating to deploys or holding execution effects are filtered by content: such an
+/// event is included if it matches any of the given accounts, keys or deploy hashes.  An empty
+/// filter includes every event.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub(super) struct ContentFilter {
+    accounts: BTreeSet<AccountHash>,
+    /// The formatted strings of the `Key`s, as used in `ExecutionEffect`s.
+    keys: BTreeSet<String>,
+    deploy_hashes: BTreeSet<DeployHash>,
+}
+
+impl ContentFilter {
+    fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.keys.is_empty() && self.deploy_hashes.is_empty()
+    }
+
+    fn matches_account(&self, public_key: &PublicKey) -> bool {
+        !self.accounts.is_empty() && self.accounts.contains(&public_key.to_account_hash())
+    }
+
+    fn matches_effect(&self, effect: &ExecutionEffect) -> bool {
+        !self.keys.is_empty()
+            && (effect
+                .transforms
+                .iter()
+                .any(|entry| self.keys.contains(&entry.key))
+                || effect
+                    .operations
+                    .iter()
+                    .any(|operation| self.keys.contains(&operation.key)))
+    }
+
+    pub(super) fn should_include(&self, data: &SseData) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match data {
+            SseData::ApiVersion(_)
+            | SseData::BlockAdded { .. }
+            | SseData::Fault { .. }
+            | SseData::FinalitySignature(_)
+            | SseData::Shutdown => true,
+            SseData::DeployAccepted { deploy } => {
+                self.deploy_hashes.contains(deploy.hash())
+                    || self.matches_account(deploy.header().account())
+            }
+            SseData::DeployProcessed {
+                deploy_hash,
+                account,
+                execution_result,
+                ..
+            } => {
+                let effect = match &**execution_result {
+                    ExecutionResult::Success { effect, .. }
+                    | ExecutionResult::Failure { effect, .. } => effect,
+                };
+                self.deploy_hashes.contains(deploy_hash)
+                    || self.matches_account(account)
+                    || self.matches_effect(effect)
+            }
+            SseData::DeployExpired { deploy_hash } => self.deploy_hashes.contains(deploy_hash),
+            SseData::Step {
+                execution_effect, ..
+            } => self.matches_effect(execution_effect),
+        }
+    }
+}
+
+/// The parsed URL query string of a new subscription.
+#[derive(Default, Debug)]
+struct Query {
+    start_from: Option<Id>,
+    content_filter: ContentFilter,
+}
+
 /// Filters the `event`, mapping it to a warp event, or `None` if it should be filtered out.
 async fn filter_map_server_sent_event(
     event: &ServerSentEvent,
     event_filter: &[EventFilter],
+    content_filter: &ContentFilter,
 ) -> Option<Result<WarpServerSentEvent, RecvError>> {
-    if !event.data.should_include(event_filter) {
+    if !event.data.should_include(event_filter) || !content_filter.should_include(&event.data) {
         return None;
     }
 
@@ -345,26 +436,52 @@ pub(super) fn get_filter(path_param: &str) -> Option<&'static [EventFilter]> {
     }
 }
 
-/// Extracts the starting event ID from the provided query, or `None` if `query` is empty.
+/// Parses the provided query into the starting event ID and the content filter.
 ///
-/// If `query` is not empty, returns a 422 response if `query` doesn't have exactly one entry,
-/// "starts_from" mapped to a value representing an event ID.
-fn parse_query(query: HashMap<String, String>) -> Result<Option<Id>, Response> {
-    if query.is_empty() {
-        return Ok(None);
-    }
-
-    if query.len() > 1 {
-        return Err(create_422());
+/// All fields are optional.  Returns a 422 response if `query` has any unknown field, or if any
+/// field's value can't be parsed.  The content filter fields may each hold a comma-separated list
+/// of values.
+fn parse_query(query: HashMap<String, String>) -> Result<Query, Response> {
+    let mut parsed = Query::default();
+    for (field, value) in query {
+        let values = value.split(QUERY_VALUE_SEPARATOR);
+        let content_filter = &mut parsed.content_filter;
+        match field.as_str() {
+            QUERY_FIELD => {
+                let id = value.parse::<Id>().map_err(|_| create_422())?;
+                parsed.start_from = Some(id);
+            }
+            ACCOUNT_QUERY_FIELD => {
+                for account in values {
+                    let account_hash = parse_account(account).ok_or_else(create_422)?;
+                    content_filter.accounts.insert(account_hash);
+                }
+            }
+            KEY_QUERY_FIELD => {
+                for key in values {
+                    let key = Key::from_formatted_str(key).map_err(|_| create_422())?;
+                    content_filter.keys.insert(key.to_formatted_string());
+                }
+            }
+            DEPLOY_QUERY_FIELD => {
+                for deploy_hash in values {
+                    let digest = Digest::from_hex(deploy_hash).map_err(|_| create_422())?;
+                    content_filter.deploy_hashes.insert(DeployHash::new(digest));
+                }
+            }
+            _ => return Err(create_422()),
+        }
     }
+    Ok(parsed)
+}
 
-    match query
-        .get(QUERY_FIELD)
-        .and_then(|id_str| id_str.parse::<Id>().ok())
-    {
-        Some(id) => Ok(Some(id)),
-        None => Err(create_422()),
-    }
+/// Parses an account given as either a formatted account hash or a hex-encoded public key.
+fn parse_account(input: &str) -> Option<AccountHash> {
+    AccountHash::from_formatted_str(input).ok().or_else(|| {
+        PublicKey::from_hex(input)
+            .ok()
+            .map(|public_key| public_key.to_account_hash())
+    })
 }
 
 /// Creates a 404 response with a useful error message in the body.
@@ -384,8 +501,10 @@ fn create_404() -> Response {
 /// string.
 fn create_422() -> Response {
     let mut response = Response::new(Body::from(format!(
-        "invalid query: expected single field '{}=<EVENT ID>'\n",
-        QUERY_FIELD
+        "invalid query: expected optional fields '{}=<EVENT ID>', \
+        '{}=<PUBLIC KEY OR ACCOUNT HASH>', '{}=<KEY>' and '{}=<DEPLOY HASH>', \
+        where the last three may each hold a comma-separated list\n",
+        QUERY_FIELD, ACCOUNT_QUERY_FIELD, KEY_QUERY_FIELD, DEPLOY_QUERY_FIELD
     )));
     *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
     response
@@ -441,13 +560,17 @@ impl ChannelsAndFilter {
                 None => return create_404(),
             };
 
-            let start_from = match parse_query(query) {
-                Ok(maybe_id) => maybe_id,
+            let Query {
+                start_from,
+                content_filter,
+            } = match parse_query(query) {
+                Ok(query) => query,
                 Err(error_response) => return error_response,
             };
 
             // Create a channel for the client's handler to receive the stream of initial events.
-            let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
+            let (initial_events_sender, initial_events_receiver) =
+                mpsc::channel(INITIAL_EVENTS_CHANNEL_CAPACITY);
 
             // Supply the server with the sender part of the channel along with the client's
             // requested starting point.
@@ -469,6 +592,7 @@ impl ChannelsAndFilter {
                 initial_events_receiver,
                 ongoing_events_receiver,
                 event_filter,
+                content_filter,
                 remote_address,
             )))
             .into_response()
@@ -505,13 +629,17 @@ impl ChannelsAndFilter {
 /// subscribed to the server's event stream.
 ///
 /// It also takes an `EventFilter` which causes events to which the client didn't subscribe to be
-/// skipped.
+/// skipped, and a `ContentFilter` which causes events not matching the client's requested accounts,
+/// keys or deploys to be skipped.
 fn stream_to_client(
-    initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
+    initial_events: mpsc::Receiver<ServerSentEvent>,
     ongoing_events: broadcast::Receiver<BroadcastChannelMessage>,
     event_filter: &'static [EventFilter],
+    content_filter: ContentFilter,
     remote_address: String,
 ) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + 'static {
+    let content_filter = Arc::new(content_filter);
+
     // Keep a record of the IDs of the events delivered via the `initial_events` receiver.
     let initial_stream_ids = Arc::new(RwLock::new(HashSet::new()));
     let cloned_initial_ids = Arc::clone(&initial_stream_ids);
@@ -549,7 +677,7 @@ fn stream_to_client(
 
     // Serve the initial events followed by the ongoing ones, filtering as dictated by the
     // `event_filter`.
-    UnboundedReceiverStream::new(initial_events)
+    ReceiverStream::new(initial_events)
         .map(move |event| {
             if let Some(id) = event.id {
                 let _ = initial_stream_ids.write().unwrap().insert(id);
@@ -557,10 +685,15 @@ fn stream_to_client(
             Ok(event)
         })
         .chain(ongoing_stream)
-        .filter_map(move |result| async move {
-            match result {
-                Ok(event) => filter_map_server_sent_event(&event, event_filter).await,
-                Err(error) => Some(Err(error)),
+        .filter_map(move |result| {
+            let content_filter = Arc::clone(&content_filter);
+            async move {
+                match result {
+                    Ok(event) => {
+                        filter_map_server_sent_event(&event, event_filter, &content_filter).await
+                    }
+                    Err(error) => Some(Err(error)),
+                }
             }
         })
 }
@@ -569,14 +702,16 @@ fn stream_to_client(
 mod tests {
     use std::iter;
 
-    use casper_types::testing::TestRng;
+    use casper_types::{testing::TestRng, Transform, TransformEntry};
 
     use super::*;
     use crate::logging;
 
     async fn should_filter_out(event: &ServerSentEvent, filter: &'static [EventFilter]) {
         assert!(
-            filter_map_server_sent_event(event, filter).await.is_none(),
+            filter_map_server_sent_event(event, filter, &ContentFilter::default())
+                .await
+                .is_none(),
             "should filter out {:?} with {:?}",
             event,
             filter
@@ -585,7 +720,9 @@ mod tests {
 
     async fn should_not_filter_out(event: &ServerSentEvent, filter: &'static [EventFilter]) {
         assert!(
-            filter_map_server_sent_event(event, filter).await.is_some(),
+            filter_map_server_sent_event(event, filter, &ContentFilter::default())
+                .await
+                .is_some(),
             "should not filter out {:?} with {:?}",
             event,
             filter
@@ -833,13 +970,14 @@ mod tests {
                 &mut deploys,
             );
 
-            let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
+            let (initial_events_sender, initial_events_receiver) =
+                mpsc::channel(initial_events.len());
             let (ongoing_events_sender, ongoing_events_receiver) =
                 broadcast::channel(NUM_INITIAL_EVENTS + NUM_ONGOING_EVENTS + 1);
 
             // Send all the events.
             for event in initial_events.iter().cloned() {
-                initial_events_sender.send(event).unwrap();
+                initial_events_sender.try_send(event).unwrap();
             }
             for event in ongoing_events.iter().cloned() {
                 let _ = ongoing_events_sender
@@ -855,6 +993,7 @@ mod tests {
                 initial_events_receiver,
                 ongoing_events_receiver,
                 get_filter(path_filter).unwrap(),
+                ContentFilter::default(),
                 "127.0.0.1:3456".to_string(),
             )
             .collect()
@@ -920,4 +1059,158 @@ mod tests {
     async fn should_filter_duplicate_signature_events() {
         should_filter_duplicate_events(SSE_API_SIGNATURES_PATH).await
     }
+
+    fn query(fields: &[(&str, String)]) -> HashMap<String, String> {
+        fields
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.clone()))
+            .collect()
+    }
+
+    fn deploy_processed(deploy: &Deploy, touched_key: Key) -> SseData {
+        let effect = ExecutionEffect::new(vec![TransformEntry {
+            key: touched_key.to_formatted_string(),
+            transform: Transform::Identity,
+        }]);
+        SseData::DeployProcessed {
+            deploy_hash: Box::new(*deploy.hash()),
+            account: Box::new(deploy.header().account().clone()),
+            timestamp: deploy.header().timestamp(),
+            ttl: deploy.header().ttl(),
+            dependencies: deploy.header().dependencies().clone(),
+            block_hash: Box::default(),
+            execution_result: Box::new(ExecutionResult::Success {
+                effect,
+                transfers: vec![],
+                cost: 1.into(),
+            }),
+        }
+    }
+
+    /// This test checks that the content filter query fields are parsed, and that bad values are
+    /// rejected.
+    #[test]
+    fn should_parse_content_filter_query() {
+        let mut rng = TestRng::new();
+        let public_key = PublicKey::random(&mut rng);
+        let account_hash = AccountHash::new(rng.gen());
+        let key = Key::Hash(rng.gen());
+        let deploy_hash = DeployHash::random(&mut rng);
+
+        let parsed = parse_query(query(&[
+            (QUERY_FIELD, "5".to_string()),
+            (
+                ACCOUNT_QUERY_FIELD,
+                format!(
+                    "{},{}",
+                    public_key.to_hex(),
+                    account_hash.to_formatted_string()
+                ),
+            ),
+            (KEY_QUERY_FIELD, key.to_formatted_string()),
+            (DEPLOY_QUERY_FIELD, base16::encode_lower(&deploy_hash)),
+        ]))
+        .unwrap();
+        assert_eq!(parsed.start_from, Some(5));
+        assert_eq!(
+            parsed.content_filter.accounts,
+            [public_key.to_account_hash(), account_hash]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            parsed.content_filter.keys,
+            iter::once(key.to_formatted_string()).collect()
+        );
+        assert_eq!(
+            parsed.content_filter.deploy_hashes,
+            iter::once(deploy_hash).collect()
+        );
+
+        for (field, bad_value) in [
+            (ACCOUNT_QUERY_FIELD, "not-an-account"),
+            (ACCOUNT_QUERY_FIELD, ""),
+            (KEY_QUERY_FIELD, "not-a-key"),
+            (DEPLOY_QUERY_FIELD, "not-a-hash"),
+        ] {
+            let response = parse_query(query(&[(field, bad_value.to_string())])).unwrap_err();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    /// This test checks that deploy and step events are filtered by account, key or deploy hash,
+    /// while other events are unaffected by the content filter.
+    #[test]
+    fn should_filter_events_by_content() {
+        let mut rng = TestRng::new();
+        let deploy = Deploy::random(&mut rng);
+        let other_deploy = Deploy::random(&mut rng);
+        let key = Key::Hash(rng.gen());
+        let other_key = Key::Hash(rng.gen());
+
+        let by_account = ContentFilter {
+            accounts: iter::once(deploy.header().account().to_account_hash()).collect(),
+            ..ContentFilter::default()
+        };
+        let by_key = ContentFilter {
+            keys: iter::once(key.to_formatted_string()).collect(),
+            ..ContentFilter::default()
+        };
+        let by_deploy = ContentFilter {
+            deploy_hashes: iter::once(*deploy.hash()).collect(),
+            ..ContentFilter::default()
+        };
+
+        let accepted = SseData::DeployAccepted {
+            deploy: Arc::new(deploy.clone()),
+        };
+        let other_accepted = SseData::DeployAccepted {
+            deploy: Arc::new(other_deploy.clone()),
+        };
+        assert!(by_account.should_include(&accepted));
+        assert!(!by_account.should_include(&other_accepted));
+        assert!(by_deploy.should_include(&accepted));
+        assert!(!by_deploy.should_include(&other_accepted));
+        assert!(!by_key.should_include(&accepted));
+
+        let processed = deploy_processed(&deploy, other_key);
+        let other_processed_touching_key = deploy_processed(&other_deploy, key);
+        let other_processed = deploy_processed(&other_deploy, other_key);
+        assert!(by_account.should_include(&processed));
+        assert!(!by_account.should_include(&other_processed_touching_key));
+        assert!(by_deploy.should_include(&processed));
+        assert!(!by_deploy.should_include(&other_processed));
+        assert!(!by_key.should_include(&processed));
+        assert!(by_key.should_include(&other_processed_touching_key));
+        assert!(!by_key.should_include(&other_processed));
+
+        let expired = SseData::DeployExpired {
+            deploy_hash: *deploy.hash(),
+        };
+        assert!(by_deploy.should_include(&expired));
+        assert!(!by_account.should_include(&expired));
+
+        let step = SseData::Step {
+            era_id: EraId::new(1),
+            execution_effect: ExecutionEffect::new(vec![TransformEntry {
+                key: key.to_formatted_string(),
+                transform: Transform::Identity,
+            }]),
+        };
+        assert!(by_key.should_include(&step));
+        assert!(!by_account.should_include(&step));
+
+        for unfiltered in [
+            SseData::random_api_version(&mut rng),
+            SseData::random_block_added(&mut rng),
+            SseData::random_fault(&mut rng),
+            SseData::random_finality_signature(&mut rng),
+            SseData::Shutdown,
+        ] {
+            assert!(by_account.should_include(&unfiltered));
+            assert!(by_key.should_include(&unfiltered));
+            assert!(by_deploy.should_include(&unfiltered));
+        }
+        assert!(ContentFilter::default().should_include(&other_processed));
+    }
 }
@@ -27,9 +27,9 @@ use casper_types::testing::TestRng;
 use super::*;
 use crate::{logging, testing::assert_schema};
 use sse_server::{
-    DeployAccepted, Id, QUERY_FIELD, SSE_API_DEPLOYS_PATH as DEPLOYS_PATH,
-    SSE_API_MAIN_PATH as MAIN_PATH, SSE_API_ROOT_PATH as ROOT_PATH,
-    SSE_API_SIGNATURES_PATH as SIGS_PATH,
+    DeployAccepted, Id, ACCOUNT_QUERY_FIELD, DEPLOY_QUERY_FIELD, KEY_QUERY_FIELD, QUERY_FIELD,
+    SSE_API_DEPLOYS_PATH as DEPLOYS_PATH, SSE_API_MAIN_PATH as MAIN_PATH,
+    SSE_API_ROOT_PATH as ROOT_PATH, SSE_API_SIGNATURES_PATH as SIGS_PATH,
 };
 
 /// The total number of random events each `EventStreamServer` will emit by default, excluding the
@@ -955,11 +955,16 @@ async fn should_handle_bad_url_query() {
         format!("{}?{}=0&extra=1", main_url, QUERY_FIELD),
         format!("{}?{}=0&extra=1", deploys_url, QUERY_FIELD),
         format!("{}?{}=0&extra=1", sigs_url, QUERY_FIELD),
+        format!("{}?{}=not-an-account", main_url, ACCOUNT_QUERY_FIELD),
+        format!("{}?{}=not-a-key", main_url, KEY_QUERY_FIELD),
+        format!("{}?{}=not-a-hash", deploys_url, DEPLOY_QUERY_FIELD),
     ];
 
     let expected_body = format!(
-        "invalid query: expected single field '{}=<EVENT ID>'",
-        QUERY_FIELD
+        "invalid query: expected optional fields '{}=<EVENT ID>', \
+        '{}=<PUBLIC KEY OR ACCOUNT HASH>', '{}=<KEY>' and '{}=<DEPLOY HASH>', \
+        where the last three may each hold a comma-separated list",
+        QUERY_FIELD, ACCOUNT_QUERY_FIELD, KEY_QUERY_FIELD, DEPLOY_QUERY_FIELD
     );
     for url in &urls {
         let response = reqwest::get(url).await.unwrap();
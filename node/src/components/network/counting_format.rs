@@ -265,6 +265,11 @@ impl ConnectionId {
         &self.0
     }
 
+    /// Returns the connection ID as a `Digest`, i.e. with the same bytes.
+    pub(crate) fn to_digest(self) -> Digest {
+        Digest::from(self.0)
+    }
+
     /// Creates a new connection ID from an existing SSL connection.
     #[inline]
     pub(crate) fn from_connection(ssl: &SslRef, our_id: NodeId, their_id: NodeId) -> Self {
@@ -299,7 +299,7 @@ mod tests {
     use tokio::time::Instant;
 
     use super::{Limiter, NodeId, PublicKey};
-    use crate::{testing::init_logging, types::ValidatorMatrix};
+    use crate::{signer::Signer, testing::init_logging, types::ValidatorMatrix};
 
     /// Something that happens almost immediately, with some allowance for test jitter.
     const SHORT_TIME: Duration = Duration::from_millis(250);
@@ -453,7 +453,6 @@ mod tests {
         let mut rng = crate::new_rng();
 
         let secret_key = SecretKey::random(&mut rng);
-        let consensus_key = PublicKey::from(&secret_key);
         let wait_metric = new_wait_time_sec();
         let limiter = Limiter::new(
             1_000,
@@ -462,8 +461,7 @@ mod tests {
                 Ratio::new(1, 3),
                 None,
                 EraId::from(0),
-                Arc::new(secret_key),
-                consensus_key.clone(),
+                Signer::local(Arc::new(secret_key)),
                 2,
             ),
         );
@@ -1,7 +1,8 @@
+#[cfg(test)]
+use std::sync::Arc;
 use std::{
     fmt::{self, Debug, Display, Formatter},
     net::SocketAddr,
-    sync::Arc,
 };
 
 use datasize::DataSize;
@@ -11,16 +12,18 @@ use serde::{
     Deserialize, Deserializer, Serialize, Serializer,
 };
 use strum::EnumDiscriminants;
+use tracing::warn;
 
 use casper_hashing::Digest;
+use casper_types::{crypto, AsymmetricType, ProtocolVersion, PublicKey, Signature};
 #[cfg(test)]
-use casper_types::testing::TestRng;
-use casper_types::{crypto, AsymmetricType, ProtocolVersion, PublicKey, SecretKey, Signature};
+use casper_types::{testing::TestRng, SecretKey};
 
 use super::{counting_format::ConnectionId, health::Nonce, BincodeFormat};
 use crate::{
     effect::EffectBuilder,
     protocol,
+    signer::{self, SignRequest, Signer},
     types::{Chainspec, NodeId},
     utils::{
         opt_display::OptDisplay,
@@ -136,24 +139,15 @@ impl<P: Payload> Message<P> {
     }
 }
 
-/// A pair of secret keys used by consensus.
+/// The signer of the consensus key, used to prove to peers that we are a validator.
 pub(super) struct NodeKeyPair {
-    secret_key: Arc<SecretKey>,
-    public_key: PublicKey,
+    signer: Signer,
 }
 
 impl NodeKeyPair {
     /// Creates a new key pair for consensus signing.
-    pub(super) fn new(key_pair: (Arc<SecretKey>, PublicKey)) -> Self {
-        Self {
-            secret_key: key_pair.0,
-            public_key: key_pair.1,
-        }
-    }
-
-    /// Sign a value using this keypair.
-    fn sign<T: AsRef<[u8]>>(&self, value: T) -> Signature {
-        crypto::sign(value, &self.secret_key, &self.public_key)
+    pub(super) fn new(signer: Signer) -> Self {
+        Self { signer }
     }
 }
 
@@ -170,17 +164,39 @@ pub(crate) struct ConsensusCertificate {
 
 impl ConsensusCertificate {
     /// Creates a new consensus certificate from a connection ID and key pair.
-    pub(super) fn create(connection_id: ConnectionId, key_pair: &NodeKeyPair) -> Self {
-        let signature = key_pair.sign(connection_id.as_bytes());
-        ConsensusCertificate {
-            public_key: key_pair.public_key.clone(),
-            signature,
+    ///
+    /// A signer process only signs the tagged connection ID, which peers running versions without
+    /// signer support reject, see `consensus.remote_signer` in the node config.
+    ///
+    /// Returns `None` if the signer failed to sign it.
+    pub(super) async fn create(
+        connection_id: ConnectionId,
+        key_pair: &NodeKeyPair,
+    ) -> Option<Self> {
+        let request = SignRequest::ConsensusCertificate {
+            connection_id: connection_id.to_digest(),
+        };
+        match key_pair.signer.sign_async(request).await {
+            Ok(signature) => Some(ConsensusCertificate {
+                public_key: key_pair.signer.public_key().clone(),
+                signature,
+            }),
+            Err(error) => {
+                warn!(%error, "could not sign consensus certificate");
+                None
+            }
         }
     }
 
     /// Validates a certificate, returning a `PublicKey` if valid.
+    ///
+    /// Accepts both signatures of the plain connection ID, and of the tagged one created by
+    /// signer processes.
     pub(super) fn validate(self, connection_id: ConnectionId) -> Result<PublicKey, crypto::Error> {
-        crypto::verify(connection_id.as_bytes(), &self.signature, &self.public_key)?;
+        if crypto::verify(connection_id.as_bytes(), &self.signature, &self.public_key).is_err() {
+            let tagged = signer::protocol::certificate_bytes(&connection_id.to_digest());
+            crypto::verify(tagged, &self.signature, &self.public_key)?;
+        }
         Ok(self.public_key)
     }
 
@@ -189,10 +205,15 @@ impl ConsensusCertificate {
     fn random(rng: &mut TestRng) -> Self {
         let secret_key = SecretKey::random(rng);
         let public_key = PublicKey::from(&secret_key);
-        ConsensusCertificate::create(
-            ConnectionId::random(rng),
-            &NodeKeyPair::new((Arc::new(secret_key), public_key)),
-        )
+        let signature = crypto::sign(
+            ConnectionId::random(rng).as_bytes(),
+            &secret_key,
+            &public_key,
+        );
+        ConsensusCertificate {
+            public_key,
+            signature,
+        }
     }
 }
 
@@ -952,6 +973,42 @@ mod tests {
         roundtrip_certificate(false)
     }
 
+    #[tokio::test]
+    async fn validates_plain_and_tagged_certificates() {
+        let mut rng = crate::new_rng();
+        let secret_key = || SecretKey::ed25519_from_bytes([3; 32]).unwrap();
+        let public_key = PublicKey::from(&secret_key());
+        let connection_id = ConnectionId::random(&mut rng);
+        let other_connection_id = ConnectionId::random(&mut rng);
+        let tagged_certificate = |connection_id: ConnectionId| ConsensusCertificate {
+            public_key: public_key.clone(),
+            signature: crypto::sign(
+                crate::signer::protocol::certificate_bytes(&connection_id.to_digest()),
+                &secret_key(),
+                &public_key,
+            ),
+        };
+
+        let plain_certificate = ConsensusCertificate::create(
+            connection_id,
+            &NodeKeyPair::new(Signer::local(Arc::new(secret_key()))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            plain_certificate.clone().validate(connection_id),
+            Ok(public_key.clone())
+        );
+        assert!(plain_certificate.validate(other_connection_id).is_err());
+        assert_eq!(
+            tagged_certificate(connection_id).validate(connection_id),
+            Ok(public_key.clone())
+        );
+        assert!(tagged_certificate(connection_id)
+            .validate(other_connection_id)
+            .is_err());
+    }
+
     #[test]
     fn assert_the_largest_specimen_type_and_size() {
         let (chainspec, _) = crate::utils::Loadable::from_resources("production");
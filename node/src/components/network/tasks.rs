@@ -48,7 +48,7 @@ use super::{
     event::{IncomingConnection, OutgoingConnection},
     full_transport,
     limiter::LimiterHandle,
-    message::NodeKeyPair,
+    message::{ConsensusCertificate, NodeKeyPair},
     message_pack_format::MessagePackFormat,
     EstimatorWeights, Event, FramedTransport, FullTransport, Identity, Message, Metrics, Payload,
     Transport,
@@ -468,11 +468,16 @@ where
 {
     let mut encoder = MessagePackFormat;
 
+    // A signer process holding the consensus key is asked off the executor.
+    let consensus_certificate = match context.node_key_pair.as_ref() {
+        Some(key_pair) => ConsensusCertificate::create(connection_id, key_pair).await,
+        None => None,
+    };
+
     // Manually encode a handshake.
     let handshake_message = context.chain_info.create_handshake::<P>(
         context.public_addr.expect("component not initialized"),
-        context.node_key_pair.as_ref(),
-        connection_id,
+        consensus_certificate,
         context.is_syncing.load(Ordering::SeqCst),
     );
 
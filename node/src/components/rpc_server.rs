@@ -10,18 +10,24 @@
 //! a component implementation that interfaces with other components via being plugged into a
 //! reactor, and an external facing http server that exposes various uri routes and converts
 //! JSON-RPC requests into the appropriate component events.
+//!
+//! Optionally, the same JSON-RPC API is also served over WebSockets, where clients can additionally
+//! subscribe to notifications of added blocks, processed deploys and finality signatures.
 
 mod config;
 mod event;
 mod http_server;
+mod rate_limiter;
 pub mod rpcs;
 mod speculative_exec_config;
 mod speculative_exec_server;
+mod ws_server;
 
-use std::{fmt::Debug, time::Instant};
+use std::{fmt::Debug, sync::Arc, time::Instant};
 
 use datasize::DataSize;
 use futures::join;
+use tokio::sync::broadcast;
 use tracing::{error, info, warn};
 
 use casper_execution_engine::core::engine_state::{
@@ -34,8 +40,8 @@ use casper_types::{system::auction::EraValidators, Key, ProtocolVersion, URef};
 use super::Component;
 use crate::{
     components::{
-        contract_runtime::EraValidatorsRequest, ComponentState, InitializedComponent,
-        PortBoundComponent,
+        contract_runtime::EraValidatorsRequest, event_stream_server::SseData, ComponentState,
+        InitializedComponent, PortBoundComponent,
     },
     effect::{
         requests::{
@@ -46,16 +52,21 @@ use crate::{
         EffectBuilder, EffectExt, Effects, Responder,
     },
     reactor::main_reactor::MainEvent,
-    types::{ChainspecInfo, StatusFeed},
+    types::{ChainspecInfo, JsonBlock, StatusFeed},
     utils::{self, ListeningError},
     NodeRng,
 };
 pub use config::Config;
 pub(crate) use event::Event;
+use rate_limiter::RateLimiter;
 pub use speculative_exec_config::Config as SpeculativeExecConfig;
 
 const COMPONENT_NAME: &str = "rpc_server";
 
+/// The number of notifications to buffer for each WebSocket client.  A client which falls further
+/// behind than this is disconnected.
+const WS_NOTIFICATION_CHANNEL_SIZE: usize = 1_000;
+
 /// A helper trait capturing all of this components Request type dependencies.
 pub(crate) trait ReactorEventT:
     From<Event>
@@ -112,6 +123,10 @@ pub(crate) struct RpcServer {
     /// no fields and no methods because all that is needed to operate it is the
     /// spawned tokio task, so a unit struct will suffice here.
     speculative_exec: Option<()>,
+    /// Channel sender to pass notifications to the WebSocket server, present only when the
+    /// WebSocket server is enabled.
+    #[data_size(skip)]
+    ws_notification_sender: Option<broadcast::Sender<SseData>>,
 }
 
 impl RpcServer {
@@ -130,8 +145,29 @@ impl RpcServer {
             network_name,
             node_startup_instant,
             speculative_exec: None,
+            ws_notification_sender: None,
         }
     }
+
+    /// Returns `true` if any clients are connected to the WebSocket server, i.e. if there is any
+    /// point in passing notifications to this component.
+    pub(crate) fn has_websocket_clients(&self) -> bool {
+        self.ws_notification_sender
+            .as_ref()
+            .map_or(false, |sender| sender.receiver_count() > 0)
+    }
+
+    /// Passes the notification to all clients connected to the WebSocket server.
+    ///
+    /// `sse_data` is only invoked if there are any connected clients.
+    fn notify_websocket_clients<F: FnOnce() -> SseData>(&self, sse_data: F) -> Effects<Event> {
+        if let Some(sender) = self.ws_notification_sender.as_ref() {
+            if sender.receiver_count() > 0 {
+                let _ = sender.send(sse_data());
+            }
+        }
+        Effects::new()
+    }
 }
 
 impl RpcServer {
@@ -228,7 +264,10 @@ where
                 | Event::GetBidsResult { .. }
                 | Event::GetDeployResult { .. }
                 | Event::GetPeersResult { .. }
-                | Event::GetBalanceResult { .. } => {
+                | Event::GetBalanceResult { .. }
+                | Event::BlockAdded(_)
+                | Event::DeployProcessed { .. }
+                | Event::FinalitySignature(_) => {
                     warn!(
                         ?event,
                         name = <Self as Component<MainEvent>>::name(self),
@@ -402,6 +441,27 @@ where
                     result,
                     main_responder,
                 } => main_responder.respond(result).ignore(),
+                Event::BlockAdded(block) => self.notify_websocket_clients(|| SseData::BlockAdded {
+                    block_hash: *block.hash(),
+                    block: Box::new(JsonBlock::new(&block, None)),
+                }),
+                Event::DeployProcessed {
+                    deploy_hash,
+                    deploy_header,
+                    block_hash,
+                    execution_result,
+                } => self.notify_websocket_clients(|| SseData::DeployProcessed {
+                    deploy_hash: Box::new(deploy_hash),
+                    account: Box::new(deploy_header.account().clone()),
+                    timestamp: deploy_header.timestamp(),
+                    ttl: deploy_header.ttl(),
+                    dependencies: deploy_header.dependencies().clone(),
+                    block_hash: Box::new(block_hash),
+                    execution_result,
+                }),
+                Event::FinalitySignature(fs) => {
+                    self.notify_websocket_clients(|| SseData::FinalitySignature(fs))
+                }
             },
         }
     }
@@ -461,6 +521,26 @@ where
         };
 
         let cfg = &self.config;
+        // Requests to the HTTP and WebSocket servers share one rate limit.
+        let limiter = Arc::new(RateLimiter::new(cfg.qps_limit));
+        self.ws_notification_sender = if cfg.websocket.enable_server {
+            let builder = utils::start_listening(&cfg.websocket.address)?;
+            let (sender, _) = broadcast::channel(WS_NOTIFICATION_CHANNEL_SIZE);
+            tokio::spawn(ws_server::run(
+                builder,
+                http_server::request_handlers(effect_builder, self.api_version, cfg.max_batch_size),
+                sender.clone(),
+                cfg.max_body_bytes,
+                cfg.websocket.max_connections,
+                cfg.websocket.max_subscriptions_per_connection,
+                cfg.websocket.qps_limit_per_connection,
+                Arc::clone(&limiter),
+            ));
+            Some(sender)
+        } else {
+            None
+        };
+
         let builder = utils::start_listening(&cfg.address)?;
         tokio::spawn(http_server::run(
             builder,
@@ -468,7 +548,9 @@ where
             self.api_version,
             cfg.qps_limit,
             cfg.max_body_bytes,
+            cfg.max_batch_size,
             cfg.cors_origin.clone(),
+            limiter,
         ));
 
         Ok(Effects::new())
@@ -12,6 +12,16 @@ const DEFAULT_QPS_LIMIT: u64 = 100;
 const DEFAULT_MAX_BODY_BYTES: u32 = 2_621_440;
 /// Default CORS origin.
 const DEFAULT_CORS_ORIGIN: &str = "";
+/// Default maximum number of requests in a single JSON-RPC batch request.
+const DEFAULT_MAX_BATCH_SIZE: u32 = 100;
+/// Default binding address for the JSON-RPC WebSocket server.
+const DEFAULT_WEBSOCKET_ADDRESS: &str = "0.0.0.0:0";
+/// Default maximum number of concurrent WebSocket connections.
+const DEFAULT_MAX_WEBSOCKET_CONNECTIONS: u32 = 100;
+/// Default maximum number of subscriptions per WebSocket connection.
+const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: u32 = 16;
+/// Default rate limit in qps for a single WebSocket connection.
+const DEFAULT_WEBSOCKET_QPS_LIMIT_PER_CONNECTION: u64 = 10;
 
 /// JSON-RPC HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
@@ -22,12 +32,20 @@ pub struct Config {
     pub enable_server: bool,
     /// Address to bind JSON-RPC HTTP server to.
     pub address: String,
-    /// Maximum rate limit in queries per second.
+    /// Maximum rate limit in queries per second, shared with the WebSocket server.  Each request
+    /// in a batch counts individually.
     pub qps_limit: u64,
     /// Maximum number of bytes to accept in a single request body.
     pub max_body_bytes: u32,
     /// CORS origin.
     pub cors_origin: String,
+    /// Maximum number of requests in a single JSON-RPC batch request.  0 disables batches.  Each
+    /// request in a batch counts towards `qps_limit`.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: u32,
+    /// JSON-RPC WebSocket server configuration.
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
 }
 
 impl Config {
@@ -39,6 +57,8 @@ impl Config {
             qps_limit: DEFAULT_QPS_LIMIT,
             max_body_bytes: DEFAULT_MAX_BODY_BYTES,
             cors_origin: DEFAULT_CORS_ORIGIN.to_string(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            websocket: WebSocketConfig::default(),
         }
     }
 }
@@ -48,3 +68,37 @@ impl Default for Config {
         Config::new()
     }
 }
+
+fn default_max_batch_size() -> u32 {
+    DEFAULT_MAX_BATCH_SIZE
+}
+
+/// JSON-RPC WebSocket server configuration.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct WebSocketConfig {
+    /// Setting to enable the WebSocket server.
+    pub enable_server: bool,
+    /// Address to bind JSON-RPC WebSocket server to.
+    pub address: String,
+    /// Maximum number of concurrent WebSocket connections.
+    pub max_connections: u32,
+    /// Maximum number of active subscriptions per WebSocket connection.
+    pub max_subscriptions_per_connection: u32,
+    /// Maximum rate limit in queries per second for a single WebSocket connection.  Requests are
+    /// also subject to the server-wide `qps_limit`.
+    pub qps_limit_per_connection: u64,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        WebSocketConfig {
+            enable_server: false,
+            address: DEFAULT_WEBSOCKET_ADDRESS.to_string(),
+            max_connections: DEFAULT_MAX_WEBSOCKET_CONNECTIONS,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            qps_limit_per_connection: DEFAULT_WEBSOCKET_QPS_LIMIT_PER_CONNECTION,
+        }
+    }
+}
@@ -1,19 +1,25 @@
+use std::sync::Arc;
+
 use hyper::server::{conn::AddrIncoming, Builder};
 
-use casper_json_rpc::{CorsOrigin, RequestHandlersBuilder};
+use casper_json_rpc::{CorsOrigin, RequestHandlers, RequestHandlersBuilder};
 use casper_types::ProtocolVersion;
 
 use super::{
+    rate_limiter::RateLimiter,
     rpcs::{
         account::PutDeploy,
         chain::{
             GetBlock, GetBlockTransfers, GetEraInfoBySwitchBlock, GetEraSummary, GetStateRootHash,
         },
         docs::ListRpcs,
-        info::{GetChainspec, GetDeploy, GetPeers, GetStatus, GetValidatorChanges},
+        info::{
+            GetAccountDeploys, GetChainspec, GetConsensusStatus, GetDeploy,
+            GetEquivocationEvidence, GetPeers, GetStatus, GetValidatorChanges,
+        },
         state::{
-            GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, GetTrie,
-            QueryBalance, QueryGlobalState,
+            GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, GetStateDiff,
+            GetTrie, ListKeys, QueryBalance, QueryGlobalState,
         },
         RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
     },
@@ -26,16 +32,14 @@ pub const RPC_API_PATH: &str = "rpc";
 
 pub const RPC_API_SERVER_NAME: &str = "JSON RPC";
 
-/// Run the JSON-RPC server.
-pub(super) async fn run<REv: ReactorEventT>(
-    builder: Builder<AddrIncoming>,
+/// Returns the handlers for all RPCs served by the JSON-RPC server.
+pub(super) fn request_handlers<REv: ReactorEventT>(
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
-    qps_limit: u64,
-    max_body_bytes: u32,
-    cors_origin: String,
-) {
+    max_batch_size: u32,
+) -> RequestHandlers {
     let mut handlers = RequestHandlersBuilder::new();
+    handlers.set_max_batch_size(max_batch_size as usize);
     PutDeploy::register_as_handler(effect_builder, api_version, &mut handlers);
     GetBlock::register_as_handler(effect_builder, api_version, &mut handlers);
     GetBlockTransfers::register_as_handler(effect_builder, api_version, &mut handlers);
@@ -45,6 +49,7 @@ pub(super) async fn run<REv: ReactorEventT>(
     GetBalance::register_as_handler(effect_builder, api_version, &mut handlers);
     GetAccountInfo::register_as_handler(effect_builder, api_version, &mut handlers);
     GetDeploy::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetAccountDeploys::register_as_handler(effect_builder, api_version, &mut handlers);
     GetPeers::register_as_handler(effect_builder, api_version, &mut handlers);
     GetStatus::register_as_handler(effect_builder, api_version, &mut handlers);
     GetEraInfoBySwitchBlock::register_as_handler(effect_builder, api_version, &mut handlers);
@@ -52,11 +57,36 @@ pub(super) async fn run<REv: ReactorEventT>(
     GetAuctionInfo::register_as_handler(effect_builder, api_version, &mut handlers);
     GetTrie::register_as_handler(effect_builder, api_version, &mut handlers);
     GetValidatorChanges::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetEquivocationEvidence::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetConsensusStatus::register_as_handler(effect_builder, api_version, &mut handlers);
     ListRpcs::register_as_handler(effect_builder, api_version, &mut handlers);
     GetDictionaryItem::register_as_handler(effect_builder, api_version, &mut handlers);
     GetChainspec::register_as_handler(effect_builder, api_version, &mut handlers);
     QueryBalance::register_as_handler(effect_builder, api_version, &mut handlers);
-    let handlers = handlers.build();
+    GetStateDiff::register_as_handler(effect_builder, api_version, &mut handlers);
+    ListKeys::register_as_handler(effect_builder, api_version, &mut handlers);
+    handlers.build()
+}
+
+/// Run the JSON-RPC server.
+///
+/// Each request, including each request in a batch, waits for `limiter` before being handled.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn run<REv: ReactorEventT>(
+    builder: Builder<AddrIncoming>,
+    effect_builder: EffectBuilder<REv>,
+    api_version: ProtocolVersion,
+    qps_limit: u64,
+    max_body_bytes: u32,
+    max_batch_size: u32,
+    cors_origin: String,
+    limiter: Arc<RateLimiter>,
+) {
+    let handlers =
+        request_handlers(effect_builder, api_version, max_batch_size).with_limiter(move || {
+            let limiter = Arc::clone(&limiter);
+            async move { limiter.acquire().await }
+        });
 
     match cors_origin.as_str() {
         "" => {
@@ -0,0 +1,78 @@
+//! A rate limiter for JSON-RPC requests.
+
+use std::{sync::Mutex, time::Duration};
+
+use tokio::time::{self, Instant};
+
+/// Limits requests to a given number per second, allowing bursts of up to that number.
+///
+/// Requests beyond the limit are delayed rather than rejected, and are let through in the order in
+/// which they arrived.
+#[derive(Debug)]
+pub(super) struct RateLimiter {
+    /// The time it takes to regain the allowance for one request.
+    interval: Duration,
+    /// The largest burst of requests allowed at once, as a duration.
+    burst: Duration,
+    /// The time at which the allowance would be back to full, were no further requests made.
+    refilled_at: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Returns a new limiter allowing `qps` requests per second.
+    pub(super) fn new(qps: u64) -> Self {
+        let interval = Duration::from_nanos(1_000_000_000 / qps.max(1));
+        RateLimiter {
+            interval,
+            burst: interval * qps.clamp(1, u32::MAX as u64) as u32,
+            refilled_at: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits until the next request is allowed.
+    pub(super) async fn acquire(&self) {
+        let now = Instant::now();
+        let allowed_at = {
+            let mut refilled_at = self
+                .refilled_at
+                .lock()
+                .expect("rate limiter mutex should not be poisoned");
+            let new_refilled_at = (*refilled_at).max(now) + self.interval;
+            *refilled_at = new_refilled_at;
+            new_refilled_at.checked_sub(self.burst).unwrap_or(now)
+        };
+        if allowed_at > now {
+            time::sleep_until(allowed_at).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn should_allow_burst_then_limit_rate() {
+        let limiter = RateLimiter::new(10);
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        // After being idle, the full burst should be allowed again.
+        time::sleep(Duration::from_secs(5)).await;
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+    }
+}
@@ -35,6 +35,12 @@ pub enum ErrorCode {
     FailedToGetTrie = -32011,
     /// The requested state root hash was not found.
     NoSuchStateRoot = -32012,
+    /// The account deploy index is not enabled on this node.
+    AccountDeployIndexDisabled = -32013,
+    /// Failed to get the differences between two global states.
+    FailedToGetStateDiff = -32014,
+    /// Failed to list the keys in global state.
+    FailedToListKeys = -32015,
 }
 
 impl From<ErrorCode> for (i64, &'static str) {
@@ -59,6 +65,11 @@ impl From<ErrorCode> for (i64, &'static str) {
             }
             ErrorCode::FailedToGetTrie => (error_code as i64, "Failed to get trie"),
             ErrorCode::NoSuchStateRoot => (error_code as i64, "No such state root"),
+            ErrorCode::AccountDeployIndexDisabled => {
+                (error_code as i64, "Account deploy index disabled")
+            }
+            ErrorCode::FailedToGetStateDiff => (error_code as i64, "Failed to get state diff"),
+            ErrorCode::FailedToListKeys => (error_code as i64, "Failed to list keys"),
         }
     }
 }
@@ -11,14 +11,14 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey};
+use casper_types::{account::AccountHash, EraId, ExecutionResult, ProtocolVersion, PublicKey};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
     Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithParams, RpcWithoutParams,
 };
 use crate::{
-    components::consensus::ValidatorChange,
+    components::consensus::{ConsensusStatus, EvidenceBundle, ValidatorChange},
     effect::EffectBuilder,
     reactor::QueueKind,
     types::{
@@ -40,6 +40,22 @@ static GET_DEPLOY_RESULT: Lazy<GetDeployResult> = Lazy::new(|| GetDeployResult {
     }],
     block_hash_and_height: None,
 });
+static GET_ACCOUNT_DEPLOYS_PARAMS: Lazy<GetAccountDeploysParams> =
+    Lazy::new(|| GetAccountDeploysParams {
+        account_hash: PublicKey::doc_example().to_account_hash(),
+        after: None,
+        max_count: Some(MAX_ACCOUNT_DEPLOYS_PER_REQUEST),
+    });
+static GET_ACCOUNT_DEPLOYS_RESULT: Lazy<GetAccountDeploysResult> =
+    Lazy::new(|| GetAccountDeploysResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        deploys: vec![JsonAccountDeploy {
+            deploy_hash: *Deploy::doc_example().hash(),
+            block_hash: *Block::doc_example().hash(),
+            block_height: Block::doc_example().height(),
+        }],
+        next: None,
+    });
 static GET_PEERS_RESULT: Lazy<GetPeersResult> = Lazy::new(|| GetPeersResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     peers: GetStatusResult::doc_example().peers.clone(),
@@ -53,6 +69,16 @@ static GET_VALIDATOR_CHANGES_RESULT: Lazy<GetValidatorChangesResult> = Lazy::new
         changes,
     }
 });
+static GET_EQUIVOCATION_EVIDENCE_RESULT: Lazy<GetEquivocationEvidenceResult> =
+    Lazy::new(|| GetEquivocationEvidenceResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        evidence: vec![EvidenceBundle::doc_example().clone()],
+    });
+static GET_CONSENSUS_STATUS_RESULT: Lazy<GetConsensusStatusResult> =
+    Lazy::new(|| GetConsensusStatusResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        consensus_status: Some(ConsensusStatus::doc_example().clone()),
+    });
 static GET_CHAINSPEC_RESULT: Lazy<GetChainspecResult> = Lazy::new(|| GetChainspecResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     chainspec_bytes: ChainspecRawBytes::new(vec![42, 42].into(), None, None),
@@ -177,6 +203,139 @@ impl RpcWithParams for GetDeploy {
     }
 }
 
+/// The maximum number of deploys returned by a single "info_get_account_deploys" request.
+pub const MAX_ACCOUNT_DEPLOYS_PER_REQUEST: u32 = 100;
+
+/// A position in the list of deploys involving an account.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AccountDeploysCursor {
+    /// The height of the block which included the deploy.
+    pub block_height: u64,
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+}
+
+/// Params for "info_get_account_deploys" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetAccountDeploysParams {
+    /// The account hash.
+    pub account_hash: AccountHash,
+    /// The position after which to continue listing deploys, as returned in `next` by a previous
+    /// request. If omitted, listing starts with the earliest deploy involving the account.
+    #[serde(default)]
+    pub after: Option<AccountDeploysCursor>,
+    /// The maximum number of deploys to return. If omitted or greater than 100, at most 100
+    /// deploys are returned.
+    #[serde(default)]
+    pub max_count: Option<u32>,
+}
+
+impl DocExample for GetAccountDeploysParams {
+    fn doc_example() -> &'static Self {
+        &GET_ACCOUNT_DEPLOYS_PARAMS
+    }
+}
+
+/// A deploy involving an account, either as the deploy's account or as an approval's signer.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonAccountDeploy {
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+    /// The hash of the block which included the deploy.
+    pub block_hash: BlockHash,
+    /// The height of the block which included the deploy.
+    pub block_height: u64,
+}
+
+/// Result for "info_get_account_deploys" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetAccountDeploysResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The deploys involving the account, in the order in which they were included in blocks.
+    pub deploys: Vec<JsonAccountDeploy>,
+    /// The position to pass as `after` in order to retrieve the next page of deploys, if there
+    /// are more.
+    pub next: Option<AccountDeploysCursor>,
+}
+
+impl DocExample for GetAccountDeploysResult {
+    fn doc_example() -> &'static Self {
+        &GET_ACCOUNT_DEPLOYS_RESULT
+    }
+}
+
+/// "info_get_account_deploys" RPC.
+pub struct GetAccountDeploys {}
+
+#[async_trait]
+impl RpcWithParams for GetAccountDeploys {
+    const METHOD: &'static str = "info_get_account_deploys";
+    type RequestParams = GetAccountDeploysParams;
+    type ResponseResult = GetAccountDeploysResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let max_count = params
+            .max_count
+            .unwrap_or(MAX_ACCOUNT_DEPLOYS_PER_REQUEST)
+            .min(MAX_ACCOUNT_DEPLOYS_PER_REQUEST) as usize;
+        let after = params
+            .after
+            .map(|cursor| (cursor.block_height, cursor.deploy_hash));
+
+        // Request one more than required in order to find out whether there is a next page.
+        let mut account_deploys = match effect_builder
+            .get_account_deploys_from_storage(params.account_hash, after, max_count + 1)
+            .await
+        {
+            Some(account_deploys) => account_deploys,
+            None => {
+                let message = "the account deploy index is not enabled on this node".to_string();
+                info!("{}", message);
+                return Err(Error::new(ErrorCode::AccountDeployIndexDisabled, message));
+            }
+        };
+
+        let has_more = account_deploys.len() > max_count;
+        account_deploys.truncate(max_count);
+        let next = if has_more {
+            account_deploys
+                .last()
+                .map(
+                    |(deploy_hash, block_hash_and_height)| AccountDeploysCursor {
+                        block_height: block_hash_and_height.block_height,
+                        deploy_hash: *deploy_hash,
+                    },
+                )
+        } else {
+            None
+        };
+        let deploys = account_deploys
+            .into_iter()
+            .map(|(deploy_hash, block_hash_and_height)| JsonAccountDeploy {
+                deploy_hash,
+                block_hash: block_hash_and_height.block_hash,
+                block_height: block_hash_and_height.block_height,
+            })
+            .collect();
+
+        Ok(Self::ResponseResult {
+            api_version,
+            deploys,
+            next,
+        })
+    }
+}
+
 /// Result for "info_get_peers" RPC response.
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -348,6 +507,80 @@ impl RpcWithoutParams for GetValidatorChanges {
     }
 }
 
+/// Result for the "info_get_equivocation_evidence" RPC.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEquivocationEvidenceResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The evidence of all equivocations in the eras the node still keeps in memory.
+    pub evidence: Vec<EvidenceBundle>,
+}
+
+impl DocExample for GetEquivocationEvidenceResult {
+    fn doc_example() -> &'static Self {
+        &GET_EQUIVOCATION_EVIDENCE_RESULT
+    }
+}
+
+/// "info_get_equivocation_evidence" RPC.
+pub struct GetEquivocationEvidence {}
+
+#[async_trait]
+impl RpcWithoutParams for GetEquivocationEvidence {
+    const METHOD: &'static str = "info_get_equivocation_evidence";
+    type ResponseResult = GetEquivocationEvidenceResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+    ) -> Result<Self::ResponseResult, Error> {
+        let evidence = effect_builder.get_consensus_equivocation_evidence().await;
+        Ok(Self::ResponseResult {
+            api_version,
+            evidence,
+        })
+    }
+}
+
+/// Result for the "info_get_consensus_status" RPC.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetConsensusStatusResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// A summary of the current era's consensus state, if consensus is initialized.
+    pub consensus_status: Option<ConsensusStatus>,
+}
+
+impl DocExample for GetConsensusStatusResult {
+    fn doc_example() -> &'static Self {
+        &GET_CONSENSUS_STATUS_RESULT
+    }
+}
+
+/// "info_get_consensus_status" RPC.
+pub struct GetConsensusStatus {}
+
+#[async_trait]
+impl RpcWithoutParams for GetConsensusStatus {
+    const METHOD: &'static str = "info_get_consensus_status";
+    type ResponseResult = GetConsensusStatusResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+    ) -> Result<Self::ResponseResult, Error> {
+        let consensus_status = effect_builder.get_consensus_status_summary().await;
+        Ok(Self::ResponseResult {
+            api_version,
+            consensus_status,
+        })
+    }
+}
+
 /// Result for the "info_get_chainspec" RPC.
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct GetChainspecResult {
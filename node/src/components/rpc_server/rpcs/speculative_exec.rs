@@ -1,4 +1,4 @@
-//! RPC related to speculative execution.
+//! RPCs related to speculative execution.
 
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
@@ -10,9 +10,18 @@ use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use casper_execution_engine::core::engine_state::Error as EngineStateError;
+use casper_execution_engine::core::{
+    engine_state::Error as EngineStateError,
+    runtime::{ExecutionTrace, HostFunctionArg, HostFunctionCall},
+};
 use casper_json_rpc::ReservedErrorCode;
-use casper_types::{ExecutionResult, ProtocolVersion};
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{self, Bytes},
+    system::CallStackElement,
+    CLValue, ContractHash, ContractPackageHash, ContractWasm, ContractWasmHash, ExecutionResult,
+    Key, Phase, ProtocolVersion, StoredValue, URef, U512,
+};
 
 use super::{
     chain::BlockIdentifier,
@@ -23,17 +32,50 @@ use super::{
 use crate::{
     components::contract_runtime::SpeculativeExecutionState,
     effect::EffectBuilder,
-    types::{Block, BlockHash, Deploy},
+    types::{Block, BlockHash, Deploy, DeployHash},
 };
 
+/// The maximum number of deploys which can be executed in a single "speculative_exec_batch"
+/// request.
+pub const MAX_DEPLOYS_PER_SPECULATIVE_EXEC_BATCH: usize = 10;
+
 static SPECULATIVE_EXEC_PARAMS: Lazy<SpeculativeExecParams> = Lazy::new(|| SpeculativeExecParams {
     block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
     deploy: Deploy::doc_example().clone(),
+    state_overrides: None,
+    trace: None,
 });
 static SPECULATIVE_EXEC_RESULT: Lazy<SpeculativeExecResult> = Lazy::new(|| SpeculativeExecResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     block_hash: *Block::doc_example().hash(),
     execution_result: ExecutionResult::example().clone(),
+    execution_trace: None,
+});
+static SPECULATIVE_EXEC_BATCH_PARAMS: Lazy<SpeculativeExecBatchParams> =
+    Lazy::new(|| SpeculativeExecBatchParams {
+        block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
+        deploys: vec![Deploy::doc_example().clone()],
+    });
+static SPECULATIVE_EXEC_BATCH_RESULT: Lazy<SpeculativeExecBatchResult> =
+    Lazy::new(|| SpeculativeExecBatchResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        block_hash: *Block::doc_example().hash(),
+        execution_results: vec![SpeculativeDeployExecutionResult {
+            deploy_hash: *Deploy::doc_example().hash(),
+            execution_result: ExecutionResult::example().clone(),
+        }],
+    });
+static ESTIMATE_GAS_PARAMS: Lazy<EstimateGasParams> = Lazy::new(|| EstimateGasParams {
+    block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
+    deploy: Deploy::doc_example().clone(),
+});
+static ESTIMATE_GAS_RESULT: Lazy<EstimateGasResult> = Lazy::new(|| EstimateGasResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    block_hash: *Block::doc_example().hash(),
+    payment_gas: U512::from(100_000_000),
+    session_gas: U512::from(23_456_789),
+    recommended_payment_amount: Some(U512::from(123_456_789)),
+    execution_result: ExecutionResult::example().clone(),
 });
 
 /// Params for "speculative_exec" RPC request.
@@ -44,6 +86,13 @@ pub struct SpeculativeExecParams {
     pub block_identifier: Option<BlockIdentifier>,
     /// Deploy to execute.
     pub deploy: Deploy,
+    /// Values to write to global state on top of the block's state before executing the deploy.
+    ///
+    /// These only apply to the execution itself; the deploy must still be valid against the
+    /// block's unmodified state.
+    pub state_overrides: Option<Vec<StateOverride>>,
+    /// Whether to record and return a trace of the host function calls made during execution.
+    pub trace: Option<bool>,
 }
 
 impl DocExample for SpeculativeExecParams {
@@ -52,6 +101,73 @@ impl DocExample for SpeculativeExecParams {
     }
 }
 
+/// A value to write to global state prior to speculatively executing a deploy.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum StateOverride {
+    /// Replaces the value under the given key.
+    StoredValue {
+        /// The formatted key under which to write the value.
+        key: String,
+        /// The hex-encoded, bytesrepr-serialized `StoredValue` to write.
+        #[schemars(with = "String")]
+        stored_value: Bytes,
+    },
+    /// Sets the balance of the given purse.
+    PurseBalance {
+        /// The purse whose balance to set.
+        purse: URef,
+        /// The balance in motes.
+        #[schemars(with = "String")]
+        balance: U512,
+    },
+    /// Replaces the Wasm under the given contract Wasm hash.
+    ContractWasm {
+        /// The hash under which to write the Wasm.
+        contract_wasm_hash: ContractWasmHash,
+        /// The hex-encoded Wasm module bytes.
+        #[schemars(with = "String")]
+        module_bytes: Bytes,
+    },
+}
+
+impl StateOverride {
+    /// Returns the global state key and value to write for this override.
+    fn into_key_and_value(self) -> Result<(Key, StoredValue), Error> {
+        match self {
+            StateOverride::StoredValue { key, stored_value } => {
+                let key = Key::from_formatted_str(&key).map_err(|error| {
+                    Error::new(
+                        ReservedErrorCode::InvalidParams,
+                        format!("failed to parse key: {}", error),
+                    )
+                })?;
+                let stored_value =
+                    bytesrepr::deserialize(stored_value.into()).map_err(|error| {
+                        Error::new(
+                            ReservedErrorCode::InvalidParams,
+                            format!("failed to parse stored value for {}: {}", key, error),
+                        )
+                    })?;
+                Ok((key, stored_value))
+            }
+            StateOverride::PurseBalance { purse, balance } => {
+                let cl_value = CLValue::from_t(balance).map_err(|error| {
+                    Error::new(ReservedErrorCode::InvalidParams, error.to_string())
+                })?;
+                Ok((Key::Balance(purse.addr()), StoredValue::CLValue(cl_value)))
+            }
+            StateOverride::ContractWasm {
+                contract_wasm_hash,
+                module_bytes,
+            } => Ok((
+                Key::from(contract_wasm_hash),
+                StoredValue::ContractWasm(ContractWasm::new(module_bytes.into())),
+            )),
+        }
+    }
+}
+
 /// Result for "speculative_exec" RPC response.
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -63,6 +179,9 @@ pub struct SpeculativeExecResult {
     pub block_hash: BlockHash,
     /// Result of the execution.
     pub execution_result: ExecutionResult,
+    /// Trace of the host function calls made during execution, if requested.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub execution_trace: Option<JsonExecutionTrace>,
 }
 
 impl DocExample for SpeculativeExecResult {
@@ -71,6 +190,185 @@ impl DocExample for SpeculativeExecResult {
     }
 }
 
+/// The host function calls made while speculatively executing a deploy, in the order in which they
+/// were made.
+///
+/// Calls made by nested contract calls follow the call which invoked them, with a greater `depth`.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonExecutionTrace {
+    /// The host function calls.
+    pub host_function_calls: Vec<JsonHostFunctionCall>,
+    /// The gas consumed by the payment phase.
+    #[schemars(with = "String")]
+    pub payment_cost: U512,
+    /// The gas consumed by the session phase.
+    #[schemars(with = "String")]
+    pub session_cost: U512,
+}
+
+impl From<ExecutionTrace> for JsonExecutionTrace {
+    fn from(execution_trace: ExecutionTrace) -> Self {
+        JsonExecutionTrace {
+            host_function_calls: execution_trace
+                .host_function_calls
+                .into_iter()
+                .map(JsonHostFunctionCall::from)
+                .collect(),
+            payment_cost: execution_trace.payment_cost.value(),
+            session_cost: execution_trace.session_cost.value(),
+        }
+    }
+}
+
+/// A single host function call made by a Wasm module.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonHostFunctionCall {
+    /// The name of the host function, as imported by the Wasm module.
+    pub name: String,
+    /// The arguments identifying what the call operates on, decoded from the module's memory.
+    pub args: Vec<JsonHostFunctionArg>,
+    /// The execution phase in which the call was made.
+    pub phase: JsonPhase,
+    /// The height of the call stack when the call was made.
+    pub depth: u64,
+    /// The call stack element of the contract or session code making the call.
+    pub call_stack_element: Option<JsonCallStackElement>,
+    /// The gas charged for the call itself.
+    #[schemars(with = "String")]
+    pub cost: U512,
+    /// The total gas consumed while the call was in progress, including that of any contract
+    /// calls made by it.
+    #[schemars(with = "String")]
+    pub gas_consumed: U512,
+    /// The trap raised by the call, if any, e.g. when reverting or running out of gas.
+    pub trap: Option<String>,
+}
+
+impl From<HostFunctionCall> for JsonHostFunctionCall {
+    fn from(host_function_call: HostFunctionCall) -> Self {
+        JsonHostFunctionCall {
+            name: host_function_call.name.to_string(),
+            args: host_function_call
+                .args
+                .into_iter()
+                .map(JsonHostFunctionArg::from)
+                .collect(),
+            phase: host_function_call.phase.into(),
+            depth: host_function_call.depth as u64,
+            call_stack_element: host_function_call
+                .call_stack_element
+                .map(JsonCallStackElement::from),
+            cost: host_function_call.cost.value(),
+            gas_consumed: host_function_call.gas_consumed.value(),
+            trap: host_function_call.trap,
+        }
+    }
+}
+
+/// An argument of a host function call.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum JsonHostFunctionArg {
+    /// A global state key, or the address of a called contract or contract package.
+    Key(#[schemars(with = "String")] Key),
+    /// The name of a named key, an entry point or a dictionary item.
+    Name(String),
+    /// The size in bytes of a serialized value or of serialized runtime arguments.
+    ByteLength(u32),
+    /// The error code passed to `casper_revert`.
+    ErrorCode(u32),
+}
+
+impl From<HostFunctionArg> for JsonHostFunctionArg {
+    fn from(host_function_arg: HostFunctionArg) -> Self {
+        match host_function_arg {
+            HostFunctionArg::Key(key) => JsonHostFunctionArg::Key(key),
+            HostFunctionArg::Name(name) => JsonHostFunctionArg::Name(name),
+            HostFunctionArg::ByteLength(length) => JsonHostFunctionArg::ByteLength(length),
+            HostFunctionArg::ErrorCode(code) => JsonHostFunctionArg::ErrorCode(code),
+        }
+    }
+}
+
+/// The phase in which a host function call was made.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+pub enum JsonPhase {
+    /// Set while committing the genesis or upgrade configurations.
+    System,
+    /// Set while executing the payment code of a deploy.
+    Payment,
+    /// Set while executing the session code of a deploy.
+    Session,
+    /// Set while finalizing payment at the end of a deploy.
+    FinalizePayment,
+}
+
+impl From<Phase> for JsonPhase {
+    fn from(phase: Phase) -> Self {
+        match phase {
+            Phase::System => JsonPhase::System,
+            Phase::Payment => JsonPhase::Payment,
+            Phase::Session => JsonPhase::Session,
+            Phase::FinalizePayment => JsonPhase::FinalizePayment,
+        }
+    }
+}
+
+/// The origin of a host function call.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum JsonCallStackElement {
+    /// Session code.
+    Session {
+        /// The account hash of the caller.
+        account_hash: AccountHash,
+    },
+    /// Stored session code.
+    StoredSession {
+        /// The account hash of the caller.
+        account_hash: AccountHash,
+        /// The contract package hash.
+        contract_package_hash: ContractPackageHash,
+        /// The contract hash.
+        contract_hash: ContractHash,
+    },
+    /// A stored contract.
+    StoredContract {
+        /// The contract package hash.
+        contract_package_hash: ContractPackageHash,
+        /// The contract hash.
+        contract_hash: ContractHash,
+    },
+}
+
+impl From<CallStackElement> for JsonCallStackElement {
+    fn from(call_stack_element: CallStackElement) -> Self {
+        match call_stack_element {
+            CallStackElement::Session { account_hash } => {
+                JsonCallStackElement::Session { account_hash }
+            }
+            CallStackElement::StoredSession {
+                account_hash,
+                contract_package_hash,
+                contract_hash,
+            } => JsonCallStackElement::StoredSession {
+                account_hash,
+                contract_package_hash,
+                contract_hash,
+            },
+            CallStackElement::StoredContract {
+                contract_package_hash,
+                contract_hash,
+            } => JsonCallStackElement::StoredContract {
+                contract_package_hash,
+                contract_hash,
+            },
+        }
+    }
+}
+
 /// "speculative_exec" RPC
 pub struct SpeculativeExec {}
 
@@ -88,8 +386,15 @@ impl RpcWithParams for SpeculativeExec {
         let SpeculativeExecParams {
             block_identifier: maybe_block_id,
             deploy,
+            state_overrides,
+            trace,
         } = params;
         let deploy = Arc::new(deploy);
+        let state_overrides = state_overrides
+            .unwrap_or_default()
+            .into_iter()
+            .map(StateOverride::into_key_and_value)
+            .collect::<Result<Vec<_>, _>>()?;
         let only_from_available_block_range = true;
 
         let block = common::get_block(
@@ -114,15 +419,162 @@ impl RpcWithParams for SpeculativeExec {
         }
 
         let result = effect_builder
-            .speculative_execute_deploy(execution_prestate, Arc::clone(&deploy))
+            .speculative_execute_deploy(
+                execution_prestate,
+                Arc::clone(&deploy),
+                state_overrides,
+                trace.unwrap_or_default(),
+            )
             .await;
 
         match result {
-            Ok(Some(execution_result)) => {
+            Ok(Some((execution_result, maybe_execution_trace))) => {
                 let result = Self::ResponseResult {
                     api_version,
                     block_hash,
                     execution_result,
+                    execution_trace: maybe_execution_trace.map(JsonExecutionTrace::from),
+                };
+                Ok(result)
+            }
+            Ok(None) => Err(Error::new(
+                ErrorCode::NoSuchBlock,
+                "block hash not found".to_string(),
+            )),
+            Err(error) => Err(engine_state_error_to_rpc_error(error)),
+        }
+    }
+}
+
+/// Params for "speculative_exec_batch" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SpeculativeExecBatchParams {
+    /// Block hash on top of which to execute the deploys.
+    pub block_identifier: Option<BlockIdentifier>,
+    /// Deploys to execute, in order.  Each is executed on top of the effects of the previous ones.
+    pub deploys: Vec<Deploy>,
+}
+
+impl DocExample for SpeculativeExecBatchParams {
+    fn doc_example() -> &'static Self {
+        &SPECULATIVE_EXEC_BATCH_PARAMS
+    }
+}
+
+/// The result of speculatively executing a single deploy of a batch.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SpeculativeDeployExecutionResult {
+    /// Hash of the executed deploy.
+    pub deploy_hash: DeployHash,
+    /// Result of the execution.
+    pub execution_result: ExecutionResult,
+}
+
+/// Result for "speculative_exec_batch" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SpeculativeExecBatchResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// Hash of the block on top of which the deploys were executed.
+    pub block_hash: BlockHash,
+    /// Results of the executions, in the same order as the requested deploys.
+    pub execution_results: Vec<SpeculativeDeployExecutionResult>,
+}
+
+impl DocExample for SpeculativeExecBatchResult {
+    fn doc_example() -> &'static Self {
+        &SPECULATIVE_EXEC_BATCH_RESULT
+    }
+}
+
+/// "speculative_exec_batch" RPC
+pub struct SpeculativeExecBatch {}
+
+#[async_trait]
+impl RpcWithParams for SpeculativeExecBatch {
+    const METHOD: &'static str = "speculative_exec_batch";
+    type RequestParams = SpeculativeExecBatchParams;
+    type ResponseResult = SpeculativeExecBatchResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let SpeculativeExecBatchParams {
+            block_identifier: maybe_block_id,
+            deploys,
+        } = params;
+        if deploys.is_empty() {
+            return Err(Error::new(
+                ReservedErrorCode::InvalidParams,
+                "at least one deploy must be provided",
+            ));
+        }
+        if deploys.len() > MAX_DEPLOYS_PER_SPECULATIVE_EXEC_BATCH {
+            return Err(Error::new(
+                ReservedErrorCode::InvalidParams,
+                format!(
+                    "{} deploys provided, but the maximum allowed is {}",
+                    deploys.len(),
+                    MAX_DEPLOYS_PER_SPECULATIVE_EXEC_BATCH
+                ),
+            ));
+        }
+        let deploys: Vec<_> = deploys.into_iter().map(Arc::new).collect();
+        let only_from_available_block_range = true;
+
+        let block = common::get_block(
+            maybe_block_id,
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+        let block_hash = *block.hash();
+        let execution_prestate = SpeculativeExecutionState {
+            state_root_hash: *block.state_root_hash(),
+            block_time: block.timestamp(),
+            protocol_version: block.protocol_version(),
+        };
+        let block_header = block.take_header();
+
+        for deploy in &deploys {
+            let accept_deploy_result = effect_builder
+                .try_accept_deploy(Arc::clone(deploy), Some(Box::new(block_header.clone())))
+                .await;
+
+            if let Err(error) = accept_deploy_result {
+                return Err(Error::new(
+                    ErrorCode::InvalidDeploy,
+                    format!("deploy {}: {}", deploy.hash(), error),
+                ));
+            }
+        }
+
+        let deploy_hashes: Vec<_> = deploys.iter().map(|deploy| *deploy.hash()).collect();
+        let result = effect_builder
+            .speculative_execute_deploys(execution_prestate, deploys)
+            .await;
+
+        match result {
+            Ok(Some(execution_results)) => {
+                let result = Self::ResponseResult {
+                    api_version,
+                    block_hash,
+                    execution_results: deploy_hashes
+                        .into_iter()
+                        .zip(execution_results)
+                        .map(
+                            |(deploy_hash, execution_result)| SpeculativeDeployExecutionResult {
+                                deploy_hash,
+                                execution_result,
+                            },
+                        )
+                        .collect(),
                 };
                 Ok(result)
             }
@@ -130,50 +582,171 @@ impl RpcWithParams for SpeculativeExec {
                 ErrorCode::NoSuchBlock,
                 "block hash not found".to_string(),
             )),
-            Err(error) => {
-                let rpc_error = match error {
-                    EngineStateError::RootNotFound(_) => Error::new(ErrorCode::NoSuchStateRoot, ""),
-                    EngineStateError::WasmPreprocessing(error) => {
-                        Error::new(ErrorCode::InvalidDeploy, error.to_string())
-                    }
-                    EngineStateError::InvalidDeployItemVariant(error) => {
-                        Error::new(ErrorCode::InvalidDeploy, error)
-                    }
-                    EngineStateError::InvalidProtocolVersion(_) => Error::new(
-                        ErrorCode::InvalidDeploy,
-                        format!("deploy used invalid protocol version {}", error),
-                    ),
-                    EngineStateError::Deploy => Error::new(ErrorCode::InvalidDeploy, ""),
-                    EngineStateError::Genesis(_)
-                    | EngineStateError::WasmSerialization(_)
-                    | EngineStateError::Exec(_)
-                    | EngineStateError::Storage(_)
-                    | EngineStateError::Authorization
-                    | EngineStateError::InsufficientPayment
-                    | EngineStateError::GasConversionOverflow
-                    | EngineStateError::Finalization
-                    | EngineStateError::Bytesrepr(_)
-                    | EngineStateError::Mint(_)
-                    | EngineStateError::InvalidKeyVariant
-                    | EngineStateError::ProtocolUpgrade(_)
-                    | EngineStateError::CommitError(_)
-                    | EngineStateError::MissingSystemContractRegistry
-                    | EngineStateError::MissingSystemContractHash(_)
-                    | EngineStateError::RuntimeStackOverflow
-                    | EngineStateError::FailedToGetWithdrawKeys
-                    | EngineStateError::FailedToGetStoredWithdraws
-                    | EngineStateError::FailedToGetWithdrawPurses
-                    | EngineStateError::FailedToRetrieveUnbondingDelay
-                    | EngineStateError::FailedToRetrieveEraId => {
-                        Error::new(ReservedErrorCode::InternalError, error.to_string())
-                    }
-                    _ => Error::new(
-                        ReservedErrorCode::InternalError,
-                        format!("Unhandled engine state error: {}", error),
-                    ),
+            Err(error) => Err(engine_state_error_to_rpc_error(error)),
+        }
+    }
+}
+
+/// Params for "estimate_gas" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EstimateGasParams {
+    /// Block hash on top of which to execute the deploy.
+    pub block_identifier: Option<BlockIdentifier>,
+    /// Deploy to estimate.  It must use standard payment, whose amount is ignored.
+    pub deploy: Deploy,
+}
+
+impl DocExample for EstimateGasParams {
+    fn doc_example() -> &'static Self {
+        &ESTIMATE_GAS_PARAMS
+    }
+}
+
+/// Result for "estimate_gas" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EstimateGasResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// Hash of the block on top of which the deploy was executed.
+    pub block_hash: BlockHash,
+    /// The gas consumed by the payment phase.
+    #[schemars(with = "String")]
+    pub payment_gas: U512,
+    /// The gas consumed by the session phase.
+    #[schemars(with = "String")]
+    pub session_gas: U512,
+    /// The smallest `amount` argument for standard payment, in motes, with which the deploy was
+    /// verified to execute successfully, or `null` if it fails even with the maximum payment
+    /// amount.
+    #[schemars(with = "Option<String>")]
+    pub recommended_payment_amount: Option<U512>,
+    /// Result of executing the deploy with the maximum payment amount, i.e. the lesser of the
+    /// account's balance and the cost of the block gas limit.
+    pub execution_result: ExecutionResult,
+}
+
+impl DocExample for EstimateGasResult {
+    fn doc_example() -> &'static Self {
+        &ESTIMATE_GAS_RESULT
+    }
+}
+
+/// "estimate_gas" RPC
+pub struct EstimateGas {}
+
+#[async_trait]
+impl RpcWithParams for EstimateGas {
+    const METHOD: &'static str = "estimate_gas";
+    type RequestParams = EstimateGasParams;
+    type ResponseResult = EstimateGasResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let EstimateGasParams {
+            block_identifier: maybe_block_id,
+            deploy,
+        } = params;
+        if !deploy.payment().is_standard_payment(Phase::Payment) {
+            return Err(Error::new(
+                ReservedErrorCode::InvalidParams,
+                "gas can only be estimated for deploys using standard payment",
+            ));
+        }
+        let deploy = Arc::new(deploy);
+        let only_from_available_block_range = true;
+
+        let block = common::get_block(
+            maybe_block_id,
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+        let block_hash = *block.hash();
+        let execution_prestate = SpeculativeExecutionState {
+            state_root_hash: *block.state_root_hash(),
+            block_time: block.timestamp(),
+            protocol_version: block.protocol_version(),
+        };
+
+        let accept_deploy_result = effect_builder
+            .try_accept_deploy(Arc::clone(&deploy), Some(Box::new(block.take_header())))
+            .await;
+
+        if let Err(error) = accept_deploy_result {
+            return Err(Error::new(ErrorCode::InvalidDeploy, error.to_string()));
+        }
+
+        let result = effect_builder
+            .estimate_gas(execution_prestate, Arc::clone(&deploy))
+            .await;
+
+        match result {
+            Ok(Some(gas_estimate)) => {
+                let result = Self::ResponseResult {
+                    api_version,
+                    block_hash,
+                    payment_gas: gas_estimate.payment_gas.value(),
+                    session_gas: gas_estimate.session_gas.value(),
+                    recommended_payment_amount: gas_estimate.recommended_payment_amount,
+                    execution_result: gas_estimate.execution_result,
                 };
-                Err(rpc_error)
+                Ok(result)
             }
+            Ok(None) => Err(Error::new(
+                ErrorCode::NoSuchBlock,
+                "block hash not found".to_string(),
+            )),
+            Err(error) => Err(engine_state_error_to_rpc_error(error)),
+        }
+    }
+}
+
+fn engine_state_error_to_rpc_error(error: EngineStateError) -> Error {
+    match error {
+        EngineStateError::RootNotFound(_) => Error::new(ErrorCode::NoSuchStateRoot, ""),
+        EngineStateError::WasmPreprocessing(error) => {
+            Error::new(ErrorCode::InvalidDeploy, error.to_string())
+        }
+        EngineStateError::InvalidDeployItemVariant(error) => {
+            Error::new(ErrorCode::InvalidDeploy, error)
+        }
+        EngineStateError::InvalidProtocolVersion(_) => Error::new(
+            ErrorCode::InvalidDeploy,
+            format!("deploy used invalid protocol version {}", error),
+        ),
+        EngineStateError::Deploy => Error::new(ErrorCode::InvalidDeploy, ""),
+        EngineStateError::Genesis(_)
+        | EngineStateError::WasmSerialization(_)
+        | EngineStateError::Exec(_)
+        | EngineStateError::Storage(_)
+        | EngineStateError::Authorization
+        | EngineStateError::InsufficientPayment
+        | EngineStateError::GasConversionOverflow
+        | EngineStateError::Finalization
+        | EngineStateError::Bytesrepr(_)
+        | EngineStateError::Mint(_)
+        | EngineStateError::InvalidKeyVariant
+        | EngineStateError::ProtocolUpgrade(_)
+        | EngineStateError::CommitError(_)
+        | EngineStateError::MissingSystemContractRegistry
+        | EngineStateError::MissingSystemContractHash(_)
+        | EngineStateError::RuntimeStackOverflow
+        | EngineStateError::FailedToGetWithdrawKeys
+        | EngineStateError::FailedToGetStoredWithdraws
+        | EngineStateError::FailedToGetWithdrawPurses
+        | EngineStateError::FailedToRetrieveUnbondingDelay
+        | EngineStateError::FailedToRetrieveEraId => {
+            Error::new(ReservedErrorCode::InternalError, error.to_string())
         }
+        _ => Error::new(
+            ReservedErrorCode::InternalError,
+            format!("Unhandled engine state error: {}", error),
+        ),
     }
 }
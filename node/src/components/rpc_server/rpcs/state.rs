@@ -3,7 +3,7 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use std::str;
+use std::{convert::TryFrom, str};
 
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
@@ -13,15 +13,18 @@ use tracing::{debug, error, info, warn};
 
 use casper_execution_engine::{
     core::engine_state::{self, BalanceResult, GetBidsResult, QueryResult},
-    storage::trie::merkle_proof::TrieMerkleProof,
+    storage::{
+        trie::merkle_proof::TrieMerkleProof,
+        trie_store::{DiffResult, ListKeysResult as DomainListKeysResult, TrieDiff},
+    },
 };
 use casper_hashing::Digest;
 use casper_json_rpc::ReservedErrorCode;
 use casper_types::{
     account::AccountHash,
-    bytesrepr::{Bytes, ToBytes},
-    CLValue, Key, ProtocolVersion, PublicKey, SecretKey, StoredValue as DomainStoredValue, URef,
-    U512,
+    bytesrepr::{self, Bytes, ToBytes},
+    CLValue, Key, KeyTag, ProtocolVersion, PublicKey, SecretKey, StoredValue as DomainStoredValue,
+    URef, U512,
 };
 
 use crate::{
@@ -119,6 +122,41 @@ static GET_TRIE_RESULT: Lazy<GetTrieResult> = Lazy::new(|| GetTrieResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     maybe_trie_bytes: None,
 });
+static GET_STATE_DIFF_PARAMS: Lazy<GetStateDiffParams> = Lazy::new(|| GetStateDiffParams {
+    old_state_root_hash: *Block::doc_example().header().state_root_hash(),
+    new_state_root_hash: Digest::hash([1; 32]),
+    start_after: None,
+    limit: Some(10),
+});
+static GET_STATE_DIFF_RESULT: Lazy<GetStateDiffResult> = Lazy::new(|| GetStateDiffResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    entries: vec![StateDiffEntry::Modified {
+        key: "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007"
+            .to_string(),
+        old_value: StoredValue::CLValue(CLValue::from_t(1u64).unwrap()),
+        new_value: StoredValue::CLValue(CLValue::from_t(2u64).unwrap()),
+    }],
+    next_start_after: None,
+});
+static LIST_KEYS_PARAMS: Lazy<ListKeysParams> = Lazy::new(|| ListKeysParams {
+    state_identifier: Some(GlobalStateIdentifier::BlockHash(
+        *Block::doc_example().hash(),
+    )),
+    key_tag: JsonKeyTag::Dictionary,
+    prefix: None,
+    dictionary_seed_uref: Some(
+        "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007".to_string(),
+    ),
+    start_after: None,
+    limit: Some(10),
+});
+static LIST_KEYS_RESULT: Lazy<ListKeysResult> = Lazy::new(|| ListKeysResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    keys: vec![
+        "dictionary-67518854aa916c97d4e53df8570c8217ccc259da2721b692102d76acd0ee8d1f".to_string(),
+    ],
+    next_start_after: None,
+});
 static QUERY_BALANCE_PARAMS: Lazy<QueryBalanceParams> = Lazy::new(|| QueryBalanceParams {
     state_identifier: Some(GlobalStateIdentifier::BlockHash(
         *Block::doc_example().hash(),
@@ -1098,6 +1136,469 @@ impl RpcWithParams for GetTrie {
     }
 }
 
+/// The maximum number of entries returned by a single "state_get_diff" RPC request.
+const MAX_STATE_DIFF_ENTRIES: u32 = 1000;
+
+/// Params for "state_get_diff" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetStateDiffParams {
+    /// Hash of the state root of the base global state.
+    pub old_state_root_hash: Digest,
+    /// Hash of the state root of the global state to be compared against the base.
+    pub new_state_root_hash: Digest,
+    /// `casper_types::Key` as formatted string.  If given, only differences in keys following this
+    /// one are returned.  Used to request subsequent pages of the differences.
+    pub start_after: Option<String>,
+    /// The maximum number of entries to return.  Defaults to, and is capped at, 1000.
+    pub limit: Option<u32>,
+}
+
+impl DocExample for GetStateDiffParams {
+    fn doc_example() -> &'static Self {
+        &GET_STATE_DIFF_PARAMS
+    }
+}
+
+/// A difference between two global states.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum StateDiffEntry {
+    /// A value only present in the new global state.
+    Added {
+        /// `casper_types::Key` as formatted string.
+        key: String,
+        /// The value in the new global state.
+        value: StoredValue,
+    },
+    /// A value only present in the old global state.
+    Removed {
+        /// `casper_types::Key` as formatted string.
+        key: String,
+        /// The value in the old global state.
+        value: StoredValue,
+    },
+    /// A value present in both global states, but differing between them.
+    Modified {
+        /// `casper_types::Key` as formatted string.
+        key: String,
+        /// The value in the old global state.
+        old_value: StoredValue,
+        /// The value in the new global state.
+        new_value: StoredValue,
+    },
+}
+
+impl TryFrom<TrieDiff<Key, DomainStoredValue>> for StateDiffEntry {
+    type Error = bytesrepr::Error;
+
+    fn try_from(diff: TrieDiff<Key, DomainStoredValue>) -> Result<Self, Self::Error> {
+        let entry = match diff {
+            TrieDiff::Added { key, value } => StateDiffEntry::Added {
+                key: key.to_formatted_string(),
+                value: StoredValue::try_from(value)?,
+            },
+            TrieDiff::Removed { key, value } => StateDiffEntry::Removed {
+                key: key.to_formatted_string(),
+                value: StoredValue::try_from(value)?,
+            },
+            TrieDiff::Modified {
+                key,
+                old_value,
+                new_value,
+            } => StateDiffEntry::Modified {
+                key: key.to_formatted_string(),
+                old_value: StoredValue::try_from(old_value)?,
+                new_value: StoredValue::try_from(new_value)?,
+            },
+        };
+        Ok(entry)
+    }
+}
+
+/// Result for "state_get_diff" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetStateDiffResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The differences between the global states, in the order of their serialized keys.
+    pub entries: Vec<StateDiffEntry>,
+    /// If there are more differences than returned, the key to pass as `start_after` in order to
+    /// request the next page.
+    pub next_start_after: Option<String>,
+}
+
+impl DocExample for GetStateDiffResult {
+    fn doc_example() -> &'static Self {
+        &GET_STATE_DIFF_RESULT
+    }
+}
+
+/// "state_get_diff" RPC.
+pub struct GetStateDiff {}
+
+#[async_trait]
+impl RpcWithParams for GetStateDiff {
+    const METHOD: &'static str = "state_get_diff";
+    type RequestParams = GetStateDiffParams;
+    type ResponseResult = GetStateDiffResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let start_after = match params.start_after {
+            Some(start_after) => match Key::from_formatted_str(&start_after) {
+                Ok(key) => Some(key),
+                Err(error) => {
+                    let error_msg = format!("failed to parse start_after key: {}", error);
+                    info!("{}", error_msg);
+                    return Err(Error::new(ErrorCode::FailedToParseQueryKey, error_msg));
+                }
+            },
+            None => None,
+        };
+        let limit = match params.limit {
+            Some(0) => {
+                return Err(Error::new(
+                    ReservedErrorCode::InvalidParams,
+                    "limit must be greater than 0",
+                ))
+            }
+            Some(limit) => limit.min(MAX_STATE_DIFF_ENTRIES),
+            None => MAX_STATE_DIFF_ENTRIES,
+        } as usize;
+
+        // Request one entry more than the limit in order to know whether there is another page.
+        let diff_result = effect_builder
+            .get_state_diff(
+                params.old_state_root_hash,
+                params.new_state_root_hash,
+                start_after,
+                limit + 1,
+            )
+            .await;
+
+        let mut diffs = match diff_result {
+            Ok(DiffResult::Found(diffs)) => diffs,
+            Ok(DiffResult::TrieNotFound(digest))
+                if digest == params.old_state_root_hash || digest == params.new_state_root_hash =>
+            {
+                info!(%digest, "state diff failed: root not found");
+                let error = common::missing_block_or_state_root_error(
+                    effect_builder,
+                    ErrorCode::NoSuchStateRoot,
+                    format!("failed to get state root at {:?}", digest),
+                )
+                .await;
+                return Err(error);
+            }
+            Ok(DiffResult::TrieNotFound(digest)) => {
+                warn!(%digest, "state diff failed: trie not found");
+                return Err(Error::new(
+                    ErrorCode::FailedToGetStateDiff,
+                    format!("missing trie {}", digest),
+                ));
+            }
+            Err(error) => {
+                warn!(?error, "failed to get state diff");
+                return Err(Error::new(
+                    ErrorCode::FailedToGetStateDiff,
+                    format!("{:?}", error),
+                ));
+            }
+        };
+
+        let next_start_after = if diffs.len() > limit {
+            diffs.truncate(limit);
+            diffs.last().map(|diff| diff.key().to_formatted_string())
+        } else {
+            None
+        };
+
+        let entries = diffs
+            .into_iter()
+            .map(StateDiffEntry::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| {
+                warn!(?error, "failed to encode stored value");
+                Error::new(
+                    ReservedErrorCode::InternalError,
+                    format!("failed to encode stored value: {}", error),
+                )
+            })?;
+
+        Ok(Self::ResponseResult {
+            api_version,
+            entries,
+            next_start_after,
+        })
+    }
+}
+
+/// The maximum number of keys returned by a single "state_list_keys" RPC request.
+const MAX_LISTED_KEYS: u32 = 1000;
+
+/// The maximum number of entries read by a single "state_list_keys" RPC request.  Only makes a
+/// difference when listing a dictionary's entries, as they're interleaved with all others.
+const MAX_SCANNED_KEYS: usize = 10_000;
+
+/// The type of a `casper_types::Key`, as given by its variant.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum JsonKeyTag {
+    /// `Key::Account`.
+    Account,
+    /// `Key::Hash`.
+    Hash,
+    /// `Key::URef`.
+    URef,
+    /// `Key::Transfer`.
+    Transfer,
+    /// `Key::DeployInfo`.
+    DeployInfo,
+    /// `Key::EraInfo`.
+    EraInfo,
+    /// `Key::Balance`.
+    Balance,
+    /// `Key::Bid`.
+    Bid,
+    /// `Key::Withdraw`.
+    Withdraw,
+    /// `Key::Dictionary`.
+    Dictionary,
+    /// `Key::SystemContractRegistry`.
+    SystemContractRegistry,
+    /// `Key::EraSummary`.
+    EraSummary,
+    /// `Key::Unbond`.
+    Unbond,
+    /// `Key::ChainspecRegistry`.
+    ChainspecRegistry,
+    /// `Key::ChecksumRegistry`.
+    ChecksumRegistry,
+}
+
+impl From<JsonKeyTag> for KeyTag {
+    fn from(key_tag: JsonKeyTag) -> Self {
+        match key_tag {
+            JsonKeyTag::Account => KeyTag::Account,
+            JsonKeyTag::Hash => KeyTag::Hash,
+            JsonKeyTag::URef => KeyTag::URef,
+            JsonKeyTag::Transfer => KeyTag::Transfer,
+            JsonKeyTag::DeployInfo => KeyTag::DeployInfo,
+            JsonKeyTag::EraInfo => KeyTag::EraInfo,
+            JsonKeyTag::Balance => KeyTag::Balance,
+            JsonKeyTag::Bid => KeyTag::Bid,
+            JsonKeyTag::Withdraw => KeyTag::Withdraw,
+            JsonKeyTag::Dictionary => KeyTag::Dictionary,
+            JsonKeyTag::SystemContractRegistry => KeyTag::SystemContractRegistry,
+            JsonKeyTag::EraSummary => KeyTag::EraSummary,
+            JsonKeyTag::Unbond => KeyTag::Unbond,
+            JsonKeyTag::ChainspecRegistry => KeyTag::ChainspecRegistry,
+            JsonKeyTag::ChecksumRegistry => KeyTag::ChecksumRegistry,
+        }
+    }
+}
+
+/// Params for "state_list_keys" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListKeysParams {
+    /// The identifier for the state used for the query.  If none is passed, the latest block will
+    /// be used.
+    pub state_identifier: Option<GlobalStateIdentifier>,
+    /// The type of the keys to list.
+    pub key_tag: JsonKeyTag,
+    /// Hex-encoded bytes which the serialized keys have to start with, following the key tag.
+    pub prefix: Option<String>,
+    /// `casper_types::URef` as formatted string.  If given, only the entries of the dictionary
+    /// with this seed are listed.  Only valid with the `Dictionary` key tag.  As the entries of
+    /// all dictionaries are searched through, fewer than `limit` keys may be returned even if
+    /// there are more: continue from `next_start_after` until it is no longer given.
+    pub dictionary_seed_uref: Option<String>,
+    /// `casper_types::Key` as formatted string.  If given, only keys following this one are
+    /// returned.  Used to request subsequent pages of the keys.
+    pub start_after: Option<String>,
+    /// The maximum number of keys to return.  Defaults to, and is capped at, 1000.
+    pub limit: Option<u32>,
+}
+
+impl DocExample for ListKeysParams {
+    fn doc_example() -> &'static Self {
+        &LIST_KEYS_PARAMS
+    }
+}
+
+/// Result for "state_list_keys" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListKeysResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The keys, as formatted strings, in the order of their serialized forms.
+    pub keys: Vec<String>,
+    /// If there may be more keys than returned, the key to pass as `start_after` in order to
+    /// request the next page.
+    pub next_start_after: Option<String>,
+}
+
+impl DocExample for ListKeysResult {
+    fn doc_example() -> &'static Self {
+        &LIST_KEYS_RESULT
+    }
+}
+
+/// "state_list_keys" RPC.
+pub struct ListKeys {}
+
+#[async_trait]
+impl RpcWithParams for ListKeys {
+    const METHOD: &'static str = "state_list_keys";
+    type RequestParams = ListKeysParams;
+    type ResponseResult = ListKeysResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let state_root_hash = match params.state_identifier {
+            None => match effect_builder
+                .get_highest_complete_block_header_from_storage()
+                .await
+            {
+                None => {
+                    return Err(Error::new(
+                        ErrorCode::NoSuchBlock,
+                        "list-keys failed to retrieve highest block header",
+                    ))
+                }
+                Some(block_header) => *block_header.state_root_hash(),
+            },
+            Some(state_identifier) => {
+                let (state_root_hash, _) =
+                    get_state_root_hash_and_optional_header(effect_builder, state_identifier)
+                        .await?;
+                state_root_hash
+            }
+        };
+
+        let mut prefix = vec![KeyTag::from(params.key_tag) as u8];
+        if let Some(hex_prefix) = params.prefix {
+            match base16::decode(&hex_prefix) {
+                Ok(bytes) => prefix.extend(bytes),
+                Err(error) => {
+                    return Err(Error::new(
+                        ReservedErrorCode::InvalidParams,
+                        format!("failed to decode prefix: {}", error),
+                    ))
+                }
+            }
+        }
+        let dictionary_seed_uref = match params.dictionary_seed_uref {
+            Some(_) if params.key_tag != JsonKeyTag::Dictionary => {
+                return Err(Error::new(
+                    ReservedErrorCode::InvalidParams,
+                    "dictionary_seed_uref is only valid with the Dictionary key tag",
+                ))
+            }
+            Some(seed_uref) => match URef::from_formatted_str(&seed_uref) {
+                Ok(seed_uref) => Some(seed_uref),
+                Err(error) => {
+                    return Err(Error::new(
+                        ReservedErrorCode::InvalidParams,
+                        format!("failed to parse dictionary_seed_uref: {}", error),
+                    ))
+                }
+            },
+            None => None,
+        };
+        let start_after = match params.start_after {
+            Some(start_after) => match Key::from_formatted_str(&start_after) {
+                Ok(key) => Some(key),
+                Err(error) => {
+                    let error_msg = format!("failed to parse start_after key: {}", error);
+                    info!("{}", error_msg);
+                    return Err(Error::new(ErrorCode::FailedToParseQueryKey, error_msg));
+                }
+            },
+            None => None,
+        };
+        let limit = match params.limit {
+            Some(0) => {
+                return Err(Error::new(
+                    ReservedErrorCode::InvalidParams,
+                    "limit must be greater than 0",
+                ))
+            }
+            Some(limit) => limit.min(MAX_LISTED_KEYS),
+            None => MAX_LISTED_KEYS,
+        } as usize;
+
+        // Request one key more than the limit in order to know whether there is another page.
+        let list_keys_result = effect_builder
+            .list_keys(
+                state_root_hash,
+                prefix,
+                dictionary_seed_uref,
+                start_after,
+                limit + 1,
+                MAX_SCANNED_KEYS,
+            )
+            .await;
+
+        let (mut keys, scan_stopped_after) = match list_keys_result {
+            Ok(DomainListKeysResult::Found {
+                keys,
+                scan_stopped_after,
+            }) => (keys, scan_stopped_after),
+            Ok(DomainListKeysResult::TrieNotFound(digest)) if digest == state_root_hash => {
+                info!(%digest, "list keys failed: root not found");
+                let error = common::missing_block_or_state_root_error(
+                    effect_builder,
+                    ErrorCode::NoSuchStateRoot,
+                    format!("failed to get state root at {:?}", digest),
+                )
+                .await;
+                return Err(error);
+            }
+            Ok(DomainListKeysResult::TrieNotFound(digest)) => {
+                warn!(%digest, "list keys failed: trie not found");
+                return Err(Error::new(
+                    ErrorCode::FailedToListKeys,
+                    format!("missing trie {}", digest),
+                ));
+            }
+            Err(error) => {
+                warn!(?error, "failed to list keys");
+                return Err(Error::new(
+                    ErrorCode::FailedToListKeys,
+                    format!("{:?}", error),
+                ));
+            }
+        };
+
+        let next_start_after = if keys.len() > limit {
+            keys.truncate(limit);
+            keys.last().map(|key| key.to_formatted_string())
+        } else {
+            // The search may have been cut short before finding `limit` keys.
+            scan_stopped_after.map(|key| key.to_formatted_string())
+        };
+
+        Ok(Self::ResponseResult {
+            api_version,
+            keys: keys.into_iter().map(Key::to_formatted_string).collect(),
+            next_start_after,
+        })
+    }
+}
+
 type QuerySuccess = (
     DomainStoredValue,
     Vec<TrieMerkleProof<Key, DomainStoredValue>>,
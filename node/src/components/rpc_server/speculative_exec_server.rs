@@ -6,7 +6,10 @@ use casper_types::ProtocolVersion;
 use super::ReactorEventT;
 use crate::{
     effect::EffectBuilder,
-    rpcs::{speculative_exec::SpeculativeExec, RpcWithParams},
+    rpcs::{
+        speculative_exec::{EstimateGas, SpeculativeExec, SpeculativeExecBatch},
+        RpcWithParams,
+    },
 };
 
 /// The URL path for all JSON-RPC requests.
@@ -25,6 +28,8 @@ pub(super) async fn run<REv: ReactorEventT>(
 ) {
     let mut handlers = RequestHandlersBuilder::new();
     SpeculativeExec::register_as_handler(effect_builder, api_version, &mut handlers);
+    SpeculativeExecBatch::register_as_handler(effect_builder, api_version, &mut handlers);
+    EstimateGas::register_as_handler(effect_builder, api_version, &mut handlers);
     let handlers = handlers.build();
 
     match cors_origin.as_str() {
@@ -0,0 +1,705 @@
+//! A WebSocket transport for the JSON-RPC server.
+//!
+//! Every text or binary message received on a WebSocket connection is handled as a single JSON-RPC
+//! request or batch of requests, using the same set of handlers as the HTTP server.  Responses are
+//! sent back as text messages on the same connection.
+//!
+//! In addition, clients may call the `subscribe` method with params `{"event": <EVENT>}` where
+//! `<EVENT>` is one of `"BlockAdded"`, `"DeployProcessed"` or `"FinalitySignature"`.  The result
+//! is a subscription ID, and from then on matching events are pushed to the client as JSON-RPC
+//! notifications of the form:
+//! ```json
+//! {"jsonrpc":"2.0","method":"subscription","params":{"subscription":<ID>,"result":<EVENT DATA>}}
+//! ```
+//! where `<EVENT DATA>` is the same JSON as provided by the event stream server.  A subscription is
+//! cancelled by calling `unsubscribe` with params `{"subscription": <ID>}`.  These two methods are
+//! not supported within batch requests.
+//!
+//! Requests, counting each request in a batch individually, are rate-limited both per connection
+//! and across all connections.
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use futures::{stream::SplitStream, SinkExt, StreamExt};
+use http::StatusCode;
+use hyper::server::{
+    conn::{AddrIncoming, AddrStream},
+    Builder,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc, oneshot, OwnedSemaphorePermit, Semaphore,
+};
+use tracing::{debug, info, warn};
+use warp::{
+    ws::{Message, WebSocket, Ws},
+    Filter, Reply,
+};
+
+use casper_json_rpc::{
+    Error, Params, RequestHandlers, RequestHandlersBuilder, ReservedErrorCode, ResponseBody,
+};
+
+use super::{
+    http_server::RPC_API_PATH, rate_limiter::RateLimiter,
+    rpcs::ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST,
+};
+use crate::components::event_stream_server::SseData;
+
+pub const WS_API_SERVER_NAME: &str = "JSON RPC WebSocket";
+
+/// The JSON-RPC method to subscribe to an event type.
+const SUBSCRIBE_METHOD: &str = "subscribe";
+/// The JSON-RPC method to cancel a subscription.
+const UNSUBSCRIBE_METHOD: &str = "unsubscribe";
+/// The JSON-RPC method used in notifications pushed to subscribers.
+const NOTIFICATION_METHOD: &str = "subscription";
+
+/// Maximum number of responses queued for sending on a single connection.
+const RESPONSE_QUEUE_SIZE: usize = 16;
+
+/// The ID assigned to a subscription, unique per connection.
+type SubscriptionId = u64;
+
+/// The event types to which a WebSocket client can subscribe.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+enum SubscriptionTopic {
+    /// A block has been added to the linear chain and stored locally.
+    BlockAdded,
+    /// A deploy has been executed, committed and forms part of a block.
+    DeployProcessed,
+    /// A new finality signature has been received.
+    FinalitySignature,
+}
+
+impl SubscriptionTopic {
+    fn matches(self, sse_data: &SseData) -> bool {
+        matches!(
+            (self, sse_data),
+            (SubscriptionTopic::BlockAdded, SseData::BlockAdded { .. })
+                | (
+                    SubscriptionTopic::DeployProcessed,
+                    SseData::DeployProcessed { .. }
+                )
+                | (
+                    SubscriptionTopic::FinalitySignature,
+                    SseData::FinalitySignature(_)
+                )
+        )
+    }
+}
+
+/// Params for the "subscribe" method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SubscribeParams {
+    event: SubscriptionTopic,
+}
+
+/// Params for the "unsubscribe" method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnsubscribeParams {
+    subscription: SubscriptionId,
+}
+
+/// The subset of a JSON-RPC request used to decide which set of handlers to dispatch it to.
+#[derive(Deserialize)]
+struct RequestMethod<'a> {
+    #[serde(borrow)]
+    method: Cow<'a, str>,
+}
+
+/// A JSON-RPC notification pushed to a subscriber.
+#[derive(Serialize)]
+struct Notification<'a> {
+    jsonrpc: &'static str,
+    method: &'static str,
+    params: NotificationParams<'a>,
+}
+
+#[derive(Serialize)]
+struct NotificationParams<'a> {
+    subscription: SubscriptionId,
+    result: &'a SseData,
+}
+
+/// The active subscriptions of a single connection.
+#[derive(Debug)]
+struct Subscriptions {
+    next_id: SubscriptionId,
+    max_count: usize,
+    topics: BTreeMap<SubscriptionId, SubscriptionTopic>,
+}
+
+impl Subscriptions {
+    fn new(max_count: u32) -> Self {
+        Subscriptions {
+            next_id: 0,
+            max_count: max_count as usize,
+            topics: BTreeMap::new(),
+        }
+    }
+
+    fn subscribe(&mut self, topic: SubscriptionTopic) -> Result<SubscriptionId, Error> {
+        if self.topics.len() >= self.max_count {
+            return Err(Error::new(
+                ReservedErrorCode::InvalidRequest,
+                format!(
+                    "too many subscriptions on this connection (limit: {})",
+                    self.max_count
+                ),
+            ));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.topics.insert(id, topic);
+        Ok(id)
+    }
+
+    fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.topics.remove(&id).is_some()
+    }
+
+    fn matching(&self, sse_data: &SseData) -> Vec<SubscriptionId> {
+        self.topics
+            .iter()
+            .filter(|(_, topic)| topic.matches(sse_data))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(maybe_params: Option<Params>) -> Result<T, Error> {
+    let params = match maybe_params {
+        Some(params) => serde_json::Value::from(params),
+        None => {
+            return Err(Error::new(
+                ReservedErrorCode::InvalidParams,
+                "Missing 'params' field",
+            ))
+        }
+    };
+    serde_json::from_value(params).map_err(|error| {
+        Error::new(
+            ReservedErrorCode::InvalidParams,
+            format!("Failed to parse 'params' field: {}", error),
+        )
+    })
+}
+
+/// Returns the handlers for "subscribe" and "unsubscribe" which act on the given subscriptions.
+fn subscription_handlers(subscriptions: &Arc<Mutex<Subscriptions>>) -> RequestHandlers {
+    let mut handlers = RequestHandlersBuilder::new();
+
+    let subs = Arc::clone(subscriptions);
+    let subscribe = move |maybe_params| {
+        let subs = Arc::clone(&subs);
+        async move {
+            let params: SubscribeParams = parse_params(maybe_params)?;
+            subs.lock()
+                .expect("subscriptions mutex should not be poisoned")
+                .subscribe(params.event)
+        }
+    };
+    handlers.register_handler(SUBSCRIBE_METHOD, Arc::new(subscribe));
+
+    let subs = Arc::clone(subscriptions);
+    let unsubscribe = move |maybe_params| {
+        let subs = Arc::clone(&subs);
+        async move {
+            let params: UnsubscribeParams = parse_params(maybe_params)?;
+            Ok::<_, Error>(
+                subs.lock()
+                    .expect("subscriptions mutex should not be poisoned")
+                    .unsubscribe(params.subscription),
+            )
+        }
+    };
+    handlers.register_handler(UNSUBSCRIBE_METHOD, Arc::new(unsubscribe));
+
+    handlers.build()
+}
+
+/// Handles a single incoming message, returning the JSON-RPC response if one should be sent.
+async fn handle_message(
+    message: &[u8],
+    rpc_handlers: &RequestHandlers,
+    subscription_handlers: &RequestHandlers,
+) -> Option<ResponseBody> {
+    let is_subscription_request = serde_json::from_slice::<RequestMethod>(message)
+        .map(|request| request.method == SUBSCRIBE_METHOD || request.method == UNSUBSCRIBE_METHOD)
+        .unwrap_or(false);
+    let handlers = if is_subscription_request {
+        subscription_handlers
+    } else {
+        rpc_handlers
+    };
+    handlers
+        .handle_raw_request(message, ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST)
+        .await
+}
+
+fn notification_message(subscription: SubscriptionId, sse_data: &SseData) -> Option<Message> {
+    let notification = Notification {
+        jsonrpc: "2.0",
+        method: NOTIFICATION_METHOD,
+        params: NotificationParams {
+            subscription,
+            result: sse_data,
+        },
+    };
+    match serde_json::to_string(&notification) {
+        Ok(text) => Some(Message::text(text)),
+        Err(error) => {
+            warn!(%error, "failed to encode websocket notification");
+            None
+        }
+    }
+}
+
+/// Handles the requests received on `stream` one at a time, queuing the responses for sending.
+///
+/// Returns once the client closes the connection, or once the responses can no longer be sent.
+async fn handle_requests(
+    mut stream: SplitStream<WebSocket>,
+    remote_address: SocketAddr,
+    rpc_handlers: RequestHandlers,
+    subscription_handlers: RequestHandlers,
+    responses: mpsc::Sender<Message>,
+) {
+    while let Some(maybe_message) = stream.next().await {
+        let message = match maybe_message {
+            Ok(message) => message,
+            Err(error) => {
+                debug!(%remote_address, %error, "websocket connection error");
+                return;
+            }
+        };
+        if message.is_close() {
+            return;
+        }
+        if !message.is_text() && !message.is_binary() {
+            continue;
+        }
+        let maybe_response =
+            handle_message(message.as_bytes(), &rpc_handlers, &subscription_handlers).await;
+        let text = match maybe_response.map(|response| serde_json::to_string(&response)) {
+            Some(Ok(text)) => text,
+            Some(Err(error)) => {
+                warn!(%error, "failed to encode websocket response");
+                continue;
+            }
+            None => continue,
+        };
+        if responses.send(Message::text(text)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Serves a single WebSocket connection until either side closes it.
+///
+/// Requests are limited to `qps_limit` per second on this connection, as well as by the limiter
+/// shared by all connections.
+#[allow(clippy::too_many_arguments)]
+async fn handle_connection(
+    socket: WebSocket,
+    remote_address: SocketAddr,
+    rpc_handlers: RequestHandlers,
+    mut notifications: broadcast::Receiver<SseData>,
+    max_subscriptions: u32,
+    qps_limit: u64,
+    shared_limiter: Arc<RateLimiter>,
+    _permit: OwnedSemaphorePermit,
+) {
+    let connection_limiter = Arc::new(RateLimiter::new(qps_limit));
+    let limit = move || {
+        let connection_limiter = Arc::clone(&connection_limiter);
+        let shared_limiter = Arc::clone(&shared_limiter);
+        async move {
+            connection_limiter.acquire().await;
+            shared_limiter.acquire().await;
+        }
+    };
+    let rpc_handlers = rpc_handlers.with_limiter(limit.clone());
+    let subscriptions = Arc::new(Mutex::new(Subscriptions::new(max_subscriptions)));
+    let subscription_handlers = subscription_handlers(&subscriptions).with_limiter(limit);
+    let (mut sink, stream) = socket.split();
+
+    // Requests are handled on their own task, so that notifications keep being received while a
+    // request is slow or rate-limited, rather than lagging behind and dropping the connection.
+    let (response_sender, mut responses) = mpsc::channel(RESPONSE_QUEUE_SIZE);
+    let request_handler = tokio::spawn(handle_requests(
+        stream,
+        remote_address,
+        rpc_handlers,
+        subscription_handlers,
+        response_sender,
+    ));
+
+    loop {
+        let outgoing = tokio::select! {
+            maybe_response = responses.recv() => match maybe_response {
+                Some(response) => vec![response],
+                None => break,
+            },
+            result = notifications.recv() => match result {
+                Ok(sse_data) => subscriptions
+                    .lock()
+                    .expect("subscriptions mutex should not be poisoned")
+                    .matching(&sse_data)
+                    .into_iter()
+                    .filter_map(|subscription| notification_message(subscription, &sse_data))
+                    .collect(),
+                Err(RecvError::Lagged(lagged_count)) => {
+                    info!(
+                        %remote_address,
+                        %lagged_count,
+                        "client lagged: dropping websocket connection to client",
+                    );
+                    break;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        for message in outgoing {
+            if let Err(error) = sink.send(message).await {
+                debug!(%remote_address, %error, "failed to send websocket message");
+                request_handler.abort();
+                return;
+            }
+        }
+    }
+
+    request_handler.abort();
+    let _ = sink.close().await;
+    debug!(%remote_address, "websocket connection closed");
+}
+
+/// Run the JSON-RPC WebSocket server.
+///
+/// Requests on each connection are limited to `qps_limit_per_connection`, and across all
+/// connections by `shared_limiter`.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn run(
+    builder: Builder<AddrIncoming>,
+    handlers: RequestHandlers,
+    notification_sender: broadcast::Sender<SseData>,
+    max_message_bytes: u32,
+    max_connections: u32,
+    max_subscriptions_per_connection: u32,
+    qps_limit_per_connection: u64,
+    shared_limiter: Arc<RateLimiter>,
+) {
+    let permits = Arc::new(Semaphore::new(max_connections as usize));
+    let upgrade = move |remote_address: SocketAddr, ws: Ws| {
+        let permit = match Arc::clone(&permits).try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                info!(
+                    %remote_address,
+                    "websocket connection limit reached: rejecting client",
+                );
+                return warp::reply::with_status(
+                    "websocket connection limit reached",
+                    StatusCode::SERVICE_UNAVAILABLE,
+                )
+                .into_response();
+            }
+        };
+        let handlers = handlers.clone();
+        let notifications = notification_sender.subscribe();
+        let shared_limiter = Arc::clone(&shared_limiter);
+        ws.max_message_size(max_message_bytes as usize)
+            .on_upgrade(move |socket| {
+                handle_connection(
+                    socket,
+                    remote_address,
+                    handlers,
+                    notifications,
+                    max_subscriptions_per_connection,
+                    qps_limit_per_connection,
+                    shared_limiter,
+                    permit,
+                )
+            })
+            .into_response()
+    };
+
+    let make_svc = hyper::service::make_service_fn(move |connection: &AddrStream| {
+        let remote_address = connection.remote_addr();
+        let ws_route = warp::any()
+            .map(move || remote_address)
+            .and(warp::path(RPC_API_PATH))
+            .and(warp::path::end())
+            .and(warp::ws())
+            .map(upgrade.clone());
+        let service = warp::service(ws_route);
+        async move { Ok::<_, Infallible>(service) }
+    });
+
+    let server = builder.serve(make_svc);
+    info!(address = %server.local_addr(), "started {} server", WS_API_SERVER_NAME);
+
+    let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+    let server_with_shutdown = server.with_graceful_shutdown(async {
+        shutdown_receiver.await.ok();
+    });
+
+    let _ = tokio::spawn(server_with_shutdown).await;
+    let _ = shutdown_sender.send(());
+    info!("{} server shut down", WS_API_SERVER_NAME);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::Rng;
+    use serde_json::{json, Value};
+    use tokio::time::Instant;
+    use warp::test::WsClient;
+
+    use casper_types::testing::TestRng;
+
+    use super::*;
+    use crate::types::{BlockHash, DeployHash, FinalitySignature};
+
+    const MAX_SUBSCRIPTIONS: u32 = 2;
+
+    async fn connect(notification_sender: &broadcast::Sender<SseData>) -> WsClient {
+        connect_with_limits(
+            notification_sender,
+            u64::MAX,
+            Arc::new(RateLimiter::new(u64::MAX)),
+        )
+        .await
+    }
+
+    async fn connect_with_limits(
+        notification_sender: &broadcast::Sender<SseData>,
+        qps_limit: u64,
+        shared_limiter: Arc<RateLimiter>,
+    ) -> WsClient {
+        let mut handlers = RequestHandlersBuilder::new();
+        let ping = |_params| async move { Ok::<_, Error>("pong") };
+        handlers.register_handler("ping", Arc::new(ping));
+        handlers.set_max_batch_size(10);
+        let handlers = handlers.build();
+
+        let notification_sender = notification_sender.clone();
+        let permits = Arc::new(Semaphore::new(1));
+        let filter = warp::ws().map(move |ws: Ws| {
+            let handlers = handlers.clone();
+            let notifications = notification_sender.subscribe();
+            let permit = Arc::clone(&permits).try_acquire_owned().unwrap();
+            let shared_limiter = Arc::clone(&shared_limiter);
+            ws.on_upgrade(move |socket| {
+                handle_connection(
+                    socket,
+                    ([127, 0, 0, 1], 0).into(),
+                    handlers,
+                    notifications,
+                    MAX_SUBSCRIPTIONS,
+                    qps_limit,
+                    shared_limiter,
+                    permit,
+                )
+            })
+        });
+        warp::test::ws().handshake(filter).await.unwrap()
+    }
+
+    async fn call(client: &mut WsClient, id: u64, method: &str, params: Option<Value>) -> Value {
+        let mut request = json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+        client.send_text(request.to_string()).await;
+        let response: Value = serde_json::from_slice(client.recv().await.unwrap().as_bytes())
+            .expect("should parse response");
+        assert_eq!(response["id"], json!(id));
+        response
+    }
+
+    #[tokio::test]
+    async fn should_serve_rpcs_over_websocket() {
+        let (sender, _) = broadcast::channel(10);
+        let mut client = connect(&sender).await;
+
+        let response = call(&mut client, 1, "ping", None).await;
+        assert_eq!(response["result"], json!("pong"));
+
+        let response = call(&mut client, 2, "no_such_method", None).await;
+        assert_eq!(
+            response["error"]["code"],
+            json!(ReservedErrorCode::MethodNotFound as i64)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_push_notifications_to_subscribers() {
+        let mut rng = TestRng::new();
+        let (sender, _) = broadcast::channel(10);
+        let mut client = connect(&sender).await;
+
+        let response = call(
+            &mut client,
+            1,
+            SUBSCRIBE_METHOD,
+            Some(json!({ "event": "FinalitySignature" })),
+        )
+        .await;
+        let subscription = response["result"].clone();
+        assert_eq!(subscription, json!(0));
+
+        // An event which hasn't been subscribed to should not be pushed to the client.
+        sender
+            .send(SseData::DeployExpired {
+                deploy_hash: DeployHash::random(&mut rng),
+            })
+            .unwrap();
+        let finality_signature = SseData::FinalitySignature(Box::new(
+            FinalitySignature::random_for_block(BlockHash::random(&mut rng), rng.gen()),
+        ));
+        sender.send(finality_signature.clone()).unwrap();
+
+        let notification: Value =
+            serde_json::from_slice(client.recv().await.unwrap().as_bytes()).unwrap();
+        assert_eq!(notification["method"], json!(NOTIFICATION_METHOD));
+        assert_eq!(notification["params"]["subscription"], subscription);
+        assert_eq!(
+            notification["params"]["result"],
+            serde_json::to_value(&finality_signature).unwrap()
+        );
+
+        let response = call(
+            &mut client,
+            2,
+            UNSUBSCRIBE_METHOD,
+            Some(json!({ "subscription": subscription })),
+        )
+        .await;
+        assert_eq!(response["result"], json!(true));
+        let response = call(
+            &mut client,
+            3,
+            UNSUBSCRIBE_METHOD,
+            Some(json!({ "subscription": subscription })),
+        )
+        .await;
+        assert_eq!(response["result"], json!(false));
+    }
+
+    #[tokio::test]
+    async fn should_limit_subscriptions_per_connection() {
+        let (sender, _) = broadcast::channel(10);
+        let mut client = connect(&sender).await;
+
+        for id in 0..MAX_SUBSCRIPTIONS {
+            let params = Some(json!({ "event": "BlockAdded" }));
+            let response = call(&mut client, id as u64, SUBSCRIBE_METHOD, params).await;
+            assert_eq!(response["result"], json!(id));
+        }
+
+        let params = Some(json!({ "event": "BlockAdded" }));
+        let response = call(&mut client, 10, SUBSCRIBE_METHOD, params).await;
+        assert_eq!(
+            response["error"]["code"],
+            json!(ReservedErrorCode::InvalidRequest as i64)
+        );
+
+        let params = Some(json!({ "event": "Step" }));
+        let response = call(&mut client, 11, SUBSCRIBE_METHOD, params).await;
+        assert_eq!(
+            response["error"]["code"],
+            json!(ReservedErrorCode::InvalidParams as i64)
+        );
+    }
+
+    /// Sends a batch of `count` "ping" requests and returns how long it took to get the responses.
+    async fn time_pings(client: &mut WsClient, count: usize) -> Duration {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" });
+        let batch = Value::Array(vec![request; count]);
+        let start = Instant::now();
+        client.send_text(batch.to_string()).await;
+        let response: Value = serde_json::from_slice(client.recv().await.unwrap().as_bytes())
+            .expect("should parse response");
+        assert_eq!(response.as_array().map(Vec::len), Some(count));
+        start.elapsed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_limit_requests_per_connection() {
+        let (sender, _) = broadcast::channel(10);
+        let shared_limiter = Arc::new(RateLimiter::new(u64::MAX));
+        let mut client = connect_with_limits(&sender, 2, Arc::clone(&shared_limiter)).await;
+        let mut other_client = connect_with_limits(&sender, 2, shared_limiter).await;
+
+        // Each request in a batch should count against the limit.
+        assert_eq!(time_pings(&mut client, 2).await, Duration::ZERO);
+        assert_eq!(time_pings(&mut client, 1).await, Duration::from_millis(500));
+
+        // Other connections should be unaffected.
+        assert_eq!(time_pings(&mut other_client, 2).await, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_limit_requests_across_connections() {
+        let (sender, _) = broadcast::channel(10);
+        let shared_limiter = Arc::new(RateLimiter::new(2));
+        let mut client = connect_with_limits(&sender, u64::MAX, Arc::clone(&shared_limiter)).await;
+        let mut other_client = connect_with_limits(&sender, u64::MAX, shared_limiter).await;
+
+        assert_eq!(time_pings(&mut client, 2).await, Duration::ZERO);
+        assert_eq!(
+            time_pings(&mut other_client, 1).await,
+            Duration::from_millis(500)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_push_notifications_while_requests_are_throttled() {
+        let mut rng = TestRng::new();
+        let (sender, _) = broadcast::channel(1);
+        let shared_limiter = Arc::new(RateLimiter::new(u64::MAX));
+        let mut client = connect_with_limits(&sender, 1, shared_limiter).await;
+
+        let params = Some(json!({ "event": "FinalitySignature" }));
+        let response = call(&mut client, 1, SUBSCRIBE_METHOD, params).await;
+        let subscription = response["result"].clone();
+
+        // These requests take seconds to be allowed through.
+        let request = json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" });
+        let batch = Value::Array(vec![request; 5]);
+        client.send_text(batch.to_string()).await;
+
+        // Meanwhile, notifications should still be pushed, without lagging behind.
+        for _ in 0..3 {
+            let finality_signature = SseData::FinalitySignature(Box::new(
+                FinalitySignature::random_for_block(BlockHash::random(&mut rng), rng.gen()),
+            ));
+            sender.send(finality_signature.clone()).unwrap();
+            let notification: Value =
+                serde_json::from_slice(client.recv().await.unwrap().as_bytes()).unwrap();
+            assert_eq!(notification["params"]["subscription"], subscription);
+            assert_eq!(
+                notification["params"]["result"],
+                serde_json::to_value(&finality_signature).unwrap()
+            );
+        }
+
+        let response: Value = serde_json::from_slice(client.recv().await.unwrap().as_bytes())
+            .expect("should parse response");
+        assert_eq!(response.as_array().map(Vec::len), Some(5));
+    }
+}
@@ -7,7 +7,7 @@
 //! * storing and loading deploys,
 //! * [temporary until refactored] holding `DeployMetadata` for each deploy,
 //! * keeping an index of blocks by height and
-//! * [unimplemented] managing disk usage by pruning blocks and deploys from storage.
+//! * optionally managing disk usage by pruning old blocks and deploys from storage.
 //!
 //! Any I/O performed by the component is done on the event handling thread, this is on purpose as
 //! the assumption is that caching by LMDB will offset any gains from offloading it onto a separate
@@ -38,16 +38,14 @@ mod object_pool;
 #[cfg(test)]
 mod tests;
 
-#[cfg(test)]
-use std::collections::BTreeSet;
 use std::{
     borrow::Cow,
-    collections::{btree_map, hash_map, BTreeMap, HashMap, HashSet},
+    collections::{btree_map, hash_map, BTreeMap, BTreeSet, HashMap, HashSet},
     convert::{TryFrom, TryInto},
     fmt::{self, Display, Formatter},
     fs::{self, OpenOptions},
     io::ErrorKind,
-    mem,
+    iter, mem,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
@@ -70,7 +68,8 @@ use tracing::{debug, error, info, trace, warn};
 
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::{FromBytes, ToBytes},
+    account::{AccountHash, ACCOUNT_HASH_LENGTH},
+    bytesrepr::{self, FromBytes, ToBytes, U64_SERIALIZED_LENGTH},
     EraId, ExecutionResult, ProtocolVersion, PublicKey, Timestamp, Transfer, Transform,
 };
 
@@ -90,8 +89,8 @@ use crate::{
     fatal,
     protocol::Message,
     types::{
-        ApprovalsHash, ApprovalsHashes, AvailableBlockRange, Block, BlockAndDeploys, BlockBody,
-        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
+        Approval, ApprovalsHash, ApprovalsHashes, AvailableBlockRange, Block, BlockAndDeploys,
+        BlockBody, BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
         BlockHashAndHeight, BlockHashHeightAndEra, BlockHeader, BlockHeaderWithMetadata,
         BlockSignatures, BlockWithMetadata, Deploy, DeployHash, DeployHeader, DeployId,
         DeployMetadata, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
@@ -129,11 +128,22 @@ const DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE: usize = 300 * GIB;
 /// Default max state store size.
 const DEFAULT_MAX_STATE_STORE_SIZE: usize = 10 * GIB;
 /// Maximum number of allowed dbs.
-const MAX_DB_COUNT: u32 = 9;
+const MAX_DB_COUNT: u32 = 10;
 /// Key under which completed blocks are to be stored.
 const COMPLETED_BLOCKS_STORAGE_KEY: &[u8] = b"completed_blocks_disjoint_sequences";
+/// Length of the keys of the account deploy index.
+const ACCOUNT_DEPLOY_INDEX_KEY_LENGTH: usize =
+    ACCOUNT_HASH_LENGTH + U64_SERIALIZED_LENGTH + Digest::LENGTH;
+/// Key under which the marker of a completely built account deploy index is to be stored.
+const ACCOUNT_DEPLOY_INDEX_BUILT_STORAGE_KEY: &[u8] = b"account_deploy_index_built";
 /// Name of the file created when initializing a force resync.
 const FORCE_RESYNC_FILE_NAME: &str = "force_resync";
+/// Default number of most recent eras retained when pruning is enabled.
+const DEFAULT_PRUNING_RETAINED_ERAS: u64 = 100;
+/// Default number of most recent blocks retained when pruning is enabled.
+const DEFAULT_PRUNING_RETAINED_BLOCKS: u64 = 10_000;
+/// Default maximum number of blocks removed in a single pruning pass.
+const DEFAULT_PRUNING_MAX_BLOCKS_PER_PASS: u64 = 10;
 
 /// OS-specific lmdb flags.
 #[cfg(not(target_os = "macos"))]
@@ -192,6 +202,11 @@ pub struct Storage {
     /// The finalized approvals database.
     #[data_size(skip)]
     finalized_approvals_db: Database,
+    /// The account deploy index database.
+    ///
+    /// Only populated if `enable_account_deploy_index` is set.
+    #[data_size(skip)]
+    account_deploy_index_db: Database,
     /// A map of block height to block ID.
     block_height_index: BTreeMap<u64, BlockHash>,
     /// A map of era ID to switch block ID.
@@ -206,6 +221,8 @@ pub struct Storage {
     key_block_height_for_activation_point: Option<u64>,
     /// Whether or not memory deduplication is enabled.
     enable_mem_deduplication: bool,
+    /// Whether or not the index of deploys by account is maintained.
+    enable_account_deploy_index: bool,
     /// An in-memory pool of already loaded serialized items.
     ///
     /// Keyed by serialized item ID, contains the serialized item.
@@ -217,6 +234,8 @@ pub struct Storage {
     metrics: Option<Metrics>,
     /// The maximum TTL of a deploy.
     max_ttl: MaxTtl,
+    /// The block and deploy pruning configuration.
+    pruning: PruningConfig,
 }
 
 /// A storage component event.
@@ -417,6 +436,8 @@ impl Storage {
         let block_body_db = env.create_db(Some("block_body"), DatabaseFlags::empty())?;
         let approvals_hashes_db =
             env.create_db(Some("approvals_hashes"), DatabaseFlags::empty())?;
+        let account_deploy_index_db =
+            env.create_db(Some("account_deploy_index"), DatabaseFlags::empty())?;
 
         // We now need to restore the block-height index. Log messages allow timing here.
         info!("indexing block store");
@@ -508,6 +529,7 @@ impl Storage {
             transfer_db,
             state_store_db,
             finalized_approvals_db,
+            account_deploy_index_db,
             block_height_index,
             switch_block_era_id_index,
             deploy_hash_index,
@@ -515,9 +537,11 @@ impl Storage {
             activation_era,
             key_block_height_for_activation_point: None,
             enable_mem_deduplication: config.enable_mem_deduplication,
+            enable_account_deploy_index: config.enable_account_deploy_index,
             serialized_item_pool: ObjectPool::new(config.mem_pool_prune_interval),
             recent_era_count,
             max_ttl,
+            pruning: config.pruning,
             metrics,
         };
 
@@ -598,9 +622,60 @@ impl Storage {
             }
         }
 
+        // Blocks removed by a hard reset might still be referenced by the account deploy index.
+        let index_invalidated = !deleted_block_hashes.is_empty();
+        component.initialize_account_deploy_index(index_invalidated)?;
+
         Ok(component)
     }
 
+    /// Builds the account deploy index from the blocks and deploys held in storage, unless it is
+    /// already known to be complete and `force_rebuild` is `false`.
+    ///
+    /// If the index is disabled, its marker of completeness is removed instead, since it will not
+    /// be kept up to date from now on.
+    fn initialize_account_deploy_index(
+        &mut self,
+        force_rebuild: bool,
+    ) -> Result<(), FatalStorageError> {
+        let mut txn = self.env.begin_rw_txn()?;
+        if !self.enable_account_deploy_index {
+            let _ =
+                txn.delete_value(self.state_store_db, &ACCOUNT_DEPLOY_INDEX_BUILT_STORAGE_KEY)?;
+            txn.commit()?;
+            return Ok(());
+        }
+        if !force_rebuild
+            && txn.value_exists(self.state_store_db, &ACCOUNT_DEPLOY_INDEX_BUILT_STORAGE_KEY)?
+        {
+            return Ok(());
+        }
+
+        info!("building account deploy index");
+        txn.clear_db(self.account_deploy_index_db)?;
+        let mut indexed_deploy_count: u64 = 0;
+        for (deploy_hash, block_hash_height_and_era) in &self.deploy_hash_index {
+            let maybe_deploy: Option<Deploy> = txn.get_value(self.deploy_db, deploy_hash)?;
+            if let Some(deploy) = maybe_deploy {
+                self.put_account_deploy_index_entries(
+                    &mut txn,
+                    &deploy,
+                    block_hash_height_and_era.block_height,
+                )?;
+                indexed_deploy_count += 1;
+            }
+        }
+        txn.put(
+            self.state_store_db,
+            &ACCOUNT_DEPLOY_INDEX_BUILT_STORAGE_KEY,
+            &[],
+            WriteFlags::empty(),
+        )?;
+        txn.commit()?;
+        info!(indexed_deploy_count, "account deploy index built");
+        Ok(())
+    }
+
     /// Reads from the state storage database.
     ///
     /// If key is non-empty, returns bytes from under the key. Otherwise returns `Ok(None)`.
@@ -928,6 +1003,14 @@ impl Storage {
                 txn.commit()?;
                 responder.respond(()).ignore()
             }
+            StorageRequest::GetAccountDeploys {
+                account_hash,
+                after,
+                max_count,
+                responder,
+            } => responder
+                .respond(self.read_account_deploys(&account_hash, after, max_count)?)
+                .ignore(),
             StorageRequest::GetDeployAndMetadata {
                 deploy_hash,
                 responder,
@@ -1215,6 +1298,9 @@ impl Storage {
         }: MarkBlockCompletedRequest,
     ) -> Result<Effects<Event>, FatalStorageError> {
         let is_new = self.mark_block_complete(block_height)?;
+        if is_new {
+            self.prune()?;
+        }
         Ok(responder.respond(is_new).ignore())
     }
 
@@ -1248,6 +1334,158 @@ impl Storage {
         self.write_state_store(Cow::Borrowed(COMPLETED_BLOCKS_STORAGE_KEY), &serialized)
     }
 
+    /// Removes the oldest blocks and their deploys from storage if pruning is enabled.
+    ///
+    /// Blocks are only pruned once they fall outside all of the retention limits: the configured
+    /// number of most recent blocks, the configured number of most recent eras (never fewer than
+    /// the chainspec's recent era count) and the max TTL measured from the highest switch block,
+    /// so that both replay protection and syncing to TTL remain satisfied by what is left.  Switch
+    /// blocks are never pruned, as they are required to construct sync leaps.
+    ///
+    /// At most `max_blocks_per_pass` blocks are removed per call in order to bound the time spent
+    /// handling a single event.  The completed blocks index is shrunk accordingly, so the available
+    /// block range never covers pruned blocks.
+    fn prune(&mut self) -> Result<(), FatalStorageError> {
+        if !self.pruning.enabled {
+            return Ok(());
+        }
+
+        let highest_complete_height = match self.completed_blocks.highest_sequence() {
+            Some(sequence) => sequence.high(),
+            None => return Ok(()),
+        };
+        let highest_switch_block_hash = match self.switch_block_era_id_index.values().last() {
+            Some(&block_hash) => block_hash,
+            None => return Ok(()),
+        };
+
+        let env = Rc::clone(&self.env);
+        let mut txn = env.begin_rw_txn()?;
+        let highest_complete_header = match self.block_height_index.get(&highest_complete_height) {
+            Some(block_hash) => self.get_single_block_header(&mut txn, block_hash)?,
+            None => None,
+        };
+        let highest_switch_block_header =
+            self.get_single_block_header(&mut txn, &highest_switch_block_hash)?;
+        let (highest_complete_header, highest_switch_block_header) =
+            match (highest_complete_header, highest_switch_block_header) {
+                (Some(complete_header), Some(switch_block_header)) => {
+                    (complete_header, switch_block_header)
+                }
+                _ => return Ok(()),
+            };
+
+        let retained_eras = self.pruning.retained_eras.max(self.recent_era_count);
+        let lowest_retained_era = highest_complete_header
+            .era_id()
+            .saturating_sub(retained_eras);
+        let lowest_retained_height =
+            highest_complete_height.saturating_sub(self.pruning.retained_blocks);
+        let switch_block_hashes: HashSet<BlockHash> =
+            self.switch_block_era_id_index.values().copied().collect();
+        let candidates: Vec<(u64, BlockHash)> = self
+            .block_height_index
+            .range(..lowest_retained_height)
+            .filter(|(_, block_hash)| !switch_block_hashes.contains(block_hash))
+            .take(self.pruning.max_blocks_per_pass as usize)
+            .map(|(height, block_hash)| (*height, *block_hash))
+            .collect();
+
+        let mut highest_pruned_height = None;
+        for (height, block_hash) in candidates {
+            // The block succeeding a pruned one becomes the lowest one we hold, so it is the one
+            // which needs to be outside the retention limits for the pruning to be acceptable.
+            let successor_header = match self.block_height_index.get(&(height + 1)) {
+                Some(successor_hash) => self.get_single_block_header(&mut txn, successor_hash)?,
+                None => None,
+            };
+            let successor_header = match successor_header {
+                Some(header) => header,
+                None => break,
+            };
+            if successor_header.era_id() >= lowest_retained_era
+                || !self.max_ttl.ttl_elapsed(
+                    highest_switch_block_header.timestamp(),
+                    successor_header.timestamp(),
+                )
+            {
+                break;
+            }
+
+            let block_header = match self.get_single_block_header(&mut txn, &block_hash)? {
+                Some(header) => header,
+                None => break,
+            };
+            self.prune_block(&mut txn, &block_header)?;
+            highest_pruned_height = Some(height);
+        }
+
+        let highest_pruned_height = match highest_pruned_height {
+            Some(height) => height,
+            None => return Ok(()),
+        };
+        txn.commit()?;
+
+        self.completed_blocks
+            .remove_below(highest_pruned_height.saturating_add(1));
+        self.persist_completed_blocks()?;
+        info!(
+            highest_pruned_height,
+            "Storage: pruned blocks: {}",
+            self.get_available_block_range()
+        );
+        self.update_chain_height_metrics();
+        Ok(())
+    }
+
+    /// Deletes the given block along with its deploys and all associated metadata, and removes it
+    /// from the in-memory indices.
+    fn prune_block(
+        &mut self,
+        txn: &mut RwTransaction,
+        block_header: &BlockHeader,
+    ) -> Result<(), FatalStorageError> {
+        let block_hash = block_header.block_hash();
+        if let Some(block_body) =
+            get_body_for_block_header(txn, block_header.body_hash(), self.block_body_db)?
+        {
+            let mut has_deploys = false;
+            for deploy_hash in block_body.deploy_and_transfer_hashes() {
+                has_deploys = true;
+                if self.enable_account_deploy_index {
+                    let maybe_deploy: Option<Deploy> =
+                        txn.get_value(self.deploy_db, deploy_hash)?;
+                    for account_hash in maybe_deploy.iter().flat_map(deploy_account_hashes) {
+                        let key = account_deploy_index_key(
+                            &account_hash,
+                            block_header.height(),
+                            deploy_hash,
+                        );
+                        let _ = txn.delete_value(self.account_deploy_index_db, &key)?;
+                    }
+                }
+                let _ = txn.delete_value(self.deploy_db, deploy_hash)?;
+                let _ = txn.delete_value(self.deploy_metadata_db, deploy_hash)?;
+                let _ = txn.delete_value(self.finalized_approvals_db, deploy_hash)?;
+                let _ = self.deploy_hash_index.remove(deploy_hash);
+            }
+            // Bodies without deploys are not unique to a single block (they only consist of the
+            // proposer's key), so they might still be referenced by retained blocks.  They are tiny
+            // and are hence simply kept.
+            if has_deploys {
+                let _ = txn.delete_value(self.block_body_db, block_header.body_hash())?;
+            }
+        }
+
+        let _ = txn.delete_value(self.block_header_db, &block_hash)?;
+        let _ = txn.delete_value(self.block_metadata_db, &block_hash)?;
+        let _ = txn.delete_value(self.approvals_hashes_db, &block_hash)?;
+        let _ = txn.delete_value(self.transfer_db, &block_hash)?;
+        let _ = self.block_height_index.remove(&block_header.height());
+        debug!(%block_hash, height = block_header.height(), "Storage: pruned block");
+        Ok(())
+    }
+
     /// Put a single deploy into storage.
     pub fn put_deploy(&self, deploy: &Deploy) -> Result<bool, FatalStorageError> {
         let mut txn = self.env.begin_rw_txn()?;
@@ -1255,6 +1493,14 @@ impl Storage {
         let outcome = txn.put_value(self.deploy_db, deploy_hash, deploy, false)?;
         if outcome {
             debug!(%deploy_hash, "Storage: new deploy stored");
+            // The block containing the deploy might have been stored before the deploy itself.
+            if let Some(block_hash_height_and_era) = self.deploy_hash_index.get(deploy_hash) {
+                self.put_account_deploy_index_entries(
+                    &mut txn,
+                    deploy,
+                    block_hash_height_and_era.block_height,
+                )?;
+            }
         } else {
             debug!(%deploy_hash, "Storage: attempt to store existing deploy");
         }
@@ -1587,9 +1833,91 @@ impl Storage {
                 block.header().era_id(),
             )?;
         }
+
+        if self.enable_account_deploy_index {
+            for deploy_hash in block.body().deploy_and_transfer_hashes() {
+                let maybe_deploy: Option<Deploy> = txn.get_value(self.deploy_db, deploy_hash)?;
+                if let Some(deploy) = maybe_deploy {
+                    self.put_account_deploy_index_entries(txn, &deploy, block.height())?;
+                }
+            }
+        }
         Ok(true)
     }
 
+    /// Records the inclusion of the given deploy in the block at `block_height` in the account
+    /// deploy index, under each of the accounts involved in the deploy.
+    ///
+    /// Does nothing if the account deploy index is disabled.
+    fn put_account_deploy_index_entries(
+        &self,
+        txn: &mut RwTransaction,
+        deploy: &Deploy,
+        block_height: u64,
+    ) -> Result<(), FatalStorageError> {
+        if !self.enable_account_deploy_index {
+            return Ok(());
+        }
+        for account_hash in deploy_account_hashes(deploy) {
+            let key = account_deploy_index_key(&account_hash, block_height, deploy.hash());
+            txn.put(self.account_deploy_index_db, &key, &[], WriteFlags::empty())?;
+        }
+        Ok(())
+    }
+
+    /// Retrieves up to `max_count` deploys involving the given account, along with the blocks
+    /// which included them, in the order of inclusion.
+    ///
+    /// If `after` is given, only deploys included after the given position are returned.  Returns
+    /// `None` if the account deploy index is disabled.
+    pub(crate) fn read_account_deploys(
+        &self,
+        account_hash: &AccountHash,
+        after: Option<(u64, DeployHash)>,
+        max_count: usize,
+    ) -> Result<Option<Vec<(DeployHash, BlockHashAndHeight)>>, FatalStorageError> {
+        if !self.enable_account_deploy_index {
+            return Ok(None);
+        }
+
+        let prefix = account_hash.as_bytes();
+        let start_key = match after {
+            Some((block_height, deploy_hash)) => {
+                account_deploy_index_key(account_hash, block_height, &deploy_hash)
+            }
+            None => prefix.to_vec(),
+        };
+        let txn = self.env.begin_ro_txn()?;
+        let mut cursor = txn.open_ro_cursor(self.account_deploy_index_db)?;
+        let mut account_deploys = vec![];
+        for row in cursor.iter_from(&start_key) {
+            if account_deploys.len() >= max_count {
+                break;
+            }
+            let (raw_key, _) = row?;
+            if !raw_key.starts_with(prefix) {
+                break;
+            }
+            if raw_key == start_key.as_slice() {
+                continue;
+            }
+            let (block_height, deploy_hash) = parse_account_deploy_index_key(raw_key)?;
+            // Skip any entries which don't match the deploy index, e.g. due to a hard reset.
+            match self.deploy_hash_index.get(&deploy_hash) {
+                Some(block_hash_height_and_era)
+                    if block_hash_height_and_era.block_height == block_height =>
+                {
+                    account_deploys.push((
+                        deploy_hash,
+                        BlockHashAndHeight::from(block_hash_height_and_era),
+                    ));
+                }
+                _ => continue,
+            }
+        }
+        Ok(Some(account_deploys))
+    }
+
     /// Retrieves single switch block by era ID by looking it up in the index and returning it.
     fn get_switch_block_by_era_id<Tx: Transaction>(
         &self,
@@ -2726,6 +3054,50 @@ fn insert_to_deploy_index(
     Ok(())
 }
 
+/// Returns the hashes of all accounts involved in the given deploy, i.e. its account and the
+/// signers of its approvals.
+fn deploy_account_hashes(deploy: &Deploy) -> BTreeSet<AccountHash> {
+    iter::once(deploy.header().account())
+        .chain(deploy.approvals().iter().map(Approval::signer))
+        .map(PublicKey::to_account_hash)
+        .collect()
+}
+
+/// Returns the key of an entry in the account deploy index.
+///
+/// Keys consist of the account hash followed by the big-endian block height and the deploy hash,
+/// so that a cursor iterates over all the deploys of a single account in the order of inclusion.
+fn account_deploy_index_key(
+    account_hash: &AccountHash,
+    block_height: u64,
+    deploy_hash: &DeployHash,
+) -> Vec<u8> {
+    let mut key = Vec::with_capacity(ACCOUNT_DEPLOY_INDEX_KEY_LENGTH);
+    key.extend_from_slice(account_hash.as_bytes());
+    key.extend_from_slice(&block_height.to_be_bytes());
+    key.extend_from_slice(deploy_hash.inner().as_ref());
+    key
+}
+
+/// Parses the block height and deploy hash from a key of the account deploy index.
+fn parse_account_deploy_index_key(raw_key: &[u8]) -> Result<(u64, DeployHash), LmdbExtError> {
+    if raw_key.len() != ACCOUNT_DEPLOY_INDEX_KEY_LENGTH {
+        return Err(LmdbExtError::DataCorrupted(Box::new(BytesreprError(
+            bytesrepr::Error::Formatting,
+        ))));
+    }
+    let (height_bytes, deploy_hash_bytes) =
+        raw_key[ACCOUNT_HASH_LENGTH..].split_at(U64_SERIALIZED_LENGTH);
+    let mut block_height = [0; U64_SERIALIZED_LENGTH];
+    block_height.copy_from_slice(height_bytes);
+    let deploy_hash = Digest::try_from(deploy_hash_bytes)
+        .map_err(|err| LmdbExtError::DataCorrupted(Box::new(err)))?;
+    Ok((
+        u64::from_be_bytes(block_height),
+        DeployHash::new(deploy_hash),
+    ))
+}
+
 fn should_move_storage_files_to_network_subdir(
     root: &Path,
     file_names: &[&str],
@@ -2813,6 +3185,14 @@ pub struct Config {
     pub enable_mem_deduplication: bool,
     /// How many loads before memory duplication checks for dead references.
     pub mem_pool_prune_interval: u16,
+    /// Whether or not an index of deploys by the accounts involved in them is maintained.
+    ///
+    /// Enabling this on a node with existing data causes the index to be built on startup.
+    #[serde(default)]
+    pub enable_account_deploy_index: bool,
+    /// Block and deploy pruning configuration.
+    #[serde(default)]
+    pub pruning: PruningConfig,
 }
 
 impl Default for Config {
@@ -2826,6 +3206,37 @@ impl Default for Config {
             max_state_store_size: DEFAULT_MAX_STATE_STORE_SIZE,
             enable_mem_deduplication: true,
             mem_pool_prune_interval: 4096,
+            enable_account_deploy_index: false,
+            pruning: PruningConfig::default(),
+        }
+    }
+}
+
+/// Configuration of the pruning of old blocks and deploys from storage.
+///
+/// Pruning is intended for non-archival nodes and is disabled by default.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PruningConfig {
+    /// Whether or not old blocks and deploys should be pruned.
+    pub enabled: bool,
+    /// The minimum number of most recent eras for which blocks and deploys are retained.
+    ///
+    /// Values lower than the chainspec's recent era count are raised to match it.
+    pub retained_eras: u64,
+    /// The minimum number of most recent blocks which are retained, along with their deploys.
+    pub retained_blocks: u64,
+    /// The maximum number of blocks pruned each time a new block is marked complete.
+    pub max_blocks_per_pass: u64,
+}
+
+impl Default for PruningConfig {
+    fn default() -> Self {
+        PruningConfig {
+            enabled: false,
+            retained_eras: DEFAULT_PRUNING_RETAINED_ERAS,
+            retained_blocks: DEFAULT_PRUNING_RETAINED_BLOCKS,
+            max_blocks_per_pass: DEFAULT_PRUNING_MAX_BLOCKS_PER_PASS,
         }
     }
 }
@@ -16,14 +16,14 @@ use smallvec::smallvec;
 use casper_types::{
     generate_ed25519_keypair, system::auction::UnbondingPurse, testing::TestRng, AccessRights,
     EraId, ExecutionEffect, ExecutionResult, Key, ProtocolVersion, PublicKey, SecretKey, TimeDiff,
-    Transfer, Transform, TransformEntry, URef, U512,
+    Timestamp, Transfer, Transform, TransformEntry, URef, U512,
 };
 
 use super::{
     initialize_block_metadata_db,
     lmdb_ext::{deserialize_internal, serialize_internal, TransactionExt, WriteTransactionExt},
     move_storage_files_to_network_subdir, should_move_storage_files_to_network_subdir, Config,
-    Storage, FORCE_RESYNC_FILE_NAME,
+    PruningConfig, Storage, FORCE_RESYNC_FILE_NAME,
 };
 use crate::{
     components::fetcher::{FetchItem, FetchResponse},
@@ -57,6 +57,8 @@ fn new_config(harness: &ComponentHarness<UnitTestEvent>) -> Config {
         max_state_store_size: 50 * MIB,
         enable_mem_deduplication: true,
         mem_pool_prune_interval: 4,
+        enable_account_deploy_index: false,
+        pruning: PruningConfig::default(),
     }
 }
 
@@ -1422,6 +1424,184 @@ fn persist_blocks_deploys_and_deploy_metadata_across_instantiations() {
     );
 }
 
+#[test]
+fn should_prune_blocks_and_deploys_outside_retention_limits() {
+    const BLOCKS_PER_ERA: u64 = 3;
+    const BLOCK_COUNT: u64 = 30;
+
+    let mut harness = ComponentHarness::default();
+    let mut cfg = new_config(&harness);
+    cfg.pruning = PruningConfig {
+        enabled: true,
+        retained_eras: 2,
+        retained_blocks: 5,
+        max_blocks_per_pass: 100,
+    };
+    let cfg = WithDir::new(harness.tmp.path(), cfg);
+    let new_storage = |cfg: &WithDir<Config>| {
+        Storage::new(
+            cfg,
+            None,
+            ProtocolVersion::V1_0_0,
+            EraId::default(),
+            "test",
+            TimeDiff::from_seconds(10).into(),
+            1,
+            None,
+            false,
+        )
+        .expect("could not create storage component fixture")
+    };
+    let mut storage = new_storage(&cfg);
+
+    // Blocks are 10 seconds apart, with the last one in each era being a switch block.
+    let mut blocks = vec![];
+    for height in 0..BLOCK_COUNT {
+        let deploy = Deploy::random(&mut harness.rng);
+        let block = TestBlockBuilder::new()
+            .era(height / BLOCKS_PER_ERA)
+            .height(height)
+            .switch_block(height % BLOCKS_PER_ERA == BLOCKS_PER_ERA - 1)
+            .timestamp(Timestamp::zero() + TimeDiff::from_seconds(10 * height as u32))
+            .deploys(iter::once(&deploy))
+            .build(&mut harness.rng);
+        put_deploy(&mut harness, &mut storage, Arc::new(deploy.clone()));
+        put_complete_block(&mut harness, &mut storage, Arc::new(block.clone()));
+        blocks.push((block, deploy));
+    }
+
+    // The highest block is in era 9, so all blocks before era 7 (i.e. below height 21) are outside
+    // the retained eras, except for the highest of them: it is the lowest one we still hold.
+    let assert_pruned = |harness: &mut ComponentHarness<UnitTestEvent>, storage: &mut Storage| {
+        assert_eq!(
+            storage.get_available_block_range(),
+            AvailableBlockRange::new(20, BLOCK_COUNT - 1)
+        );
+        for (block, deploy) in &blocks {
+            let expect_retained = block.height() >= 20 || block.header().is_switch_block();
+            assert_eq!(
+                get_block(harness, storage, *block.hash()).is_some(),
+                expect_retained,
+                "unexpected presence of block {}",
+                block.height()
+            );
+            assert_eq!(
+                get_naive_deploys(harness, storage, smallvec![*deploy.hash()]),
+                vec![expect_retained.then(|| deploy.clone())],
+                "unexpected presence of deploy in block {}",
+                block.height()
+            );
+        }
+    };
+    assert_pruned(&mut harness, &mut storage);
+
+    // Pruning should survive a restart.
+    drop(storage);
+    let mut storage = new_storage(&cfg);
+    assert_pruned(&mut harness, &mut storage);
+}
+
+#[test]
+fn should_index_deploys_by_account() {
+    let mut harness = ComponentHarness::default();
+    let new_storage = |harness: &ComponentHarness<UnitTestEvent>, enable_index: bool| {
+        let mut cfg = new_config(harness);
+        cfg.enable_account_deploy_index = enable_index;
+        Storage::new(
+            &WithDir::new(harness.tmp.path(), cfg),
+            None,
+            ProtocolVersion::V1_0_0,
+            EraId::default(),
+            "test",
+            MAX_TTL.into(),
+            RECENT_ERA_COUNT,
+            None,
+            false,
+        )
+        .expect("could not create storage component fixture")
+    };
+    let mut storage = new_storage(&harness, true);
+
+    let signer = SecretKey::random(&mut harness.rng);
+    let signer_account_hash = PublicKey::from(&signer).to_account_hash();
+    let mut deploy_1 = Deploy::random(&mut harness.rng);
+    deploy_1.sign(&signer);
+    let mut deploy_2 = Deploy::random(&mut harness.rng);
+    deploy_2.sign(&signer);
+    let deploy_3 = Deploy::random(&mut harness.rng);
+    let deploy_3_account_hash = deploy_3.header().account().to_account_hash();
+
+    let block_1 = TestBlockBuilder::new()
+        .height(1)
+        .deploys([&deploy_1, &deploy_3])
+        .build(&mut harness.rng);
+    let block_2 = TestBlockBuilder::new()
+        .height(2)
+        .deploys(iter::once(&deploy_2))
+        .build(&mut harness.rng);
+
+    // Store deploys both before and after the blocks containing them.
+    put_deploy(&mut harness, &mut storage, Arc::new(deploy_1.clone()));
+    put_complete_block(&mut harness, &mut storage, Arc::new(block_1.clone()));
+    put_deploy(&mut harness, &mut storage, Arc::new(deploy_3.clone()));
+    put_deploy(&mut harness, &mut storage, Arc::new(deploy_2.clone()));
+    put_complete_block(&mut harness, &mut storage, Arc::new(block_2.clone()));
+
+    let in_block_1 = BlockHashAndHeight::new(*block_1.hash(), 1);
+    let in_block_2 = BlockHashAndHeight::new(*block_2.hash(), 2);
+    let assert_indexed = |storage: &Storage| {
+        assert_eq!(
+            storage
+                .read_account_deploys(&signer_account_hash, None, 10)
+                .unwrap(),
+            Some(vec![
+                (*deploy_1.hash(), in_block_1),
+                (*deploy_2.hash(), in_block_2)
+            ])
+        );
+        assert_eq!(
+            storage
+                .read_account_deploys(&deploy_3_account_hash, None, 10)
+                .unwrap(),
+            Some(vec![(*deploy_3.hash(), in_block_1)])
+        );
+    };
+    assert_indexed(&storage);
+
+    // Results should be paginated.
+    assert_eq!(
+        storage
+            .read_account_deploys(&signer_account_hash, None, 1)
+            .unwrap(),
+        Some(vec![(*deploy_1.hash(), in_block_1)])
+    );
+    assert_eq!(
+        storage
+            .read_account_deploys(&signer_account_hash, Some((1, *deploy_1.hash())), 1)
+            .unwrap(),
+        Some(vec![(*deploy_2.hash(), in_block_2)])
+    );
+    assert_eq!(
+        storage
+            .read_account_deploys(&signer_account_hash, Some((2, *deploy_2.hash())), 1)
+            .unwrap(),
+        Some(vec![])
+    );
+
+    // The index should be unavailable while disabled, and rebuilt once enabled again.
+    drop(storage);
+    let storage = new_storage(&harness, false);
+    assert_eq!(
+        storage
+            .read_account_deploys(&signer_account_hash, None, 10)
+            .unwrap(),
+        None
+    );
+    drop(storage);
+    let storage = new_storage(&harness, true);
+    assert_indexed(&storage);
+}
+
 #[test]
 fn should_hard_reset() {
     let blocks_count = 8_usize;
@@ -120,13 +120,18 @@ use casper_execution_engine::{
         GetBidsResult, QueryRequest, QueryResult,
     },
     shared::execution_journal::ExecutionJournal,
-    storage::trie::TrieRaw,
+    storage::{
+        trie::TrieRaw,
+        trie_store::{DiffResult, ListKeysResult},
+    },
 };
 use casper_hashing::Digest;
 use casper_types::{
-    account::Account, bytesrepr::Bytes, system::auction::EraValidators, Contract, ContractPackage,
-    EraId, ExecutionEffect, ExecutionResult, Key, PublicKey, TimeDiff, Timestamp, Transfer, URef,
-    U512,
+    account::{Account, AccountHash},
+    bytesrepr::Bytes,
+    system::auction::EraValidators,
+    Contract, ContractPackage, EraId, ExecutionEffect, ExecutionResult, Key, PublicKey,
+    StoredValue, TimeDiff, Timestamp, Transfer, URef, U512,
 };
 
 use crate::{
@@ -135,7 +140,9 @@ use crate::{
             BlockSynchronizerStatus, GlobalStateSynchronizerError, GlobalStateSynchronizerResponse,
             TrieAccumulatorError, TrieAccumulatorResponse,
         },
-        consensus::{ClContext, EraDump, ProposedBlock, ValidatorChange},
+        consensus::{
+            ClContext, ConsensusStatus, EraDump, EvidenceBundle, ProposedBlock, ValidatorChange,
+        },
         contract_runtime::{ContractRuntimeError, EraValidatorsRequest},
         deploy_acceptor,
         diagnostics_port::StopAtSpec,
@@ -144,16 +151,17 @@ use crate::{
         network::{blocklist::BlocklistJustification, FromIncoming, NetworkInsights},
         upgrade_watcher::NextUpgrade,
     },
-    contract_runtime::SpeculativeExecutionState,
+    contract_runtime::{GasEstimate, SpeculativeExecutionResult, SpeculativeExecutionState},
     failpoints::FailpointActivation,
     reactor::{main_reactor::ReactorState, EventQueueHandle, QueueKind},
     types::{
         appendable_block::AppendableBlock, ApprovalsHashes, AvailableBlockRange, Block,
-        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash, BlockHeader,
-        BlockSignatures, BlockWithMetadata, ChainspecRawBytes, Deploy, DeployHash, DeployHeader,
-        DeployId, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
-        FinalitySignatureId, FinalizedApprovals, FinalizedBlock, LegacyDeploy, MetaBlock,
-        MetaBlockState, NodeId, TrieOrChunk, TrieOrChunkId,
+        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
+        BlockHashAndHeight, BlockHeader, BlockSignatures, BlockWithMetadata, ChainspecRawBytes,
+        Deploy, DeployHash, DeployHeader, DeployId, DeployMetadataExt,
+        DeployWithFinalizedApprovals, FinalitySignature, FinalitySignatureId, FinalizedApprovals,
+        FinalizedBlock, LegacyDeploy, MetaBlock, MetaBlockState, NodeId, TrieOrChunk,
+        TrieOrChunkId,
     },
     utils::{fmt_limit::FmtLimit, SharedFlag, Source},
 };
@@ -1434,6 +1442,60 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the differences between the global states under two state root hashes, in the order of
+    /// their serialized keys.
+    pub(crate) async fn get_state_diff(
+        self,
+        old_state_root_hash: Digest,
+        new_state_root_hash: Digest,
+        start_after: Option<Key>,
+        limit: usize,
+    ) -> Result<DiffResult<Key, StoredValue>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetStateDiff {
+                old_state_root_hash,
+                new_state_root_hash,
+                start_after,
+                limit,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Lists the keys in the global state under a state root hash whose serialized forms start with
+    /// `prefix`, in the order of their serialized forms, reading at most `max_scanned` entries.
+    pub(crate) async fn list_keys(
+        self,
+        state_root_hash: Digest,
+        prefix: Vec<u8>,
+        dictionary_seed_uref: Option<URef>,
+        start_after: Option<Key>,
+        limit: usize,
+        max_scanned: usize,
+    ) -> Result<ListKeysResult<Key>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::ListKeys {
+                state_root_hash,
+                prefix,
+                dictionary_seed_uref,
+                start_after,
+                limit,
+                max_scanned,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
     /// Puts a trie into the trie store; succeeds only if all the children of the trie are already
     /// present in the store.
     /// Returns the digest under which the trie was stored if successful.
@@ -1578,6 +1640,29 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets up to `max_count` deploys involving the given account, starting after the given block
+    /// height and deploy hash, or `None` if the account deploy index is disabled.
+    pub(crate) async fn get_account_deploys_from_storage(
+        self,
+        account_hash: AccountHash,
+        after: Option<(u64, DeployHash)>,
+        max_count: usize,
+    ) -> Option<Vec<(DeployHash, BlockHashAndHeight)>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetAccountDeploys {
+                account_hash: Box::new(account_hash),
+                after,
+                max_count,
+                responder,
+            },
+            QueueKind::FromStorage,
+        )
+        .await
+    }
+
     /// Gets the requested block and its finality signatures.
     pub(crate) async fn get_block_at_height_with_metadata_from_storage(
         self,
@@ -2068,6 +2153,24 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Returns the evidence of all equivocations known to consensus.
+    pub(crate) async fn get_consensus_equivocation_evidence(self) -> Vec<EvidenceBundle>
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(ConsensusRequest::EquivocationEvidence, QueueKind::Consensus)
+            .await
+    }
+
+    /// Returns a summary of the current era's consensus state, if consensus is initialized.
+    pub(crate) async fn get_consensus_status_summary(self) -> Option<ConsensusStatus>
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(ConsensusRequest::ConsensusStatus, QueueKind::Consensus)
+            .await
+    }
+
     /// Dump consensus state for a specific era, using the supplied function to serialize the
     /// output.
     pub(crate) async fn diagnostics_port_dump_consensus_state(
@@ -2201,16 +2304,66 @@ impl<REv> EffectBuilder<REv> {
 
     /// Requests execution of a single deploy, without commiting its effects.
     /// Inteded to be used for debugging & discovery purposes.
+    ///
+    /// Any `state_overrides` are written to a scratch copy of global state prior to execution.  If
+    /// `trace` is set, the host function calls made during execution are recorded and returned.
     pub(crate) async fn speculative_execute_deploy(
         self,
         execution_prestate: SpeculativeExecutionState,
         deploy: Arc<Deploy>,
-    ) -> Result<Option<ExecutionResult>, engine_state::Error>
+        state_overrides: Vec<(Key, StoredValue)>,
+        trace: bool,
+    ) -> Result<Option<SpeculativeExecutionResult>, engine_state::Error>
     where
         REv: From<ContractRuntimeRequest>,
     {
         self.make_request(
             |responder| ContractRuntimeRequest::SpeculativeDeployExecution {
+                execution_prestate,
+                deploy,
+                state_overrides,
+                trace,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Requests execution of a sequence of deploys, each on top of the effects of the previous
+    /// ones, without commiting their effects.
+    /// Inteded to be used for debugging & discovery purposes.
+    pub(crate) async fn speculative_execute_deploys(
+        self,
+        execution_prestate: SpeculativeExecutionState,
+        deploys: Vec<Arc<Deploy>>,
+    ) -> Result<Option<Vec<ExecutionResult>>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::SpeculativeDeploySequenceExecution {
+                execution_prestate,
+                deploys,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Requests an estimate of the gas required to execute a single deploy using standard payment,
+    /// without commiting its effects.
+    pub(crate) async fn estimate_gas(
+        self,
+        execution_prestate: SpeculativeExecutionState,
+        deploy: Arc<Deploy>,
+    ) -> Result<Option<GasEstimate>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::EstimateGas {
                 execution_prestate,
                 deploy,
                 responder,
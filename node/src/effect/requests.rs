@@ -23,12 +23,15 @@ use casper_execution_engine::{
         get_bids::{GetBidsRequest, GetBidsResult},
         query::{QueryRequest, QueryResult},
     },
-    storage::trie::TrieRaw,
+    storage::{
+        trie::TrieRaw,
+        trie_store::{DiffResult, ListKeysResult},
+    },
 };
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::Bytes, system::auction::EraValidators, EraId, ExecutionResult, Key, ProtocolVersion,
-    PublicKey, TimeDiff, Timestamp, Transfer, URef,
+    account::AccountHash, bytesrepr::Bytes, system::auction::EraValidators, EraId, ExecutionResult,
+    Key, ProtocolVersion, PublicKey, StoredValue, TimeDiff, Timestamp, Transfer, URef,
 };
 
 use crate::{
@@ -37,7 +40,7 @@ use crate::{
             BlockSynchronizerStatus, GlobalStateSynchronizerError, GlobalStateSynchronizerResponse,
             TrieAccumulatorError, TrieAccumulatorResponse,
         },
-        consensus::{ClContext, ProposedBlock, ValidatorChange},
+        consensus::{ClContext, ConsensusStatus, EvidenceBundle, ProposedBlock, ValidatorChange},
         contract_runtime::EraValidatorsRequest,
         deploy_acceptor,
         diagnostics_port::StopAtSpec,
@@ -46,17 +49,20 @@ use crate::{
         network::NetworkInsights,
         upgrade_watcher::NextUpgrade,
     },
-    contract_runtime::{ContractRuntimeError, SpeculativeExecutionState},
+    contract_runtime::{
+        ContractRuntimeError, GasEstimate, SpeculativeExecutionResult, SpeculativeExecutionState,
+    },
     effect::{AutoClosingResponder, Responder},
     reactor::main_reactor::ReactorState,
     rpcs::docs::OpenRpcSchema,
     types::{
         appendable_block::AppendableBlock, ApprovalsHashes, AvailableBlockRange, Block,
-        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash, BlockHeader,
-        BlockSignatures, BlockWithMetadata, ChainspecRawBytes, Deploy, DeployHash, DeployHeader,
-        DeployId, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
-        FinalitySignatureId, FinalizedApprovals, FinalizedBlock, LegacyDeploy, MetaBlockState,
-        NodeId, StatusFeed, TrieOrChunk, TrieOrChunkId,
+        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
+        BlockHashAndHeight, BlockHeader, BlockSignatures, BlockWithMetadata, ChainspecRawBytes,
+        Deploy, DeployHash, DeployHeader, DeployId, DeployMetadataExt,
+        DeployWithFinalizedApprovals, FinalitySignature, FinalitySignatureId, FinalizedApprovals,
+        FinalizedBlock, LegacyDeploy, MetaBlockState, NodeId, StatusFeed, TrieOrChunk,
+        TrieOrChunkId,
     },
     utils::{DisplayIter, Source},
 };
@@ -414,6 +420,17 @@ pub(crate) enum StorageRequest {
         /// Responder to call with the results.
         responder: Responder<Option<(DeployWithFinalizedApprovals, DeployMetadataExt)>>,
     },
+    /// Retrieve the deploys involving an account, along with the blocks which included them.
+    GetAccountDeploys {
+        /// Hash of the account.
+        account_hash: Box<AccountHash>,
+        /// Block height and deploy hash after which to start retrieving deploys.
+        after: Option<(u64, DeployHash)>,
+        /// Maximum number of deploys to retrieve.
+        max_count: usize,
+        /// Responder to call with the results, or with `None` if the index is disabled.
+        responder: Responder<Option<Vec<(DeployHash, BlockHashAndHeight)>>>,
+    },
     /// Retrieve block and its metadata by its hash.
     GetBlockAndMetadataByHash {
         /// The hash of the block.
@@ -562,6 +579,9 @@ impl Display for StorageRequest {
             StorageRequest::GetDeployAndMetadata { deploy_hash, .. } => {
                 write!(formatter, "get deploy and metadata for {}", deploy_hash)
             }
+            StorageRequest::GetAccountDeploys { account_hash, .. } => {
+                write!(formatter, "get deploys of account {}", account_hash)
+            }
             StorageRequest::GetFinalitySignature { id, .. } => {
                 write!(formatter, "get finality signature {}", id)
             }
@@ -927,8 +947,63 @@ pub(crate) enum ContractRuntimeRequest {
         execution_prestate: SpeculativeExecutionState,
         /// Deploy to execute.
         deploy: Arc<Deploy>,
-        /// Results
-        responder: Responder<Result<Option<ExecutionResult>, engine_state::Error>>,
+        /// Values to write to global state before executing the deploy.  These are never
+        /// persisted.
+        state_overrides: Vec<(Key, StoredValue)>,
+        /// Whether to record a trace of the host function calls made during execution.
+        trace: bool,
+        /// Results, along with the execution trace if requested.
+        responder: Responder<Result<Option<SpeculativeExecutionResult>, engine_state::Error>>,
+    },
+    /// Execute a sequence of deploys, each on top of the effects of the previous ones, without
+    /// commiting results
+    SpeculativeDeploySequenceExecution {
+        /// Hash of a block on top of which to execute the deploys.
+        execution_prestate: SpeculativeExecutionState,
+        /// Deploys to execute, in order.
+        deploys: Vec<Arc<Deploy>>,
+        /// Results, in the same order as the deploys.
+        responder: Responder<Result<Option<Vec<ExecutionResult>>, engine_state::Error>>,
+    },
+    /// Estimate the gas required to execute a deploy using standard payment, without commiting
+    /// results
+    EstimateGas {
+        /// Hash of a block on top of which to execute the deploy.
+        execution_prestate: SpeculativeExecutionState,
+        /// Deploy to execute.
+        deploy: Arc<Deploy>,
+        /// Gas estimate.
+        responder: Responder<Result<Option<GasEstimate>, engine_state::Error>>,
+    },
+    /// Get the differences between the global states under two state root hashes.
+    GetStateDiff {
+        /// The state root hash of the base global state.
+        old_state_root_hash: Digest,
+        /// The state root hash of the global state compared against the base.
+        new_state_root_hash: Digest,
+        /// If given, only differences in keys following this one are returned.
+        start_after: Option<Key>,
+        /// The maximum number of differences to return.
+        limit: usize,
+        /// Responder to call with the result.
+        responder: Responder<Result<DiffResult<Key, StoredValue>, engine_state::Error>>,
+    },
+    /// List the keys in the global state under a state root hash.
+    ListKeys {
+        /// The state root hash of the global state.
+        state_root_hash: Digest,
+        /// Only keys whose serialized forms start with this prefix are returned.
+        prefix: Vec<u8>,
+        /// If given, only the entries of the dictionary with this seed are returned.
+        dictionary_seed_uref: Option<URef>,
+        /// If given, only keys following this one are returned.
+        start_after: Option<Key>,
+        /// The maximum number of keys to return.
+        limit: usize,
+        /// The maximum number of entries to read, whether returned or not.
+        max_scanned: usize,
+        /// Responder to call with the result.
+        responder: Responder<Result<ListKeysResult<Key>, engine_state::Error>>,
     },
 }
 
@@ -984,6 +1059,53 @@ impl Display for ContractRuntimeRequest {
                     execution_prestate.state_root_hash
                 )
             }
+            ContractRuntimeRequest::SpeculativeDeploySequenceExecution {
+                execution_prestate,
+                deploys,
+                ..
+            } => {
+                write!(
+                    formatter,
+                    "Execute sequence of {} deploys on {}",
+                    deploys.len(),
+                    execution_prestate.state_root_hash
+                )
+            }
+            ContractRuntimeRequest::EstimateGas {
+                execution_prestate,
+                deploy,
+                ..
+            } => {
+                write!(
+                    formatter,
+                    "Estimate gas of {} on {}",
+                    deploy.hash(),
+                    execution_prestate.state_root_hash
+                )
+            }
+            ContractRuntimeRequest::GetStateDiff {
+                old_state_root_hash,
+                new_state_root_hash,
+                ..
+            } => {
+                write!(
+                    formatter,
+                    "get state diff between {} and {}",
+                    old_state_root_hash, new_state_root_hash
+                )
+            }
+            ContractRuntimeRequest::ListKeys {
+                state_root_hash,
+                prefix,
+                ..
+            } => {
+                write!(
+                    formatter,
+                    "list keys with prefix {} under {}",
+                    base16::encode_lower(prefix),
+                    state_root_hash
+                )
+            }
         }
     }
 }
@@ -1076,6 +1198,10 @@ pub(crate) enum ConsensusRequest {
     Status(Responder<Option<(PublicKey, Option<TimeDiff>)>>),
     /// Request for a list of validator status changes, by public key.
     ValidatorChanges(Responder<BTreeMap<PublicKey, Vec<(EraId, ValidatorChange)>>>),
+    /// Request for the evidence of all equivocations in the eras consensus still keeps in memory.
+    EquivocationEvidence(Responder<Vec<EvidenceBundle>>),
+    /// Request for a summary of the current era's consensus state.
+    ConsensusStatus(Responder<Option<ConsensusStatus>>),
 }
 
 /// ChainspecLoader component requests.
@@ -33,9 +33,11 @@ pub(crate) mod failpoints;
 pub mod logging;
 pub(crate) mod protocol;
 pub(crate) mod reactor;
+pub mod signer;
 #[cfg(test)]
 pub(crate) mod testing;
 pub(crate) mod tls;
+mod trie_compaction;
 pub mod types;
 pub mod utils;
 
@@ -44,7 +44,7 @@ use crate::{
         metrics::Metrics,
         network::{self, GossipedAddress, Identity as NetworkIdentity, Network},
         rest_server::RestServer,
-        rpc_server::RpcServer,
+        rpc_server::{self, RpcServer},
         shutdown_trigger::{self, ShutdownTrigger},
         storage::Storage,
         sync_leaper::SyncLeaper,
@@ -243,6 +243,10 @@ impl reactor::Reactor for MainReactor {
             MainEvent::MetaBlockAnnouncement(MetaBlockAnnouncement(meta_block)) => {
                 self.handle_meta_block(effect_builder, rng, meta_block)
             }
+            MainEvent::FinalitySignatureCreated(None) => Effects::new(),
+            MainEvent::FinalitySignatureCreated(Some(finality_signature)) => {
+                self.handle_created_finality_signature(effect_builder, rng, *finality_signature)
+            }
             MainEvent::UnexecutedBlockAnnouncement(UnexecutedBlockAnnouncement(block_height)) => {
                 let only_from_available_block_range = true;
                 if let Ok(Some(block_header)) = self
@@ -507,6 +511,17 @@ impl reactor::Reactor for MainReactor {
                     ),
                 );
 
+                if self.rpc_server.has_websocket_clients() {
+                    effects.extend(reactor::wrap_effects(
+                        MainEvent::RpcServer,
+                        self.rpc_server.handle_event(
+                            effect_builder,
+                            rng,
+                            rpc_server::Event::FinalitySignature(finality_signature.clone()),
+                        ),
+                    ));
+                }
+
                 effects.extend(reactor::wrap_effects(
                     MainEvent::EventStreamServer,
                     self.event_stream_server.handle_event(
@@ -1009,7 +1024,7 @@ impl reactor::Reactor for MainReactor {
 
         let trusted_hash = config.value().node.trusted_hash;
         let (root_dir, config) = config.into_parts();
-        let (our_secret_key, our_public_key) = config.consensus.load_keys(&root_dir)?;
+        let signer = config.consensus.load_signer(&root_dir)?;
         let validator_matrix = ValidatorMatrix::new(
             chainspec.core_config.finality_threshold_fraction,
             chainspec
@@ -1018,8 +1033,7 @@ impl reactor::Reactor for MainReactor {
                 .as_ref()
                 .and_then(|global_state_update| global_state_update.validators.clone()),
             chainspec.protocol_config.activation_point.era_id(),
-            our_secret_key.clone(),
-            our_public_key.clone(),
+            signer.clone(),
             chainspec.core_config.auction_delay,
         );
 
@@ -1065,12 +1079,13 @@ impl reactor::Reactor for MainReactor {
             chainspec.core_config.allow_unrestricted_transfers,
             chainspec.core_config.refund_handling,
             chainspec.core_config.fee_handling,
+            chainspec.deploy_config.block_gas_limit,
         )?;
 
         let network = Network::new(
             config.network.clone(),
             network_identity,
-            Some((our_secret_key.clone(), our_public_key.clone())),
+            Some(signer.clone()),
             registry,
             chainspec.as_ref(),
             validator_matrix.clone(),
@@ -1129,8 +1144,7 @@ impl reactor::Reactor for MainReactor {
         // consensus
         let consensus = EraSupervisor::new(
             storage.root_path(),
-            our_secret_key,
-            our_public_key,
+            signer,
             config.consensus,
             chainspec.clone(),
             registry,
@@ -1362,43 +1376,13 @@ impl MainReactor {
 
         if state.register_we_have_tried_to_sign().was_updated() {
             // When this node is a validator in this era, sign and announce.
-            if let Some(finality_signature) = self
+            if let Some(signing) = self
                 .validator_matrix
                 .create_finality_signature(block.header())
             {
-                debug!(
-                    %finality_signature,
-                    "MetaBlock: registering finality signature: {} {}",
-                    block.height(),
-                    block.hash(),
-                );
-
-                effects.extend(reactor::wrap_effects(
-                    MainEvent::Storage,
-                    effect_builder
-                        .put_finality_signature_to_storage(finality_signature.clone())
-                        .ignore(),
-                ));
-
-                effects.extend(reactor::wrap_effects(
-                    MainEvent::BlockAccumulator,
-                    self.block_accumulator.handle_event(
-                        effect_builder,
-                        rng,
-                        block_accumulator::Event::CreatedFinalitySignature {
-                            finality_signature: Box::new(finality_signature.clone()),
-                        },
-                    ),
-                ));
-
-                let era_id = finality_signature.era_id;
-                let payload = Message::FinalitySignature(Box::new(finality_signature));
-                effects.extend(reactor::wrap_effects(
-                    MainEvent::Network,
-                    effect_builder
-                        .broadcast_message_to_validators(payload, era_id)
-                        .ignore(),
-                ));
+                effects.extend(signing.event(|maybe_finality_signature| {
+                    MainEvent::FinalitySignatureCreated(maybe_finality_signature.map(Box::new))
+                }));
             }
         }
 
@@ -1526,7 +1510,32 @@ impl MainReactor {
             ),
         ));
 
+        let notify_rpc_server = self.rpc_server.has_websocket_clients();
+        if notify_rpc_server {
+            effects.extend(reactor::wrap_effects(
+                MainEvent::RpcServer,
+                self.rpc_server.handle_event(
+                    effect_builder,
+                    rng,
+                    rpc_server::Event::BlockAdded(Arc::clone(&block)),
+                ),
+            ));
+        }
+
         for (deploy_hash, deploy_header, execution_result) in execution_results {
+            if notify_rpc_server {
+                let event = rpc_server::Event::DeployProcessed {
+                    deploy_hash,
+                    deploy_header: Box::new(deploy_header.clone()),
+                    block_hash: *block.hash(),
+                    execution_result: Box::new(execution_result.clone()),
+                };
+                effects.extend(reactor::wrap_effects(
+                    MainEvent::RpcServer,
+                    self.rpc_server.handle_event(effect_builder, rng, event),
+                ));
+            }
+
             let event = event_stream_server::Event::DeployProcessed {
                 deploy_hash,
                 deploy_header: Box::new(deploy_header),
@@ -1556,6 +1565,43 @@ impl MainReactor {
         effects
     }
 
+    fn handle_created_finality_signature(
+        &mut self,
+        effect_builder: EffectBuilder<MainEvent>,
+        rng: &mut NodeRng,
+        finality_signature: FinalitySignature,
+    ) -> Effects<MainEvent> {
+        debug!(%finality_signature, "registering created finality signature");
+
+        let mut effects = reactor::wrap_effects(
+            MainEvent::Storage,
+            effect_builder
+                .put_finality_signature_to_storage(finality_signature.clone())
+                .ignore(),
+        );
+
+        effects.extend(reactor::wrap_effects(
+            MainEvent::BlockAccumulator,
+            self.block_accumulator.handle_event(
+                effect_builder,
+                rng,
+                block_accumulator::Event::CreatedFinalitySignature {
+                    finality_signature: Box::new(finality_signature.clone()),
+                },
+            ),
+        ));
+
+        let era_id = finality_signature.era_id;
+        let payload = Message::FinalitySignature(Box::new(finality_signature));
+        effects.extend(reactor::wrap_effects(
+            MainEvent::Network,
+            effect_builder
+                .broadcast_message_to_validators(payload, era_id)
+                .ignore(),
+        ));
+        effects
+    }
+
     fn update_meta_block_gossip_state(
         &mut self,
         effect_builder: EffectBuilder<MainEvent>,
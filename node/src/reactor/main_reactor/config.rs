@@ -69,5 +69,12 @@ impl Config {
             );
             self.deploy_acceptor.timestamp_leeway = chainspec.deploy_config.max_timestamp_leeway;
         }
+        if self.storage.pruning.enabled && self.node.sync_handling.is_sync_to_genesis() {
+            error!(
+                "disabling 'storage.pruning' as it is incompatible with 'node.sync_handling' set \
+                to 'genesis'",
+            );
+            self.storage.pruning.enabled = false;
+        }
     }
 }
@@ -244,6 +244,8 @@ pub(crate) enum MainEvent {
     MetaBlockAnnouncement(MetaBlockAnnouncement),
     #[from]
     UnexecutedBlockAnnouncement(UnexecutedBlockAnnouncement),
+    /// Our finality signature for an executed block, or `None` if signing failed.
+    FinalitySignatureCreated(Option<Box<FinalitySignature>>),
 
     // Event related to figuring out validators for blocks after upgrades.
     GotBlockAfterUpgradeEraValidators(EraId, EraValidators, EraValidators),
@@ -359,6 +361,7 @@ impl ReactorEvent for MainEvent {
             MainEvent::MakeBlockExecutableRequest(_) => "MakeBlockExecutableRequest",
             MainEvent::MetaBlockAnnouncement(_) => "MetaBlockAnnouncement",
             MainEvent::UnexecutedBlockAnnouncement(_) => "UnexecutedBlockAnnouncement",
+            MainEvent::FinalitySignatureCreated(_) => "FinalitySignatureCreated",
             MainEvent::GotBlockAfterUpgradeEraValidators(_, _, _) => {
                 "GotImmediateSwitchBlockEraValidators"
             }
@@ -539,6 +542,12 @@ impl Display for MainEvent {
             MainEvent::MakeBlockExecutableRequest(inner) => Display::fmt(inner, f),
             MainEvent::MetaBlockAnnouncement(inner) => Display::fmt(inner, f),
             MainEvent::UnexecutedBlockAnnouncement(inner) => Display::fmt(inner, f),
+            MainEvent::FinalitySignatureCreated(Some(finality_signature)) => {
+                write!(f, "created finality signature: {}", finality_signature)
+            }
+            MainEvent::FinalitySignatureCreated(None) => {
+                write!(f, "failed to create finality signature")
+            }
             MainEvent::GotBlockAfterUpgradeEraValidators(era_id, _, _) => {
                 write!(
                     f,
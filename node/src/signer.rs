@@ -0,0 +1,303 @@
+//! Signing on behalf of the validator.
+//!
+//! The validator's secret key is either loaded by the node itself, or held by a separate signer
+//! process the node talks to over a Unix socket, using the [`protocol`]. Such a signer keeps track
+//! of what it signed, and refuses to sign messages that would make the validator equivocate.
+//! [`daemon`] contains a reference implementation.
+
+pub mod daemon;
+pub mod protocol;
+mod remote;
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::task::JoinError;
+
+use casper_types::{crypto, PublicKey, SecretKey, Signature, TimeDiff};
+
+pub use protocol::SignRequest;
+use protocol::{Request, Response};
+use remote::RemoteSigner;
+
+const DEFAULT_REQUEST_TIMEOUT: &str = "5 seconds";
+
+/// Configuration of a signer process holding the validator's secret key.
+#[derive(DataSize, Debug, Serialize, Deserialize, Clone)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct RemoteSignerConfig {
+    /// Path to the Unix socket the signer listens on.
+    pub socket_path: PathBuf,
+    /// How long to wait for the signer to answer a request.
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: TimeDiff,
+}
+
+fn default_request_timeout() -> TimeDiff {
+    DEFAULT_REQUEST_TIMEOUT.parse().unwrap()
+}
+
+/// An error signing a message.
+#[derive(Debug, Error)]
+pub enum SignerError {
+    /// Could not connect to the signer.
+    #[error("could not connect to signer at {path:?}: {error}")]
+    Connect {
+        /// The path of the signer's socket.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// Could not communicate with the signer.
+    #[error("signer connection error: {0}")]
+    Io(#[from] io::Error),
+    /// The signer closed the connection without answering.
+    #[error("signer closed the connection")]
+    ConnectionClosed,
+    /// The signer refused to sign the message, because it contradicts an earlier one.
+    #[error("signer refused to sign: {0}")]
+    Refused(String),
+    /// The signer failed to handle the request.
+    #[error("signer error: {0}")]
+    Remote(String),
+    /// The signer's answer doesn't match the request.
+    #[error("unexpected response from signer: {0:?}")]
+    UnexpectedResponse(Box<Response>),
+    /// The signer returned a signature that doesn't verify.
+    #[error("signer returned an invalid signature: {0}")]
+    InvalidSignature(crypto::Error),
+    /// The task waiting for the signer panicked or was cancelled.
+    #[error("signer task failed: {0}")]
+    Task(#[from] JoinError),
+}
+
+/// Signs messages with the validator's secret key.
+#[derive(Clone, DataSize)]
+pub struct Signer {
+    public_key: PublicKey,
+    backend: Backend,
+}
+
+#[derive(Clone, DataSize)]
+enum Backend {
+    /// The secret key is held in memory.
+    Local(Arc<SecretKey>),
+    /// The secret key is held by a signer process.
+    Remote(#[data_size(skip)] Arc<RemoteSigner>),
+}
+
+impl Signer {
+    /// Creates a signer using the given secret key.
+    pub fn local(secret_key: Arc<SecretKey>) -> Self {
+        Signer {
+            public_key: PublicKey::from(secret_key.as_ref()),
+            backend: Backend::Local(secret_key),
+        }
+    }
+
+    /// Connects to the signer process listening at the configured socket, and asks for the
+    /// validator's public key.
+    pub fn remote(config: &RemoteSignerConfig) -> Result<Self, SignerError> {
+        let remote =
+            RemoteSigner::connect(config.socket_path.clone(), config.request_timeout.into())?;
+        let public_key = match remote.request(&Request::PublicKey)? {
+            Response::PublicKey(public_key) => public_key,
+            response => return Err(unexpected_response(response)),
+        };
+        Ok(Signer {
+            public_key,
+            backend: Backend::Remote(Arc::new(remote)),
+        })
+    }
+
+    /// Returns the validator's public key.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Returns whether the key is held by a signer process.
+    pub fn is_remote(&self) -> bool {
+        matches!(self.backend, Backend::Remote(_))
+    }
+
+    /// Signs the described message.
+    ///
+    /// This blocks until a signer process answers, so on the reactor use `sign_async` instead.
+    ///
+    /// A local key signs consensus certificates without the [`protocol::CERTIFICATE_TAG`], so
+    /// that peers running older versions accept them. The node can't sign arbitrary messages
+    /// with a signer process, which is why those always use the tag.
+    pub fn sign(&self, request: SignRequest) -> Result<Signature, SignerError> {
+        match &self.backend {
+            Backend::Local(secret_key) => {
+                let bytes = match &request {
+                    SignRequest::ConsensusCertificate { connection_id } => {
+                        connection_id.as_ref().to_vec()
+                    }
+                    _ => request.bytes_to_sign(),
+                };
+                Ok(crypto::sign(bytes, secret_key, &self.public_key))
+            }
+            Backend::Remote(remote) => match remote.request(&Request::Sign(request.clone()))? {
+                Response::Signature(signature) => {
+                    crypto::verify(request.bytes_to_sign(), &signature, &self.public_key)
+                        .map_err(SignerError::InvalidSignature)?;
+                    Ok(signature)
+                }
+                response => Err(unexpected_response(response)),
+            },
+        }
+    }
+
+    /// Signs the described message, without blocking the executor while waiting for a signer
+    /// process.
+    pub async fn sign_async(&self, request: SignRequest) -> Result<Signature, SignerError> {
+        if !self.is_remote() {
+            return self.sign(request);
+        }
+        let signer = self.clone();
+        tokio::task::spawn_blocking(move || signer.sign(request)).await?
+    }
+}
+
+impl Debug for Signer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let backend = match self.backend {
+            Backend::Local(_) => "local",
+            Backend::Remote(_) => "remote",
+        };
+        f.debug_struct("Signer")
+            .field("public_key", &self.public_key)
+            .field("backend", &backend)
+            .finish()
+    }
+}
+
+/// Converts a response that isn't the expected one into an error.
+fn unexpected_response(response: Response) -> SignerError {
+    match response {
+        Response::Refused(reason) => SignerError::Refused(reason),
+        Response::Error(error) => SignerError::Remote(error),
+        response => SignerError::UnexpectedResponse(Box::new(response)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::BufReader,
+        os::unix::net::UnixListener,
+        sync::Arc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use casper_hashing::Digest;
+    use casper_types::{crypto, SecretKey};
+
+    use super::{daemon::SignerDaemon, *};
+    use crate::components::consensus::{protocols::zug::Content, utils::ValidatorIndex, ClContext};
+
+    #[test]
+    fn remote_signer_refuses_conflicting_votes() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let socket_path = tmp_dir.path().join("signer.socket");
+        let secret_key = SecretKey::ed25519_from_bytes([7; 32]).unwrap();
+        let daemon = Arc::new(
+            SignerDaemon::new(secret_key, tmp_dir.path().join("slashing_protection.json")).unwrap(),
+        );
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let expected_public_key = daemon.public_key().clone();
+        thread::spawn(move || daemon.serve(listener));
+
+        let signer = Signer::remote(&RemoteSignerConfig {
+            socket_path,
+            request_timeout: default_request_timeout(),
+        })
+        .unwrap();
+        assert_eq!(*signer.public_key(), expected_public_key);
+
+        let instance_id = Digest::hash([0]);
+        let vote = |vote| SignRequest::ZugMessage {
+            fields: bincode::serialize(&(
+                0u32,
+                instance_id,
+                Content::<ClContext>::Vote(vote),
+                ValidatorIndex(0),
+            ))
+            .unwrap(),
+        };
+        let request = vote(true);
+        let signature = signer.sign(request.clone()).unwrap();
+        crypto::verify(request.bytes_to_sign(), &signature, signer.public_key()).unwrap();
+        assert!(matches!(
+            signer.sign(vote(false)),
+            Err(SignerError::Refused(_))
+        ));
+
+        // Certificates are signed with the tag, and validate as such.
+        let connection_id = Digest::hash([1]);
+        let signature = signer
+            .sign(SignRequest::ConsensusCertificate { connection_id })
+            .unwrap();
+        crypto::verify(
+            protocol::certificate_bytes(&connection_id),
+            &signature,
+            signer.public_key(),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn hanging_signer_does_not_block_the_executor() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let socket_path = tmp_dir.path().join("signer.socket");
+        let secret_key = SecretKey::ed25519_from_bytes([7; 32]).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        // A signer that tells us its public key, but never answers a signing request.
+        thread::spawn(move || {
+            let mut open_streams = vec![];
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                if let Ok(Some(Request::PublicKey)) = protocol::read_message(&mut reader) {
+                    protocol::write_message(&mut stream, &Response::PublicKey(public_key.clone()))
+                        .unwrap();
+                }
+                open_streams.push(stream);
+            }
+        });
+
+        let request_timeout = TimeDiff::from_millis(200);
+        let signer = Signer::remote(&RemoteSignerConfig {
+            socket_path,
+            request_timeout,
+        })
+        .unwrap();
+        let request = SignRequest::ConsensusCertificate {
+            connection_id: Digest::hash([1]),
+        };
+
+        let start = Instant::now();
+        let signing = signer.sign_async(request);
+        tokio::pin!(signing);
+        tokio::select! {
+            result = &mut signing => panic!("hanging signer answered: {:?}", result),
+            _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+        }
+        assert!(start.elapsed() < request_timeout.into());
+
+        // The request times out eventually, including the retry on a new connection.
+        assert!(matches!(signing.await, Err(SignerError::Io(_))));
+        assert!(start.elapsed() >= request_timeout.into());
+    }
+}
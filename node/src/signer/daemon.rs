@@ -0,0 +1,524 @@
+//! A reference implementation of a signer process.
+//!
+//! The daemon holds the validator's secret key and serves the [`protocol`](super::protocol) on a
+//! Unix socket. Before signing anything it checks the request against a record of everything it
+//! signed so far, which is persisted to disk before the signature is returned.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fs::{self, File},
+    io::{self, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use casper_hashing::Digest;
+use casper_types::{crypto, PublicKey, SecretKey};
+
+use super::protocol::{self, Message, Request, Response};
+use crate::types::BlockHash;
+
+/// The number of consensus instances, i.e. eras, whose signed messages are remembered.
+///
+/// Once an instance is evicted, nothing is signed for it anymore.
+const MAX_INSTANCES: usize = 8;
+/// The number of block heights whose finality signatures are remembered.
+///
+/// Finality signatures for lower heights are refused.
+const MAX_FINALITY_HEIGHTS: usize = 10_000;
+
+/// A record of the messages the validator signed, used to refuse signing conflicting ones.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SlashingProtection {
+    /// The messages signed in each recent consensus instance, oldest first.
+    instances: VecDeque<InstanceRecord>,
+    /// Consensus instances that were evicted from `instances`.
+    retired_instances: BTreeSet<Digest>,
+    /// The hash of the block signed at each recent height.
+    finality_signatures: BTreeMap<u64, BlockHash>,
+    /// The lowest height for which finality signatures can still be created.
+    min_finality_height: u64,
+}
+
+/// The messages signed in a single consensus instance.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct InstanceRecord {
+    instance_id: Digest,
+    /// The sequence number and hash of the latest Highway unit.
+    highway_unit: Option<(u64, Digest)>,
+    /// The proposal hash echoed in each Zug round.
+    zug_echoes: BTreeMap<u32, Digest>,
+    /// The vote cast in each Zug round.
+    zug_votes: BTreeMap<u32, bool>,
+}
+
+impl SlashingProtection {
+    /// Checks whether the message can be signed without contradicting an earlier one, and records
+    /// it if so.
+    ///
+    /// Returns whether the record was changed, or the reason for refusing the request. Requests
+    /// identical to earlier ones are allowed, and don't change the record.
+    pub fn check_and_record(&mut self, message: &Message) -> Result<bool, String> {
+        match message {
+            Message::HighwayUnit {
+                instance_id,
+                seq_number,
+                hash,
+            } => {
+                let record = self.instance(instance_id)?;
+                match record.highway_unit {
+                    Some((prev_seq_number, prev_hash)) if prev_seq_number == *seq_number => {
+                        if prev_hash != *hash {
+                            return Err(format!(
+                                "already signed a different unit with sequence number {}",
+                                seq_number
+                            ));
+                        }
+                        Ok(false)
+                    }
+                    Some((prev_seq_number, _)) if prev_seq_number > *seq_number => Err(format!(
+                        "already signed a unit with sequence number {}",
+                        prev_seq_number
+                    )),
+                    _ => {
+                        record.highway_unit = Some((*seq_number, *hash));
+                        Ok(true)
+                    }
+                }
+            }
+            Message::ZugEcho {
+                instance_id,
+                round_id,
+                proposal_hash,
+            } => {
+                let record = self.instance(instance_id)?;
+                match record.zug_echoes.get(round_id) {
+                    Some(prev_hash) if prev_hash == proposal_hash => Ok(false),
+                    Some(_) => Err(format!(
+                        "already echoed a different proposal in round {}",
+                        round_id
+                    )),
+                    None => {
+                        record.zug_echoes.insert(*round_id, *proposal_hash);
+                        Ok(true)
+                    }
+                }
+            }
+            Message::ZugVote {
+                instance_id,
+                round_id,
+                vote,
+            } => {
+                let record = self.instance(instance_id)?;
+                match record.zug_votes.get(round_id) {
+                    Some(prev_vote) if prev_vote == vote => Ok(false),
+                    Some(_) => Err(format!("already voted differently in round {}", round_id)),
+                    None => {
+                        record.zug_votes.insert(*round_id, *vote);
+                        Ok(true)
+                    }
+                }
+            }
+            Message::FinalitySignature { block_hash, height } => {
+                if *height < self.min_finality_height {
+                    return Err(format!(
+                        "finality signatures below height {} are not signed anymore",
+                        self.min_finality_height
+                    ));
+                }
+                match self.finality_signatures.get(height) {
+                    Some(prev_hash) if prev_hash == block_hash => Ok(false),
+                    Some(_) => Err(format!(
+                        "already signed a different block at height {}",
+                        height
+                    )),
+                    None => {
+                        self.finality_signatures.insert(*height, *block_hash);
+                        while self.finality_signatures.len() > MAX_FINALITY_HEIGHTS {
+                            if let Some((lowest, _)) = self.finality_signatures.pop_first() {
+                                self.min_finality_height = lowest.saturating_add(1);
+                            }
+                        }
+                        Ok(true)
+                    }
+                }
+            }
+            Message::HighwayPing { .. }
+            | Message::HighwayEndorsement
+            | Message::ConsensusCertificate => Ok(false),
+        }
+    }
+
+    /// Returns the record of the given instance, creating it if necessary.
+    fn instance(&mut self, instance_id: &Digest) -> Result<&mut InstanceRecord, String> {
+        if self.retired_instances.contains(instance_id) {
+            return Err(format!(
+                "instance {} is not signed for anymore",
+                instance_id
+            ));
+        }
+        let index = match self
+            .instances
+            .iter()
+            .position(|record| record.instance_id == *instance_id)
+        {
+            Some(index) => index,
+            None => {
+                if self.instances.len() >= MAX_INSTANCES {
+                    if let Some(oldest) = self.instances.pop_front() {
+                        self.retired_instances.insert(oldest.instance_id);
+                    }
+                }
+                self.instances.push_back(InstanceRecord {
+                    instance_id: *instance_id,
+                    highway_unit: None,
+                    zug_echoes: BTreeMap::new(),
+                    zug_votes: BTreeMap::new(),
+                });
+                self.instances.len().saturating_sub(1)
+            }
+        };
+        self.instances
+            .get_mut(index)
+            .ok_or_else(|| "missing instance record".to_string())
+    }
+
+    /// Loads the record from `path`, or returns an empty one if the file doesn't exist.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(SlashingProtection::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Atomically replaces the file at `path` with this record, and syncs it to disk.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A signer process holding the validator's secret key.
+pub struct SignerDaemon {
+    secret_key: SecretKey,
+    public_key: PublicKey,
+    protection_path: PathBuf,
+    protection: Mutex<SlashingProtection>,
+}
+
+impl SignerDaemon {
+    /// Creates a signer using `secret_key`, recording signed messages in the file at
+    /// `protection_path`.
+    pub fn new(secret_key: SecretKey, protection_path: PathBuf) -> io::Result<Self> {
+        let protection = SlashingProtection::load(&protection_path)?;
+        Ok(SignerDaemon {
+            public_key: PublicKey::from(&secret_key),
+            secret_key,
+            protection_path,
+            protection: Mutex::new(protection),
+        })
+    }
+
+    /// Returns the validator's public key.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Accepts connections on `listener` and serves each of them on its own thread.
+    pub fn serve(self: Arc<Self>, listener: UnixListener) -> io::Result<()> {
+        info!(public_key = %self.public_key, "signer listening");
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let daemon = Arc::clone(&self);
+            thread::spawn(move || {
+                if let Err(err) = daemon.serve_connection(stream) {
+                    warn!(%err, "signer connection failed");
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Answers the requests made over `stream` until it is closed.
+    fn serve_connection(&self, stream: UnixStream) -> io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+        while let Some(request) = protocol::read_message(&mut reader)? {
+            let response = self.handle_request(request);
+            protocol::write_message(&mut writer, &response)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the answer to a single request.
+    pub fn handle_request(&self, request: Request) -> Response {
+        let sign_request = match request {
+            Request::PublicKey => return Response::PublicKey(self.public_key.clone()),
+            Request::Sign(sign_request) => sign_request,
+        };
+        let message = match sign_request.decode() {
+            Ok(message) => message,
+            Err(error) => {
+                warn!(%error, "refusing to sign malformed message");
+                return Response::Error(error);
+            }
+        };
+        let mut protection = self.protection.lock().expect("lock poisoned");
+        // Update a copy, so the record in memory never gets ahead of the one on disk.
+        let mut updated = protection.clone();
+        match updated.check_and_record(&message) {
+            Ok(false) => (),
+            Ok(true) => {
+                if let Err(err) = updated.save(&self.protection_path) {
+                    warn!(%err, "could not save slashing protection record");
+                    return Response::Error(format!("could not save record: {}", err));
+                }
+                *protection = updated;
+            }
+            Err(reason) => {
+                warn!(?message, %reason, "refusing to sign");
+                return Response::Refused(reason);
+            }
+        }
+        debug!(?message, "signing");
+        Response::Signature(crypto::sign(
+            sign_request.bytes_to_sign(),
+            &self.secret_key,
+            &self.public_key,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use casper_types::{testing::TestRng, Timestamp};
+
+    use super::*;
+    use crate::{
+        components::consensus::{
+            highway_core::{highway::WireUnit, state::Panorama},
+            utils::ValidatorIndex,
+            ClContext,
+        },
+        signer::SignRequest,
+    };
+
+    fn unit(instance_id: Digest, seq_number: u64, hash: Digest) -> Message {
+        Message::HighwayUnit {
+            instance_id,
+            seq_number,
+            hash,
+        }
+    }
+
+    /// Returns a request to sign a serialized unit without a value.
+    fn unit_request(instance_id: Digest, seq_number: u64, timestamp: Timestamp) -> SignRequest {
+        let wire_unit: WireUnit<ClContext> = WireUnit {
+            panorama: Panorama::new(1),
+            creator: ValidatorIndex(0),
+            instance_id,
+            value: None,
+            seq_number,
+            timestamp,
+            round_exp: 0,
+            endorsed: BTreeSet::new(),
+        };
+        SignRequest::HighwayUnit {
+            wire_unit: bincode::serialize(&wire_unit).unwrap(),
+        }
+    }
+
+    #[test]
+    fn refuses_conflicting_highway_units() {
+        let mut protection = SlashingProtection::default();
+        let instance_id = Digest::hash([0]);
+        let (hash0, hash1) = (Digest::hash([1]), Digest::hash([2]));
+        assert_eq!(
+            protection.check_and_record(&unit(instance_id, 0, hash0)),
+            Ok(true)
+        );
+        assert_eq!(
+            protection.check_and_record(&unit(instance_id, 0, hash0)),
+            Ok(false)
+        );
+        assert!(protection
+            .check_and_record(&unit(instance_id, 0, hash1))
+            .is_err());
+        assert_eq!(
+            protection.check_and_record(&unit(instance_id, 1, hash1)),
+            Ok(true)
+        );
+        assert!(protection
+            .check_and_record(&unit(instance_id, 0, hash0))
+            .is_err());
+        // Other instances are independent.
+        let other_instance_id = Digest::hash([3]);
+        assert_eq!(
+            protection.check_and_record(&unit(other_instance_id, 0, hash1)),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn refuses_conflicting_zug_messages() {
+        let mut protection = SlashingProtection::default();
+        let instance_id = Digest::hash([0]);
+        let echo = |round_id, proposal_hash| Message::ZugEcho {
+            instance_id,
+            round_id,
+            proposal_hash,
+        };
+        let vote = |round_id, vote| Message::ZugVote {
+            instance_id,
+            round_id,
+            vote,
+        };
+        let (hash0, hash1) = (Digest::hash([1]), Digest::hash([2]));
+        assert_eq!(protection.check_and_record(&echo(0, hash0)), Ok(true));
+        assert_eq!(protection.check_and_record(&echo(0, hash0)), Ok(false));
+        assert!(protection.check_and_record(&echo(0, hash1)).is_err());
+        assert_eq!(protection.check_and_record(&echo(1, hash1)), Ok(true));
+        assert_eq!(protection.check_and_record(&vote(0, true)), Ok(true));
+        assert!(protection.check_and_record(&vote(0, false)).is_err());
+        assert_eq!(protection.check_and_record(&vote(1, false)), Ok(true));
+    }
+
+    #[test]
+    fn refuses_conflicting_finality_signatures() {
+        let mut rng = TestRng::new();
+        let mut protection = SlashingProtection::default();
+        let finality_signature =
+            |block_hash, height| Message::FinalitySignature { block_hash, height };
+        let (hash0, hash1) = (BlockHash::random(&mut rng), BlockHash::random(&mut rng));
+        assert_eq!(
+            protection.check_and_record(&finality_signature(hash0, 5)),
+            Ok(true)
+        );
+        assert_eq!(
+            protection.check_and_record(&finality_signature(hash0, 5)),
+            Ok(false)
+        );
+        assert!(protection
+            .check_and_record(&finality_signature(hash1, 5))
+            .is_err());
+        assert_eq!(
+            protection.check_and_record(&finality_signature(hash1, 6)),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn retires_old_instances() {
+        let mut protection = SlashingProtection::default();
+        let hash = Digest::hash([0]);
+        for i in 0..=MAX_INSTANCES {
+            let instance_id = Digest::hash([i as u8]);
+            assert_eq!(
+                protection.check_and_record(&unit(instance_id, 0, hash)),
+                Ok(true)
+            );
+        }
+        // The first instance was evicted, and can't be signed for anymore.
+        let first_instance_id = Digest::hash([0]);
+        assert!(protection
+            .check_and_record(&unit(first_instance_id, 1, hash))
+            .is_err());
+    }
+
+    #[test]
+    fn persists_record() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("slashing_protection.json");
+        let secret_key = || SecretKey::ed25519_from_bytes([7; 32]).unwrap();
+        let instance_id = Digest::hash([0]);
+        let request = |millis| unit_request(instance_id, 0, Timestamp::from(millis));
+
+        let daemon = SignerDaemon::new(secret_key(), path.clone()).unwrap();
+        let response = daemon.handle_request(Request::Sign(request(1)));
+        assert!(matches!(response, Response::Signature(_)));
+
+        // After a restart the daemon still refuses to sign a conflicting unit.
+        let daemon = SignerDaemon::new(secret_key(), path).unwrap();
+        let response = daemon.handle_request(Request::Sign(request(2)));
+        assert!(matches!(response, Response::Refused(_)));
+    }
+
+    #[test]
+    fn refuses_malformed_messages() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("slashing_protection.json");
+        let daemon =
+            SignerDaemon::new(SecretKey::ed25519_from_bytes([7; 32]).unwrap(), path).unwrap();
+        let instance_id = Digest::hash([0]);
+        let ping =
+            bincode::serialize(&(ValidatorIndex(0), Timestamp::from(1), instance_id)).unwrap();
+        let endorsement = bincode::serialize(&(Digest::hash([1]), ValidatorIndex(0))).unwrap();
+        let SignRequest::HighwayUnit { wire_unit } =
+            unit_request(instance_id, 0, Timestamp::from(1))
+        else {
+            unreachable!()
+        };
+        let mut extended_ping = ping.clone();
+        extended_ping.push(0);
+
+        let sign = |request| daemon.handle_request(Request::Sign(request));
+        assert!(matches!(
+            sign(SignRequest::HighwayPing {
+                fields: ping.clone()
+            }),
+            Response::Signature(_)
+        ));
+        // Arbitrary 32 bytes, e.g. a connection ID or a hash, are not signed.
+        assert!(matches!(
+            sign(SignRequest::HighwayPing {
+                fields: Digest::hash([2]).as_ref().to_vec()
+            }),
+            Response::Error(_)
+        ));
+        // Trailing bytes are not accepted.
+        assert!(matches!(
+            sign(SignRequest::HighwayPing {
+                fields: extended_ping
+            }),
+            Response::Error(_)
+        ));
+        // No serialized message can be passed off as a different kind of message.
+        let payloads = [ping, endorsement, wire_unit];
+        for payload in &payloads {
+            let requests = [
+                SignRequest::HighwayUnit {
+                    wire_unit: payload.clone(),
+                },
+                SignRequest::HighwayPing {
+                    fields: payload.clone(),
+                },
+                SignRequest::HighwayEndorsement {
+                    endorsement: payload.clone(),
+                },
+                SignRequest::ZugMessage {
+                    fields: payload.clone(),
+                },
+            ];
+            let accepted = requests
+                .iter()
+                .filter(|request| request.decode().is_ok())
+                .count();
+            assert_eq!(accepted, 1);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! The protocol spoken between the node and a signer process.
+//!
+//! Each message is a single line containing a JSON object. The node sends a [`Request`] and the
+//! signer answers with exactly one [`Response`], in order.
+
+use std::io::{self, BufRead, Write};
+
+use hex_buffer_serde::{Hex as _, HexForm};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use casper_hashing::Digest;
+use casper_types::{EraId, PublicKey, Signature, Timestamp};
+
+use crate::{
+    components::consensus::{
+        highway_core::highway::WireUnit,
+        protocols::zug::{Content, RoundId},
+        utils::ValidatorIndex,
+        ClContext,
+    },
+    types::{BlockHash, FinalitySignature},
+};
+
+/// A request from the node to the signer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Request {
+    /// Asks for the public key of the validator.
+    PublicKey,
+    /// Asks for a signature of the described message.
+    Sign(SignRequest),
+}
+
+/// The signer's answer to a [`Request`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    /// The public key of the validator.
+    PublicKey(PublicKey),
+    /// The requested signature.
+    Signature(Signature),
+    /// The signer refused to sign, because the message contradicts one it signed before.
+    Refused(String),
+    /// The request could not be handled.
+    Error(String),
+}
+
+/// The prefix of the message signed for a consensus certificate by a signer process.
+///
+/// Its length makes sure the message can't coincide with the bytes signed for any other request.
+pub const CERTIFICATE_TAG: &[u8] = b"casper-consensus-certificate:";
+
+/// A message to be signed by the validator.
+///
+/// Consensus messages are sent in the serialization their hash is computed from, so that the
+/// signer can hash them itself, and check the decoded message against the ones it signed before.
+/// A node can therefore only obtain signatures of well-formed messages that don't make the
+/// validator equivocate.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignRequest {
+    /// A serialized Highway `WireUnit`.
+    HighwayUnit {
+        /// The serialized unit.
+        #[serde(with = "HexForm::<Vec<u8>>")]
+        wire_unit: Vec<u8>,
+    },
+    /// A Highway ping, signaling that the validator is online.
+    HighwayPing {
+        /// The serialized creator, timestamp and instance ID.
+        #[serde(with = "HexForm::<Vec<u8>>")]
+        fields: Vec<u8>,
+    },
+    /// A Highway endorsement.
+    HighwayEndorsement {
+        /// The serialized unit hash and creator.
+        #[serde(with = "HexForm::<Vec<u8>>")]
+        endorsement: Vec<u8>,
+    },
+    /// A Zug echo or vote.
+    ZugMessage {
+        /// The serialized round ID, instance ID, content and creator.
+        #[serde(with = "HexForm::<Vec<u8>>")]
+        fields: Vec<u8>,
+    },
+    /// A finality signature of a block.
+    FinalitySignature {
+        /// The block's hash.
+        block_hash: BlockHash,
+        /// The era the block belongs to.
+        era_id: EraId,
+        /// The block's height.
+        height: u64,
+    },
+    /// A certificate proving to a peer that the validator controls a network connection.
+    ConsensusCertificate {
+        /// The ID of the connection.
+        connection_id: Digest,
+    },
+}
+
+/// The content of a [`SignRequest`], as far as it is relevant for slashing protection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// A Highway unit with the given sequence number.
+    HighwayUnit {
+        /// The ID of the consensus protocol instance.
+        instance_id: Digest,
+        /// The unit's sequence number.
+        seq_number: u64,
+        /// The unit's hash.
+        hash: Digest,
+    },
+    /// A Highway ping.
+    HighwayPing {
+        /// The ID of the consensus protocol instance.
+        instance_id: Digest,
+    },
+    /// A Highway endorsement.
+    HighwayEndorsement,
+    /// A Zug echo of a proposal.
+    ZugEcho {
+        /// The ID of the consensus protocol instance.
+        instance_id: Digest,
+        /// The round the proposal belongs to.
+        round_id: u32,
+        /// The hash of the proposal.
+        proposal_hash: Digest,
+    },
+    /// A Zug vote.
+    ZugVote {
+        /// The ID of the consensus protocol instance.
+        instance_id: Digest,
+        /// The round the vote belongs to.
+        round_id: u32,
+        /// Whether this is a vote to accept the round's proposal.
+        vote: bool,
+    },
+    /// A finality signature of a block.
+    FinalitySignature {
+        /// The block's hash.
+        block_hash: BlockHash,
+        /// The block's height.
+        height: u64,
+    },
+    /// A consensus certificate.
+    ConsensusCertificate,
+}
+
+impl SignRequest {
+    /// Returns the bytes the signature is computed over.
+    ///
+    /// For consensus messages this is the hash of the serialized message. The different kinds of
+    /// consensus messages have different serialized lengths, so a signature is only ever valid for
+    /// one of them.
+    pub fn bytes_to_sign(&self) -> Vec<u8> {
+        match self {
+            SignRequest::HighwayUnit { wire_unit: bytes }
+            | SignRequest::HighwayPing { fields: bytes }
+            | SignRequest::HighwayEndorsement { endorsement: bytes }
+            | SignRequest::ZugMessage { fields: bytes } => Digest::hash(bytes).as_ref().to_vec(),
+            SignRequest::FinalitySignature {
+                block_hash, era_id, ..
+            } => FinalitySignature::bytes_to_sign(block_hash, *era_id),
+            SignRequest::ConsensusCertificate { connection_id } => certificate_bytes(connection_id),
+        }
+    }
+
+    /// Decodes the message, and returns an error if it is not in the canonical serialization of
+    /// the expected type.
+    pub fn decode(&self) -> Result<Message, String> {
+        match self {
+            SignRequest::HighwayUnit { wire_unit } => {
+                let unit: WireUnit<ClContext> = decode_canonical(wire_unit, "unit")?;
+                Ok(Message::HighwayUnit {
+                    instance_id: unit.instance_id,
+                    seq_number: unit.seq_number,
+                    hash: Digest::hash(wire_unit),
+                })
+            }
+            SignRequest::HighwayPing { fields } => {
+                let (_creator, _timestamp, instance_id): (ValidatorIndex, Timestamp, Digest) =
+                    decode_canonical(fields, "ping")?;
+                Ok(Message::HighwayPing { instance_id })
+            }
+            SignRequest::HighwayEndorsement { endorsement } => {
+                let _: (Digest, ValidatorIndex) = decode_canonical(endorsement, "endorsement")?;
+                Ok(Message::HighwayEndorsement)
+            }
+            SignRequest::ZugMessage { fields } => {
+                let (round_id, instance_id, content, _creator): (
+                    RoundId,
+                    Digest,
+                    Content<ClContext>,
+                    ValidatorIndex,
+                ) = decode_canonical(fields, "Zug message")?;
+                Ok(match content {
+                    Content::Echo(proposal_hash) => Message::ZugEcho {
+                        instance_id,
+                        round_id,
+                        proposal_hash,
+                    },
+                    Content::Vote(vote) => Message::ZugVote {
+                        instance_id,
+                        round_id,
+                        vote,
+                    },
+                })
+            }
+            SignRequest::FinalitySignature {
+                block_hash, height, ..
+            } => Ok(Message::FinalitySignature {
+                block_hash: *block_hash,
+                height: *height,
+            }),
+            SignRequest::ConsensusCertificate { .. } => Ok(Message::ConsensusCertificate),
+        }
+    }
+}
+
+/// Returns the message a signer process signs for a consensus certificate.
+pub fn certificate_bytes(connection_id: &Digest) -> Vec<u8> {
+    [CERTIFICATE_TAG, connection_id.as_ref()].concat()
+}
+
+/// Deserializes `bytes`, and checks that they are the exact serialization of the result.
+fn decode_canonical<T: Serialize + DeserializeOwned>(
+    bytes: &[u8],
+    kind: &str,
+) -> Result<T, String> {
+    let value: T =
+        bincode::deserialize(bytes).map_err(|err| format!("invalid {}: {}", kind, err))?;
+    if bincode::serialize(&value).ok().as_deref() != Some(bytes) {
+        return Err(format!("{} is not in canonical serialization", kind));
+    }
+    Ok(value)
+}
+
+/// Writes `message` as a single line of JSON, and flushes the writer.
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// Reads a message written by [`write_message`].
+///
+/// Returns `Ok(None)` if the other side closed the connection.
+pub fn read_message<R: BufRead, T: DeserializeOwned>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
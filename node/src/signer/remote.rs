@@ -0,0 +1,98 @@
+//! The client side of the connection to a signer process.
+
+use std::{
+    io::BufReader,
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use tracing::{debug, warn};
+
+use super::{
+    protocol::{self, Request, Response},
+    SignerError,
+};
+
+/// An open connection to the signer.
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Connection {
+    fn open(socket_path: &Path, timeout: Duration) -> Result<Self, SignerError> {
+        let connect_error = |error| SignerError::Connect {
+            path: socket_path.to_path_buf(),
+            error,
+        };
+        let stream = UnixStream::connect(socket_path).map_err(connect_error)?;
+        stream
+            .set_read_timeout(Some(timeout))
+            .map_err(connect_error)?;
+        stream
+            .set_write_timeout(Some(timeout))
+            .map_err(connect_error)?;
+        let writer = stream.try_clone().map_err(connect_error)?;
+        Ok(Connection {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    fn request(&mut self, request: &Request) -> Result<Response, SignerError> {
+        protocol::write_message(&mut self.writer, request)?;
+        protocol::read_message(&mut self.reader)?.ok_or(SignerError::ConnectionClosed)
+    }
+}
+
+/// A client of a signer process listening on a Unix socket.
+///
+/// Requests are blocking, so the node only makes them via `Signer::sign_async`, on a thread for
+/// blocking tasks: A slow or hanging signer must not stall the reactor.
+pub(super) struct RemoteSigner {
+    socket_path: PathBuf,
+    timeout: Duration,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl RemoteSigner {
+    /// Connects to the signer listening at `socket_path`.
+    pub(super) fn connect(socket_path: PathBuf, timeout: Duration) -> Result<Self, SignerError> {
+        let connection = Connection::open(&socket_path, timeout)?;
+        Ok(RemoteSigner {
+            socket_path,
+            timeout,
+            connection: Mutex::new(Some(connection)),
+        })
+    }
+
+    /// Sends a request to the signer and returns its response.
+    ///
+    /// If the connection was lost, reconnects and retries once. Signers answer repeated requests
+    /// for the same message consistently, so a retry never causes a conflicting signature.
+    pub(super) fn request(&self, request: &Request) -> Result<Response, SignerError> {
+        // A poisoned lock only means another request failed; the connection is replaced anyway.
+        let mut connection = self
+            .connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(open_connection) = connection.as_mut() {
+            match open_connection.request(request) {
+                Ok(response) => return Ok(response),
+                Err(error) => {
+                    debug!(%error, "lost connection to signer; reconnecting");
+                    *connection = None;
+                }
+            }
+        }
+        let mut new_connection = Connection::open(&self.socket_path, self.timeout)?;
+        let result = new_connection.request(request);
+        match &result {
+            Ok(_) => *connection = Some(new_connection),
+            Err(error) => warn!(%error, path = ?self.socket_path, "signer request failed"),
+        }
+        result
+    }
+}
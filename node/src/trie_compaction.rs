@@ -0,0 +1,131 @@
+//! Offline compaction of the global state.
+//!
+//! Once a block has been executed, the tries of its pre-state which were replaced are never
+//! deleted, so the trie store keeps growing.  This removes all tries not reachable from the state
+//! roots of a configurable set of recent blocks and switch blocks.  It must only be run while the
+//! node is stopped.
+
+use std::{collections::BTreeSet, sync::Arc};
+
+use lmdb::DatabaseFlags;
+use thiserror::Error;
+use tracing::info;
+
+use casper_execution_engine::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        error::Error as GlobalStateError,
+        global_state::lmdb::{GarbageCollectionError, GarbageCollectionStats, LmdbGlobalState},
+        transaction_source::lmdb::LmdbEnvironment,
+        trie_store::lmdb::LmdbTrieStore,
+    },
+};
+use casper_hashing::Digest;
+
+use crate::{
+    components::storage::{FatalStorageError, Storage},
+    reactor::main_reactor::Config,
+    types::{chainspec, Chainspec, ChainspecRawBytes},
+    utils::{Loadable, WithDir},
+};
+
+/// Error returned as a result of compacting the global state.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// Error loading the chainspec.
+    #[error("error loading chainspec: {0}")]
+    LoadChainspec(chainspec::Error),
+
+    /// Error reading the block headers from storage.
+    #[error("error reading block headers: {0}")]
+    Storage(#[from] FatalStorageError),
+
+    /// Error accessing the global state.
+    #[error("error accessing global state: {0}")]
+    GlobalState(#[from] GlobalStateError),
+
+    /// Deleting the unreachable tries would risk losing data.
+    #[error("{0}; pass --force to delete anyway")]
+    Refused(GarbageCollectionError),
+}
+
+impl From<GarbageCollectionError> for Error {
+    fn from(error: GarbageCollectionError) -> Self {
+        match error {
+            GarbageCollectionError::Storage(error) => Error::GlobalState(error),
+            GarbageCollectionError::NoRoots | GarbageCollectionError::MissingTries(_) => {
+                Error::Refused(error)
+            }
+        }
+    }
+}
+
+/// Deletes all tries from the global state which aren't reachable from the state roots of the
+/// highest `keep_blocks` blocks, or of the highest `keep_switch_blocks` switch blocks (all of them
+/// if `None`).  Only counts the unreachable tries if `dry_run` is set.
+///
+/// Unless `force` is set, refuses to delete anything if no blocks are retained, or if any tries
+/// reachable from the retained state roots are missing.
+pub(crate) fn compact_global_state(
+    config: WithDir<Config>,
+    keep_blocks: u64,
+    keep_switch_blocks: Option<u64>,
+    dry_run: bool,
+    force: bool,
+) -> Result<GarbageCollectionStats, Error> {
+    let (root_dir, config) = config.into_parts();
+    let (chainspec, _) =
+        <(Chainspec, ChainspecRawBytes)>::from_path(&root_dir).map_err(Error::LoadChainspec)?;
+
+    let storage = Storage::new(
+        &WithDir::new(&root_dir, config.storage.clone()),
+        None,
+        chainspec.protocol_version(),
+        chainspec.protocol_config.activation_point.era_id(),
+        &chainspec.network_config.name,
+        chainspec.deploy_config.max_ttl.into(),
+        chainspec.core_config.recent_era_count(),
+        None,
+        false,
+    )?;
+
+    let mut state_roots = BTreeSet::new();
+    if let Some(highest_height) = storage.read_highest_block_height() {
+        let lowest_height = highest_height.saturating_sub(keep_blocks.saturating_sub(1));
+        for height in (lowest_height..=highest_height).take(keep_blocks as usize) {
+            if let Some(header) = storage.read_block_header_by_height(height, false)? {
+                state_roots.insert(*header.state_root_hash());
+            }
+        }
+    }
+    let switch_block_headers =
+        storage.read_highest_switch_block_headers(keep_switch_blocks.unwrap_or(u64::MAX))?;
+    state_roots.extend(
+        switch_block_headers
+            .iter()
+            .map(|header| *header.state_root_hash()),
+    );
+    let state_roots: Vec<Digest> = state_roots.into_iter().collect();
+    info!(
+        state_root_count = state_roots.len(),
+        dry_run, "collecting unreachable tries"
+    );
+
+    let contract_runtime_config = &config.contract_runtime;
+    let environment = Arc::new(LmdbEnvironment::new(
+        storage.root_path(),
+        contract_runtime_config.max_global_state_size_or_default(),
+        contract_runtime_config.max_readers_or_default(),
+        contract_runtime_config.manual_sync_enabled_or_default(),
+    )?);
+    let trie_store = Arc::new(LmdbTrieStore::new(
+        &environment,
+        None,
+        DatabaseFlags::empty(),
+    )?);
+    let global_state = LmdbGlobalState::empty(environment, trie_store)?;
+
+    let stats = global_state.collect_garbage(CorrelationId::new(), &state_roots, dry_run, force)?;
+    info!(?stats, dry_run, "finished collecting unreachable tries");
+    Ok(stats)
+}
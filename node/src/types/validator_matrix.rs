@@ -3,6 +3,7 @@ use std::iter;
 use std::{
     collections::{BTreeMap, HashSet},
     fmt::{self, Debug, Formatter},
+    future::Future,
     sync::{Arc, RwLock, RwLockReadGuard},
 };
 
@@ -11,11 +12,14 @@ use itertools::Itertools;
 use num_rational::Ratio;
 use serde::Serialize;
 use static_assertions::const_assert;
-use tracing::info;
+use tracing::{info, warn};
 
-use casper_types::{EraId, PublicKey, SecretKey, U512};
+#[cfg(test)]
+use casper_types::SecretKey;
+use casper_types::{EraId, PublicKey, U512};
 
 use super::{BlockHeader, FinalitySignature};
+use crate::signer::{SignRequest, Signer};
 
 const MAX_VALIDATOR_MATRIX_ENTRIES: usize = 6;
 const_assert!(MAX_VALIDATOR_MATRIX_ENTRIES % 2 == 0);
@@ -47,8 +51,7 @@ pub(crate) struct ValidatorMatrix {
     chainspec_activation_era: EraId,
     #[data_size(skip)]
     finality_threshold_fraction: Ratio<u64>,
-    secret_signing_key: Arc<SecretKey>,
-    public_signing_key: PublicKey,
+    signer: Signer,
     auction_delay: u64,
     retrograde_latch: Option<EraId>,
 }
@@ -58,8 +61,7 @@ impl ValidatorMatrix {
         finality_threshold_fraction: Ratio<u64>,
         chainspec_validators: Option<BTreeMap<PublicKey, U512>>,
         chainspec_activation_era: EraId,
-        secret_signing_key: Arc<SecretKey>,
-        public_signing_key: PublicKey,
+        signer: Signer,
         auction_delay: u64,
     ) -> Self {
         let inner = Arc::new(RwLock::new(BTreeMap::new()));
@@ -68,8 +70,7 @@ impl ValidatorMatrix {
             finality_threshold_fraction,
             chainspec_validators: chainspec_validators.map(Arc::new),
             chainspec_activation_era,
-            secret_signing_key,
-            public_signing_key,
+            signer,
             auction_delay,
             retrograde_latch: None,
         }
@@ -78,12 +79,13 @@ impl ValidatorMatrix {
     /// Creates a new validator matrix with just a single validator.
     #[cfg(test)]
     pub(crate) fn new_with_validator(secret_signing_key: Arc<SecretKey>) -> Self {
-        let public_signing_key = PublicKey::from(&*secret_signing_key);
+        let signer = Signer::local(secret_signing_key);
+        let public_signing_key = signer.public_key().clone();
         let finality_threshold_fraction = Ratio::new(1, 3);
         let era_id = EraId::new(0);
         let weights = EraValidatorWeights::new(
             era_id,
-            iter::once((public_signing_key.clone(), 100.into())).collect(),
+            iter::once((public_signing_key, 100.into())).collect(),
             finality_threshold_fraction,
         );
         ValidatorMatrix {
@@ -91,8 +93,7 @@ impl ValidatorMatrix {
             chainspec_validators: None,
             chainspec_activation_era: EraId::from(0),
             finality_threshold_fraction,
-            public_signing_key,
-            secret_signing_key,
+            signer,
             auction_delay: 1,
             retrograde_latch: None,
         }
@@ -241,13 +242,13 @@ impl ValidatorMatrix {
     }
 
     pub(crate) fn public_signing_key(&self) -> &PublicKey {
-        &self.public_signing_key
+        self.signer.public_key()
     }
 
     /// Returns whether `pub_key` is the ID of a validator in this era, or `None` if the validator
     /// information for that era is missing.
     pub(crate) fn is_self_validator_in_era(&self, era_id: EraId) -> Option<bool> {
-        self.is_validator_in_era(era_id, &self.public_signing_key)
+        self.is_validator_in_era(era_id, self.signer.public_key())
     }
 
     /// Determine if the active validator is in a current or upcoming set of active validators.
@@ -262,22 +263,42 @@ impl ValidatorMatrix {
             .any(|validator_weights| validator_weights.is_validator(public_key))
     }
 
+    /// Returns a future creating our finality signature for the block, if we are a validator in
+    /// its era.
+    ///
+    /// The future doesn't block the executor while waiting for a signer process.
     pub(crate) fn create_finality_signature(
         &self,
         block_header: &BlockHeader,
-    ) -> Option<FinalitySignature> {
-        if self
+    ) -> Option<impl Future<Output = Option<FinalitySignature>> + Send + 'static> {
+        if !self
             .is_self_validator_in_era(block_header.era_id())
             .unwrap_or(false)
         {
-            return Some(FinalitySignature::create(
-                block_header.block_hash(),
-                block_header.era_id(),
-                &self.secret_signing_key,
-                self.public_signing_key.clone(),
-            ));
+            return None;
         }
-        None
+        let block_hash = block_header.block_hash();
+        let era_id = block_header.era_id();
+        let request = SignRequest::FinalitySignature {
+            block_hash,
+            era_id,
+            height: block_header.height(),
+        };
+        let signer = self.signer.clone();
+        Some(async move {
+            match signer.sign_async(request).await {
+                Ok(signature) => Some(FinalitySignature::new(
+                    block_hash,
+                    era_id,
+                    signature,
+                    signer.public_key().clone(),
+                )),
+                Err(error) => {
+                    warn!(%error, %block_hash, "could not create finality signature");
+                    None
+                }
+            }
+        })
     }
 
     fn read_inner(&self) -> RwLockReadGuard<BTreeMap<EraId, EraValidatorWeights>> {
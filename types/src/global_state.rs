@@ -0,0 +1,375 @@
+//! Verification of Merkle proofs of global state entries against a state root hash.
+//!
+//! This allows clients which can't run a node, such as wallets and light clients, to check the
+//! results of the node's `query_global_state`, `state_get_dictionary_item` and
+//! `state_get_balance` RPCs against the state root hash of a trusted block header.
+
+mod trie_merkle_proof;
+
+use alloc::string::String;
+use core::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+pub use trie_merkle_proof::{Pointer, TrieHash, TrieMerkleProof, TrieMerkleProofStep};
+
+use crate::{
+    bytesrepr::{self, Bytes},
+    CLValue, CLValueError, Key, StoredValue, URef, U512,
+};
+
+/// Error conditions of a proof validation.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The path should not have a different length than the proof less one.
+    PathLengthDifferentThanProofLessOne,
+    /// The provided key does not match the key in the proof.
+    UnexpectedKey,
+    /// The provided value does not match the value in the proof.
+    UnexpectedValue,
+    /// The proof hash is invalid.
+    InvalidProofHash,
+    /// The path went cold.
+    PathCold,
+    /// (De)serialization error.
+    BytesRepr(bytesrepr::Error),
+    /// Key is not a URef.
+    KeyIsNotAURef(Key),
+    /// Error converting a stored value to a [`CLValue`].
+    ValueToCLValueConversion,
+    /// CLValue conversion error.
+    CLValueError(CLValueError),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::PathLengthDifferentThanProofLessOne => write!(
+                formatter,
+                "The path should not have a different length than the proof less one."
+            ),
+            ValidationError::UnexpectedKey => write!(
+                formatter,
+                "The provided key does not match the key in the proof."
+            ),
+            ValidationError::UnexpectedValue => write!(
+                formatter,
+                "The provided value does not match the value in the proof."
+            ),
+            ValidationError::InvalidProofHash => write!(formatter, "The proof hash is invalid."),
+            ValidationError::PathCold => write!(formatter, "The path went cold."),
+            ValidationError::BytesRepr(error) => {
+                write!(formatter, "Serialization error: {}", error)
+            }
+            ValidationError::KeyIsNotAURef(_) => write!(formatter, "Key is not a URef"),
+            ValidationError::ValueToCLValueConversion => {
+                write!(formatter, "Failed to convert stored value to CLValue")
+            }
+            ValidationError::CLValueError(error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+impl From<CLValueError> for ValidationError {
+    fn from(error: CLValueError) -> Self {
+        ValidationError::CLValueError(error)
+    }
+}
+
+impl From<bytesrepr::Error> for ValidationError {
+    fn from(error: bytesrepr::Error) -> Self {
+        ValidationError::BytesRepr(error)
+    }
+}
+
+/// Validates the proofs of a query of `expected_first_key` followed by the named keys in `path`,
+/// as returned by the `query_global_state` RPC.
+///
+/// Dictionary items are stored wrapped together with the dictionary's seed [`URef`] and item key;
+/// if the queried entry is a dictionary item, `expected_value` is the unwrapped value, as returned
+/// by the RPC.
+pub fn validate_query_proof(
+    state_root_hash: &TrieHash,
+    proofs: &[TrieMerkleProof<Key, StoredValue>],
+    expected_first_key: &Key,
+    path: &[String],
+    expected_value: &StoredValue,
+) -> Result<(), ValidationError> {
+    if proofs.len() != path.len() + 1 {
+        return Err(ValidationError::PathLengthDifferentThanProofLessOne);
+    }
+
+    let mut proofs_iter = proofs.iter();
+
+    // length check above means we are safe to unwrap here
+    let first_proof = proofs_iter.next().unwrap();
+
+    if first_proof.key() != &expected_first_key.normalize() {
+        return Err(ValidationError::UnexpectedKey);
+    }
+
+    if state_root_hash != &first_proof.compute_state_hash()? {
+        return Err(ValidationError::InvalidProofHash);
+    }
+
+    let mut last_proof = first_proof;
+
+    for (proof, path_component) in proofs_iter.zip(path.iter()) {
+        let named_keys = match last_proof.value() {
+            StoredValue::Account(account) => account.named_keys(),
+            StoredValue::Contract(contract) => contract.named_keys(),
+            _ => return Err(ValidationError::PathCold),
+        };
+
+        let key = match named_keys.get(path_component) {
+            Some(key) => key,
+            None => return Err(ValidationError::PathCold),
+        };
+
+        if proof.key() != &key.normalize() {
+            return Err(ValidationError::UnexpectedKey);
+        }
+
+        if state_root_hash != &proof.compute_state_hash()? {
+            return Err(ValidationError::InvalidProofHash);
+        }
+
+        last_proof = proof;
+    }
+
+    let proven_value = match last_proof.key() {
+        Key::Dictionary(_) => StoredValue::CLValue(unwrap_dictionary_value(last_proof.value())?.0),
+        _ => last_proof.value().clone(),
+    };
+    if &proven_value != expected_value {
+        return Err(ValidationError::UnexpectedValue);
+    }
+
+    Ok(())
+}
+
+/// Validates the proof of the balance of the purse `expected_purse_key`, as returned by the
+/// `state_get_balance` RPC.
+pub fn validate_balance_proof(
+    state_root_hash: &TrieHash,
+    balance_proof: &TrieMerkleProof<Key, StoredValue>,
+    expected_purse_key: Key,
+    expected_motes: &U512,
+) -> Result<(), ValidationError> {
+    let expected_balance_key = expected_purse_key
+        .into_uref()
+        .map(|uref| Key::Balance(uref.addr()))
+        .ok_or(ValidationError::KeyIsNotAURef(expected_purse_key))?;
+
+    if balance_proof.key() != &expected_balance_key.normalize() {
+        return Err(ValidationError::UnexpectedKey);
+    }
+
+    if state_root_hash != &balance_proof.compute_state_hash()? {
+        return Err(ValidationError::InvalidProofHash);
+    }
+
+    let balance_proof_clvalue = CLValue::try_from(balance_proof.value().clone())
+        .map_err(|_| ValidationError::ValueToCLValueConversion)?;
+
+    let balance_motes: U512 = balance_proof_clvalue.into_t()?;
+
+    if expected_motes != &balance_motes {
+        return Err(ValidationError::UnexpectedValue);
+    }
+
+    Ok(())
+}
+
+/// Validates the proof of the item under `dictionary_item_key` in the dictionary seeded by
+/// `seed_uref`, as returned by the `state_get_dictionary_item` RPC.
+pub fn validate_dictionary_item_proof(
+    state_root_hash: &TrieHash,
+    proof: &TrieMerkleProof<Key, StoredValue>,
+    seed_uref: URef,
+    dictionary_item_key: &str,
+    expected_value: &CLValue,
+) -> Result<(), ValidationError> {
+    if proof.key() != &Key::dictionary(seed_uref, dictionary_item_key.as_bytes()) {
+        return Err(ValidationError::UnexpectedKey);
+    }
+
+    if state_root_hash != &proof.compute_state_hash()? {
+        return Err(ValidationError::InvalidProofHash);
+    }
+
+    let (cl_value, seed_uref_addr, item_key_bytes) = unwrap_dictionary_value(proof.value())?;
+    if seed_uref_addr.as_slice() != seed_uref.addr()
+        || item_key_bytes.as_slice() != dictionary_item_key.as_bytes()
+    {
+        return Err(ValidationError::UnexpectedKey);
+    }
+
+    if &cl_value != expected_value {
+        return Err(ValidationError::UnexpectedValue);
+    }
+
+    Ok(())
+}
+
+/// Splits a stored dictionary item into its value, the address of the dictionary's seed `URef`
+/// and the item key.
+fn unwrap_dictionary_value(
+    stored_value: &StoredValue,
+) -> Result<(CLValue, Bytes, Bytes), ValidationError> {
+    let wrapped = CLValue::try_from(stored_value.clone())
+        .map_err(|_| ValidationError::ValueToCLValueConversion)?;
+    Ok(bytesrepr::deserialize_from_slice(wrapped.inner_bytes())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::VecDeque, string::ToString, vec};
+
+    use super::*;
+    use crate::{
+        bytesrepr::ToBytes, contracts::NamedKeys, AccessRights, CLType, Contract,
+        ContractPackageHash, ContractWasmHash, EntryPoints, ProtocolVersion,
+    };
+
+    fn dictionary_item(seed_uref: URef, item_key: &str, value: &CLValue) -> StoredValue {
+        let wrapped = (
+            value.clone(),
+            Bytes::from(seed_uref.addr().to_vec()),
+            Bytes::from(item_key.as_bytes().to_vec()),
+        );
+        StoredValue::CLValue(CLValue::from_components(
+            CLType::Any,
+            wrapped.to_bytes().unwrap(),
+        ))
+    }
+
+    /// Returns a proof of an entry in a trie holding only that entry, along with the state root
+    /// hash of that trie.
+    fn sole_entry_proof(
+        key: Key,
+        value: StoredValue,
+    ) -> (TrieMerkleProof<Key, StoredValue>, TrieHash) {
+        let proof = TrieMerkleProof::new(key, value, VecDeque::new());
+        let state_root_hash = proof.compute_state_hash().unwrap();
+        (proof, state_root_hash)
+    }
+
+    #[test]
+    fn should_validate_dictionary_item_proof() {
+        let seed_uref = URef::new([4; 32], AccessRights::READ_ADD_WRITE);
+        let value = CLValue::from_t("value".to_string()).unwrap();
+        let key = Key::dictionary(seed_uref, b"item");
+        let (proof, state_root_hash) =
+            sole_entry_proof(key, dictionary_item(seed_uref, "item", &value));
+
+        assert_eq!(
+            validate_dictionary_item_proof(&state_root_hash, &proof, seed_uref, "item", &value),
+            Ok(())
+        );
+        assert_eq!(
+            validate_query_proof(
+                &state_root_hash,
+                &[proof.clone()],
+                &key,
+                &[],
+                &StoredValue::CLValue(value.clone())
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_dictionary_item_proof(&state_root_hash, &proof, seed_uref, "other", &value),
+            Err(ValidationError::UnexpectedKey)
+        );
+        let other_value = CLValue::from_t("other".to_string()).unwrap();
+        assert_eq!(
+            validate_dictionary_item_proof(
+                &state_root_hash,
+                &proof,
+                seed_uref,
+                "item",
+                &other_value
+            ),
+            Err(ValidationError::UnexpectedValue)
+        );
+        assert_eq!(
+            validate_dictionary_item_proof(&[0; 32], &proof, seed_uref, "item", &value),
+            Err(ValidationError::InvalidProofHash)
+        );
+    }
+
+    #[test]
+    fn should_validate_balance_proof() {
+        let purse = URef::new([5; 32], AccessRights::READ_ADD_WRITE);
+        let motes = U512::from(1_000);
+        let (proof, state_root_hash) = sole_entry_proof(
+            Key::Balance(purse.addr()),
+            StoredValue::CLValue(CLValue::from_t(motes).unwrap()),
+        );
+
+        assert_eq!(
+            validate_balance_proof(&state_root_hash, &proof, Key::URef(purse), &motes),
+            Ok(())
+        );
+        assert_eq!(
+            validate_balance_proof(&state_root_hash, &proof, Key::URef(purse), &U512::one()),
+            Err(ValidationError::UnexpectedValue)
+        );
+        assert_eq!(
+            validate_balance_proof(&state_root_hash, &proof, Key::Hash([5; 32]), &motes),
+            Err(ValidationError::KeyIsNotAURef(Key::Hash([5; 32])))
+        );
+    }
+
+    #[test]
+    fn should_validate_path_based_query_proof() {
+        let value_key = Key::Hash([6; 32]);
+        let value = StoredValue::CLValue(CLValue::from_t(6_u8).unwrap());
+        let (value_proof, state_root_hash) = sole_entry_proof(value_key, value.clone());
+
+        // A single-entry trie can't hold both the contract and the value, so a valid query can't be
+        // constructed here; see the execution engine's tests for that.
+        let mut named_keys = NamedKeys::new();
+        named_keys.insert("value".to_string(), value_key);
+        let contract = StoredValue::Contract(Contract::new(
+            ContractPackageHash::new([0; 32]),
+            ContractWasmHash::new([0; 32]),
+            named_keys,
+            EntryPoints::new(),
+            ProtocolVersion::V1_0_0,
+        ));
+        let contract_proof = TrieMerkleProof::new(value_key, contract, VecDeque::new());
+        let contract_root_hash = contract_proof.compute_state_hash().unwrap();
+        let proofs = vec![contract_proof, value_proof];
+
+        assert_eq!(
+            validate_query_proof(
+                &state_root_hash,
+                &proofs,
+                &value_key,
+                &["value".to_string()],
+                &value
+            ),
+            Err(ValidationError::InvalidProofHash)
+        );
+        assert_eq!(
+            validate_query_proof(
+                &contract_root_hash,
+                &proofs,
+                &value_key,
+                &["missing".to_string()],
+                &value
+            ),
+            Err(ValidationError::PathCold)
+        );
+        assert_eq!(
+            validate_query_proof(&contract_root_hash, &proofs, &value_key, &[], &value),
+            Err(ValidationError::PathLengthDifferentThanProofLessOne)
+        );
+    }
+}
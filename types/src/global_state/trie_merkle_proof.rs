@@ -0,0 +1,379 @@
+use alloc::{collections::VecDeque, vec::Vec};
+use core::mem;
+
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+
+use crate::{
+    bytesrepr::{self, Bytes, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    BLAKE2B_DIGEST_LENGTH,
+};
+
+/// The hash of a trie, as used for state root hashes and for the pointers between tries.
+pub type TrieHash = [u8; BLAKE2B_DIGEST_LENGTH];
+
+/// The number of children of a node in the trie.
+const RADIX: usize = 256;
+/// The size of the chunks into which tries too large to be hashed in one go are split.
+const CHUNK_SIZE_BYTES: usize = 8 * 1024 * 1024;
+
+const TRIE_LEAF_TAG: u8 = 0;
+const TRIE_NODE_TAG: u8 = 1;
+const TRIE_EXTENSION_TAG: u8 = 2;
+
+const POINTER_LEAF_TAG: u8 = 0;
+const POINTER_NODE_TAG: u8 = 1;
+
+const TRIE_MERKLE_PROOF_STEP_NODE_ID: u8 = 0;
+const TRIE_MERKLE_PROOF_STEP_EXTENSION_ID: u8 = 1;
+
+/// A pointer from a node or extension of the trie to one of its children.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Pointer {
+    /// Pointer to a leaf.
+    LeafPointer(TrieHash),
+    /// Pointer to a node or extension.
+    NodePointer(TrieHash),
+}
+
+impl Pointer {
+    /// Returns the hash of the trie this points to.
+    pub fn hash(&self) -> &TrieHash {
+        match self {
+            Pointer::LeafPointer(hash) | Pointer::NodePointer(hash) => hash,
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            Pointer::LeafPointer(_) => POINTER_LEAF_TAG,
+            Pointer::NodePointer(_) => POINTER_NODE_TAG,
+        }
+    }
+}
+
+impl ToBytes for Pointer {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret = bytesrepr::unchecked_allocate_buffer(self);
+        self.write_bytes(&mut ret)?;
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH + BLAKE2B_DIGEST_LENGTH
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        writer.push(self.tag());
+        writer.extend_from_slice(self.hash());
+        Ok(())
+    }
+}
+
+impl FromBytes for Pointer {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, rem) = u8::from_bytes(bytes)?;
+        let (hash, rem) = TrieHash::from_bytes(rem)?;
+        match tag {
+            POINTER_LEAF_TAG => Ok((Pointer::LeafPointer(hash), rem)),
+            POINTER_NODE_TAG => Ok((Pointer::NodePointer(hash), rem)),
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+/// A component of a proof that an entry exists in the Merkle trie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieMerkleProofStep {
+    /// Corresponds to a node of the trie.
+    Node {
+        /// The index of the child on the path to the proven entry.
+        hole_index: u8,
+        /// All other children of the node.
+        indexed_pointers_with_hole: Vec<(u8, Pointer)>,
+    },
+    /// Corresponds to an extension of the trie.
+    Extension {
+        /// Affix bytes.
+        affix: Bytes,
+    },
+}
+
+impl ToBytes for TrieMerkleProofStep {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret: Vec<u8> = bytesrepr::allocate_buffer(self)?;
+        match self {
+            TrieMerkleProofStep::Node {
+                hole_index,
+                indexed_pointers_with_hole,
+            } => {
+                ret.push(TRIE_MERKLE_PROOF_STEP_NODE_ID);
+                ret.push(*hole_index);
+                ret.append(&mut indexed_pointers_with_hole.to_bytes()?)
+            }
+            TrieMerkleProofStep::Extension { affix } => {
+                ret.push(TRIE_MERKLE_PROOF_STEP_EXTENSION_ID);
+                ret.append(&mut affix.to_bytes()?)
+            }
+        };
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+            + match self {
+                TrieMerkleProofStep::Node {
+                    hole_index,
+                    indexed_pointers_with_hole,
+                } => {
+                    hole_index.serialized_length() + indexed_pointers_with_hole.serialized_length()
+                }
+                TrieMerkleProofStep::Extension { affix } => affix.serialized_length(),
+            }
+    }
+}
+
+impl FromBytes for TrieMerkleProofStep {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, rem) = u8::from_bytes(bytes)?;
+        match tag {
+            TRIE_MERKLE_PROOF_STEP_NODE_ID => {
+                let (hole_index, rem) = u8::from_bytes(rem)?;
+                let (indexed_pointers_with_hole, rem) = Vec::<(u8, Pointer)>::from_bytes(rem)?;
+                Ok((
+                    TrieMerkleProofStep::Node {
+                        hole_index,
+                        indexed_pointers_with_hole,
+                    },
+                    rem,
+                ))
+            }
+            TRIE_MERKLE_PROOF_STEP_EXTENSION_ID => {
+                let (affix, rem) = Bytes::from_bytes(rem)?;
+                Ok((TrieMerkleProofStep::Extension { affix }, rem))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+/// A proof that an entry with the given `key` and `value` is present in the Merkle trie.
+///
+/// Given a state root hash `x`, a proof `p` is valid if `x == p.compute_state_hash()`.  This
+/// shares its serialized form with the proofs returned by the node's RPC server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieMerkleProof<K, V> {
+    key: K,
+    value: V,
+    proof_steps: VecDeque<TrieMerkleProofStep>,
+}
+
+impl<K, V> TrieMerkleProof<K, V> {
+    /// Constructs a new `TrieMerkleProof`.
+    pub fn new(key: K, value: V, proof_steps: VecDeque<TrieMerkleProofStep>) -> Self {
+        TrieMerkleProof {
+            key,
+            value,
+            proof_steps,
+        }
+    }
+
+    /// Returns the key of the proven entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the value of the proven entry.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Returns the proof steps, ordered from the leaf up to the root.
+    pub fn proof_steps(&self) -> &VecDeque<TrieMerkleProofStep> {
+        &self.proof_steps
+    }
+
+    /// Consumes the proof, returning the value of the proven entry.
+    pub fn into_value(self) -> V {
+        self.value
+    }
+}
+
+impl<K: ToBytes, V: ToBytes> TrieMerkleProof<K, V> {
+    /// Recomputes the state root hash from the proven entry and the proof steps.
+    ///
+    /// Starting from the hash of the leaf holding the entry, every proof step is used to
+    /// reconstruct the parent trie on the path to the root, whose hash is then computed in turn.
+    pub fn compute_state_hash(&self) -> Result<TrieHash, bytesrepr::Error> {
+        let mut leaf_bytes = Vec::with_capacity(
+            U8_SERIALIZED_LENGTH + self.key.serialized_length() + self.value.serialized_length(),
+        );
+        leaf_bytes.push(TRIE_LEAF_TAG);
+        self.key.write_bytes(&mut leaf_bytes)?;
+        self.value.write_bytes(&mut leaf_bytes)?;
+        let mut hash = hash_into_chunks_if_necessary(&leaf_bytes);
+
+        for (proof_step_index, proof_step) in self.proof_steps.iter().enumerate() {
+            let pointer = if proof_step_index == 0 {
+                Pointer::LeafPointer(hash)
+            } else {
+                Pointer::NodePointer(hash)
+            };
+            let mut trie_bytes = Vec::new();
+            match proof_step {
+                TrieMerkleProofStep::Node {
+                    hole_index,
+                    indexed_pointers_with_hole,
+                } => {
+                    let mut pointer_block = [None; RADIX];
+                    for (index, indexed_pointer) in indexed_pointers_with_hole {
+                        pointer_block[usize::from(*index)] = Some(*indexed_pointer);
+                    }
+                    pointer_block[usize::from(*hole_index)] = Some(pointer);
+                    trie_bytes.push(TRIE_NODE_TAG);
+                    for maybe_pointer in &pointer_block {
+                        maybe_pointer.write_bytes(&mut trie_bytes)?;
+                    }
+                }
+                TrieMerkleProofStep::Extension { affix } => {
+                    trie_bytes.push(TRIE_EXTENSION_TAG);
+                    affix.write_bytes(&mut trie_bytes)?;
+                    pointer.write_bytes(&mut trie_bytes)?;
+                }
+            }
+            hash = blake2b_hash(&[&trie_bytes]);
+        }
+        Ok(hash)
+    }
+}
+
+impl<K: ToBytes, V: ToBytes> ToBytes for TrieMerkleProof<K, V> {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret: Vec<u8> = bytesrepr::allocate_buffer(self)?;
+        ret.append(&mut self.key.to_bytes()?);
+        ret.append(&mut self.value.to_bytes()?);
+        ret.append(&mut self.proof_steps.to_bytes()?);
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.key.serialized_length()
+            + self.value.serialized_length()
+            + self.proof_steps.serialized_length()
+    }
+}
+
+impl<K: FromBytes, V: FromBytes> FromBytes for TrieMerkleProof<K, V> {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (key, rem) = K::from_bytes(bytes)?;
+        let (value, rem) = V::from_bytes(rem)?;
+        let (proof_steps, rem) = VecDeque::<TrieMerkleProofStep>::from_bytes(rem)?;
+        Ok((
+            TrieMerkleProof {
+                key,
+                value,
+                proof_steps,
+            },
+            rem,
+        ))
+    }
+}
+
+/// Computes the BLAKE2b hash of the concatenation of `parts`.
+fn blake2b_hash(parts: &[&[u8]]) -> TrieHash {
+    let mut hash = [0; BLAKE2B_DIGEST_LENGTH];
+    // NOTE: Safe to unwrap here because our digest length is constant and valid.
+    let mut hasher = VarBlake2b::new(BLAKE2B_DIGEST_LENGTH).unwrap();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize_variable(|result| hash.copy_from_slice(result));
+    hash
+}
+
+/// Hashes `bytes` in the same way as the node hashes tries: directly if they fit in a single
+/// chunk, or otherwise as the Merkle tree of the hashes of their chunks.
+fn hash_into_chunks_if_necessary(bytes: &[u8]) -> TrieHash {
+    if bytes.len() <= CHUNK_SIZE_BYTES {
+        return blake2b_hash(&[bytes]);
+    }
+
+    let mut level: Vec<TrieHash> = bytes
+        .chunks(CHUNK_SIZE_BYTES)
+        .map(|chunk| blake2b_hash(&[chunk]))
+        .collect();
+    let leaf_count = level.len() as u64;
+    while level.len() > 1 {
+        level = mem::take(&mut level)
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => blake2b_hash(&[left, right]),
+                [single] => *single,
+                _ => unreachable!("chunks of two hashes"),
+            })
+            .collect();
+    }
+
+    // The root is prefixed with a chunk worth of zeros and the leaf count, as done by the node to
+    // prevent pre-image attacks.
+    let mut hasher = VarBlake2b::new(BLAKE2B_DIGEST_LENGTH).unwrap();
+    let zeros = [0; 4096];
+    for _ in 0..CHUNK_SIZE_BYTES / zeros.len() {
+        hasher.update(zeros);
+    }
+    hasher.update(leaf_count.to_le_bytes());
+    hasher.update(level[0]);
+    let mut hash = [0; BLAKE2B_DIGEST_LENGTH];
+    hasher.finalize_variable(|result| hash.copy_from_slice(result));
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{bytesrepr, CLValue, Key, StoredValue};
+
+    fn proof_with_steps() -> TrieMerkleProof<Key, StoredValue> {
+        let steps = vec![
+            TrieMerkleProofStep::Node {
+                hole_index: 3,
+                indexed_pointers_with_hole: vec![
+                    (1, Pointer::LeafPointer([1; BLAKE2B_DIGEST_LENGTH])),
+                    (200, Pointer::NodePointer([2; BLAKE2B_DIGEST_LENGTH])),
+                ],
+            },
+            TrieMerkleProofStep::Extension {
+                affix: vec![1, 2, 3].into(),
+            },
+        ];
+        TrieMerkleProof::new(
+            Key::Hash([7; 32]),
+            StoredValue::CLValue(CLValue::from_t(7_u64).unwrap()),
+            steps.into(),
+        )
+    }
+
+    #[test]
+    fn trie_merkle_proof_serialization_roundtrip() {
+        let proof = proof_with_steps();
+        bytesrepr::test_serialization_roundtrip(&proof);
+        for step in proof.proof_steps() {
+            bytesrepr::test_serialization_roundtrip(step);
+        }
+    }
+
+    #[test]
+    fn state_hash_depends_on_every_step() {
+        let proof = proof_with_steps();
+        let state_hash = proof.compute_state_hash().unwrap();
+
+        let mut steps = proof.proof_steps().clone();
+        steps.pop_back();
+        let truncated = TrieMerkleProof::new(*proof.key(), proof.value().clone(), steps);
+        assert_ne!(truncated.compute_state_hash().unwrap(), state_hash);
+    }
+}
@@ -40,6 +40,8 @@ pub mod file_utils;
 mod gas;
 #[cfg(any(feature = "testing", feature = "gens", test))]
 pub mod gens;
+#[cfg(any(feature = "global-state-proofs", test))]
+pub mod global_state;
 mod json_pretty_printer;
 mod key;
 mod motes;
@@ -0,0 +1,157 @@
+mod snapshot;
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::Path,
+    process,
+    sync::Arc,
+};
+
+use clap::{crate_version, App, Arg, ArgMatches, SubCommand};
+use lmdb::DatabaseFlags;
+
+use casper_execution_engine::{
+    core::engine_state::{EngineConfig, EngineState},
+    storage::{
+        global_state::lmdb::LmdbGlobalState, transaction_source::lmdb::LmdbEnvironment,
+        trie_store::lmdb::LmdbTrieStore,
+    },
+};
+use casper_hashing::{ChunkWithProof, Digest};
+
+/// The default maximum size of the global state database, matching that of the node.
+const DEFAULT_MAX_GLOBAL_STATE_SIZE: &str = "805306368000";
+/// The maximum number of concurrent readers of the global state database.
+const MAX_READERS: u32 = 512;
+
+fn main() {
+    let data_dir_arg = Arg::with_name("data_dir")
+        .short("d")
+        .long("data-dir")
+        .value_name("PATH")
+        .help("Data storage directory containing the global state database file")
+        .takes_value(true)
+        .required(true);
+    let max_size_arg = Arg::with_name("max_size")
+        .long("max-global-state-size")
+        .value_name("BYTES")
+        .help("The maximum size of the global state database; a multiple of the OS page size")
+        .takes_value(true)
+        .default_value(DEFAULT_MAX_GLOBAL_STATE_SIZE);
+
+    let matches = App::new("Global State Snapshot")
+        .version(crate_version!())
+        .about("Exports and imports snapshots of the global state")
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Writes all tries under a state root hash to a snapshot file")
+                .arg(data_dir_arg.clone())
+                .arg(max_size_arg.clone())
+                .arg(
+                    Arg::with_name("hash")
+                        .short("s")
+                        .long("state-hash")
+                        .value_name("HEX_STRING")
+                        .help("The state root hash of the global state to be exported")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("The snapshot file to be written")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("Loads all tries from a snapshot file into the global state")
+                .arg(data_dir_arg)
+                .arg(max_size_arg)
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("The snapshot file to be read")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("export", Some(sub_matches)) => export(sub_matches),
+        ("import", Some(sub_matches)) => import(sub_matches),
+        (subcommand, _) => Err(format!("Unknown subcommand: \"{}\"", subcommand)),
+    };
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+fn export(matches: &ArgMatches<'_>) -> Result<(), String> {
+    let state_root_hash = Digest::from_hex(matches.value_of("hash").unwrap())
+        .map_err(|error| format!("invalid state root hash: {}", error))?;
+    let engine_state = open_engine_state(matches)?;
+    let output_path = matches.value_of("output").unwrap();
+    let output = File::create(output_path)
+        .map_err(|error| format!("couldn't create {}: {}", output_path, error))?;
+
+    let chunk_count = snapshot::export(
+        &engine_state,
+        state_root_hash,
+        ChunkWithProof::CHUNK_SIZE_BYTES,
+        BufWriter::new(output),
+    )
+    .map_err(|error| format!("failed to export the snapshot: {}", error))?;
+    println!(
+        "Exported state root {} in {} chunks to {}",
+        state_root_hash, chunk_count, output_path
+    );
+    Ok(())
+}
+
+fn import(matches: &ArgMatches<'_>) -> Result<(), String> {
+    let engine_state = open_engine_state(matches)?;
+    let input_path = matches.value_of("input").unwrap();
+    let input = File::open(input_path)
+        .map_err(|error| format!("couldn't open {}: {}", input_path, error))?;
+
+    let state_root_hash = snapshot::import(&engine_state, BufReader::new(input))
+        .map_err(|error| format!("failed to import the snapshot: {}", error))?;
+    println!(
+        "Imported state root {} from {}",
+        state_root_hash, input_path
+    );
+    Ok(())
+}
+
+/// Opens the global state in the data directory, creating it if it doesn't exist yet.
+fn open_engine_state(matches: &ArgMatches<'_>) -> Result<EngineState<LmdbGlobalState>, String> {
+    let data_dir = Path::new(matches.value_of("data_dir").unwrap());
+    let max_size = matches
+        .value_of("max_size")
+        .unwrap()
+        .parse()
+        .map_err(|error| format!("invalid maximum global state size: {}", error))?;
+
+    fs::create_dir_all(data_dir)
+        .map_err(|error| format!("couldn't create {}: {}", data_dir.display(), error))?;
+    let environment = Arc::new(
+        LmdbEnvironment::new(data_dir, max_size, MAX_READERS, true)
+            .map_err(|error| format!("couldn't open the global state database: {}", error))?,
+    );
+    let trie_store = Arc::new(
+        LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())
+            .map_err(|error| format!("couldn't open the trie store: {}", error))?,
+    );
+    let global_state = LmdbGlobalState::empty(environment, trie_store)
+        .map_err(|error| format!("couldn't open the global state: {}", error))?;
+    Ok(EngineState::new(global_state, EngineConfig::default()))
+}
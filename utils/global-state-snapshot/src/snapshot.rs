@@ -0,0 +1,395 @@
+//! The global state snapshot format.
+//!
+//! A snapshot file consists of:
+//! * the 8-byte magic number `CSPRGSSN`,
+//! * a header: the format version (`u32`), the state root hash and the number of chunks (`u64`),
+//! * the chunks, each a bytesrepr-serialized `ChunkWithProof` prefixed with its length (`u32`).
+//!
+//! The chunks' contents concatenated form the payload: every trie reachable from the state root,
+//! each as bytesrepr-serialized `Bytes`, with the children of every trie preceding it.  The root
+//! is thus the last trie in the payload, and every trie can be imported as soon as it is read.
+
+use std::{
+    cmp,
+    convert::TryInto,
+    io::{self, Read, Write},
+};
+
+use casper_execution_engine::{
+    core::engine_state::{self, EngineState},
+    shared::newtypes::CorrelationId,
+    storage::{global_state::lmdb::LmdbGlobalState, trie::Trie},
+};
+use casper_hashing::{ChunkWithProof, ChunkWithProofVerificationError, Digest};
+use casper_types::{
+    bytesrepr::{self, ToBytes},
+    Key, StoredValue,
+};
+use thiserror::Error;
+
+/// The magic number at the start of every snapshot file.
+const MAGIC: &[u8; 8] = b"CSPRGSSN";
+/// The version of the snapshot format written by this tool.
+const FORMAT_VERSION: u32 = 1;
+/// The length of the serialized header following the magic number.
+const HEADER_LENGTH: usize = 4 + Digest::LENGTH + 8;
+/// The length of the prefix of serialized `Bytes` and of chunk records.
+const LENGTH_PREFIX_LENGTH: usize = 4;
+
+/// An error encountered while exporting or importing a snapshot.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// Reading or writing the snapshot file failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// Serializing or deserializing a value failed.
+    #[error("serialization error: {0}")]
+    Bytesrepr(bytesrepr::Error),
+    /// The global state returned an error.
+    #[error("global state error: {0}")]
+    EngineState(#[from] engine_state::Error),
+    /// A trie reachable from the exported state root is missing from the global state.
+    #[error("trie {0} is missing from the global state")]
+    MissingTrie(Digest),
+    /// Constructing the proof of a chunk failed.
+    #[error("failed to construct chunk proof: {0}")]
+    ChunkProof(String),
+    /// The file doesn't start with the snapshot magic number.
+    #[error("not a global state snapshot file")]
+    InvalidMagic,
+    /// The file was written in an unsupported version of the format.
+    #[error("unsupported snapshot format version {0}")]
+    UnsupportedVersion(u32),
+    /// A chunk failed verification against its proof.
+    #[error("chunk {index} is invalid: {error}")]
+    InvalidChunk {
+        index: u64,
+        error: ChunkWithProofVerificationError,
+    },
+    /// A chunk doesn't belong at its position in the snapshot.
+    #[error("chunk {index} doesn't belong to the snapshot at this position")]
+    UnexpectedChunk { index: u64 },
+    /// The payload ended in the middle of a trie.
+    #[error("the snapshot payload is truncated")]
+    TruncatedPayload,
+    /// The last imported trie isn't the state root given in the header.
+    #[error("expected to import state root {expected}, but imported {actual:?}")]
+    RootMismatch {
+        expected: Digest,
+        actual: Option<Digest>,
+    },
+}
+
+impl From<bytesrepr::Error> for Error {
+    fn from(error: bytesrepr::Error) -> Self {
+        Error::Bytesrepr(error)
+    }
+}
+
+/// Writes a snapshot of the global state under `state_root_hash` to `writer`, splitting the
+/// payload into chunks of `chunk_size` bytes.
+///
+/// The tries are traversed twice: first to compute the hashes of all the chunks, which are needed
+/// for the chunks' proofs, and then to write the chunks.  Returns the number of chunks written.
+pub(crate) fn export<W: Write>(
+    engine_state: &EngineState<LmdbGlobalState>,
+    state_root_hash: Digest,
+    chunk_size: usize,
+    mut writer: W,
+) -> Result<u64, Error> {
+    let mut chunk_hashes = vec![];
+    for_each_chunk(engine_state, state_root_hash, chunk_size, |chunk| {
+        chunk_hashes.push(Digest::hash(chunk));
+        Ok(())
+    })?;
+    let chunk_count = chunk_hashes.len() as u64;
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(FORMAT_VERSION, state_root_hash, chunk_count).to_bytes()?)?;
+
+    let mut index = 0;
+    for_each_chunk(engine_state, state_root_hash, chunk_size, |chunk| {
+        let chunk_with_proof = ChunkWithProof::from_chunk_hashes(chunk, &chunk_hashes, index)
+            .map_err(|error| Error::ChunkProof(error.to_string()))?;
+        let record = chunk_with_proof.to_bytes()?;
+        writer.write_all(&(record.len() as u32).to_le_bytes())?;
+        writer.write_all(&record)?;
+        index += 1;
+        Ok(())
+    })?;
+    writer.flush()?;
+
+    Ok(chunk_count)
+}
+
+/// Imports a snapshot read from `reader` into the global state, returning the imported state root
+/// hash.
+///
+/// Every chunk is verified against its proof before any of its tries are stored, and the snapshot
+/// is only accepted if the last imported trie is the state root given in the header.
+pub(crate) fn import<R: Read>(
+    engine_state: &EngineState<LmdbGlobalState>,
+    mut reader: R,
+) -> Result<Digest, Error> {
+    let mut magic = [0; MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::InvalidMagic);
+    }
+    let mut header = [0; HEADER_LENGTH];
+    reader.read_exact(&mut header)?;
+    let (version, state_root_hash, chunk_count): (u32, Digest, u64) =
+        bytesrepr::deserialize(header.to_vec())?;
+    if version != FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    let correlation_id = CorrelationId::new();
+    let mut chunks_root_hash = None;
+    let mut payload = vec![];
+    let mut last_imported = None;
+    for index in 0..chunk_count {
+        let chunk_with_proof = read_chunk(&mut reader)?;
+        chunk_with_proof
+            .verify()
+            .map_err(|error| Error::InvalidChunk { index, error })?;
+        let proof = chunk_with_proof.proof();
+        if proof.index() != index
+            || proof.count() != chunk_count
+            || *chunks_root_hash.get_or_insert(proof.root_hash()) != proof.root_hash()
+        {
+            return Err(Error::UnexpectedChunk { index });
+        }
+        payload.extend_from_slice(chunk_with_proof.chunk());
+
+        let mut offset = 0;
+        while let Some(trie_bytes) = next_trie(&payload[offset..]) {
+            last_imported =
+                Some(engine_state.put_trie_if_all_children_present(correlation_id, trie_bytes)?);
+            offset += LENGTH_PREFIX_LENGTH + trie_bytes.len();
+        }
+        payload.drain(..offset);
+    }
+    if !payload.is_empty() {
+        return Err(Error::TruncatedPayload);
+    }
+    engine_state
+        .flush_environment()
+        .map_err(|error| Error::EngineState(error.into()))?;
+
+    match last_imported {
+        Some(digest) if digest == state_root_hash => Ok(digest),
+        actual => Err(Error::RootMismatch {
+            expected: state_root_hash,
+            actual,
+        }),
+    }
+}
+
+/// Calls `f` with each trie reachable from `root`, children before their parents.
+fn for_each_trie<F>(
+    engine_state: &EngineState<LmdbGlobalState>,
+    root: Digest,
+    mut f: F,
+) -> Result<(), Error>
+where
+    F: FnMut(&[u8]) -> Result<(), Error>,
+{
+    enum Visit {
+        /// The trie with the given hash is yet to be read.
+        Enter(Digest),
+        /// All children of the trie have been visited.
+        Exit(Vec<u8>),
+    }
+
+    let correlation_id = CorrelationId::new();
+    let mut stack = vec![Visit::Enter(root)];
+    while let Some(visit) = stack.pop() {
+        match visit {
+            Visit::Enter(trie_key) => {
+                let trie_bytes = engine_state
+                    .get_trie_full(correlation_id, trie_key)?
+                    .ok_or(Error::MissingTrie(trie_key))?
+                    .into_inner()
+                    .to_vec();
+                let trie: Trie<Key, StoredValue> = bytesrepr::deserialize(trie_bytes.clone())?;
+                let children: Vec<Digest> = trie.iter_children().collect();
+                stack.push(Visit::Exit(trie_bytes));
+                stack.extend(children.into_iter().rev().map(Visit::Enter));
+            }
+            Visit::Exit(trie_bytes) => f(&trie_bytes)?,
+        }
+    }
+    Ok(())
+}
+
+/// Calls `f` with each chunk of the payload of the snapshot of the global state under `root`.
+fn for_each_chunk<F>(
+    engine_state: &EngineState<LmdbGlobalState>,
+    root: Digest,
+    chunk_size: usize,
+    mut f: F,
+) -> Result<(), Error>
+where
+    F: FnMut(&[u8]) -> Result<(), Error>,
+{
+    let mut buffer = Vec::with_capacity(chunk_size);
+    for_each_trie(engine_state, root, |trie_bytes| {
+        let length_prefix = (trie_bytes.len() as u32).to_le_bytes();
+        for mut remaining in [&length_prefix[..], trie_bytes] {
+            while !remaining.is_empty() {
+                let length = cmp::min(chunk_size - buffer.len(), remaining.len());
+                buffer.extend_from_slice(&remaining[..length]);
+                remaining = &remaining[length..];
+                if buffer.len() == chunk_size {
+                    f(&buffer)?;
+                    buffer.clear();
+                }
+            }
+        }
+        Ok(())
+    })?;
+    if !buffer.is_empty() {
+        f(&buffer)?;
+    }
+    Ok(())
+}
+
+/// Reads a single length-prefixed chunk.
+fn read_chunk<R: Read>(reader: &mut R) -> Result<ChunkWithProof, Error> {
+    let mut length_prefix = [0; LENGTH_PREFIX_LENGTH];
+    reader.read_exact(&mut length_prefix)?;
+    let mut record = vec![0; u32::from_le_bytes(length_prefix) as usize];
+    reader.read_exact(&mut record)?;
+    Ok(bytesrepr::deserialize(record)?)
+}
+
+/// Returns the first trie in `payload`, if it has been read in full.
+fn next_trie(payload: &[u8]) -> Option<&[u8]> {
+    let length_prefix = payload.get(..LENGTH_PREFIX_LENGTH)?.try_into().ok()?;
+    let length = u32::from_le_bytes(length_prefix) as usize;
+    payload.get(LENGTH_PREFIX_LENGTH..LENGTH_PREFIX_LENGTH + length)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use lmdb::DatabaseFlags;
+    use tempfile::TempDir;
+
+    use casper_execution_engine::{
+        core::engine_state::EngineConfig,
+        storage::{
+            global_state::{StateProvider, StateReader},
+            transaction_source::lmdb::LmdbEnvironment,
+            trie_store::lmdb::LmdbTrieStore,
+        },
+    };
+    use casper_types::CLValue;
+
+    use super::*;
+
+    const TEST_MAP_SIZE: usize = 4096 * 2560;
+    const TEST_CHUNK_SIZE: usize = 100;
+
+    fn create_engine_state(dir: &TempDir) -> EngineState<LmdbGlobalState> {
+        let environment =
+            Arc::new(LmdbEnvironment::new(dir.path(), TEST_MAP_SIZE, 16, true).unwrap());
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let global_state = LmdbGlobalState::empty(environment, trie_store).unwrap();
+        EngineState::new(global_state, EngineConfig::default())
+    }
+
+    fn test_values() -> HashMap<Key, StoredValue> {
+        (0..100u8)
+            .map(|index| {
+                let value = CLValue::from_t(u64::from(index)).unwrap();
+                (Key::Hash([index; 32]), StoredValue::CLValue(value))
+            })
+            .collect()
+    }
+
+    fn create_snapshot() -> (Digest, Vec<u8>) {
+        let dir = tempfile::tempdir().unwrap();
+        let engine_state = create_engine_state(&dir);
+        let empty_root_hash = engine_state.get_state().empty_state_root_hash();
+        let state_root_hash = engine_state
+            .get_state()
+            .put_stored_values(CorrelationId::new(), empty_root_hash, test_values())
+            .unwrap();
+
+        let mut snapshot = vec![];
+        let chunk_count = export(
+            &engine_state,
+            state_root_hash,
+            TEST_CHUNK_SIZE,
+            &mut snapshot,
+        )
+        .unwrap();
+        assert!(chunk_count > 1);
+        (state_root_hash, snapshot)
+    }
+
+    #[test]
+    fn should_export_and_import_snapshot() {
+        let (state_root_hash, snapshot) = create_snapshot();
+
+        let dir = tempfile::tempdir().unwrap();
+        let engine_state = create_engine_state(&dir);
+        assert_eq!(
+            import(&engine_state, snapshot.as_slice()).unwrap(),
+            state_root_hash
+        );
+
+        let reader = engine_state
+            .get_state()
+            .checkout(state_root_hash)
+            .unwrap()
+            .unwrap();
+        for (key, value) in test_values() {
+            assert_eq!(
+                reader.read(CorrelationId::new(), &key).unwrap(),
+                Some(value)
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_corrupted_snapshot() {
+        let (_, mut snapshot) = create_snapshot();
+        let last_index = snapshot.len() - 1;
+        snapshot[last_index] ^= 1;
+
+        let dir = tempfile::tempdir().unwrap();
+        let engine_state = create_engine_state(&dir);
+        assert!(matches!(
+            import(&engine_state, snapshot.as_slice()),
+            Err(Error::InvalidChunk { .. })
+        ));
+    }
+
+    #[test]
+    fn should_reject_truncated_snapshot() {
+        let (_, snapshot) = create_snapshot();
+
+        let dir = tempfile::tempdir().unwrap();
+        let engine_state = create_engine_state(&dir);
+        assert!(matches!(
+            import(&engine_state, &snapshot[..snapshot.len() - 1]),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn should_fail_to_export_missing_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine_state = create_engine_state(&dir);
+        let missing_root = Digest::hash([1]);
+        assert!(matches!(
+            export(&engine_state, missing_root, TEST_CHUNK_SIZE, vec![]),
+            Err(Error::MissingTrie(digest)) if digest == missing_root
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+use casper_engine_test_support::LmdbWasmTestBuilder;
+use casper_execution_engine::{
+    shared::newtypes::CorrelationId,
+    storage::trie_store::{DiffResult, TrieDiff},
+};
+use casper_types::{bytesrepr::ToBytes, Key, StoredValue};
+use clap::ArgMatches;
+
+use crate::utils::hash_from_str;
+
+/// The number of entries read from the global state at a time.
+const PAGE_SIZE: usize = 1000;
+
+pub(crate) fn print_state_diff(matches: &ArgMatches<'_>) {
+    let data_dir = matches.value_of("data_dir").unwrap_or(".");
+    let old_state_hash = hash_from_str(matches.value_of("hash").unwrap());
+    let new_state_hash = hash_from_str(matches.value_of("new_hash").unwrap());
+
+    let builder = LmdbWasmTestBuilder::open_raw(data_dir, Default::default(), old_state_hash);
+    let engine_state = builder.get_engine_state();
+
+    let mut start_after: Option<Key> = None;
+    loop {
+        let diffs = match engine_state
+            .get_state_diff(
+                CorrelationId::new(),
+                old_state_hash,
+                new_state_hash,
+                start_after.as_ref(),
+                PAGE_SIZE,
+            )
+            .expect("couldn't compute the global state diff")
+        {
+            DiffResult::Found(diffs) => diffs,
+            DiffResult::TrieNotFound(digest) => {
+                panic!("trie {} missing from the global state", digest)
+            }
+        };
+
+        for diff in &diffs {
+            print_diff_entry(diff);
+        }
+
+        if diffs.len() < PAGE_SIZE {
+            break;
+        }
+        start_after = diffs.last().map(|diff| *diff.key());
+    }
+}
+
+/// Prints a single difference between two global states in a TOML format.
+fn print_diff_entry(diff: &TrieDiff<Key, StoredValue>) {
+    match diff {
+        TrieDiff::Added { key, value } => {
+            println!("[[added]]");
+            println!("key = \"{}\"", key.to_formatted_string());
+            println!("value = \"{}\"", encode_value(value));
+        }
+        TrieDiff::Removed { key, value } => {
+            println!("[[removed]]");
+            println!("key = \"{}\"", key.to_formatted_string());
+            println!("value = \"{}\"", encode_value(value));
+        }
+        TrieDiff::Modified {
+            key,
+            old_value,
+            new_value,
+        } => {
+            println!("[[modified]]");
+            println!("key = \"{}\"", key.to_formatted_string());
+            println!("old_value = \"{}\"", encode_value(old_value));
+            println!("new_value = \"{}\"", encode_value(new_value));
+        }
+    }
+    println!();
+}
+
+fn encode_value(value: &StoredValue) -> String {
+    base64::encode(value.to_bytes().unwrap())
+}
@@ -0,0 +1,346 @@
+//! Minimal block types needed to verify finality signatures.
+//!
+//! These mirror the node's types of the same names. Their serde and `bytesrepr` encodings must
+//! stay identical to the node's, since the block hash is computed from the latter.
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use casper_hashing::Digest;
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    crypto, EraId, ProtocolVersion, PublicKey, Signature, Timestamp, U512,
+};
+
+/// A cryptographic hash identifying a block.
+#[derive(
+    Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug,
+)]
+#[serde(deny_unknown_fields)]
+pub struct BlockHash(Digest);
+
+impl BlockHash {
+    /// Constructs a new `BlockHash`.
+    pub fn new(hash: Digest) -> Self {
+        BlockHash(hash)
+    }
+
+    /// Returns the wrapped inner hash.
+    pub fn inner(&self) -> &Digest {
+        &self.0
+    }
+}
+
+impl fmt::Display for BlockHash {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "block hash {}", self.0)
+    }
+}
+
+impl ToBytes for BlockHash {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for BlockHash {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (hash, remainder) = Digest::from_bytes(bytes)?;
+        Ok((BlockHash(hash), remainder))
+    }
+}
+
+/// Equivocation, reward and validator inactivity information reported by a switch block.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct EraReport {
+    /// The set of equivocators.
+    pub equivocators: Vec<PublicKey>,
+    /// Rewards for finalization of earlier blocks.
+    pub rewards: BTreeMap<PublicKey, u64>,
+    /// Validators that haven't produced any unit during the era.
+    pub inactive_validators: Vec<PublicKey>,
+}
+
+impl ToBytes for EraReport {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.equivocators.to_bytes()?);
+        buffer.extend(self.rewards.to_bytes()?);
+        buffer.extend(self.inactive_validators.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.equivocators.serialized_length()
+            + self.rewards.serialized_length()
+            + self.inactive_validators.serialized_length()
+    }
+}
+
+impl FromBytes for EraReport {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (equivocators, remainder) = Vec::<PublicKey>::from_bytes(bytes)?;
+        let (rewards, remainder) = BTreeMap::<PublicKey, u64>::from_bytes(remainder)?;
+        let (inactive_validators, remainder) = Vec::<PublicKey>::from_bytes(remainder)?;
+        let era_report = EraReport {
+            equivocators,
+            rewards,
+            inactive_validators,
+        };
+        Ok((era_report, remainder))
+    }
+}
+
+/// Information related to the end of an era, and validator weights for the following era.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub struct EraEnd {
+    era_report: EraReport,
+    next_era_validator_weights: BTreeMap<PublicKey, U512>,
+}
+
+impl EraEnd {
+    /// Returns the equivocation, reward and inactivity information of the era.
+    pub fn era_report(&self) -> &EraReport {
+        &self.era_report
+    }
+
+    /// Returns the validators for the upcoming era and their respective weights.
+    pub fn next_era_validator_weights(&self) -> &BTreeMap<PublicKey, U512> {
+        &self.next_era_validator_weights
+    }
+}
+
+impl ToBytes for EraEnd {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.era_report.to_bytes()?);
+        buffer.extend(self.next_era_validator_weights.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.era_report.serialized_length() + self.next_era_validator_weights.serialized_length()
+    }
+}
+
+impl FromBytes for EraEnd {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (era_report, remainder) = EraReport::from_bytes(bytes)?;
+        let (next_era_validator_weights, remainder) =
+            BTreeMap::<PublicKey, U512>::from_bytes(remainder)?;
+        let era_end = EraEnd {
+            era_report,
+            next_era_validator_weights,
+        };
+        Ok((era_end, remainder))
+    }
+}
+
+/// The header portion of a block.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct BlockHeader {
+    parent_hash: BlockHash,
+    state_root_hash: Digest,
+    body_hash: Digest,
+    random_bit: bool,
+    accumulated_seed: Digest,
+    era_end: Option<EraEnd>,
+    timestamp: Timestamp,
+    era_id: EraId,
+    height: u64,
+    protocol_version: ProtocolVersion,
+}
+
+impl BlockHeader {
+    /// Returns the hash of this block header.
+    pub fn block_hash(&self) -> BlockHash {
+        let serialized_header = self
+            .to_bytes()
+            .unwrap_or_else(|error| panic!("should serialize block header: {}", error));
+        BlockHash::new(Digest::hash(serialized_header))
+    }
+
+    /// The parent block's hash.
+    pub fn parent_hash(&self) -> &BlockHash {
+        &self.parent_hash
+    }
+
+    /// The root hash of the resulting global state.
+    pub fn state_root_hash(&self) -> &Digest {
+        &self.state_root_hash
+    }
+
+    /// The hash of the block's body.
+    pub fn body_hash(&self) -> &Digest {
+        &self.body_hash
+    }
+
+    /// Returns the era end, if this is a switch block.
+    pub fn era_end(&self) -> Option<&EraEnd> {
+        self.era_end.as_ref()
+    }
+
+    /// The block timestamp.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// The block era id.
+    pub fn era_id(&self) -> EraId {
+        self.era_id
+    }
+
+    /// The block height.
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// The protocol version.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// Returns `true` if this block is the last one in the current era.
+    pub fn is_switch_block(&self) -> bool {
+        self.era_end.is_some()
+    }
+
+    /// Returns the validators for the upcoming era and their respective weights, if this is a
+    /// switch block.
+    pub fn next_era_validator_weights(&self) -> Option<&BTreeMap<PublicKey, U512>> {
+        self.era_end
+            .as_ref()
+            .map(EraEnd::next_era_validator_weights)
+    }
+
+    /// Returns the era ID in which the next block would be created.
+    pub fn next_block_era_id(&self) -> EraId {
+        if self.is_switch_block() {
+            self.era_id.successor()
+        } else {
+            self.era_id
+        }
+    }
+}
+
+impl ToBytes for BlockHeader {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.parent_hash.to_bytes()?);
+        buffer.extend(self.state_root_hash.to_bytes()?);
+        buffer.extend(self.body_hash.to_bytes()?);
+        buffer.extend(self.random_bit.to_bytes()?);
+        buffer.extend(self.accumulated_seed.to_bytes()?);
+        buffer.extend(self.era_end.to_bytes()?);
+        buffer.extend(self.timestamp.to_bytes()?);
+        buffer.extend(self.era_id.to_bytes()?);
+        buffer.extend(self.height.to_bytes()?);
+        buffer.extend(self.protocol_version.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.parent_hash.serialized_length()
+            + self.state_root_hash.serialized_length()
+            + self.body_hash.serialized_length()
+            + self.random_bit.serialized_length()
+            + self.accumulated_seed.serialized_length()
+            + self.era_end.serialized_length()
+            + self.timestamp.serialized_length()
+            + self.era_id.serialized_length()
+            + self.height.serialized_length()
+            + self.protocol_version.serialized_length()
+    }
+}
+
+impl FromBytes for BlockHeader {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (parent_hash, remainder) = BlockHash::from_bytes(bytes)?;
+        let (state_root_hash, remainder) = Digest::from_bytes(remainder)?;
+        let (body_hash, remainder) = Digest::from_bytes(remainder)?;
+        let (random_bit, remainder) = bool::from_bytes(remainder)?;
+        let (accumulated_seed, remainder) = Digest::from_bytes(remainder)?;
+        let (era_end, remainder) = Option::<EraEnd>::from_bytes(remainder)?;
+        let (timestamp, remainder) = Timestamp::from_bytes(remainder)?;
+        let (era_id, remainder) = EraId::from_bytes(remainder)?;
+        let (height, remainder) = u64::from_bytes(remainder)?;
+        let (protocol_version, remainder) = ProtocolVersion::from_bytes(remainder)?;
+        let block_header = BlockHeader {
+            parent_hash,
+            state_root_hash,
+            body_hash,
+            random_bit,
+            accumulated_seed,
+            era_end,
+            timestamp,
+            era_id,
+            height,
+            protocol_version,
+        };
+        Ok((block_header, remainder))
+    }
+}
+
+/// Finality signatures for a block, by validator.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BlockSignatures {
+    block_hash: BlockHash,
+    era_id: EraId,
+    proofs: BTreeMap<PublicKey, Signature>,
+}
+
+impl BlockSignatures {
+    /// Constructs a new `BlockSignatures` without any signatures.
+    pub fn new(block_hash: BlockHash, era_id: EraId) -> Self {
+        BlockSignatures {
+            block_hash,
+            era_id,
+            proofs: BTreeMap::new(),
+        }
+    }
+
+    /// Inserts a signature, returning the previous signature by that validator, if any.
+    pub fn insert_proof(
+        &mut self,
+        public_key: PublicKey,
+        signature: Signature,
+    ) -> Option<Signature> {
+        self.proofs.insert(public_key, signature)
+    }
+
+    /// The hash of the signed block.
+    pub fn block_hash(&self) -> &BlockHash {
+        &self.block_hash
+    }
+
+    /// The era of the signed block.
+    pub fn era_id(&self) -> EraId {
+        self.era_id
+    }
+
+    /// The signatures, by validator.
+    pub fn proofs(&self) -> &BTreeMap<PublicKey, Signature> {
+        &self.proofs
+    }
+
+    /// Verifies every signature against the block hash and era.
+    pub fn verify(&self) -> Result<(), crypto::Error> {
+        let bytes = bytes_to_sign(&self.block_hash, self.era_id);
+        for (public_key, signature) in self.proofs.iter() {
+            crypto::verify(&bytes, signature, public_key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the bytes a validator signs to finalize the given block.
+pub(crate) fn bytes_to_sign(block_hash: &BlockHash, era_id: EraId) -> Vec<u8> {
+    let mut bytes = block_hash.inner().into_vec();
+    bytes.extend_from_slice(&era_id.to_le_bytes());
+    bytes
+}
@@ -0,0 +1,92 @@
+//! Checks whether finality signatures carry enough validator weight.
+//!
+//! This duplicates the check the node performs on the signatures of a `SyncLeap`.
+
+use std::collections::BTreeMap;
+
+use num_rational::Ratio;
+use thiserror::Error;
+
+use casper_types::{PublicKey, U512};
+
+use crate::BlockSignatures;
+
+/// Error returned if block signatures don't prove finality of the block.
+#[derive(Error, Debug)]
+pub enum BlockSignatureError {
+    /// Some signatures are by public keys which are not validators in the block's era.
+    #[error(
+        "block signatures for {} contain bogus validators: {bogus_validators:?}",
+        block_signatures.block_hash()
+    )]
+    BogusValidators {
+        /// The signatures.
+        block_signatures: Box<BlockSignatures>,
+        /// The signers which are not validators.
+        bogus_validators: Vec<PublicKey>,
+    },
+    /// The signatures' total weight does not exceed the quorum.
+    #[error(
+        "insufficient weight for finality of {}: signature weight {signature_weight}, \
+         total validator weight {total_validator_weight}, \
+         fault tolerance fraction {fault_tolerance_fraction}",
+        block_signatures.block_hash()
+    )]
+    InsufficientWeightForFinality {
+        /// The signatures.
+        block_signatures: Box<BlockSignatures>,
+        /// The total weight of the signers.
+        signature_weight: Box<U512>,
+        /// The total weight of all validators in the block's era.
+        total_validator_weight: Box<U512>,
+        /// The fault tolerance fraction the quorum is derived from.
+        fault_tolerance_fraction: Ratio<u64>,
+    },
+}
+
+/// Computes the quorum for the fraction of weight of signatures that will be considered
+/// sufficient. This is the lowest weight so that any two sets of validators with that weight have
+/// at least one honest validator in common.
+fn quorum_fraction(fault_tolerance_fraction: Ratio<u64>) -> Ratio<u64> {
+    (fault_tolerance_fraction + 1) / 2
+}
+
+/// Returns `Ok(())` if the block signatures' total weight exceeds the quorum. Returns an error if
+/// it doesn't, or if one of the signatures does not belong to a validator.
+///
+/// This does _not_ cryptographically verify the signatures.
+pub(crate) fn check_sufficient_block_signatures(
+    trusted_validator_weights: &BTreeMap<PublicKey, U512>,
+    fault_tolerance_fraction: Ratio<u64>,
+    block_signatures: &BlockSignatures,
+) -> Result<(), BlockSignatureError> {
+    let mut signature_weight = U512::zero();
+    let mut bogus_validators = vec![];
+    for public_key in block_signatures.proofs().keys() {
+        match trusted_validator_weights.get(public_key) {
+            Some(validator_weight) => signature_weight += *validator_weight,
+            None => bogus_validators.push(public_key.clone()),
+        }
+    }
+    if !bogus_validators.is_empty() {
+        return Err(BlockSignatureError::BogusValidators {
+            block_signatures: Box::new(block_signatures.clone()),
+            bogus_validators,
+        });
+    }
+
+    let total_validator_weight: U512 = trusted_validator_weights.values().copied().sum();
+    let quorum_fraction = quorum_fraction(fault_tolerance_fraction);
+    // Verify: signature_weight / total_weight > quorum_fraction
+    if signature_weight * U512::from(*quorum_fraction.denom())
+        <= total_validator_weight * U512::from(*quorum_fraction.numer())
+    {
+        return Err(BlockSignatureError::InsufficientWeightForFinality {
+            block_signatures: Box::new(block_signatures.clone()),
+            signature_weight: Box::new(signature_weight),
+            total_validator_weight: Box::new(total_validator_weight),
+            fault_tolerance_fraction,
+        });
+    }
+    Ok(())
+}
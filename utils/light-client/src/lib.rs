@@ -0,0 +1,579 @@
+//! A library verifying chains of signed switch block headers, for use by light clients.
+//!
+//! Starting from the hash of a block which is trusted to be on the correct chain, every following
+//! switch block is checked to be finalized by the validators of its era, whose weights are in turn
+//! taken from the previous switch block. This is the verification performed by a joining node on a
+//! `SyncLeap`, without requiring any networking or storage.
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/casper-network/casper-node/blob/dev/images/Casper_Logo_Favicon_48.png",
+    html_logo_url = "https://raw.githubusercontent.com/casper-network/casper-node/blob/dev/images/Casper_Logo_Favicon.png",
+    test(attr(forbid(warnings)))
+)]
+#![warn(missing_docs)]
+
+use std::collections::BTreeMap;
+
+use num_rational::Ratio;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_types::{crypto, EraId, PublicKey, U512};
+
+mod block;
+mod block_signatures;
+
+pub use block::{BlockHash, BlockHeader, BlockSignatures, EraEnd, EraReport};
+pub use block_signatures::BlockSignatureError;
+
+/// Error returned if a header chain could not be verified.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// No trusted headers were provided.
+    #[error("no trusted block header provided")]
+    MissingTrustedBlockHeader,
+    /// The first trusted header doesn't have the trusted block hash.
+    #[error("trusted block header has hash {actual}, expected {expected}")]
+    UntrustedBlockHeader {
+        /// The trusted block hash.
+        expected: BlockHash,
+        /// The hash of the provided header.
+        actual: BlockHash,
+    },
+    /// A trusted header is not the parent of the previous one.
+    #[error("trusted ancestor {block_hash} is not the parent of the previous header")]
+    TrustedAncestorsNotSorted {
+        /// The hash of the misplaced header.
+        block_hash: BlockHash,
+    },
+    /// The last trusted header is not a switch block.
+    #[error("last trusted ancestor is not a switch block")]
+    MissingAncestorSwitchBlock,
+    /// A trusted header other than the first and the last one is a switch block.
+    #[error("trusted ancestor {block_hash} is an unexpected switch block")]
+    UnexpectedAncestorSwitchBlock {
+        /// The hash of the unexpected switch block.
+        block_hash: BlockHash,
+    },
+    /// The signatures of a signed header are for a different block.
+    #[error("signatures for block {block_hash} are for a different block or era")]
+    SignaturesMismatch {
+        /// The hash of the signed header.
+        block_hash: BlockHash,
+    },
+    /// The signed headers are not in increasing height order.
+    #[error("signed block {block_hash} is not higher than the previous header")]
+    HeadersNotAscending {
+        /// The hash of the misplaced header.
+        block_hash: BlockHash,
+    },
+    /// A signed header is not a switch block.
+    #[error("signed block {block_hash} is not a switch block")]
+    NotASwitchBlock {
+        /// The hash of the header.
+        block_hash: BlockHash,
+    },
+    /// The validators of a signed header's era are not known, i.e. the switch block of the
+    /// previous era is missing.
+    #[error("no trusted validator set for {era_id}")]
+    MissingValidatorSet {
+        /// The era of the signed header.
+        era_id: EraId,
+    },
+    /// More than one switch block was provided for an era.
+    #[error("more than one switch block for {era_id}")]
+    DuplicateSwitchBlock {
+        /// The era of the switch blocks.
+        era_id: EraId,
+    },
+    /// A signed header is not signed by sufficient weight of its era's validators.
+    #[error(transparent)]
+    InsufficientSignatures(BlockSignatureError),
+    /// The signatures of a signed header are not cryptographically valid.
+    #[error("block signatures are not cryptographically valid: {0}")]
+    Crypto(crypto::Error),
+}
+
+/// A block header together with finality signatures for it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBlockHeader {
+    /// The block header.
+    pub block_header: BlockHeader,
+    /// The finality signatures for the block.
+    pub block_signatures: BlockSignatures,
+}
+
+impl SignedBlockHeader {
+    /// Constructs a new `SignedBlockHeader`.
+    pub fn new(block_header: BlockHeader, block_signatures: BlockSignatures) -> Self {
+        SignedBlockHeader {
+            block_header,
+            block_signatures,
+        }
+    }
+}
+
+/// The validator sets proven by a verified header chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrustedValidatorSets {
+    validator_weights: BTreeMap<EraId, BTreeMap<PublicKey, U512>>,
+    highest_block_header: BlockHeader,
+}
+
+impl TrustedValidatorSets {
+    /// Returns the validator weights of the given era, if it is covered by the header chain.
+    pub fn validator_weights(&self, era_id: EraId) -> Option<&BTreeMap<PublicKey, U512>> {
+        self.validator_weights.get(&era_id)
+    }
+
+    /// Returns the highest era whose validator set is known.
+    pub fn highest_era(&self) -> EraId {
+        // There is always at least one era, from the last trusted header.
+        self.validator_weights
+            .keys()
+            .next_back()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the validator weights of all eras covered by the header chain, in increasing era
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (&EraId, &BTreeMap<PublicKey, U512>)> {
+        self.validator_weights.iter()
+    }
+
+    /// Returns the highest verified block header.
+    pub fn highest_block_header(&self) -> &BlockHeader {
+        &self.highest_block_header
+    }
+}
+
+/// Verifies chains of signed switch block headers starting from a trusted block hash.
+#[derive(Clone, Debug)]
+pub struct HeaderChainVerifier {
+    finality_threshold_fraction: Ratio<u64>,
+    validator_overrides: BTreeMap<EraId, BTreeMap<PublicKey, U512>>,
+}
+
+impl HeaderChainVerifier {
+    /// Constructs a new verifier using the chainspec's `core.finality_threshold_fraction`.
+    pub fn new(finality_threshold_fraction: Ratio<u64>) -> Self {
+        HeaderChainVerifier {
+            finality_threshold_fraction,
+            validator_overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Uses the given validator weights for `era_id` instead of those reported by the preceding
+    /// switch block.
+    ///
+    /// This is required for eras starting with an upgrade that replaced the validator set via the
+    /// chainspec's `protocol.global_state_update`.
+    pub fn with_validator_override(
+        mut self,
+        era_id: EraId,
+        validator_weights: BTreeMap<PublicKey, U512>,
+    ) -> Self {
+        let _ = self.validator_overrides.insert(era_id, validator_weights);
+        self
+    }
+
+    /// Verifies the header chain, returning the validator sets it proves.
+    ///
+    /// `trusted_headers` are the header of the block with `trusted_block_hash`, followed by its
+    /// ancestors back to the most recent switch block; if the trusted block is a switch block, its
+    /// header alone suffices. `signed_switch_block_headers` are the following switch blocks in
+    /// increasing height order, each signed by the validators of its era.
+    pub fn verify(
+        &self,
+        trusted_block_hash: BlockHash,
+        trusted_headers: &[BlockHeader],
+        signed_switch_block_headers: &[SignedBlockHeader],
+    ) -> Result<TrustedValidatorSets, Error> {
+        let trusted_block_header = trusted_headers
+            .first()
+            .ok_or(Error::MissingTrustedBlockHeader)?;
+        if trusted_block_header.block_hash() != trusted_block_hash {
+            return Err(Error::UntrustedBlockHeader {
+                expected: trusted_block_hash,
+                actual: trusted_block_header.block_hash(),
+            });
+        }
+
+        let mut validator_weights = BTreeMap::new();
+        let last_index = trusted_headers.len() - 1;
+        for (index, header) in trusted_headers.iter().enumerate() {
+            if index > 0 {
+                if trusted_headers[index - 1].parent_hash() != &header.block_hash() {
+                    return Err(Error::TrustedAncestorsNotSorted {
+                        block_hash: header.block_hash(),
+                    });
+                }
+                if index < last_index && header.is_switch_block() {
+                    return Err(Error::UnexpectedAncestorSwitchBlock {
+                        block_hash: header.block_hash(),
+                    });
+                }
+            }
+            if let Some(weights) = header.next_era_validator_weights() {
+                let era_id = header.next_block_era_id();
+                let _ = validator_weights.insert(era_id, self.weights_for(era_id, weights));
+            }
+        }
+        if !trusted_headers[last_index].is_switch_block() {
+            return Err(Error::MissingAncestorSwitchBlock);
+        }
+
+        let mut highest_block_header = trusted_block_header;
+        for signed_header in signed_switch_block_headers {
+            let header = &signed_header.block_header;
+            let signatures = &signed_header.block_signatures;
+            let block_hash = header.block_hash();
+            if *signatures.block_hash() != block_hash || signatures.era_id() != header.era_id() {
+                return Err(Error::SignaturesMismatch { block_hash });
+            }
+            if header.height() <= highest_block_header.height() {
+                return Err(Error::HeadersNotAscending { block_hash });
+            }
+            let next_weights = header
+                .next_era_validator_weights()
+                .ok_or(Error::NotASwitchBlock { block_hash })?;
+
+            let weights =
+                validator_weights
+                    .get(&header.era_id())
+                    .ok_or(Error::MissingValidatorSet {
+                        era_id: header.era_id(),
+                    })?;
+            block_signatures::check_sufficient_block_signatures(
+                weights,
+                self.finality_threshold_fraction,
+                signatures,
+            )
+            .map_err(Error::InsufficientSignatures)?;
+
+            let next_era_id = header.next_block_era_id();
+            if validator_weights.contains_key(&next_era_id) {
+                return Err(Error::DuplicateSwitchBlock {
+                    era_id: header.era_id(),
+                });
+            }
+            let _ =
+                validator_weights.insert(next_era_id, self.weights_for(next_era_id, next_weights));
+            highest_block_header = header;
+        }
+
+        // defer cryptographic verification until last to avoid unnecessary computation
+        for signed_header in signed_switch_block_headers {
+            signed_header
+                .block_signatures
+                .verify()
+                .map_err(Error::Crypto)?;
+        }
+
+        Ok(TrustedValidatorSets {
+            validator_weights,
+            highest_block_header: highest_block_header.clone(),
+        })
+    }
+
+    /// Returns the validator weights to be used for `era_id`, given the weights reported by the
+    /// preceding switch block.
+    fn weights_for(
+        &self,
+        era_id: EraId,
+        reported_weights: &BTreeMap<PublicKey, U512>,
+    ) -> BTreeMap<PublicKey, U512> {
+        self.validator_overrides
+            .get(&era_id)
+            .unwrap_or(reported_weights)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use casper_hashing::Digest;
+    use casper_node::types::{self as node_types, Block};
+    use casper_types::{
+        bytesrepr::{self, ToBytes},
+        crypto,
+        testing::TestRng,
+        ProtocolVersion, SecretKey,
+    };
+
+    use super::*;
+
+    struct Validators {
+        keys: Vec<(SecretKey, PublicKey)>,
+    }
+
+    impl Validators {
+        fn new(count: usize) -> Self {
+            let keys = iter::repeat_with(crypto::generate_ed25519_keypair)
+                .take(count)
+                .collect();
+            Validators { keys }
+        }
+
+        fn weights(&self) -> BTreeMap<PublicKey, U512> {
+            self.keys
+                .iter()
+                .map(|(_, public_key)| (public_key.clone(), U512::from(10)))
+                .collect()
+        }
+
+        fn sign(&self, header: &BlockHeader, signer_count: usize) -> SignedBlockHeader {
+            let mut signatures = BlockSignatures::new(header.block_hash(), header.era_id());
+            for (secret_key, public_key) in self.keys.iter().take(signer_count) {
+                let bytes = block::bytes_to_sign(&header.block_hash(), header.era_id());
+                let signature = crypto::sign(bytes, secret_key, public_key);
+                let _ = signatures.insert_proof(public_key.clone(), signature);
+            }
+            SignedBlockHeader::new(header.clone(), signatures)
+        }
+    }
+
+    fn block_header(
+        rng: &mut TestRng,
+        era_id: u64,
+        height: u64,
+        parent_hash: Option<BlockHash>,
+        next_era_validators: Option<&Validators>,
+    ) -> BlockHeader {
+        let node_header = node_block_header(
+            rng,
+            era_id,
+            height,
+            parent_hash.map(|hash| node_types::BlockHash::new(*hash.inner())),
+            next_era_validators,
+        );
+        bytesrepr::deserialize_from_slice(node_header.to_bytes().expect("should serialize"))
+            .expect("should deserialize")
+    }
+
+    fn node_block_header(
+        rng: &mut TestRng,
+        era_id: u64,
+        height: u64,
+        parent_hash: Option<node_types::BlockHash>,
+        next_era_validators: Option<&Validators>,
+    ) -> node_types::BlockHeader {
+        Block::random_with_specifics_and_parent_and_validator_weights(
+            rng,
+            EraId::new(era_id),
+            height,
+            ProtocolVersion::V1_0_0,
+            next_era_validators.is_some(),
+            iter::empty(),
+            parent_hash,
+            next_era_validators
+                .map(Validators::weights)
+                .unwrap_or_default(),
+        )
+        .take_header()
+    }
+
+    /// A chain with a trusted non-switch block in era 2 and the switch blocks of eras 1 to 3.
+    struct TestChain {
+        validators: Vec<Validators>,
+        trusted_headers: Vec<BlockHeader>,
+        switch_blocks: Vec<BlockHeader>,
+    }
+
+    impl TestChain {
+        fn new(rng: &mut TestRng) -> Self {
+            let validators: Vec<_> = (0..5).map(|_| Validators::new(4)).collect();
+            let switch_block_1 = block_header(rng, 1, 10, None, Some(&validators[2]));
+            let trusted_block = block_header(rng, 2, 11, Some(switch_block_1.block_hash()), None);
+            let switch_block_2 = block_header(
+                rng,
+                2,
+                12,
+                Some(trusted_block.block_hash()),
+                Some(&validators[3]),
+            );
+            let switch_block_3 = block_header(
+                rng,
+                3,
+                13,
+                Some(switch_block_2.block_hash()),
+                Some(&validators[4]),
+            );
+            TestChain {
+                validators,
+                trusted_headers: vec![trusted_block, switch_block_1],
+                switch_blocks: vec![switch_block_2, switch_block_3],
+            }
+        }
+
+        fn trusted_block_hash(&self) -> BlockHash {
+            self.trusted_headers[0].block_hash()
+        }
+
+        fn signed_switch_blocks(&self) -> Vec<SignedBlockHeader> {
+            self.switch_blocks
+                .iter()
+                .map(|header| self.validators[header.era_id().value() as usize].sign(header, 3))
+                .collect()
+        }
+    }
+
+    fn verifier() -> HeaderChainVerifier {
+        HeaderChainVerifier::new(Ratio::new(1, 3))
+    }
+
+    #[test]
+    fn should_match_node_block_hash_and_encoding() {
+        let mut rng = TestRng::new();
+        let validators = Validators::new(3);
+        for next_era_validators in [None, Some(&validators)] {
+            let node_header = node_block_header(&mut rng, 1, 10, None, next_era_validators);
+            let header: BlockHeader =
+                bytesrepr::deserialize_from_slice(node_header.to_bytes().unwrap()).unwrap();
+            assert_eq!(
+                header.block_hash().inner(),
+                node_header.block_hash().inner()
+            );
+            assert_eq!(header.to_bytes().unwrap(), node_header.to_bytes().unwrap());
+
+            let json = serde_json::to_string(&node_header).unwrap();
+            let header_from_json: BlockHeader = serde_json::from_str(&json).unwrap();
+            assert_eq!(header_from_json, header);
+        }
+    }
+
+    #[test]
+    fn should_verify_header_chain() {
+        let mut rng = TestRng::new();
+        let chain = TestChain::new(&mut rng);
+
+        let validator_sets = verifier()
+            .verify(
+                chain.trusted_block_hash(),
+                &chain.trusted_headers,
+                &chain.signed_switch_blocks(),
+            )
+            .expect("should verify");
+
+        assert_eq!(validator_sets.validator_weights(EraId::new(1)), None);
+        for era_id in 2..5 {
+            assert_eq!(
+                validator_sets.validator_weights(EraId::new(era_id)),
+                Some(&chain.validators[era_id as usize].weights())
+            );
+        }
+        assert_eq!(validator_sets.highest_era(), EraId::new(4));
+        assert_eq!(
+            validator_sets.highest_block_header(),
+            &chain.switch_blocks[1]
+        );
+    }
+
+    #[test]
+    fn should_reject_untrusted_block_header() {
+        let mut rng = TestRng::new();
+        let chain = TestChain::new(&mut rng);
+
+        let result = verifier().verify(
+            BlockHash::new(Digest::hash(b"untrusted block")),
+            &chain.trusted_headers,
+            &chain.signed_switch_blocks(),
+        );
+        assert!(matches!(result, Err(Error::UntrustedBlockHeader { .. })));
+    }
+
+    #[test]
+    fn should_reject_insufficiently_signed_switch_block() {
+        let mut rng = TestRng::new();
+        let chain = TestChain::new(&mut rng);
+        let mut signed_switch_blocks = chain.signed_switch_blocks();
+        signed_switch_blocks[1] = chain.validators[3].sign(&chain.switch_blocks[1], 2);
+
+        let result = verifier().verify(
+            chain.trusted_block_hash(),
+            &chain.trusted_headers,
+            &signed_switch_blocks,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::InsufficientSignatures(
+                BlockSignatureError::InsufficientWeightForFinality { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_reject_switch_block_signed_by_other_validators() {
+        let mut rng = TestRng::new();
+        let chain = TestChain::new(&mut rng);
+        let mut signed_switch_blocks = chain.signed_switch_blocks();
+        signed_switch_blocks[1] = chain.validators[4].sign(&chain.switch_blocks[1], 4);
+
+        let result = verifier().verify(
+            chain.trusted_block_hash(),
+            &chain.trusted_headers,
+            &signed_switch_blocks,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::InsufficientSignatures(
+                BlockSignatureError::BogusValidators { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_reject_unconnected_switch_block() {
+        let mut rng = TestRng::new();
+        let chain = TestChain::new(&mut rng);
+        let mut signed_switch_blocks = chain.signed_switch_blocks();
+        signed_switch_blocks.remove(0);
+
+        let result = verifier().verify(
+            chain.trusted_block_hash(),
+            &chain.trusted_headers,
+            &signed_switch_blocks,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::MissingValidatorSet { era_id }) if era_id == EraId::new(3)
+        ));
+    }
+
+    #[test]
+    fn should_require_ancestors_back_to_switch_block() {
+        let mut rng = TestRng::new();
+        let chain = TestChain::new(&mut rng);
+
+        let result = verifier().verify(
+            chain.trusted_block_hash(),
+            &chain.trusted_headers[..1],
+            &chain.signed_switch_blocks(),
+        );
+        assert!(matches!(result, Err(Error::MissingAncestorSwitchBlock)));
+    }
+
+    #[test]
+    fn should_use_validator_override() {
+        let mut rng = TestRng::new();
+        let chain = TestChain::new(&mut rng);
+        let upgrade_validators = Validators::new(2);
+        let mut signed_switch_blocks = chain.signed_switch_blocks();
+        signed_switch_blocks[1] = upgrade_validators.sign(&chain.switch_blocks[1], 2);
+
+        let validator_sets = verifier()
+            .with_validator_override(EraId::new(3), upgrade_validators.weights())
+            .verify(
+                chain.trusted_block_hash(),
+                &chain.trusted_headers,
+                &signed_switch_blocks,
+            )
+            .expect("should verify");
+        assert_eq!(
+            validator_sets.validator_weights(EraId::new(3)),
+            Some(&upgrade_validators.weights())
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::{path::Path, process, sync::Arc};
+
+use clap::{crate_version, App, Arg, ArgMatches};
+use lmdb::{Cursor, Environment, EnvironmentFlags, Transaction};
+
+use casper_execution_engine::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        global_state::lmdb::LmdbGlobalState,
+        transaction_source::lmdb::LmdbEnvironment,
+        trie_store::{lmdb::LmdbTrieStore, IntegrityError},
+    },
+};
+use casper_hashing::Digest;
+use casper_node::types::BlockHeader;
+
+/// The name of the global state database file in the data directory.
+const GLOBAL_STATE_DB_FILENAME: &str = "data.lmdb";
+/// The name of the node's block store database file in the data directory.
+const STORAGE_DB_FILENAME: &str = "storage.lmdb";
+/// The name of the database holding the block headers within the block store.
+const BLOCK_HEADER_DB_NAME: &str = "block_header";
+/// The maximum number of named databases in the node's block store.
+const MAX_STORAGE_DBS: u32 = 10;
+/// The default maximum size of the global state database, matching that of the node.
+const DEFAULT_MAX_GLOBAL_STATE_SIZE: &str = "805306368000";
+/// The maximum number of concurrent readers of the global state database.
+const MAX_READERS: u32 = 512;
+
+/// The exit code used if any integrity violations were found.
+const EXIT_CORRUPTED: i32 = 1;
+/// The exit code used if the check couldn't be performed.
+const EXIT_FAILED: i32 = 2;
+
+fn main() {
+    let matches = App::new("Trie Integrity Check")
+        .version(crate_version!())
+        .about("Verifies the integrity of every trie under a state root hash")
+        .arg(
+            Arg::with_name("data_dir")
+                .short("d")
+                .long("data-dir")
+                .value_name("PATH")
+                .help("Data storage directory containing the global state database file")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("max_size")
+                .long("max-global-state-size")
+                .value_name("BYTES")
+                .help(
+                    "The maximum size of the global state database; a multiple of the OS page size",
+                )
+                .takes_value(true)
+                .default_value(DEFAULT_MAX_GLOBAL_STATE_SIZE),
+        )
+        .arg(
+            Arg::with_name("hash")
+                .short("s")
+                .long("state-hash")
+                .value_name("HEX_STRING")
+                .help(
+                    "The state root hash to be checked; defaults to that of the highest block in \
+                    the block store in the data directory",
+                )
+                .takes_value(true),
+        )
+        .get_matches();
+
+    match check(&matches) {
+        Ok(errors) if errors.is_empty() => (),
+        Ok(errors) => {
+            eprintln!("Found {} integrity violations", errors.len());
+            process::exit(EXIT_CORRUPTED);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(EXIT_FAILED);
+        }
+    }
+}
+
+/// Checks every trie under the state root, printing and returning the violations found.
+fn check(matches: &ArgMatches<'_>) -> Result<Vec<IntegrityError>, String> {
+    let data_dir = Path::new(matches.value_of("data_dir").unwrap());
+    let state_root_hash = match matches.value_of("hash") {
+        Some(hash) => {
+            Digest::from_hex(hash).map_err(|error| format!("invalid state root hash: {}", error))?
+        }
+        None => highest_block_state_root_hash(data_dir)?,
+    };
+    let global_state = open_global_state(matches, data_dir)?;
+
+    println!("Checking state root {}", state_root_hash);
+    let report = global_state
+        .check_integrity(CorrelationId::new(), state_root_hash)
+        .map_err(|error| format!("failed to check the global state: {}", error))?;
+    for error in &report.errors {
+        println!("{}", describe(error));
+    }
+    println!(
+        "Checked {} tries, found {} integrity violations",
+        report.tries_checked,
+        report.errors.len()
+    );
+    Ok(report.errors)
+}
+
+fn describe(error: &IntegrityError) -> String {
+    match error {
+        IntegrityError::MissingTrie(trie_key) => format!("missing trie {}", trie_key),
+        IntegrityError::HashMismatch {
+            trie_key,
+            actual_hash,
+        } => format!("trie {} hashes to {}", trie_key, actual_hash),
+        IntegrityError::Malformed { trie_key, error } => {
+            format!("trie {} is malformed: {}", trie_key, error)
+        }
+        IntegrityError::MisplacedLeaf { trie_key, path } => format!(
+            "leaf {} doesn't match its path {}",
+            trie_key,
+            base16::encode_lower(path)
+        ),
+    }
+}
+
+/// Opens the existing global state in the data directory.
+fn open_global_state(matches: &ArgMatches<'_>, data_dir: &Path) -> Result<LmdbGlobalState, String> {
+    let max_size = matches
+        .value_of("max_size")
+        .unwrap()
+        .parse()
+        .map_err(|error| format!("invalid maximum global state size: {}", error))?;
+
+    // Opening the environment would otherwise create an empty database.
+    let db_path = data_dir.join(GLOBAL_STATE_DB_FILENAME);
+    if !db_path.is_file() {
+        return Err(format!("{} doesn't exist", db_path.display()));
+    }
+    let environment = Arc::new(
+        LmdbEnvironment::new(data_dir, max_size, MAX_READERS, true)
+            .map_err(|error| format!("couldn't open the global state database: {}", error))?,
+    );
+    let trie_store = Arc::new(
+        LmdbTrieStore::open(&environment, None)
+            .map_err(|error| format!("couldn't open the trie store: {}", error))?,
+    );
+    // The empty root hash is irrelevant, as nothing is written to the global state.
+    Ok(LmdbGlobalState::new(
+        environment,
+        trie_store,
+        Digest::default(),
+    ))
+}
+
+/// Returns the state root hash of the highest block in the node's block store.
+fn highest_block_state_root_hash(data_dir: &Path) -> Result<Digest, String> {
+    let db_path = data_dir.join(STORAGE_DB_FILENAME);
+    let environment = Environment::new()
+        .set_flags(
+            EnvironmentFlags::NO_SUB_DIR | EnvironmentFlags::READ_ONLY | EnvironmentFlags::NO_TLS,
+        )
+        .set_max_dbs(MAX_STORAGE_DBS)
+        .open(&db_path)
+        .map_err(|error| format!("couldn't open {}: {}", db_path.display(), error))?;
+    let db = environment
+        .open_db(Some(BLOCK_HEADER_DB_NAME))
+        .map_err(|error| format!("couldn't open the block header database: {}", error))?;
+
+    let txn = environment
+        .begin_ro_txn()
+        .map_err(|error| format!("couldn't read the block store: {}", error))?;
+    let mut highest: Option<BlockHeader> = None;
+    {
+        let mut cursor = txn
+            .open_ro_cursor(db)
+            .map_err(|error| format!("couldn't read the block store: {}", error))?;
+        for entry in cursor.iter_start() {
+            let (_, raw) =
+                entry.map_err(|error| format!("couldn't read the block store: {}", error))?;
+            let header: BlockHeader = bincode::deserialize(raw)
+                .map_err(|error| format!("couldn't parse a block header: {}", error))?;
+            if highest
+                .as_ref()
+                .map_or(true, |highest| highest.height() < header.height())
+            {
+                highest = Some(header);
+            }
+        }
+    }
+    txn.commit()
+        .map_err(|error| format!("couldn't read the block store: {}", error))?;
+
+    highest
+        .map(|header| *header.state_root_hash())
+        .ok_or_else(|| format!("no blocks found in {}", db_path.display()))
+}